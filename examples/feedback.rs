@@ -0,0 +1,49 @@
+extern crate doodle;
+use doodle::*;
+
+/// How much smaller each frame draws the one before it.
+const SHRINK: f32 = 0.99;
+/// How far each frame turns the one before it, in degrees.
+const TURN: f32 = 1.0;
+
+#[derive(Default)]
+struct State {
+    feedback: Option<Image>,
+}
+
+fn main() {
+    let mut doodle = DoodleBuilder::new()
+            .name("Feedback")
+            .width(600)
+            .height(400)
+            .fps(60)
+            .state(State::default())
+            .draw(Box::new(|s, r| {
+                let (width, height) = r.size();
+                r.set_draw_color(Color::RGB(0, 0, 0));
+                r.clear();
+                // The last frame, a little smaller and turned a little,
+                // so everything drawn trails off into a spiraling tunnel.
+                if let Some(feedback) = s.feedback {
+                    let (w, h) = (width as f32 * SHRINK, height as f32 * SHRINK);
+                    let x = (width as f32 - w) / 2.0;
+                    let y = (height as f32 - h) / 2.0;
+                    let (x, y) = (x.round() as i32, y.round() as i32);
+                    let inside = Rect::new(x, y, w.round() as u32, h.round() as u32);
+                    r.draw_image_ex(feedback, inside, TURN, (0.5, 0.5), false, false);
+                }
+                let t = r.elapsed_seconds() as f32;
+                let center = r.center();
+                let x = center.x() as f32 + width as f32 / 3.0 * (t * 1.3).cos();
+                let y = center.y() as f32 + height as f32 / 3.0 * (t * 0.9).sin();
+                r.set_draw_color(Color::hsb(t * 60.0, 0.8, 1.0));
+                r.fill_circle(x, y, 12.0);
+                match s.feedback {
+                    Some(feedback) => r.capture_into(feedback, None).unwrap(),
+                    None => s.feedback = Some(r.capture().unwrap()),
+                }
+            }))
+            .build()
+            .unwrap();
+        doodle.run().unwrap();
+}
@@ -31,6 +31,15 @@ fn main() {
                 }
                 r.set_draw_color(Color::RGB(c, c, c));
                 r.clear();
+                let center = r.center();
+                let size = r.height() as f32 / 2.0;
+                r.set_draw_color(Color::RGB(255 - c, 255 - c, 255 - c));
+                r.fill_rect(
+                    center.x() as f32 - size / 2.0,
+                    center.y() as f32 - size / 2.0,
+                    size,
+                    size,
+                );
             }))
             .build()
             .unwrap();
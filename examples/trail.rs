@@ -0,0 +1,33 @@
+extern crate doodle;
+use doodle::*;
+
+#[derive(Default)]
+struct State {
+    angle: f32,
+}
+
+fn main() {
+    let mut doodle = DoodleBuilder::new()
+            .name("Trail")
+            .width(600)
+            .height(400)
+            .fps(60)
+            .state(State::default())
+            .setup(Box::new(|_, r| {
+                r.set_draw_color(Color::RGB(0, 0, 0));
+                r.clear();
+            }))
+            .draw(Box::new(|s, r| {
+                s.angle += 0.05;
+                r.fade(Color::RGB(0, 0, 0), 20);
+                let center = r.center();
+                let radius = r.height() as f32 / 3.0;
+                let x = center.x() as f32 + radius * s.angle.cos();
+                let y = center.y() as f32 + radius * s.angle.sin();
+                r.set_draw_color(Color::RGB(255, 200, 0));
+                r.fill_rect(x - 10.0, y - 10.0, 20.0, 20.0);
+            }))
+            .build()
+            .unwrap();
+        doodle.run().unwrap();
+}
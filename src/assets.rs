@@ -0,0 +1,381 @@
+//! A central place to load, share and unload images, fonts and sounds.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "audio")]
+use Sound;
+use {Error, Font, Image, Renderer, Result};
+
+/// A loaded asset and the number of times it has been loaded.
+struct Entry<H> {
+    handle: H,
+    count: usize,
+}
+
+/// The assets loaded through an Assets, by path and by manifest name.
+#[derive(Default)]
+pub(crate) struct AssetCache {
+    images: HashMap<PathBuf, Entry<Image>>,
+    fonts: HashMap<(PathBuf, u16), Entry<Font>>,
+    #[cfg(feature = "audio")]
+    sounds: HashMap<PathBuf, Entry<Sound>>,
+    named_images: HashMap<String, Image>,
+    named_fonts: HashMap<String, Font>,
+    #[cfg(feature = "audio")]
+    named_sounds: HashMap<String, Sound>,
+}
+
+/// One line of an asset manifest.
+enum ManifestEntry {
+    Image { name: String, path: PathBuf },
+    Font { name: String, size: u16, path: PathBuf },
+    Sound { name: String, path: PathBuf },
+}
+
+fn manifest_error(line: usize, key: &str, message: &str) -> Error {
+    Error::ConfigError {
+        line,
+        key: key.to_owned(),
+        message: message.to_owned(),
+    }
+}
+
+/// Splits the first word off a line, returning it and the rest of the line.
+fn next_word(line: &str) -> (&str, &str) {
+    let line = line.trim_start();
+    match line.find(char::is_whitespace) {
+        Some(end) => (&line[..end], &line[end..]),
+        None => (line, ""),
+    }
+}
+
+fn parse_manifest(text: &str, dir: &Path) -> Result<Vec<ManifestEntry>> {
+    let mut entries = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = match line.find('#') {
+            Some(hash) => &line[..hash],
+            None => line,
+        };
+        let (kind, rest) = next_word(line);
+        if kind.is_empty() {
+            continue;
+        }
+        let (name, rest) = next_word(rest);
+        if name.is_empty() {
+            return Err(manifest_error(line_number, kind, "is missing a name"));
+        }
+        let (size, rest) = match kind {
+            "image" | "sound" => (None, rest),
+            "font" => {
+                let (size, rest) = next_word(rest);
+                match size.parse() {
+                    Ok(size) if size > 0 => (Some(size), rest),
+                    _ => return Err(manifest_error(line_number, name, "needs a point size greater than 0")),
+                }
+            }
+            _ => return Err(manifest_error(line_number, kind, "is not a known kind of asset")),
+        };
+        let path = rest.trim();
+        if path.is_empty() {
+            return Err(manifest_error(line_number, name, "is missing a path"));
+        }
+        let (name, path) = (name.to_owned(), dir.join(path));
+        entries.push(match size {
+            Some(size) => ManifestEntry::Font { name, size, path },
+            None if kind == "sound" => ManifestEntry::Sound { name, path },
+            None => ManifestEntry::Image { name, path },
+        });
+    }
+    Ok(entries)
+}
+
+/// Loads images, fonts and sounds, sharing one copy of each file between
+/// everything that loads it.
+///
+/// Unlike `Renderer::load_image` and `load_font`, loading images and fonts
+/// through Assets never fails. An image that can't be loaded is drawn as a
+/// magenta and black checkerboard and a font that can't be loaded is
+/// replaced by the bundled default font, so a missing file is easy to spot
+/// but doesn't stop the doodle. A message is printed to stderr about every
+/// asset that couldn't be loaded. Sounds have nothing to stand in for them,
+/// so loading one can fail.
+///
+/// Every load of a file has to be matched by an unload before the file is
+/// actually freed. Drawing an image after it has been freed draws the
+/// checkerboard. `image`, `font` and `sound` get a file without counting
+/// as a load, for drawing code that runs every frame.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// # #[derive(Default)] struct State { x: f32 }
+/// let builder = DoodleBuilder::<State>::new().draw(Box::new(|state, r| {
+///     // Loaded on the first frame, and shared by every frame after it.
+///     let player = r.assets().image("player.png");
+///     r.clear();
+///     r.draw_image(player, state.x, 300.0);
+/// }));
+/// ```
+pub struct Assets<'a> {
+    pub(crate) renderer: &'a mut Renderer,
+}
+
+impl<'a> Assets<'a> {
+    /// Loads an image, or returns the already loaded copy of it.
+    pub fn load_image<P: AsRef<Path>>(&mut self, path: P) -> Image {
+        let path = path.as_ref();
+        if let Some(image) = self.cached_image(path) {
+            return image;
+        }
+        let image = match self.renderer.load_image(path) {
+            Ok(image) => image,
+            Err(error) => {
+                self.renderer
+                    .logger
+                    .warn_or_print(|| format!("couldn't load {}: {:?}", path.display(), error));
+                self.renderer.missing_image(path)
+            }
+        };
+        self.insert_image(path, image);
+        image
+    }
+    /// Returns the image already loaded from a path, counting another load
+    /// of it.
+    pub(crate) fn cached_image(&mut self, path: &Path) -> Option<Image> {
+        let entry = self.renderer.assets.images.get_mut(path)?;
+        entry.count += 1;
+        Some(entry.handle)
+    }
+    /// Shares an image that was just loaded from a path.
+    pub(crate) fn insert_image(&mut self, path: &Path, image: Image) {
+        let entry = Entry { handle: image, count: 1 };
+        self.renderer.assets.images.insert(path.to_owned(), entry);
+    }
+    /// Loads a font at a point size, or returns the already loaded copy of it.
+    pub fn load_font<P: AsRef<Path>>(&mut self, path: P, size: u16) -> Font {
+        let key = (path.as_ref().to_owned(), size);
+        if let Some(entry) = self.renderer.assets.fonts.get_mut(&key) {
+            entry.count += 1;
+            return entry.handle;
+        }
+        let font = match self.renderer.load_font(&key.0, size) {
+            Ok(font) => font,
+            Err(error) => {
+                self.renderer
+                    .logger
+                    .warn_or_print(|| format!("couldn't load {}: {:?}", key.0.display(), error));
+                match self.renderer.default_font(size) {
+                    Ok(font) => font,
+                    // The bundled font is always valid, so this only happens
+                    // if SDL itself is broken.
+                    Err(error) => panic!("couldn't load the default font: {:?}", error),
+                }
+            }
+        };
+        self.renderer.assets.fonts.insert(key, Entry { handle: font, count: 1 });
+        font
+    }
+    /// Returns the image loaded from a path, loading it the first time like
+    /// `load_image`. Later calls return it without counting another load,
+    /// so calling this every frame doesn't keep the image from being
+    /// freed.
+    pub fn image<P: AsRef<Path>>(&mut self, path: P) -> Image {
+        match self.renderer.assets.images.get(path.as_ref()) {
+            Some(entry) => entry.handle,
+            None => self.load_image(path),
+        }
+    }
+    /// Returns the font loaded from a path at a point size, loading it the
+    /// first time, like `image`.
+    pub fn font<P: AsRef<Path>>(&mut self, path: P, size: u16) -> Font {
+        let key = (path.as_ref().to_owned(), size);
+        match self.renderer.assets.fonts.get(&key) {
+            Some(entry) => entry.handle,
+            None => self.load_font(&key.0, size),
+        }
+    }
+    /// Unloads an image loaded through Assets, freeing it once it has been
+    /// unloaded as many times as it was loaded.
+    pub fn unload_image(&mut self, image: Image) {
+        let cache = &mut self.renderer.assets;
+        let path = match cache.images.iter_mut().find(|(_, e)| e.handle == image) {
+            Some((path, entry)) => {
+                entry.count -= 1;
+                if entry.count > 0 {
+                    return;
+                }
+                path.clone()
+            }
+            None => return,
+        };
+        cache.images.remove(&path);
+        cache.named_images.retain(|_, &mut i| i != image);
+        self.renderer.forget_load(image);
+        self.renderer.unload_image(image);
+    }
+    /// Unloads a font loaded through Assets, closing it once it has been
+    /// unloaded as many times as it was loaded.
+    pub fn unload_font(&mut self, font: Font) {
+        let cache = &mut self.renderer.assets;
+        let key = match cache.fonts.iter_mut().find(|(_, e)| e.handle == font) {
+            Some((key, entry)) => {
+                entry.count -= 1;
+                if entry.count > 0 {
+                    return;
+                }
+                key.clone()
+            }
+            None => return,
+        };
+        cache.fonts.remove(&key);
+        cache.named_fonts.retain(|_, &mut f| f != font);
+        // A font that failed to load may share its handle with another
+        // path that fell back to the same default font.
+        if !cache.fonts.values().any(|e| e.handle == font) {
+            self.renderer.unload_font(font);
+        }
+    }
+    /// Loads a sound, or returns the already loaded copy of it. This opens
+    /// the audio device the first time, like `Renderer::audio`.
+    #[cfg(feature = "audio")]
+    pub fn load_sound<P: AsRef<Path>>(&mut self, path: P) -> Result<Sound> {
+        let path = path.as_ref();
+        if let Some(entry) = self.renderer.assets.sounds.get_mut(path) {
+            entry.count += 1;
+            return Ok(entry.handle);
+        }
+        let sound = self.renderer.audio()?.load_sound(path)?;
+        let entry = Entry { handle: sound, count: 1 };
+        self.renderer.assets.sounds.insert(path.to_owned(), entry);
+        Ok(sound)
+    }
+    /// Returns the sound loaded from a path, loading it the first time,
+    /// like `image`.
+    #[cfg(feature = "audio")]
+    pub fn sound<P: AsRef<Path>>(&mut self, path: P) -> Result<Sound> {
+        match self.renderer.assets.sounds.get(path.as_ref()) {
+            Some(entry) => Ok(entry.handle),
+            None => self.load_sound(path),
+        }
+    }
+    /// Unloads a sound loaded through Assets, freeing it once it has been
+    /// unloaded as many times as it was loaded.
+    #[cfg(feature = "audio")]
+    pub fn unload_sound(&mut self, sound: Sound) {
+        let cache = &mut self.renderer.assets;
+        let path = match cache.sounds.iter_mut().find(|(_, e)| e.handle == sound) {
+            Some((path, entry)) => {
+                entry.count -= 1;
+                if entry.count > 0 {
+                    return;
+                }
+                path.clone()
+            }
+            None => return,
+        };
+        cache.sounds.remove(&path);
+        cache.named_sounds.retain(|_, &mut s| s != sound);
+        if let Some(ref mut audio) = self.renderer.audio {
+            audio.unload_sound(sound);
+        }
+    }
+    /// Loads every asset listed in a manifest file, calling `progress` with
+    /// the renderer, the number of assets loaded so far and the total after
+    /// each one, so it can draw a loading screen.
+    ///
+    /// Each line of the manifest names an image, a font or a sound, which
+    /// can then be looked up with `named_image`, `named_font` and
+    /// `named_sound`. Paths are relative to the manifest and go until the
+    /// end of the line. Everything after a `#` is a comment. Sounds need
+    /// the `audio` feature, and a sound that can't be loaded stops the
+    /// preload with an error.
+    ///
+    /// ```text
+    /// # kind  name    size  path
+    /// image   player        sprites/player.png
+    /// image   tiles         sprites/world tiles.png
+    /// font    title   48    fonts/Title.ttf
+    /// sound   jump          sounds/jump.wav
+    /// ```
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn setup(r: &mut Renderer) {
+    /// r.assets()
+    ///     .preload_manifest("assets/manifest.txt", |r, loaded, total| {
+    ///         r.set_draw_color(Color::RGB(0, 0, 0));
+    ///         r.clear();
+    ///         r.set_draw_color(Color::RGB(255, 255, 255));
+    ///         let width = r.width() as f32 * loaded as f32 / total as f32;
+    ///         r.fill_rect(0.0, r.height() as f32 - 10.0, width, 10.0);
+    ///         r.present();
+    ///     })
+    ///     .unwrap();
+    /// let player = r.assets().named_image("player").unwrap();
+    /// # let _ = player;
+    /// # }
+    /// ```
+    pub fn preload_manifest<P, F>(&mut self, path: P, mut progress: F) -> Result<()>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&mut Renderer, usize, usize),
+    {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)?;
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let entries = parse_manifest(&text, dir)?;
+        let total = entries.len();
+        for (loaded, entry) in entries.into_iter().enumerate() {
+            match entry {
+                ManifestEntry::Image { name, path } => {
+                    let image = self.load_image(path);
+                    self.renderer.assets.named_images.insert(name, image);
+                }
+                ManifestEntry::Font { name, size, path } => {
+                    let font = self.load_font(path, size);
+                    self.renderer.assets.named_fonts.insert(name, font);
+                }
+                #[cfg(feature = "audio")]
+                ManifestEntry::Sound { name, path } => {
+                    let sound = self.load_sound(path)?;
+                    self.renderer.assets.named_sounds.insert(name, sound);
+                }
+                #[cfg(not(feature = "audio"))]
+                ManifestEntry::Sound { name, path } => {
+                    return Err(Error::Unsupported(format!(
+                        "{} ({}) is a sound, which needs the audio feature",
+                        name,
+                        path.display()
+                    )));
+                }
+            }
+            progress(self.renderer, loaded + 1, total);
+        }
+        Ok(())
+    }
+    /// Returns the image with a name from a manifest.
+    pub fn named_image(&self, name: &str) -> Option<Image> {
+        self.renderer.assets.named_images.get(name).cloned()
+    }
+    /// Returns the font with a name from a manifest.
+    pub fn named_font(&self, name: &str) -> Option<Font> {
+        self.renderer.assets.named_fonts.get(name).cloned()
+    }
+    /// Returns the sound with a name from a manifest.
+    #[cfg(feature = "audio")]
+    pub fn named_sound(&self, name: &str) -> Option<Sound> {
+        self.renderer.assets.named_sounds.get(name).cloned()
+    }
+}
+
+impl Renderer {
+    /// Returns the asset manager of this renderer.
+    #[inline]
+    pub fn assets(&mut self) -> Assets<'_> {
+        Assets { renderer: self }
+    }
+}
@@ -0,0 +1,151 @@
+//! Packing many small images into one big one at runtime.
+
+use sdl2::rect::Rect;
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use {Error, Image, Renderer, Result, SpriteSheet};
+
+/// The widest atlas `AtlasBuilder` makes unless it's told otherwise.
+const DEFAULT_MAX_WIDTH: u32 = 2048;
+
+/// Packs images into a single texture atlas, so drawing lots of different
+/// small images doesn't make the graphics card switch between textures
+/// for every one of them.
+///
+/// The atlas is a `SpriteSheet` with a frame for each image that was
+/// added, numbered in the order they were added. Graphics can be added
+/// through their `image()`, so shapes and text drawn by the doodle can be
+/// packed too.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// # fn setup(r: &mut Renderer) -> Result<()> {
+/// let mut atlas = AtlasBuilder::new().padding(1);
+/// let mut icons = Vec::new();
+/// for name in &["coin", "gem", "heart", "key"] {
+///     let image = r.load_image(format!("icons/{}.png", name))?;
+///     icons.push(atlas.add(image));
+/// }
+/// let sheet = atlas.build(r)?;
+/// for (i, &icon) in icons.iter().enumerate() {
+///     r.draw_sprite(&sheet, icon, 10.0 + 40.0 * i as f32, 10.0);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtlasBuilder {
+    images: Vec<Image>,
+    padding: u32,
+    max_width: u32,
+}
+
+impl Default for AtlasBuilder {
+    fn default() -> Self {
+        AtlasBuilder {
+            images: Vec::new(),
+            padding: 1,
+            max_width: DEFAULT_MAX_WIDTH,
+        }
+    }
+}
+
+impl AtlasBuilder {
+    /// Creates an empty AtlasBuilder.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Sets how many transparent pixels are left around each image, 1 by
+    /// default. Without any, images drawn scaled can pick up the edges of
+    /// their neighbours.
+    #[inline]
+    pub fn padding(mut self, padding: u32) -> Self {
+        self.padding = padding;
+        self
+    }
+    /// Sets how wide the atlas can get before images are put on a new
+    /// row, 2048 pixels by default. The atlas is never wider than the
+    /// graphics card allows.
+    #[inline]
+    pub fn max_width(mut self, width: u32) -> Self {
+        self.max_width = width;
+        self
+    }
+    /// Adds an image to be packed, returning the index of its frame in the
+    /// finished atlas.
+    pub fn add(&mut self, image: Image) -> usize {
+        self.images.push(image);
+        self.images.len() - 1
+    }
+    /// Packs the images into a new image and returns it as a sprite sheet.
+    /// The images that were added are left as they are, and can be
+    /// unloaded afterwards.
+    ///
+    /// Returns an error if one of the images is too wide for the atlas, or
+    /// the images take up more room than the graphics card allows.
+    pub fn build(&self, renderer: &mut Renderer) -> Result<SpriteSheet> {
+        let info = renderer.info();
+        // A maximum of 0 means the driver doesn't have one.
+        let max_width = match info.max_texture_width {
+            0 => self.max_width,
+            max => self.max_width.min(max),
+        };
+        let sizes: Vec<(u32, u32)> = self.images.iter().map(|&image| renderer.image_size(image)).collect();
+        let (frames, width, height) = pack(&sizes, self.padding, max_width)?;
+        if info.max_texture_height != 0 && height > info.max_texture_height {
+            return Err(Error::Error(format!(
+                "the atlas would be {} pixels tall, more than the graphics card allows",
+                height
+            )));
+        }
+        let graphics = renderer.create_graphics(width.max(1), height.max(1))?;
+        let mut result = Ok(());
+        renderer.draw_to(graphics, |r| {
+            // Copying without blending keeps the images' alpha as it is.
+            let (blend_mode, tint) = (r.canvas.blend_mode(), r.tint);
+            r.canvas.set_blend_mode(BlendMode::None);
+            r.tint = Color::RGBA(255, 255, 255, 255);
+            for ((&image, &frame), &(w, h)) in self.images.iter().zip(&frames).zip(&sizes) {
+                // Images that haven't loaded have no size to take up.
+                if w > 0 && h > 0 && result.is_ok() {
+                    result = r.copy_image(image, None, frame);
+                }
+            }
+            r.canvas.set_blend_mode(blend_mode);
+            r.tint = tint;
+        })?;
+        result?;
+        Ok(SpriteSheet::new(graphics.image(), frames))
+    }
+}
+
+/// Places rectangles of the given sizes on shelves, tallest first, and
+/// returns where each one went along with the size of the whole area.
+fn pack(sizes: &[(u32, u32)], padding: u32, max_width: u32) -> Result<(Vec<Rect>, u32, u32)> {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| ::std::cmp::Reverse(sizes[i].1));
+    let mut frames = vec![Rect::new(0, 0, 0, 0); sizes.len()];
+    let (mut x, mut y, mut shelf_height, mut width) = (padding, padding, 0, 0);
+    for i in order {
+        let (w, h) = sizes[i];
+        if w + padding * 2 > max_width {
+            return Err(Error::Error(format!(
+                "an image {} pixels wide doesn't fit in an atlas {} pixels wide",
+                w, max_width
+            )));
+        }
+        if x + w + padding > max_width {
+            x = padding;
+            y += shelf_height + padding;
+            shelf_height = 0;
+        }
+        frames[i] = Rect::new(x as i32, y as i32, w, h);
+        x += w + padding;
+        width = width.max(x);
+        shelf_height = shelf_height.max(h);
+    }
+    Ok((frames, width, y + shelf_height + padding))
+}
@@ -0,0 +1,193 @@
+//! Playing sounds and music with SDL_mixer, behind the `audio` feature.
+
+use std::path::Path;
+
+use sdl2::mixer::{self, Channel, Chunk, Sdl2MixerContext, MAX_VOLUME};
+use sdl2::AudioSubsystem;
+
+use {Error, Renderer, Result};
+
+/// How many sounds can play at the same time.
+const CHANNELS: i32 = 16;
+/// How many samples are mixed at a time. Smaller is quicker to respond,
+/// but can crackle on slow machines.
+const CHUNK_SIZE: i32 = 1024;
+
+/// A handle to a sound loaded with `Audio::load_sound`.
+///
+/// Like an `Image`, a Sound is cheap to copy and can be stored in the
+/// doodle's state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Sound {
+    id: usize,
+}
+
+/// The doodle's sound effects and music, returned by `Renderer::audio`.
+///
+/// Any number of sounds can be loaded, and up to 16 play at once. There
+/// is one piece of music at a time, which plays alongside the sounds.
+pub struct Audio {
+    // Sounds and music have to be freed before the mixer is closed.
+    sounds: Vec<Option<Chunk>>,
+    music: Option<mixer::Music<'static>>,
+    paused: bool,
+    _mixer: Sdl2MixerContext,
+    _subsystem: AudioSubsystem,
+}
+
+impl Drop for Audio {
+    fn drop(&mut self) {
+        self.sounds.clear();
+        self.music = None;
+        mixer::close_audio();
+    }
+}
+
+/// Turns a volume between 0 and 1 into SDL_mixer's.
+#[inline]
+fn mixer_volume(volume: f32) -> i32 {
+    (volume.clamp(0.0, 1.0) * MAX_VOLUME as f32).round() as i32
+}
+
+impl Audio {
+    fn open(subsystem: AudioSubsystem) -> Result<Audio> {
+        // Only WAV is built in. OGG and MP3 are used if SDL_mixer was built
+        // with them, so failing to load them isn't an error, and the context
+        // is only there to unload whatever did get loaded.
+        let mixer = mixer::init(mixer::INIT_OGG | mixer::INIT_MP3).unwrap_or(Sdl2MixerContext);
+        mixer::open_audio(44_100, mixer::DEFAULT_FORMAT, mixer::DEFAULT_CHANNELS, CHUNK_SIZE)?;
+        mixer::allocate_channels(CHANNELS);
+        Ok(Audio {
+            sounds: Vec::new(),
+            music: None,
+            paused: false,
+            _mixer: mixer,
+            _subsystem: subsystem,
+        })
+    }
+    /// Loads a sound effect from a WAV file, or from OGG if SDL_mixer
+    /// supports it.
+    pub fn load_sound<P: AsRef<Path>>(&mut self, path: P) -> Result<Sound> {
+        let chunk = Chunk::from_file(path)?;
+        self.sounds.push(Some(chunk));
+        Ok(Sound {
+            id: self.sounds.len() - 1,
+        })
+    }
+    /// Plays a sound once. A sound can be played again while it's still
+    /// playing, and the two mix together.
+    ///
+    /// If 16 sounds are already playing, this returns an error and the
+    /// sound isn't played.
+    pub fn play(&mut self, sound: Sound) -> Result<()> {
+        let chunk = match self.sounds.get(sound.id) {
+            Some(Some(chunk)) => chunk,
+            Some(None) => return Err(Error::Error("the sound has been unloaded".to_owned())),
+            None => return Err(Error::Error("sound belongs to a different doodle".to_owned())),
+        };
+        Channel::all().play(chunk, 0)?;
+        Ok(())
+    }
+    /// Frees a sound, stopping it wherever it's playing. Playing it after
+    /// this returns an error.
+    pub fn unload_sound(&mut self, sound: Sound) {
+        if let Some(chunk) = self.sounds.get_mut(sound.id) {
+            *chunk = None;
+        }
+    }
+    /// Loads music from a file, stopping the music that was playing. OGG,
+    /// MP3 and the other formats SDL_mixer supports can all be used.
+    pub fn load_music<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let music = mixer::Music::from_file(path)?;
+        mixer::Music::halt();
+        self.music = Some(music);
+        Ok(())
+    }
+    /// Starts the music from the beginning, playing it `times` times, or
+    /// over and over until it's stopped if `times` is None.
+    pub fn play_music(&mut self, times: Option<u32>) -> Result<()> {
+        let music = self
+            .music
+            .as_ref()
+            .ok_or_else(|| Error::Error("no music has been loaded".to_owned()))?;
+        let loops = match times {
+            Some(0) => return Ok(()),
+            Some(times) => times.min(i32::MAX as u32) as i32,
+            None => -1,
+        };
+        music.play(loops)?;
+        Ok(())
+    }
+    /// Sets the volume of all sounds, between 0 for silent and 1 for full
+    /// volume.
+    pub fn set_volume(&mut self, volume: f32) {
+        Channel::all().set_volume(mixer_volume(volume));
+    }
+    /// Sets the volume of the music, between 0 and 1 like `set_volume`.
+    pub fn set_music_volume(&mut self, volume: f32) {
+        mixer::Music::set_volume(mixer_volume(volume));
+    }
+    /// Stops all sounds and the music.
+    pub fn stop(&mut self) {
+        Channel::all().halt();
+        mixer::Music::halt();
+        self.paused = false;
+    }
+    /// Stops the music, leaving sounds playing.
+    pub fn stop_music(&mut self) {
+        mixer::Music::halt();
+    }
+    /// Pauses all sounds and the music, for example while the doodle is
+    /// paused.
+    pub fn pause(&mut self) {
+        Channel::all().pause();
+        mixer::Music::pause();
+        self.paused = true;
+    }
+    /// Carries on playing everything that `pause` paused.
+    pub fn resume(&mut self) {
+        Channel::all().resume();
+        mixer::Music::resume();
+        self.paused = false;
+    }
+    /// Returns true if the audio has been paused with `pause`.
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+}
+
+impl Renderer {
+    /// Returns the doodle's audio, opening the audio device the first time
+    /// it's called.
+    ///
+    /// This needs the `audio` feature, and the SDL2_mixer library to be
+    /// installed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State { jump: Option<Sound> }
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .setup(Box::new(|state, r| {
+    ///         let audio = r.audio().unwrap();
+    ///         state.jump = Some(audio.load_sound("jump.wav").unwrap());
+    ///         audio.load_music("theme.ogg").unwrap();
+    ///         audio.set_music_volume(0.5);
+    ///         audio.play_music(None).unwrap();
+    ///     }))
+    ///     .on_key_down(Box::new(|state, r, key| {
+    ///         if let (Keycode::Space, Some(jump)) = (key, state.jump) {
+    ///             // Running out of channels just skips the sound.
+    ///             let _ = r.audio().unwrap().play(jump);
+    ///         }
+    ///     }));
+    /// ```
+    pub fn audio(&mut self) -> Result<&mut Audio> {
+        if self.audio.is_none() {
+            let subsystem = self._sdl.audio()?;
+            self.audio = Some(Audio::open(subsystem)?);
+        }
+        Ok(self.audio.as_mut().expect("audio was just opened"))
+    }
+}
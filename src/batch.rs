@@ -0,0 +1,141 @@
+//! Collecting points, lines and rectangles that are drawn one at a time, so
+//! a run of them in the same color is drawn with as few calls to SDL as
+//! possible.
+//!
+//! Everything else that draws, reads or changes how the canvas is set up
+//! flushes the batch first, so batching never changes what ends up on the
+//! screen, only how many calls it takes. Images and text can't be batched
+//! the same way, since SDL 2.0 has no call that draws a texture more than
+//! once, but SDL batches those itself on the renderers that support it.
+//!
+//! SDL 2.0 can't draw lines that don't join up in one call either, so
+//! batched lines are drawn a connected run at a time. Runs are only
+//! joined when the lines are opaque, since a joined run draws the pixel
+//! where two lines meet once rather than twice.
+
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::Window;
+
+use debug::DebugOverlay;
+use Renderer;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Points,
+    Lines,
+    Outlines,
+    Rects,
+}
+
+/// The shapes waiting to be drawn, all of the same kind and color.
+#[derive(Default)]
+pub(crate) struct Batch {
+    kind: Option<Kind>,
+    color: Option<Color>,
+    blend_mode: Option<BlendMode>,
+    points: Vec<Point>,
+    lines: Vec<(Point, Point)>,
+    rects: Vec<Rect>,
+}
+
+/// Draws lines a connected run at a time, or one at a time if `join` is
+/// false, counting each call.
+fn draw_lines(
+    canvas: &mut Canvas<Window>, debug: &mut DebugOverlay, lines: &[(Point, Point)], join: bool,
+) -> Result<(), String> {
+    let mut run: Vec<Point> = Vec::new();
+    for &(start, end) in lines {
+        if !(join && run.last() == Some(&start)) {
+            if run.len() > 1 {
+                debug.count_draw();
+                canvas.draw_lines(&run[..])?;
+            }
+            run.clear();
+            run.push(start);
+        }
+        run.push(end);
+    }
+    if run.len() > 1 {
+        debug.count_draw();
+        canvas.draw_lines(&run[..])?;
+    }
+    Ok(())
+}
+
+impl Renderer {
+    /// Adds a point to the batch, drawn with the current drawing color.
+    pub(crate) fn batch_point(&mut self, point: Point) {
+        self.start_batch(Kind::Points);
+        self.batch.points.push(point);
+    }
+    /// Adds a line to the batch.
+    pub(crate) fn batch_line(&mut self, start: Point, end: Point) {
+        self.start_batch(Kind::Lines);
+        self.batch.lines.push((start, end));
+    }
+    /// Adds the outline of a rectangle to the batch.
+    pub(crate) fn batch_outline(&mut self, rect: Rect) {
+        self.start_batch(Kind::Outlines);
+        self.batch.rects.push(rect);
+    }
+    /// Adds a filled rectangle to the batch.
+    pub(crate) fn batch_rect(&mut self, rect: Rect) {
+        self.start_batch(Kind::Rects);
+        self.batch.rects.push(rect);
+    }
+    /// Flushes the batch if what's being added can't join it.
+    fn start_batch(&mut self, kind: Kind) {
+        let (color, blend_mode) = (self.canvas.draw_color(), self.canvas.blend_mode());
+        let batch = &self.batch;
+        if batch.kind == Some(kind) && batch.color == Some(color) && batch.blend_mode == Some(blend_mode) {
+            return;
+        }
+        self.flush_batch();
+        self.batch.kind = Some(kind);
+        self.batch.color = Some(color);
+        self.batch.blend_mode = Some(blend_mode);
+    }
+    /// Draws what's in the batch, with the color and blend mode it was
+    /// added with.
+    pub(crate) fn flush_batch(&mut self) {
+        let kind = match self.batch.kind.take() {
+            Some(kind) => kind,
+            None => return,
+        };
+        let (color, blend_mode) = (self.canvas.draw_color(), self.canvas.blend_mode());
+        if let Some(batch_color) = self.batch.color {
+            self.canvas.set_draw_color(batch_color);
+        }
+        if let Some(batch_blend_mode) = self.batch.blend_mode {
+            self.canvas.set_blend_mode(batch_blend_mode);
+        }
+        if kind != Kind::Lines {
+            self.debug.count_draw();
+        }
+        let result = match kind {
+            Kind::Points => self.canvas.draw_points(&self.batch.points[..]),
+            Kind::Lines => {
+                let opaque = self.batch.color.is_some_and(|c| c.a == 255);
+                let join = opaque || self.batch.blend_mode == Some(BlendMode::None);
+                draw_lines(&mut self.canvas, &mut self.debug, &self.batch.lines, join)
+            }
+            Kind::Outlines => self.canvas.draw_rects(&self.batch.rects),
+            Kind::Rects => self.canvas.fill_rects(&self.batch.rects),
+        };
+        self.batch.points.clear();
+        self.batch.lines.clear();
+        self.batch.rects.clear();
+        self.canvas.set_blend_mode(blend_mode);
+        self.canvas.set_draw_color(color);
+        self.log_failure("draw a batch of shapes", result);
+    }
+    /// Flushes the batch before a call that draws straight to the canvas,
+    /// and counts the call.
+    #[inline]
+    pub(crate) fn draw_call(&mut self) {
+        self.flush_batch();
+        self.debug.count_draw();
+    }
+}
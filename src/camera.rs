@@ -0,0 +1,128 @@
+//! A 2D camera for looking around a world that's bigger than the window.
+
+use {Renderer, Vec2};
+
+/// A view of a 2D world: which point of it is in the middle of the window,
+/// how far it's zoomed in, and how it's turned.
+///
+/// A camera does nothing by itself. `Renderer::set_camera` makes what's
+/// drawn afterwards go through it, and `Renderer::screen_to_world` turns
+/// mouse positions into world coordinates.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// # struct State { camera: Camera2D, player: Vec2 }
+/// # fn draw(state: &mut State, r: &mut Renderer) {
+/// // Follow the player, and zoom in and out with the arrow keys.
+/// state.camera.position = state.player;
+/// if r.is_key_down(Keycode::Up) {
+///     state.camera.zoom *= 1.02;
+/// }
+/// if r.is_key_down(Keycode::Down) {
+///     state.camera.zoom /= 1.02;
+/// }
+/// r.clear();
+/// r.set_camera(&state.camera);
+/// r.fill_circle_at(state.player, 10.0);
+/// // Draw the status bar straight onto the window.
+/// r.reset_matrix();
+/// r.fill_rect(0.0, 0.0, 200.0, 20.0);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera2D {
+    /// The point of the world in the middle of the window.
+    pub position: Vec2,
+    /// How many pixels on the screen one unit of the world takes up.
+    /// Bigger is closer.
+    pub zoom: f32,
+    /// How many radians the world is turned anticlockwise on the screen,
+    /// which is the camera turning clockwise.
+    pub rotation: f32,
+}
+
+impl Default for Camera2D {
+    fn default() -> Self {
+        Camera2D::new(Vec2::ZERO)
+    }
+}
+
+impl Camera2D {
+    /// Creates a camera looking at `position`, not zoomed or turned.
+    #[inline]
+    pub fn new(position: Vec2) -> Camera2D {
+        Camera2D {
+            position,
+            zoom: 1.0,
+            rotation: 0.0,
+        }
+    }
+    /// Moves the camera by `delta` pixels on the screen, which is how far
+    /// the mouse moves when the world is dragged around with it. Dragging
+    /// right moves the camera left, so the world follows the mouse.
+    #[inline]
+    pub fn pan(&mut self, delta: Vec2) {
+        self.position -= delta.rotate(self.rotation) / self.zoom;
+    }
+    /// Zooms in by `factor`, or out if it's less than 1, keeping the world
+    /// point `anchor` where it is on the screen. Zooming around the mouse
+    /// is `zoom_around(factor, r.screen_to_world(&camera, mouse))`.
+    pub fn zoom_around(&mut self, factor: f32, anchor: Vec2) {
+        if factor > 0.0 {
+            self.position = anchor + (self.position - anchor) / factor;
+            self.zoom *= factor;
+        }
+    }
+}
+
+impl Renderer {
+    /// Replaces the transformation with the camera's, so everything drawn
+    /// afterwards is in world coordinates. Like any transformation, it's
+    /// reset after every frame, and `reset_matrix` goes back to drawing in
+    /// pixels.
+    pub fn set_camera(&mut self, camera: &Camera2D) {
+        let (width, height) = self.size();
+        self.reset_matrix();
+        self.translate(width as f32 / 2.0, height as f32 / 2.0);
+        self.rotate(-camera.rotation);
+        self.scale(camera.zoom, camera.zoom);
+        self.translate(-camera.position.x, -camera.position.y);
+    }
+    /// Returns the point of the world that's at `point` in drawing
+    /// coordinates on the screen when looking through a camera, such as
+    /// where in the world the mouse is.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State { camera: Camera2D, dragging: bool }
+    /// // Scroll to zoom around the mouse, and drag to pan.
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .on_scroll(Box::new(|state, r, _, y| {
+    ///         let (mouse_x, mouse_y) = r.mouse_position();
+    ///         let mouse = Vec2::new(mouse_x as f32, mouse_y as f32);
+    ///         let anchor = r.screen_to_world(&state.camera, mouse);
+    ///         state.camera.zoom_around(1.1f32.powi(y), anchor);
+    ///     }))
+    ///     .on_mouse_down(Box::new(|state, _, _, _, _| state.dragging = true))
+    ///     .on_mouse_up(Box::new(|state, _, _, _, _| state.dragging = false))
+    ///     .on_mouse_move(Box::new(|state, _, _, _, dx, dy| {
+    ///         if state.dragging {
+    ///             state.camera.pan(Vec2::new(dx as f32, dy as f32));
+    ///         }
+    ///     }));
+    /// ```
+    pub fn screen_to_world(&self, camera: &Camera2D, point: Vec2) -> Vec2 {
+        let (width, height) = self.size();
+        let center = Vec2::new(width as f32 / 2.0, height as f32 / 2.0);
+        camera.position + ((point - center) / camera.zoom).rotate(camera.rotation)
+    }
+    /// Returns where a point of the world is on the screen, in drawing
+    /// coordinates, when looking through a camera.
+    pub fn world_to_screen(&self, camera: &Camera2D, point: Vec2) -> Vec2 {
+        let (width, height) = self.size();
+        let center = Vec2::new(width as f32 / 2.0, height as f32 / 2.0);
+        center + (point - camera.position).rotate(-camera.rotation) * camera.zoom
+    }
+}
@@ -0,0 +1,280 @@
+//! Reading the canvas back into memory and saving it as PNG.
+
+use std::mem;
+use std::os::raw::{c_int, c_void};
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use sdl2::image::{LoadSurface, SaveSurface};
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::rect::Rect;
+use sdl2::render::Texture;
+use sdl2::surface::Surface;
+use sdl2::sys;
+
+use image::into_static;
+use {Error, Image, Renderer, Result};
+
+/// The format of the pixels in a Frame, three bytes per pixel.
+pub(crate) const FORMAT: PixelFormatEnum = PixelFormatEnum::RGB24;
+pub(crate) const BYTES_PER_PIXEL: usize = 3;
+
+/// RGB pixels read back from the canvas or loaded from a file, with no
+/// padding between rows.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Frame {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) pixels: Vec<u8>,
+}
+
+impl Frame {
+    /// Loads an image file, converting it to the Frame format.
+    pub(crate) fn load<P: AsRef<Path>>(path: P) -> Result<Frame> {
+        let surface = Surface::from_file(path)?;
+        // SDL needs a PixelFormat to convert to, and the only way to get
+        // one is from a surface that already uses it.
+        let format = Surface::new(1, 1, FORMAT)?.pixel_format();
+        let surface = surface.convert(&format)?;
+        let (width, height) = (surface.width(), surface.height());
+        let row = width as usize * BYTES_PER_PIXEL;
+        let pitch = surface.pitch() as usize;
+        let pixels = surface.with_lock(|data| {
+            let mut pixels = Vec::with_capacity(row * height as usize);
+            for y in 0..height as usize {
+                pixels.extend_from_slice(&data[y * pitch..y * pitch + row]);
+            }
+            pixels
+        });
+        Ok(Frame { width, height, pixels })
+    }
+    /// Saves the frame as a PNG file.
+    pub(crate) fn save_png<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let pitch = self.width * BYTES_PER_PIXEL as u32;
+        let surface = Surface::from_data(&mut self.pixels, self.width, self.height, pitch, FORMAT)?;
+        surface.save(path)?;
+        Ok(())
+    }
+    /// Returns the pixel at (x, y) as (r, g, b).
+    #[inline]
+    pub(crate) fn pixel(&self, x: u32, y: u32) -> (u8, u8, u8) {
+        let i = (y as usize * self.width as usize + x as usize) * BYTES_PER_PIXEL;
+        (self.pixels[i], self.pixels[i + 1], self.pixels[i + 2])
+    }
+    /// Scales the frame to a new size, picking the nearest pixel.
+    pub(crate) fn resized(self, width: u32, height: u32) -> Frame {
+        if (width, height) == (self.width, self.height) {
+            return self;
+        }
+        let mut pixels = Vec::with_capacity(width as usize * height as usize * BYTES_PER_PIXEL);
+        for y in 0..height {
+            let src_y = (u64::from(y) * u64::from(self.height) / u64::from(height)) as u32;
+            for x in 0..width {
+                let src_x = (u64::from(x) * u64::from(self.width) / u64::from(width)) as u32;
+                let (r, g, b) = self.pixel(src_x, src_y);
+                pixels.extend_from_slice(&[r, g, b]);
+            }
+        }
+        Frame { width, height, pixels }
+    }
+}
+
+impl Renderer {
+    /// Reads back a rectangle of the window in physical pixels, or the
+    /// whole window if `rect` is None, into `pixels`. Returns the width
+    /// and height of what was read.
+    pub(crate) fn read_pixels_into(&mut self, rect: Option<Rect>, pixels: &mut Vec<u8>) -> Result<(u32, u32)> {
+        let (width, height) = match rect {
+            Some(rect) => (rect.width(), rect.height()),
+            None => self.canvas.output_size()?,
+        };
+        let pitch = width as usize * BYTES_PER_PIXEL;
+        pixels.resize(pitch * height as usize, 0);
+        self.flush_batch();
+        // SDL reads from inside the viewport, which is moved and scaled
+        // when a logical size is set, so the whole window is made the
+        // viewport while reading.
+        let (scale, viewport) = (self.canvas.scale(), self.canvas.viewport());
+        self.canvas.set_scale(1.0, 1.0)?;
+        self.canvas.set_viewport(None);
+        // sdl2's read_pixels allocates a new buffer every time, which is
+        // too slow for capturing every frame.
+        let result = unsafe {
+            sys::SDL_RenderReadPixels(
+                self.canvas.raw(),
+                rect.as_ref().map_or(ptr::null(), |r| r.raw()),
+                FORMAT as u32,
+                pixels.as_mut_ptr() as *mut c_void,
+                pitch as c_int,
+            )
+        };
+        self.canvas.set_scale(scale.0, scale.1)?;
+        self.canvas.set_viewport(viewport);
+        if result != 0 {
+            return Err(Error::Error(sdl2::get_error()));
+        }
+        Ok((width, height))
+    }
+    /// Reads back everything that has been drawn so far this frame,
+    /// in physical pixels.
+    pub(crate) fn read_frame(&mut self) -> Result<Frame> {
+        let mut pixels = Vec::new();
+        let (width, height) = self.read_pixels_into(None, &mut pixels)?;
+        Ok(Frame { width, height, pixels })
+    }
+    /// Returns where a rectangle in drawing coordinates is, clipped to the
+    /// drawing area, along with the physical pixels it covers.
+    fn region_pixels(&self, rect: Rect) -> Result<(Rect, Rect)> {
+        let (width, height) = self.size();
+        let region = rect
+            .intersection(Rect::new(0, 0, width, height))
+            .ok_or_else(|| Error::Error(format!("{:?} is outside of the drawing area", rect)))?;
+        let ((scale_x, scale_y), viewport) = (self.canvas.scale(), self.canvas.viewport());
+        let to_x = |x: i32| ((viewport.x() + x) as f32 * scale_x).round() as i32;
+        let to_y = |y: i32| ((viewport.y() + y) as f32 * scale_y).round() as i32;
+        let (left, top) = (to_x(region.left()), to_y(region.top()));
+        let (right, bottom) = (to_x(region.right()), to_y(region.bottom()));
+        let pixels = Rect::new(left, top, (right - left).max(1) as u32, (bottom - top).max(1) as u32);
+        Ok((region, pixels))
+    }
+    /// Reads back a rectangle in drawing coordinates, clipped to the drawing
+    /// area, into `pixels`. What is read has the size of the clipped
+    /// rectangle even when the drawing is scaled up on the screen, and
+    /// that size is returned.
+    fn read_region_into(&mut self, rect: Rect, pixels: &mut Vec<u8>) -> Result<(u32, u32)> {
+        let (region, physical) = self.region_pixels(rect)?;
+        let (width, height) = self.read_pixels_into(Some(physical), pixels)?;
+        if (width, height) != (region.width(), region.height()) {
+            let frame = Frame {
+                width,
+                height,
+                pixels: mem::take(pixels),
+            };
+            *pixels = frame.resized(region.width(), region.height()).pixels;
+        }
+        Ok((region.width(), region.height()))
+    }
+    /// Reads back a rectangle in drawing coordinates, like read_region_into.
+    pub(crate) fn read_region(&mut self, rect: Rect) -> Result<Frame> {
+        let mut pixels = Vec::new();
+        let (width, height) = self.read_region_into(rect, &mut pixels)?;
+        Ok(Frame { width, height, pixels })
+    }
+    /// Copies a rectangle of the drawing, or all of it, into a texture.
+    /// The texture in `slot` is reused if it has the right size, and is
+    /// left alone if reading the pixels fails.
+    fn capture_texture(&mut self, rect: Option<Rect>, slot: &mut Option<Texture<'static>>) -> Result<()> {
+        let (width, height) = self.size();
+        let rect = rect.unwrap_or_else(|| Rect::new(0, 0, width, height));
+        let mut pixels = mem::take(&mut self.capture_buffer);
+        let result = self.read_region_into(rect, &mut pixels).and_then(|(width, height)| {
+            let reusable = match *slot {
+                Some(ref texture) => {
+                    let query = texture.query();
+                    (query.width, query.height) == (width, height)
+                }
+                None => false,
+            };
+            if !reusable {
+                let texture = self.texture_creator.create_texture_static(FORMAT, width, height)?;
+                *slot = Some(into_static(texture));
+            }
+            if let Some(ref mut texture) = *slot {
+                let pitch = width as usize * BYTES_PER_PIXEL;
+                texture
+                    .update(None, &pixels, pitch)
+                    .map_err(|e| Error::Error(e.to_string()))?;
+            }
+            Ok(())
+        });
+        self.capture_buffer = pixels;
+        result
+    }
+    /// Copies everything drawn so far this frame into a new image, which
+    /// can be drawn like any other image.
+    ///
+    /// The image has the size of the drawing area, even when the drawing
+    /// is scaled up on the screen. To capture every frame, create the image
+    /// once and update it with `capture_into`, which reuses its pixels.
+    pub fn capture(&mut self) -> Result<Image> {
+        let mut texture = None;
+        self.capture_texture(None, &mut texture)?;
+        Ok(self.push_texture(texture))
+    }
+    /// Copies a rectangle of everything drawn so far this frame into a new
+    /// image. The rectangle is in drawing coordinates and is clipped to the
+    /// drawing area, like in `save_region`.
+    pub fn capture_region(&mut self, rect: Rect) -> Result<Image> {
+        let mut texture = None;
+        self.capture_texture(Some(rect), &mut texture)?;
+        Ok(self.push_texture(texture))
+    }
+    /// Copies everything drawn so far this frame, or a rectangle of it,
+    /// into an image that was created by `capture` or `capture_region`.
+    /// Handles to the image see the new pixels, and the old pixels are kept
+    /// if capturing fails.
+    ///
+    /// `examples/feedback.rs` is a runnable version of this example.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State { feedback: Option<Image> }
+    /// // Video feedback: each frame is the last one, shrunk and turned a
+    /// // little, with a new square on top.
+    /// let builder = DoodleBuilder::<State>::new().draw(Box::new(|state, r| {
+    ///     let (width, height) = r.size();
+    ///     r.clear();
+    ///     if let Some(feedback) = state.feedback {
+    ///         // 99% of the size, turned 1 degree.
+    ///         let (w, h) = (width * 99 / 100, height * 99 / 100);
+    ///         let inside = Rect::new(((width - w) / 2) as i32, ((height - h) / 2) as i32, w, h);
+    ///         r.draw_image_ex(feedback, inside, 1.0, (0.5, 0.5), false, false);
+    ///     }
+    ///     r.fill_rect(380.0, 280.0, 40.0, 40.0);
+    ///     match state.feedback {
+    ///         Some(feedback) => r.capture_into(feedback, None).unwrap(),
+    ///         None => state.feedback = Some(r.capture().unwrap()),
+    ///     }
+    /// }));
+    /// ```
+    pub fn capture_into(&mut self, image: Image, rect: Option<Rect>) -> Result<()> {
+        let mut texture = self.take_texture(image)?;
+        let result = self.capture_texture(rect, &mut texture);
+        self.replace_texture(image, texture);
+        result
+    }
+    /// Saves a rectangle of everything that has been drawn so far this frame
+    /// as a PNG file.
+    ///
+    /// The rectangle is in drawing coordinates, so with a logical size set
+    /// it covers the same part of the drawing however big the window is,
+    /// and the image is always the size of the rectangle. Parts of the
+    /// rectangle outside of the drawing area are cut off, and a rectangle
+    /// that is completely outside of it is an error.
+    pub fn save_region<P: AsRef<Path>>(&mut self, rect: Rect, path: P) -> Result<()> {
+        self.read_region(rect)?.save_png(path)
+    }
+    /// Saves everything that has been drawn so far this frame as a PNG file.
+    ///
+    /// The image has the size of the drawing area, like `save_region` with
+    /// a rectangle covering all of it.
+    pub fn save_frame<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let (width, height) = self.size();
+        self.save_region(Rect::new(0, 0, width, height), path)
+    }
+    /// Saves everything that has been drawn so far this frame as a PNG file
+    /// named after the frame count, and returns the path it was saved to.
+    ///
+    /// The frame count is added to `prefix` with five digits, so saving
+    /// every frame with the prefix `"frames/spiral-"` gives
+    /// `frames/spiral-00001.png`, `frames/spiral-00002.png` and so on, which
+    /// sort in order and can be turned into a video.
+    pub fn save_frame_sequence<P: AsRef<Path>>(&mut self, prefix: P) -> Result<PathBuf> {
+        let mut path = prefix.as_ref().as_os_str().to_owned();
+        path.push(format!("{:05}.png", self.frame_count()));
+        let path = PathBuf::from(path);
+        self.save_frame(&path)?;
+        Ok(path)
+    }
+}
@@ -0,0 +1,33 @@
+//! Copying text to and from the system clipboard.
+
+use {Error, Renderer, Result};
+
+impl Renderer {
+    /// Returns the text on the clipboard, or an empty string if there is
+    /// none.
+    pub fn clipboard_text(&self) -> Result<String> {
+        self.canvas.window().subsystem().clipboard().clipboard_text().map_err(Error::Error)
+    }
+    /// Puts text on the clipboard, so it can be pasted into other programs.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State { seed: u64 }
+    /// // Ctrl-C copies the seed of the current drawing so it can be shared.
+    /// let builder = DoodleBuilder::<State>::new().on_key_down(Box::new(|state, r, key| {
+    ///     let ctrl = r.is_key_down(Keycode::LCtrl) || r.is_key_down(Keycode::RCtrl);
+    ///     if ctrl && key == Keycode::C {
+    ///         r.set_clipboard_text(&state.seed.to_string()).unwrap();
+    ///     }
+    /// }));
+    /// ```
+    pub fn set_clipboard_text(&mut self, text: &str) -> Result<()> {
+        self.canvas.window().subsystem().clipboard().set_clipboard_text(text).map_err(Error::Error)
+    }
+    /// Returns true if there is text on the clipboard.
+    #[inline]
+    pub fn has_clipboard_text(&self) -> bool {
+        self.canvas.window().subsystem().clipboard().has_clipboard_text()
+    }
+}
@@ -0,0 +1,104 @@
+//! Making colors from hue and hex codes, and mixing them.
+
+use {Color, Error, Result};
+
+/// Extra ways of making and changing colors.
+///
+/// `Color` comes from SDL, so these are added by a trait, which
+/// `use doodle::*` brings in along with `Color`.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// # fn draw(r: &mut Renderer) {
+/// // A rainbow that slowly cycles through the hues.
+/// let t = r.elapsed_seconds() as f32;
+/// for i in 0..36 {
+///     let hue = i as f32 * 10.0 + t * 30.0;
+///     r.set_draw_color(Color::hsb(hue, 0.8, 1.0));
+///     r.fill_rect(i as f32 * 20.0, 0.0, 20.0, 200.0);
+/// }
+/// # }
+/// ```
+pub trait ColorExt: Sized {
+    /// Makes a color from a hue in degrees, and a saturation and
+    /// brightness between 0 and 1. Hues outside of 0 to 360 wrap around,
+    /// so a hue that keeps growing cycles through the colors.
+    fn hsb(hue: f32, saturation: f32, brightness: f32) -> Self;
+    /// Makes a color from a hue in degrees, and a saturation and lightness
+    /// between 0 and 1. A lightness of 0.5 gives the most colorful colors,
+    /// with 0 being black and 1 white.
+    fn hsl(hue: f32, saturation: f32, lightness: f32) -> Self;
+    /// Parses a CSS-style hex color, like `"#ff8800"`. The `#` is optional,
+    /// and the short `"#f80"` form and an alpha at the end, like
+    /// `"#ff880080"`, can be used too.
+    fn from_hex(hex: &str) -> Result<Self>;
+    /// Returns the same color with a different alpha.
+    fn with_alpha(self, alpha: u8) -> Self;
+}
+
+impl ColorExt for Color {
+    fn hsb(hue: f32, saturation: f32, brightness: f32) -> Color {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let brightness = brightness.clamp(0.0, 1.0);
+        let chroma = brightness * saturation;
+        from_hue(hue, chroma, brightness - chroma)
+    }
+    fn hsl(hue: f32, saturation: f32, lightness: f32) -> Color {
+        let saturation = saturation.clamp(0.0, 1.0);
+        let lightness = lightness.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        from_hue(hue, chroma, lightness - chroma / 2.0)
+    }
+    fn from_hex(hex: &str) -> Result<Color> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        let error = || Error::Error(format!("{:?} is not a hex color", hex));
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(error());
+        }
+        let parse = |i: usize, len: usize| {
+            let value = u8::from_str_radix(&digits[i * len..(i + 1) * len], 16).unwrap_or(0);
+            if len == 1 {
+                value * 17
+            } else {
+                value
+            }
+        };
+        match digits.len() {
+            3 => Ok(Color::RGB(parse(0, 1), parse(1, 1), parse(2, 1))),
+            4 => Ok(Color::RGBA(parse(0, 1), parse(1, 1), parse(2, 1), parse(3, 1))),
+            6 => Ok(Color::RGB(parse(0, 2), parse(1, 2), parse(2, 2))),
+            8 => Ok(Color::RGBA(parse(0, 2), parse(1, 2), parse(2, 2), parse(3, 2))),
+            _ => Err(error()),
+        }
+    }
+    #[inline]
+    fn with_alpha(self, alpha: u8) -> Color {
+        Color::RGBA(self.r, self.g, self.b, alpha)
+    }
+}
+
+/// Makes a color from a hue, how much of the hue there is, and how much
+/// gray to add to every channel, all of them between 0 and 1.
+fn from_hue(hue: f32, chroma: f32, gray: f32) -> Color {
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let channel = |c: f32| ((c + gray) * 255.0).round().clamp(0.0, 255.0) as u8;
+    Color::RGB(channel(r), channel(g), channel(b))
+}
+
+/// Mixes two colors, giving `a` when `t` is 0 and `b` when it is 1.
+/// Alpha is mixed too, and `t` is clamped to between 0 and 1.
+pub fn lerp_color(a: Color, b: Color, t: f32) -> Color {
+    let t = t.clamp(0.0, 1.0);
+    let mix = |a: u8, b: u8| (f32::from(a) + (f32::from(b) - f32::from(a)) * t).round() as u8;
+    Color::RGBA(mix(a.r, b.r), mix(a.g, b.g), mix(a.b, b.b), mix(a.a, b.a))
+}
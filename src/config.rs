@@ -0,0 +1,276 @@
+//! Loading doodle settings from a config file and the environment.
+
+use std::borrow::Cow;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use {DoodleBuilder, Error, Result};
+
+/// The environment variables that override config settings, and the
+/// setting each one overrides.
+const ENV_VARS: [(&str, &str); 6] = [
+    ("DOODLE_TITLE", "title"),
+    ("DOODLE_WIDTH", "width"),
+    ("DOODLE_HEIGHT", "height"),
+    ("DOODLE_FPS", "fps"),
+    ("DOODLE_FULLSCREEN", "fullscreen"),
+    ("DOODLE_VSYNC", "vsync"),
+];
+
+/// The settings found in a config file or the environment.
+/// Settings that weren't given are None.
+#[derive(Debug, Default)]
+pub(crate) struct Config {
+    title: Option<String>,
+    width: Option<u32>,
+    height: Option<u32>,
+    fps: Option<u32>,
+    fullscreen: Option<bool>,
+    vsync: Option<bool>,
+}
+
+impl Config {
+    /// Parses the contents of a config file.
+    ///
+    /// The file is a small subset of TOML: one `key = value` per line,
+    /// with `#` comments, quoted strings, whole numbers and booleans.
+    pub(crate) fn parse(text: &str) -> Result<Config> {
+        let mut config = Config::default();
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = match line.find('=') {
+                Some(eq) => (line[..eq].trim(), line[eq + 1..].trim()),
+                None if line.starts_with('[') => {
+                    return Err(config_error(line_number, line, "tables are not supported"))
+                }
+                None => return Err(config_error(line_number, line, "expected `key = value`")),
+            };
+            if config.is_set(key) {
+                return Err(config_error(line_number, key, "is set more than once"));
+            }
+            config
+                .set(key, value, true)
+                .map_err(|message| config_error(line_number, key, message))?;
+        }
+        Ok(config)
+    }
+    /// Reads the `DOODLE_*` environment variables.
+    pub(crate) fn from_env() -> Result<Config> {
+        let mut config = Config::default();
+        for &(var, key) in &ENV_VARS {
+            let value = match env::var(var) {
+                Ok(value) => value,
+                Err(env::VarError::NotPresent) => continue,
+                Err(env::VarError::NotUnicode(_)) => {
+                    return Err(config_error(0, var, "is not valid unicode"))
+                }
+            };
+            config
+                .set(key, value.trim(), false)
+                .map_err(|message| config_error(0, var, message))?;
+        }
+        Ok(config)
+    }
+    fn is_set(&self, key: &str) -> bool {
+        match key {
+            "title" => self.title.is_some(),
+            "width" => self.width.is_some(),
+            "height" => self.height.is_some(),
+            "fps" => self.fps.is_some(),
+            "fullscreen" => self.fullscreen.is_some(),
+            "vsync" => self.vsync.is_some(),
+            _ => false,
+        }
+    }
+    /// Sets one setting from its text. Strings have to be quoted in config
+    /// files, but not in environment variables.
+    fn set(&mut self, key: &str, value: &str, quoted: bool) -> ::std::result::Result<(), &'static str> {
+        match key {
+            "title" => {
+                self.title = Some(if quoted {
+                    parse_string(value)?
+                } else {
+                    value.to_owned()
+                })
+            }
+            "width" => self.width = Some(parse_size(value)?),
+            "height" => self.height = Some(parse_size(value)?),
+            "fps" => self.fps = Some(parse_size(value)?),
+            "fullscreen" => self.fullscreen = Some(parse_bool(value)?),
+            "vsync" => self.vsync = Some(parse_bool(value)?),
+            _ => return Err("is not a known setting"),
+        }
+        Ok(())
+    }
+    /// Overwrites the builder's settings with the ones that were given.
+    pub(crate) fn apply<'a, T>(self, mut builder: DoodleBuilder<'a, T>) -> DoodleBuilder<'a, T> {
+        if let Some(title) = self.title {
+            builder.name = Cow::Owned(title);
+        }
+        if let Some(width) = self.width {
+            builder.width = width;
+        }
+        if let Some(height) = self.height {
+            builder.height = height;
+        }
+        if let Some(fps) = self.fps {
+            builder.fps = fps;
+        }
+        if let Some(fullscreen) = self.fullscreen {
+            builder = builder.fullscreen(fullscreen);
+        }
+        if let Some(vsync) = self.vsync {
+            builder.vsync = vsync;
+        }
+        builder
+    }
+}
+
+/// Returns the `DOODLE_*` environment variables that are set.
+pub(crate) fn set_env_vars() -> Vec<&'static str> {
+    ENV_VARS
+        .iter()
+        .map(|&(var, _)| var)
+        .filter(|var| env::var_os(var).is_some())
+        .collect()
+}
+
+pub(crate) fn config_error(line: usize, key: &str, message: &str) -> Error {
+    Error::ConfigError {
+        line,
+        key: key.to_owned(),
+        message: message.to_owned(),
+    }
+}
+
+/// Removes a `#` comment from the end of a line, ignoring `#`s in strings.
+pub(crate) fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => (),
+        }
+    }
+    line
+}
+
+pub(crate) fn parse_string(value: &str) -> ::std::result::Result<String, &'static str> {
+    if value.len() < 2 || !value.starts_with('"') || !value.ends_with('"') {
+        return Err("must be a quoted string");
+    }
+    let mut string = String::new();
+    let mut chars = value[1..value.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('"') => string.push('"'),
+                Some('\\') => string.push('\\'),
+                Some('n') => string.push('\n'),
+                Some('t') => string.push('\t'),
+                _ => return Err("contains an unsupported escape sequence"),
+            },
+            '"' => return Err("must be a quoted string"),
+            c => string.push(c),
+        }
+    }
+    Ok(string)
+}
+
+fn parse_size(value: &str) -> ::std::result::Result<u32, &'static str> {
+    match value.replace('_', "").parse() {
+        Ok(0) | Err(_) => Err("must be a whole number greater than 0"),
+        Ok(n) => Ok(n),
+    }
+}
+
+pub(crate) fn parse_bool(value: &str) -> ::std::result::Result<bool, &'static str> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err("must be true or false"),
+    }
+}
+
+impl<'a, T> DoodleBuilder<'a, T> {
+    /// Changes the builder's settings to the ones in a config file, like
+    /// `from_config` does. This is how a builder made with `with_state`
+    /// can use a config file.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # struct Editor { path: String }
+    /// let doodle = DoodleBuilder::with_state(Editor { path: "map.txt".to_owned() })
+    ///     .config("doodle.toml")
+    ///     .unwrap()
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn config<P: AsRef<Path>>(self, path: P) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Ok(Config::parse(&text)?.apply(self).env_overrides(true))
+    }
+}
+
+impl<'a, T: Default> DoodleBuilder<'a, T> {
+    /// Creates a DoodleBuilder with the settings in a config file.
+    ///
+    /// The file can set `title`, `width`, `height`, `fps`, `fullscreen`
+    /// and `vsync`. Any builder methods called afterwards override the
+    /// values from the file, and the `DOODLE_TITLE`, `DOODLE_WIDTH`,
+    /// `DOODLE_HEIGHT`, `DOODLE_FPS`, `DOODLE_FULLSCREEN` and
+    /// `DOODLE_VSYNC` environment variables override both when the doodle
+    /// is built. Use `env_overrides(false)` to ignore the environment.
+    ///
+    /// A file that doesn't exist is an error. Use `from_config_or_default`
+    /// to make the file optional.
+    ///
+    /// # Example
+    /// With a `doodle.toml` like this:
+    ///
+    /// ```toml
+    /// title = "Asteroids"
+    /// width = 1280
+    /// height = 720
+    /// fullscreen = false # players can change this
+    /// vsync = true
+    /// ```
+    ///
+    /// the settings can be loaded with
+    ///
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// let doodle = DoodleBuilder::<State>::from_config("doodle.toml")
+    ///     .unwrap()
+    ///     .fps(60)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    #[inline]
+    pub fn from_config<P: AsRef<Path>>(path: P) -> Result<Self> {
+        DoodleBuilder::new().config(path)
+    }
+    /// Creates a DoodleBuilder with the settings in a config file, like
+    /// `from_config`, but uses the default settings if the file
+    /// doesn't exist.
+    pub fn from_config_or_default<P: AsRef<Path>>(path: P) -> Result<Self> {
+        match DoodleBuilder::from_config(path) {
+            Err(Error::IoError(ref error)) if error.kind() == io::ErrorKind::NotFound => {
+                Ok(DoodleBuilder::new().env_overrides(true))
+            }
+            result => result,
+        }
+    }
+}
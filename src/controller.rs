@@ -0,0 +1,132 @@
+//! Game controllers: reading their sticks and buttons, and rumbling them.
+//!
+//! Controllers are opened as soon as they're plugged in, including the
+//! ones that are already plugged in when the doodle starts. Each one has an
+//! id that stays the same until it's unplugged; plugging it back in gives
+//! it a new one.
+
+use sdl2::controller::{Axis, Button, GameController};
+use sdl2::haptic::Haptic;
+
+use log::Level;
+use {Error, Renderer, Result};
+
+/// A connected game controller, and its rumble motors if it has any.
+pub(crate) struct Controller {
+    controller: GameController,
+    haptic: Option<Haptic>,
+}
+
+impl Controller {
+    #[inline]
+    pub(crate) fn id(&self) -> i32 {
+        self.controller.instance_id()
+    }
+}
+
+impl Renderer {
+    /// Starts receiving events from a newly connected game controller,
+    /// returning its id.
+    pub(crate) fn open_controller(&mut self, index: u32) -> Option<i32> {
+        match self.controller_subsystem.open(index) {
+            Ok(controller) => {
+                let haptic = self
+                    .haptic_subsystem
+                    .as_ref()
+                    .and_then(|haptic| haptic.open_from_joystick_id(index as i32).ok());
+                let id = controller.instance_id();
+                self.controllers.push(Controller { controller, haptic });
+                Some(id)
+            }
+            Err(error) => {
+                self.logger
+                    .log(Level::Warn, || format!("couldn't open game controller {}: {}", index, error));
+                None
+            }
+        }
+    }
+    /// Forgets a controller that was unplugged.
+    pub(crate) fn close_controller(&mut self, id: i32) {
+        self.controllers.retain(|c| c.id() != id);
+    }
+    fn controller(&self, id: i32) -> Option<&Controller> {
+        self.controllers.iter().find(|c| c.id() == id)
+    }
+    /// Returns the ids of the connected game controllers, in the order they
+    /// were plugged in.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # struct Player { x: f32, y: f32 }
+    /// # fn draw(players: &mut Vec<Player>, r: &mut Renderer) {
+    /// // Everyone with a controller gets a player.
+    /// let speed = 200.0 * r.delta_seconds() as f32;
+    /// for (player, id) in players.iter_mut().zip(r.controllers()) {
+    ///     player.x += r.controller_axis(id, Axis::LeftX) * speed;
+    ///     player.y += r.controller_axis(id, Axis::LeftY) * speed;
+    /// }
+    /// # }
+    /// ```
+    ///
+    /// While input is being replayed, these are the controllers the replay
+    /// has had input from so far instead, and the other functions that
+    /// read controllers answer from the replay too.
+    pub fn controllers(&self) -> Vec<i32> {
+        if let Some(controllers) = self.replayed_controllers() {
+            return controllers;
+        }
+        self.controllers.iter().map(Controller::id).collect()
+    }
+    /// Returns the name of a controller, or None if it isn't connected.
+    pub fn controller_name(&self, id: i32) -> Option<String> {
+        self.controller(id).map(|c| c.controller.name())
+    }
+    /// Returns how far an axis of a controller is pushed, between -1 and 1.
+    /// Sticks are 0 in the middle and positive to the right and down, and
+    /// triggers go from 0 to 1. A controller that isn't connected is always
+    /// 0.
+    ///
+    /// Sticks rarely rest exactly in the middle, so values closer to 0 than
+    /// `AXIS_DEADZONE` should usually be ignored. Actions bound to axes do
+    /// that already.
+    pub fn controller_axis(&self, id: i32, axis: Axis) -> f32 {
+        let value = match self.replayed_controller_axis(id, axis) {
+            Some(value) => value,
+            None => match self.controller(id) {
+                Some(c) => c.controller.axis(axis),
+                None => 0,
+            },
+        };
+        (f32::from(value) / 32767.0).max(-1.0)
+    }
+    /// Returns true if a button of a controller is being held.
+    pub fn controller_button(&self, id: i32, button: Button) -> bool {
+        match self.replayed_controller_button(id, button) {
+            Some(held) => held,
+            None => self.controller(id).is_some_and(|c| c.controller.button(button)),
+        }
+    }
+    /// Rumbles a controller at `strength`, between 0 and 1, for `millis`
+    /// milliseconds.
+    ///
+    /// Controllers without rumble motors, and controllers SDL can't rumble
+    /// on this platform, return `Error::Unsupported`.
+    pub fn rumble(&mut self, id: i32, strength: f32, millis: u32) -> Result<()> {
+        let controller = self
+            .controllers
+            .iter_mut()
+            .find(|c| c.id() == id)
+            .ok_or_else(|| Error::Error(format!("there is no game controller with id {}", id)))?;
+        match controller.haptic {
+            Some(ref mut haptic) => {
+                haptic.rumble_play(strength.clamp(0.0, 1.0), millis);
+                Ok(())
+            }
+            None => Err(Error::Unsupported(format!(
+                "{} can't rumble",
+                controller.controller.name()
+            ))),
+        }
+    }
+}
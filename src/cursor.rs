@@ -0,0 +1,150 @@
+//! Hiding the mouse cursor, changing what it looks like, and keeping the
+//! mouse inside the window.
+
+use std::path::Path;
+
+use sdl2::image::LoadSurface;
+use sdl2::mouse::{Cursor, SystemCursor};
+use sdl2::surface::Surface;
+
+use {Error, Image, PixelBuffer, Renderer, Result};
+
+impl Renderer {
+    /// Hides the mouse cursor while it's over the window. The mouse still
+    /// moves and clicks as usual.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// // A fullscreen installation with nothing in the way.
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .fullscreen(true)
+    ///     .setup(Box::new(|_, r| r.hide_cursor()));
+    /// ```
+    #[inline]
+    pub fn hide_cursor(&mut self) {
+        self._sdl.mouse().show_cursor(false);
+    }
+    /// Shows the mouse cursor again after `hide_cursor`.
+    #[inline]
+    pub fn show_cursor(&mut self) {
+        self._sdl.mouse().show_cursor(true);
+    }
+    /// Returns true if the mouse cursor is shown.
+    #[inline]
+    pub fn is_cursor_shown(&self) -> bool {
+        self._sdl.mouse().is_cursor_showing()
+    }
+    /// Changes the mouse cursor to one of the system's own, like
+    /// `SystemCursor::Crosshair` or `SystemCursor::Hand`.
+    /// `SystemCursor::Arrow` puts back the usual one.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// // Show a hand over the button.
+    /// let button = Rect::new(20, 20, 120, 40);
+    /// let builder = DoodleBuilder::<State>::new().on_mouse_move(Box::new(move |_, r, x, y, _, _| {
+    ///     let kind = if button.contains_point((x, y)) {
+    ///         SystemCursor::Hand
+    ///     } else {
+    ///         SystemCursor::Arrow
+    ///     };
+    ///     r.set_system_cursor(kind).unwrap();
+    /// }));
+    /// ```
+    pub fn set_system_cursor(&mut self, kind: SystemCursor) -> Result<()> {
+        let cursor = Cursor::from_system(kind).map_err(Error::Error)?;
+        self.use_cursor(cursor);
+        Ok(())
+    }
+    /// Changes the mouse cursor to an image. The hotspot is the pixel of
+    /// the image, counted from its top left corner, that points at where
+    /// the mouse is.
+    ///
+    /// The image's pixels are copied, so changing or unloading it later
+    /// doesn't change the cursor.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn setup(r: &mut Renderer) -> Result<()> {
+    /// // The tip of the brush is at its bottom left corner.
+    /// let brush = r.load_image("assets/brush.png")?;
+    /// let (_, height) = r.image_size(brush);
+    /// r.set_cursor_image(brush, (0, height as i32 - 1))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_cursor_image(&mut self, image: Image, hotspot: (i32, i32)) -> Result<()> {
+        let pixels = self.image_pixels(image)?;
+        self.set_cursor_pixels(&pixels, hotspot)
+    }
+    /// Changes the mouse cursor to an image file, without loading it as an
+    /// `Image` first. The hotspot works like it does for `set_cursor_image`.
+    pub fn set_cursor_file<P: AsRef<Path>>(&mut self, path: P, hotspot: (i32, i32)) -> Result<()> {
+        let surface = Surface::from_file(path)?;
+        self.set_cursor_surface(&surface, hotspot)
+    }
+    /// Changes the mouse cursor to a pixel buffer, so it can be drawn by
+    /// the doodle. The hotspot works like it does for `set_cursor_image`.
+    pub fn set_cursor_pixels(&mut self, cursor: &PixelBuffer, hotspot: (i32, i32)) -> Result<()> {
+        let surface = cursor.to_surface()?;
+        self.set_cursor_surface(&surface, hotspot)
+    }
+    fn set_cursor_surface(&mut self, surface: &Surface, (x, y): (i32, i32)) -> Result<()> {
+        let cursor = Cursor::from_surface(surface, x, y).map_err(Error::Error)?;
+        self.use_cursor(cursor);
+        Ok(())
+    }
+    /// Switches to a cursor, keeping it because SDL only borrows it.
+    fn use_cursor(&mut self, cursor: Cursor) {
+        cursor.set();
+        self.cursor = Some(cursor);
+    }
+    /// Turns relative mouse mode on or off. While it's on, the cursor is
+    /// hidden and held in place, and the mouse keeps reporting how far it
+    /// moved even where the edge of the window or screen would have
+    /// stopped it, which is what looking around a first person camera or
+    /// dragging a slider forever needs.
+    ///
+    /// Returns `Error::Unsupported` if the platform can't do it.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct Camera { yaw: f32, pitch: f32 }
+    /// let builder = DoodleBuilder::<Camera>::new()
+    ///     .setup(Box::new(|_, r| r.set_relative_mouse(true).unwrap()))
+    ///     .on_mouse_move(Box::new(|camera, _, _, _, dx, dy| {
+    ///         camera.yaw += dx as f32 * 0.005;
+    ///         camera.pitch = (camera.pitch - dy as f32 * 0.005).clamp(-1.5, 1.5);
+    ///     }));
+    /// ```
+    pub fn set_relative_mouse(&mut self, on: bool) -> Result<()> {
+        self._sdl.mouse().set_relative_mouse_mode(on);
+        if self.is_relative_mouse() != on {
+            return Err(Error::Unsupported("relative mouse mode isn't supported here".to_owned()));
+        }
+        Ok(())
+    }
+    /// Returns true if relative mouse mode is on.
+    #[inline]
+    pub fn is_relative_mouse(&self) -> bool {
+        self._sdl.mouse().relative_mouse_mode()
+    }
+    /// Confines the mouse to the window, or lets it leave again. Unlike
+    /// relative mode the cursor stays visible and moves as usual, it just
+    /// stops at the edges of the window.
+    #[inline]
+    pub fn set_mouse_grab(&mut self, grab: bool) {
+        self.canvas.window_mut().set_grab(grab);
+    }
+    /// Returns true if the mouse is confined to the window.
+    #[inline]
+    pub fn is_mouse_grabbed(&self) -> bool {
+        self.canvas.window().grab()
+    }
+}
@@ -0,0 +1,204 @@
+//! The on-screen debug console, with the frame rate and drawing statistics.
+
+use std::collections::VecDeque;
+use std::fmt::Display;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Texture};
+
+use text::Font;
+use {Renderer, Result};
+
+/// The point size of the text in the overlay.
+const FONT_SIZE: u16 = 14;
+/// The space between the edge of the panel and the text, in pixels.
+const PADDING: i32 = 4;
+/// The most messages that are shown in a single frame.
+const MAX_LINES: usize = 40;
+/// How many of the latest frames the frame time graph shows, one pixel
+/// wide each.
+const GRAPH_FRAMES: usize = 120;
+/// The height of the frame time graph, in pixels. Frames that take as long
+/// as the frame rate allows reach halfway up.
+const GRAPH_HEIGHT: i32 = 40;
+
+/// What was drawn during a frame.
+#[derive(Default)]
+struct FrameStats {
+    draw_calls: u32,
+    texture_switches: u32,
+    /// The texture that was drawn last, to tell when it changes.
+    texture: Option<usize>,
+}
+
+/// Debug output collected during a frame, drawn in the top left corner
+/// of the window on top of everything else.
+#[derive(Default)]
+pub(crate) struct DebugOverlay {
+    pub(crate) visible: bool,
+    lines: Vec<String>,
+    watches: Vec<(String, String)>,
+    stats: FrameStats,
+    /// How long the latest frames took, in seconds, oldest first.
+    frame_times: VecDeque<f64>,
+    /// When the previous frame started.
+    previous_frame: Option<f64>,
+}
+
+impl DebugOverlay {
+    /// Counts a call to SDL that draws something other than a texture.
+    #[inline]
+    pub(crate) fn count_draw(&mut self) {
+        self.stats.draw_calls += 1;
+    }
+    /// Counts a call to SDL that draws a texture.
+    pub(crate) fn count_copy(&mut self, texture: &Texture) {
+        self.stats.draw_calls += 1;
+        let raw = texture.raw() as usize;
+        if self.stats.texture != Some(raw) {
+            self.stats.texture_switches += 1;
+            self.stats.texture = Some(raw);
+        }
+    }
+}
+
+impl Renderer {
+    /// Shows a message in the debug overlay for the current frame.
+    ///
+    /// Messages are cleared after every frame, so a value that should stay
+    /// on screen has to be sent again each frame (or use `debug_watch`).
+    pub fn debug<D: Display>(&mut self, msg: D) {
+        self.debug.lines.push(msg.to_string());
+    }
+    /// Shows `key: value` in the debug overlay until the key is watched again
+    /// with a new value or removed with `clear_debug_watch`.
+    pub fn debug_watch<V: Display>(&mut self, key: &str, value: V) {
+        let value = value.to_string();
+        match self.debug.watches.iter_mut().find(|w| w.0 == key) {
+            Some(watch) => watch.1 = value,
+            None => self.debug.watches.push((key.to_owned(), value)),
+        }
+    }
+    /// Stops showing a watched value in the debug overlay.
+    pub fn clear_debug_watch(&mut self, key: &str) {
+        self.debug.watches.retain(|w| w.0 != key);
+    }
+    /// Shows or hides the debug overlay. It can also be toggled with F3.
+    ///
+    /// Along with the messages and watched values, the overlay shows the
+    /// frame rate, a graph of how long the latest frames took, and how
+    /// many times the frame called SDL to draw and switched to drawing a
+    /// different texture. Frames that stutter stick out of the graph in
+    /// red, and lots of texture switches mean images could be drawn in a
+    /// better order or packed into an atlas.
+    #[inline]
+    pub fn set_debug_overlay(&mut self, visible: bool) {
+        self.debug.visible = visible;
+    }
+    /// Returns true if the debug overlay is being shown.
+    #[inline]
+    pub fn debug_overlay(&self) -> bool {
+        self.debug.visible
+    }
+    /// Draws the debug overlay if it is visible and clears this frame's messages.
+    pub(crate) fn finish_debug_overlay(&mut self) -> Result<()> {
+        let elapsed = self.elapsed_seconds();
+        if let Some(previous) = self.debug.previous_frame.replace(elapsed) {
+            if self.debug.frame_times.len() == GRAPH_FRAMES {
+                self.debug.frame_times.pop_front();
+            }
+            self.debug.frame_times.push_back(elapsed - previous);
+        }
+        let result = if self.debug.visible {
+            self.draw_debug_overlay()
+        } else {
+            Ok(())
+        };
+        self.debug.lines.clear();
+        self.debug.stats = FrameStats::default();
+        result
+    }
+    fn draw_debug_overlay(&mut self) -> Result<()> {
+        let last_frame = self.debug.frame_times.back().cloned().unwrap_or(0.0);
+        let mut lines = vec![
+            format!("{:.1} fps, {:.1} ms", self.current_fps(), last_frame * 1000.0),
+            format!(
+                "{} draw calls, {} texture switches",
+                self.debug.stats.draw_calls, self.debug.stats.texture_switches
+            ),
+        ];
+        lines.extend(self.debug.watches.iter().map(|(key, value)| format!("{}: {}", key, value)));
+        lines.extend(self.debug.lines.iter().take(MAX_LINES).cloned());
+        if self.debug.lines.len() > MAX_LINES {
+            lines.push(format!("... {} more", self.debug.lines.len() - MAX_LINES));
+        }
+
+        let font = self.default_font(FONT_SIZE)?;
+        let effect = self.text_effect.take();
+        let color = self.canvas.draw_color();
+        let blend_mode = self.canvas.blend_mode();
+        let result = self.draw_debug_lines(font, &lines);
+        self.canvas.set_blend_mode(blend_mode);
+        self.canvas.set_draw_color(color);
+        self.text_effect = effect;
+        result
+    }
+    fn draw_debug_lines(&mut self, font: Font, lines: &[String]) -> Result<()> {
+        let mut width = GRAPH_FRAMES as i32;
+        let mut line_height = 0;
+        for line in lines {
+            let (w, h) = self.measure_text(font, line)?;
+            width = width.max(w as i32);
+            line_height = line_height.max(h as i32);
+        }
+        let height = line_height * lines.len() as i32;
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 160));
+        self.fill_rect(
+            0.0,
+            0.0,
+            (width + 2 * PADDING) as f32,
+            (height + GRAPH_HEIGHT + 3 * PADDING) as f32,
+        );
+        self.draw_frame_graph(PADDING, height + 2 * PADDING);
+        self.canvas.set_draw_color(Color::RGB(255, 255, 255));
+        for (i, line) in lines.iter().enumerate() {
+            let y = PADDING + i as i32 * line_height;
+            self.draw_text(font, line, PADDING as f32, y as f32)?;
+        }
+        Ok(())
+    }
+    /// Draws a bar for each of the latest frames with its top left corner at
+    /// (x, y), green for frames that kept up with the frame rate and red for
+    /// ones that didn't.
+    fn draw_frame_graph(&mut self, x: i32, y: i32) {
+        let target = 1.0 / f64::from(self.frame_rate.max(1));
+        let (mut fast, mut slow) = (Vec::new(), Vec::new());
+        for (i, &time) in self.debug.frame_times.iter().enumerate() {
+            let bar = ((time / target * f64::from(GRAPH_HEIGHT) / 2.0).ceil() as i32).clamp(1, GRAPH_HEIGHT);
+            let rect = Rect::new(x + i as i32, y + GRAPH_HEIGHT - bar, 1, bar as u32);
+            // A little slack keeps jitter in the timer from showing as red.
+            if time > target * 1.2 {
+                slow.push(rect);
+            } else {
+                fast.push(rect);
+            }
+        }
+        for &(color, ref rects) in &[(Color::RGB(80, 200, 80), fast), (Color::RGB(230, 60, 60), slow)] {
+            if !rects.is_empty() {
+                self.canvas.set_draw_color(color);
+                self.draw_call();
+                let result = self.canvas.fill_rects(rects);
+                self.log_failure("draw the frame time graph", result);
+            }
+        }
+        // A line where frames that take as long as the frame rate allows reach.
+        self.canvas.set_draw_color(Color::RGBA(255, 255, 255, 120));
+        let target_y = y + GRAPH_HEIGHT / 2;
+        let width = GRAPH_FRAMES as i32;
+        self.draw_call();
+        let result = self.canvas.draw_line((x, target_y), (x + width - 1, target_y));
+        self.log_failure("draw the frame time graph", result);
+    }
+}
@@ -0,0 +1,109 @@
+//! Display modes for true fullscreen.
+
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::video::{self, FullscreenType};
+
+use log::Level;
+use {Error, Renderer, Result};
+
+/// A resolution and refresh rate that a display supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DisplayMode {
+    /// The width of the mode in pixels.
+    pub width: u32,
+    /// The height of the mode in pixels.
+    pub height: u32,
+    /// The refresh rate of the mode in Hz, or 0 if it's unknown.
+    pub refresh_rate: u32,
+}
+
+impl DisplayMode {
+    fn from_sdl(mode: video::DisplayMode) -> Self {
+        DisplayMode {
+            width: mode.w.max(0) as u32,
+            height: mode.h.max(0) as u32,
+            refresh_rate: mode.refresh_rate.max(0) as u32,
+        }
+    }
+}
+
+impl Renderer {
+    /// Returns the modes supported by the display the window is on, from
+    /// the largest to the smallest.
+    pub fn available_display_modes(&self) -> Result<Vec<DisplayMode>> {
+        let window = self.canvas.window();
+        let video = window.subsystem();
+        let display = window.display_index()?;
+        let count = video.num_display_modes(display)?;
+        let mut modes = Vec::with_capacity(count as usize);
+        for index in 0..count {
+            let mode = DisplayMode::from_sdl(video.display_mode(display, index)?);
+            // Modes that only differ by pixel format show up more than once.
+            if !modes.contains(&mode) {
+                modes.push(mode);
+            }
+        }
+        Ok(modes)
+    }
+    /// Switches the display to the supported mode closest to the given size
+    /// and refresh rate and makes the window truly fullscreen, returning the
+    /// mode that was picked. A refresh rate of 0 picks any rate.
+    ///
+    /// The mode is only closest in SDL's sense: it's the smallest mode that
+    /// is at least as big as the one asked for. If the mode can't be set,
+    /// the window is put back in windowed mode and the error is returned.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn setup(r: &mut Renderer) -> Result<()> {
+    /// // Chunky pixels, the old-school way.
+    /// let mode = r.set_display_mode(640, 480, 60)?;
+    /// println!("running at {}x{}", mode.width, mode.height);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_display_mode(&mut self, width: u32, height: u32, refresh_rate: u32) -> Result<DisplayMode> {
+        let wanted = video::DisplayMode::new(
+            PixelFormatEnum::Unknown,
+            width as i32,
+            height as i32,
+            refresh_rate as i32,
+        );
+        let mode = {
+            let window = self.canvas.window();
+            let display = window.display_index()?;
+            window
+                .subsystem()
+                .closest_display_mode(display, &wanted)
+                .map_err(|e| Error::Error(format!("no display mode close to {}x{}: {}", width, height, e)))?
+        };
+        let picked = DisplayMode::from_sdl(mode);
+        if (picked.width, picked.height) != (width, height)
+            || (refresh_rate != 0 && picked.refresh_rate != refresh_rate)
+        {
+            self.logger.log(Level::Info, || {
+                format!(
+                    "asked for a {}x{} display mode at {} Hz, using {}x{} at {} Hz",
+                    width, height, refresh_rate, picked.width, picked.height, picked.refresh_rate
+                )
+            });
+        }
+        let result = {
+            let window = self.canvas.window_mut();
+            window
+                .set_display_mode(mode)
+                .and_then(|_| window.set_fullscreen(FullscreenType::True))
+        };
+        if let Err(error) = result {
+            self.logger.log(Level::Warn, || {
+                format!("couldn't switch to {}x{}, going back to windowed: {}", picked.width, picked.height, error)
+            });
+            let result = self.canvas.window_mut().set_fullscreen(FullscreenType::Off);
+            self.log_failure("leave fullscreen", result);
+            return Err(Error::Error(error));
+        }
+        self.dirty = true;
+        Ok(picked)
+    }
+}
@@ -0,0 +1,47 @@
+//! Putting asset files inside the executable, so a doodle can be handed
+//! out as a single file.
+
+/// Includes the contents of an asset file in the executable, as a
+/// `&'static [u8]` for `Renderer::load_image_bytes` or
+/// `Renderer::load_font_bytes`.
+///
+/// The path is relative to the directory of the crate's Cargo.toml, which
+/// is where `cargo run` runs a doodle from, so the same path works with
+/// `embed!` and with `load_image` or `load_font` while developing. Like
+/// `include_bytes!`, the file is read when the doodle is compiled, and it
+/// has to exist then.
+///
+/// # Example
+/// ```no_run
+/// #[macro_use]
+/// extern crate doodle;
+/// use doodle::*;
+///
+/// #[derive(Default)]
+/// struct State {
+///     title: Option<Font>,
+/// }
+///
+/// fn main() {
+///     DoodleBuilder::<State>::new()
+///         .setup(Box::new(|state, r| {
+///             state.title = r.load_font_bytes(embed!("assets/DejaVuSansMono.ttf"), 48).ok();
+///         }))
+///         .draw(Box::new(|state, r| {
+///             r.clear();
+///             if let Some(title) = state.title {
+///                 r.draw_text(title, "Embedded", 20.0, 20.0).unwrap();
+///             }
+///         }))
+///         .build()
+///         .unwrap()
+///         .run()
+///         .unwrap();
+/// }
+/// ```
+#[macro_export]
+macro_rules! embed {
+    ($path:expr) => {
+        &include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path))[..]
+    };
+}
@@ -0,0 +1,373 @@
+//! Shapes for hit-testing: rectangles, circles, line segments and polygons
+//! that can be checked for overlaps and for containing points.
+//!
+//! These are in floating point drawing coordinates, unlike the `Rect` at
+//! the top of the crate, which is SDL's rectangle of whole pixels. Points on
+//! the edge of a shape count as inside it, so shapes that touch intersect.
+//!
+//! # Example
+//! ```no_run
+//! # use doodle::*;
+//! use doodle::geometry::{Circle, Rect as Bounds};
+//! # struct Ball { position: Vec2, velocity: Vec2 }
+//! # fn update(ball: &mut Ball, paddle: Bounds) {
+//! let circle = Circle::new(ball.position, 8.0);
+//! if circle.intersects_rect(&paddle) && ball.velocity.y > 0.0 {
+//!     ball.velocity.y = -ball.velocity.y;
+//! }
+//! # }
+//! ```
+
+use sdl2::rect;
+
+use Vec2;
+
+/// How far apart, in drawing coordinates, points can be and still count as
+/// touching, so rounding errors don't decide whether a point is on an edge.
+const EPSILON: f32 = 1e-4;
+
+/// An axis-aligned rectangle, given by its top left corner and its size.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    /// The left edge.
+    pub x: f32,
+    /// The top edge.
+    pub y: f32,
+    /// The width, which shouldn't be negative.
+    pub width: f32,
+    /// The height, which shouldn't be negative.
+    pub height: f32,
+}
+
+impl Rect {
+    /// Creates a rectangle from its top left corner and its size.
+    #[inline]
+    pub fn new(x: f32, y: f32, width: f32, height: f32) -> Rect {
+        Rect { x, y, width, height }
+    }
+    /// Creates a rectangle of the given size centered on a point.
+    #[inline]
+    pub fn from_center(center: Vec2, width: f32, height: f32) -> Rect {
+        Rect::new(center.x - width / 2.0, center.y - height / 2.0, width, height)
+    }
+    /// Returns the smallest rectangle that holds all of the points, or None
+    /// if there aren't any.
+    pub fn bounding(points: &[Vec2]) -> Option<Rect> {
+        let first = *points.first()?;
+        let (min, max) = points.iter().fold((first, first), |(min, max), p| {
+            (Vec2::new(min.x.min(p.x), min.y.min(p.y)), Vec2::new(max.x.max(p.x), max.y.max(p.y)))
+        });
+        Some(Rect::new(min.x, min.y, max.x - min.x, max.y - min.y))
+    }
+    /// Returns the x coordinate of the right edge.
+    #[inline]
+    pub fn right(&self) -> f32 {
+        self.x + self.width
+    }
+    /// Returns the y coordinate of the bottom edge.
+    #[inline]
+    pub fn bottom(&self) -> f32 {
+        self.y + self.height
+    }
+    /// Returns the point in the middle of the rectangle.
+    #[inline]
+    pub fn center(&self) -> Vec2 {
+        Vec2::new(self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+    /// Returns true if the point is inside the rectangle or on its edge.
+    #[inline]
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        point.x >= self.x && point.x <= self.right() && point.y >= self.y && point.y <= self.bottom()
+    }
+    /// Returns true if the rectangles overlap or touch.
+    #[inline]
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x <= other.right()
+            && other.x <= self.right()
+            && self.y <= other.bottom()
+            && other.y <= self.bottom()
+    }
+    /// Returns the part of the rectangles that overlaps, or None if they
+    /// don't. Rectangles that only touch overlap in a rectangle with no
+    /// width or height.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        if !self.intersects(other) {
+            return None;
+        }
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        Some(Rect::new(x, y, self.right().min(other.right()) - x, self.bottom().min(other.bottom()) - y))
+    }
+    /// Returns true if the rectangle and the circle overlap or touch.
+    #[inline]
+    pub fn intersects_circle(&self, circle: &Circle) -> bool {
+        circle.intersects_rect(self)
+    }
+    /// Returns true if any part of the line segment is inside the rectangle
+    /// or on its edge.
+    pub fn intersects_line(&self, line: &Line) -> bool {
+        if self.contains_point(line.start) || self.contains_point(line.end) {
+            return true;
+        }
+        let corners = self.corners();
+        (0..4).any(|i| line.intersects(&Line::new(corners[i], corners[(i + 1) % 4])))
+    }
+    /// Returns the closest point to `point` that is inside the rectangle or
+    /// on its edge.
+    #[inline]
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        Vec2::new(point.x.max(self.x).min(self.right()), point.y.max(self.y).min(self.bottom()))
+    }
+    /// Returns the corners clockwise from the top left.
+    pub fn corners(&self) -> [Vec2; 4] {
+        [
+            Vec2::new(self.x, self.y),
+            Vec2::new(self.right(), self.y),
+            Vec2::new(self.right(), self.bottom()),
+            Vec2::new(self.x, self.bottom()),
+        ]
+    }
+}
+
+impl From<rect::Rect> for Rect {
+    fn from(rect: rect::Rect) -> Rect {
+        Rect::new(rect.x() as f32, rect.y() as f32, rect.width() as f32, rect.height() as f32)
+    }
+}
+
+/// A circle, given by its center and radius.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Circle {
+    /// The point in the middle.
+    pub center: Vec2,
+    /// The distance from the center to the edge.
+    pub radius: f32,
+}
+
+impl Circle {
+    /// Creates a circle from its center and radius.
+    #[inline]
+    pub fn new(center: Vec2, radius: f32) -> Circle {
+        Circle { center, radius }
+    }
+    /// Returns true if the point is inside the circle or on its edge.
+    #[inline]
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        (point - self.center).length_squared() <= self.radius * self.radius
+    }
+    /// Returns true if the circles overlap or touch.
+    #[inline]
+    pub fn intersects(&self, other: &Circle) -> bool {
+        let reach = self.radius + other.radius;
+        (other.center - self.center).length_squared() <= reach * reach
+    }
+    /// Returns true if the circle and the rectangle overlap or touch.
+    #[inline]
+    pub fn intersects_rect(&self, rect: &Rect) -> bool {
+        self.contains_point(rect.closest_point(self.center))
+    }
+    /// Returns true if any part of the line segment is inside the circle
+    /// or on its edge.
+    #[inline]
+    pub fn intersects_line(&self, line: &Line) -> bool {
+        self.contains_point(line.closest_point(self.center))
+    }
+}
+
+/// A line segment between two points.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Line {
+    /// One end of the segment.
+    pub start: Vec2,
+    /// The other end of the segment.
+    pub end: Vec2,
+}
+
+/// Returns the z part of the cross product of two vectors, which is
+/// positive when `b` is clockwise of `a` on the screen.
+#[inline]
+fn cross(a: Vec2, b: Vec2) -> f32 {
+    a.x * b.y - a.y * b.x
+}
+
+impl Line {
+    /// Creates a line segment between two points.
+    #[inline]
+    pub fn new(start: Vec2, end: Vec2) -> Line {
+        Line { start, end }
+    }
+    /// Returns the length of the segment.
+    #[inline]
+    pub fn length(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+    /// Returns the point on the segment that is closest to `point`.
+    pub fn closest_point(&self, point: Vec2) -> Vec2 {
+        let direction = self.end - self.start;
+        let length_squared = direction.length_squared();
+        if length_squared == 0.0 {
+            return self.start;
+        }
+        let t = ((point - self.start).dot(direction) / length_squared).clamp(0.0, 1.0);
+        self.start + direction * t
+    }
+    /// Returns the distance from `point` to the closest point on the
+    /// segment.
+    #[inline]
+    pub fn distance_to_point(&self, point: Vec2) -> f32 {
+        point.distance(self.closest_point(point))
+    }
+    /// Returns true if the point is on the segment, give or take rounding
+    /// errors.
+    #[inline]
+    pub fn contains_point(&self, point: Vec2) -> bool {
+        self.distance_to_point(point) <= EPSILON
+    }
+    /// Returns the point where two segments cross, or None if they don't.
+    /// Segments that lie along the same line and overlap give the first
+    /// point of `other` that is on this one.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// use doodle::geometry::Line;
+    /// let a = Line::new(Vec2::new(0.0, 0.0), Vec2::new(10.0, 10.0));
+    /// let b = Line::new(Vec2::new(0.0, 10.0), Vec2::new(10.0, 0.0));
+    /// assert_eq!(a.intersection(&b), Some(Vec2::new(5.0, 5.0)));
+    /// ```
+    pub fn intersection(&self, other: &Line) -> Option<Vec2> {
+        let r = self.end - self.start;
+        let s = other.end - other.start;
+        let denominator = cross(r, s);
+        let offset = other.start - self.start;
+        // The cross products are scaled by the lengths they're made from, so
+        // these compare the sine of the angle between the segments and the
+        // distance between their lines.
+        if denominator.abs() <= EPSILON * r.length() * s.length() {
+            if cross(offset, r).abs() > EPSILON * r.length() {
+                // Parallel and apart.
+                return None;
+            }
+            // Along the same line, so they cross where they overlap.
+            return [other.start, other.end, self.start, self.end]
+                .iter()
+                .cloned()
+                .find(|&p| self.contains_point(p) && other.contains_point(p));
+        }
+        let t = cross(offset, s) / denominator;
+        let u = cross(offset, r) / denominator;
+        if (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u) {
+            Some(self.start + r * t)
+        } else {
+            None
+        }
+    }
+    /// Returns true if two segments cross or touch.
+    #[inline]
+    pub fn intersects(&self, other: &Line) -> bool {
+        self.intersection(other).is_some()
+    }
+}
+
+/// Returns true if a point is inside a polygon, given by its corners in
+/// order. The polygon is closed from the last point back to the first,
+/// and can be concave. Where the edges of a polygon cross themselves,
+/// areas that are inside an odd number of times count as inside.
+///
+/// # Example
+/// ```
+/// # use doodle::*;
+/// use doodle::geometry::polygon_contains_point;
+/// let triangle = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(0.0, 10.0)];
+/// assert!(polygon_contains_point(&triangle, Vec2::new(2.0, 2.0)));
+/// assert!(!polygon_contains_point(&triangle, Vec2::new(8.0, 8.0)));
+/// ```
+pub fn polygon_contains_point(points: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    let mut previous = match points.last() {
+        Some(&last) => last,
+        None => return false,
+    };
+    for &current in points {
+        // The ray test doesn't reliably count points on an edge, so those
+        // are found first.
+        if Line::new(previous, current).contains_point(point) {
+            return true;
+        }
+        // Count the edges a ray going right from the point crosses.
+        if (current.y > point.y) != (previous.y > point.y) {
+            let x = current.x + (point.y - current.y) / (previous.y - current.y) * (previous.x - current.x);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+        previous = current;
+    }
+    inside
+}
+
+/// Returns true if two polygons overlap, meaning an edge of one crosses an
+/// edge of the other or one is inside the other.
+pub fn polygons_intersect(a: &[Vec2], b: &[Vec2]) -> bool {
+    let edges = |points: &[Vec2]| -> Vec<Line> {
+        (0..points.len())
+            .map(|i| Line::new(points[i], points[(i + 1) % points.len()]))
+            .collect()
+    };
+    let (a_edges, b_edges) = (edges(a), edges(b));
+    if a_edges.iter().any(|e| b_edges.iter().any(|f| e.intersects(f))) {
+        return true;
+    }
+    a.first().is_some_and(|&p| polygon_contains_point(b, p))
+        || b.first().is_some_and(|&p| polygon_contains_point(a, p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polygon_edges_are_inside() {
+        let square = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0), Vec2::new(0.0, 10.0)];
+        for &point in &[
+            Vec2::new(0.0, 5.0),
+            Vec2::new(10.0, 5.0),
+            Vec2::new(5.0, 0.0),
+            Vec2::new(5.0, 10.0),
+            Vec2::new(10.0, 10.0),
+        ] {
+            assert!(polygon_contains_point(&square, point), "{:?}", point);
+        }
+        assert!(!polygon_contains_point(&square, Vec2::new(10.1, 5.0)));
+        let triangle = [Vec2::new(0.0, 0.0), Vec2::new(3.0, 0.0), Vec2::new(0.0, 7.0)];
+        let hypotenuse = Line::new(triangle[1], triangle[2]);
+        assert!(polygon_contains_point(&triangle, hypotenuse.closest_point(Vec2::new(2.0, 2.0))));
+    }
+
+    #[test]
+    fn touching_polygons_intersect() {
+        let a = [Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0), Vec2::new(10.0, 10.0)];
+        let b = [Vec2::new(10.0, 10.0), Vec2::new(20.0, 10.0), Vec2::new(20.0, 20.0)];
+        assert!(polygons_intersect(&a, &b));
+    }
+
+    #[test]
+    fn nearly_collinear_segments_overlap() {
+        let a = Line::new(Vec2::new(0.1, 0.1), Vec2::new(0.7, 0.7));
+        let b = Line::new(Vec2::new(0.3, 0.3), Vec2::new(0.9, 0.9));
+        assert_eq!(a.intersection(&b), Some(b.start));
+        let apart = Line::new(Vec2::new(0.3, 0.4), Vec2::new(0.9, 1.0));
+        assert_eq!(a.intersection(&apart), None);
+        let after = Line::new(Vec2::new(0.8, 0.8), Vec2::new(0.9, 0.9));
+        assert_eq!(a.intersection(&after), None);
+    }
+
+    #[test]
+    fn segments_touching_at_an_end_intersect() {
+        let a = Line::new(Vec2::new(0.0, 0.0), Vec2::new(1.0, 3.0));
+        let b = Line::new(a.end, Vec2::new(5.0, -2.0));
+        assert!(a.intersects(&b));
+        let c = Line::new(Vec2::new(0.0, 1.0), Vec2::new(0.2, 0.9));
+        assert!(!a.contains_point(c.end));
+        assert!(!a.intersects(&c));
+    }
+}
@@ -0,0 +1,200 @@
+//! Writing animated GIF files.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use capture::Frame;
+use {Error, Result};
+
+/// The number of colors a GIF frame can have.
+const PALETTE_SIZE: usize = 256;
+/// Colors are grouped by their top 5 bits of red, green and blue when
+/// picking a palette.
+const BUCKET_BITS: u32 = 5;
+/// The biggest LZW code a GIF can use, after which the table starts over.
+const MAX_CODE: u16 = 4095;
+
+/// Writes frames to an animated GIF that loops forever. The file is
+/// finished when `finish` is called.
+pub(crate) struct GifWriter {
+    out: BufWriter<File>,
+    width: u32,
+    height: u32,
+}
+
+impl GifWriter {
+    /// Creates the file and writes everything that comes before the frames.
+    pub(crate) fn create<P: AsRef<Path>>(path: P, width: u32, height: u32) -> Result<GifWriter> {
+        if width == 0 || height == 0 || width > 0xFFFF || height > 0xFFFF {
+            return Err(Error::Error(format!("a GIF can't be {}x{}", width, height)));
+        }
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(b"GIF89a")?;
+        out.write_all(&(width as u16).to_le_bytes())?;
+        out.write_all(&(height as u16).to_le_bytes())?;
+        // No global color table, since every frame has its own.
+        out.write_all(&[0, 0, 0])?;
+        // The Netscape extension that makes the animation loop forever.
+        out.write_all(&[0x21, 0xFF, 11])?;
+        out.write_all(b"NETSCAPE2.0")?;
+        out.write_all(&[3, 1, 0, 0, 0])?;
+        Ok(GifWriter { out, width, height })
+    }
+    /// Adds a frame that is shown for `delay` hundredths of a second. The
+    /// frame has to be the size of the GIF.
+    pub(crate) fn write_frame(&mut self, frame: &Frame, delay: u16) -> Result<()> {
+        debug_assert_eq!((frame.width, frame.height), (self.width, self.height));
+        let (palette, indices) = quantize(frame);
+        let out = &mut self.out;
+        out.write_all(&[0x21, 0xF9, 4, 0])?;
+        out.write_all(&delay.to_le_bytes())?;
+        out.write_all(&[0, 0])?;
+        out.write_all(&[0x2C, 0, 0, 0, 0])?;
+        out.write_all(&(self.width as u16).to_le_bytes())?;
+        out.write_all(&(self.height as u16).to_le_bytes())?;
+        // A local color table of 2^8 entries.
+        out.write_all(&[0x87])?;
+        let mut table = vec![0; PALETTE_SIZE * 3];
+        for (entry, color) in table.chunks_mut(3).zip(&palette) {
+            entry.copy_from_slice(color);
+        }
+        out.write_all(&table)?;
+        out.write_all(&[8])?;
+        for block in lzw_encode(&indices).chunks(255) {
+            out.write_all(&[block.len() as u8])?;
+            out.write_all(block)?;
+        }
+        out.write_all(&[0])?;
+        Ok(())
+    }
+    /// Ends the GIF, so it can be opened.
+    pub(crate) fn finish(mut self) -> Result<()> {
+        self.out.write_all(&[0x3B])?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// Picks up to 256 colors for a frame and returns them with the index of
+/// the color for each pixel.
+///
+/// Colors are sorted into buckets of similar colors, the most used buckets
+/// become the palette, and each bucket is drawn with the closest color in
+/// the palette. Frames with few colors, like most doodles, come out exact.
+fn quantize(frame: &Frame) -> (Vec<[u8; 3]>, Vec<u8>) {
+    let shift = 8 - BUCKET_BITS;
+    let bucket = |p: &[u8]| {
+        (usize::from(p[0] >> shift) << (2 * BUCKET_BITS))
+            | (usize::from(p[1] >> shift) << BUCKET_BITS)
+            | usize::from(p[2] >> shift)
+    };
+    // The number of pixels in each bucket and the sum of their colors.
+    let mut buckets = vec![(0u32, [0u32; 3]); 1 << (3 * BUCKET_BITS)];
+    for p in frame.pixels.chunks(3) {
+        let (count, sum) = &mut buckets[bucket(p)];
+        *count += 1;
+        for (total, &c) in sum.iter_mut().zip(p) {
+            *total += u32::from(c);
+        }
+    }
+    let mut used: Vec<usize> = (0..buckets.len()).filter(|&b| buckets[b].0 > 0).collect();
+    used.sort_by_key(|&b| ::std::cmp::Reverse(buckets[b].0));
+    let palette: Vec<[u8; 3]> = used
+        .iter()
+        .take(PALETTE_SIZE)
+        .map(|&b| {
+            let (count, sum) = buckets[b];
+            [(sum[0] / count) as u8, (sum[1] / count) as u8, (sum[2] / count) as u8]
+        })
+        .collect();
+    let mut lookup = vec![0u8; buckets.len()];
+    for &b in &used {
+        let (count, sum) = buckets[b];
+        let color = [sum[0] / count, sum[1] / count, sum[2] / count];
+        let distance = |entry: &[u8; 3]| -> u32 {
+            color
+                .iter()
+                .zip(entry)
+                .map(|(&a, &b)| (a as i32 - i32::from(b)).pow(2) as u32)
+                .sum()
+        };
+        let nearest = (0..palette.len()).min_by_key(|&i| distance(&palette[i])).unwrap_or(0);
+        lookup[b] = nearest as u8;
+    }
+    let indices = frame.pixels.chunks(3).map(|p| lookup[bucket(p)]).collect();
+    (palette, indices)
+}
+
+/// Packs codes of varying widths into bytes, lowest bit first.
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, width: u32) {
+        self.buffer |= u32::from(code) << self.bits;
+        self.bits += width;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+/// Compresses 8 bit color indices with GIF's variant of LZW.
+fn lzw_encode(indices: &[u8]) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+    let mut out = BitWriter {
+        bytes: Vec::new(),
+        buffer: 0,
+        bits: 0,
+    };
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = END + 1;
+    let mut width = 9;
+    out.write(CLEAR, width);
+    let mut current = match indices.first() {
+        Some(&first) => u16::from(first),
+        None => {
+            out.write(END, width);
+            return out.finish();
+        }
+    };
+    for &index in &indices[1..] {
+        if let Some(&code) = table.get(&(current, index)) {
+            current = code;
+            continue;
+        }
+        out.write(current, width);
+        if next_code > MAX_CODE {
+            out.write(CLEAR, width);
+            table.clear();
+            next_code = END + 1;
+            width = 9;
+        } else {
+            table.insert((current, index), next_code);
+            // The decoder widens its codes one code later than this, once
+            // it has added the entry.
+            if next_code == 1 << width && width < 12 {
+                width += 1;
+            }
+            next_code += 1;
+        }
+        current = u16::from(index);
+    }
+    out.write(current, width);
+    out.write(END, width);
+    out.finish()
+}
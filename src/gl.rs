@@ -0,0 +1,444 @@
+//! Raw OpenGL access and fragment shaders, for effects that SDL's renderer
+//! can't do on its own.
+//!
+//! SDL 2.0's renderer has no shaders, so doodle loads the few GL functions
+//! it needs to run them itself. This only works when SDL draws with its
+//! desktop OpenGL driver, which `DoodleBuilder::opengl` asks for.
+
+use std::ffi::CString;
+use std::fs;
+use std::mem;
+use std::os::raw::{c_char, c_float, c_int, c_uint, c_void};
+use std::path::Path;
+use std::ptr;
+
+use sdl2::pixels::Color;
+use sdl2::sys;
+
+use graphics::Graphics;
+use {DoodleBuilder, Error, Image, Renderer, Result};
+
+const GL_QUADS: c_uint = 0x0007;
+const GL_FRAGMENT_SHADER: c_uint = 0x8B30;
+const GL_VERTEX_SHADER: c_uint = 0x8B31;
+const GL_COMPILE_STATUS: c_uint = 0x8B81;
+const GL_LINK_STATUS: c_uint = 0x8B82;
+const GL_INFO_LOG_LENGTH: c_uint = 0x8B84;
+const GL_CURRENT_PROGRAM: c_uint = 0x8B8D;
+
+/// Passes the texture coordinates on to the fragment shader as `uv`.
+const VERTEX_SHADER: &str = "#version 110
+varying vec2 uv;
+void main() {
+    gl_Position = gl_ModelViewProjectionMatrix * gl_Vertex;
+    uv = gl_MultiTexCoord0.xy;
+}
+";
+
+macro_rules! gl_functions {
+    ($($field:ident: $name:expr, fn($($arg:ty),*) $(-> $ret:ty)*;)*) => {
+        /// The GL functions that drawing with shaders needs.
+        #[derive(Clone, Copy)]
+        pub(crate) struct Gl {
+            $($field: unsafe extern "system" fn($($arg),*) $(-> $ret)*,)*
+        }
+
+        impl Gl {
+            fn load(r: &Renderer) -> Result<Gl> {
+                let video = r.canvas.window().subsystem();
+                Ok(Gl {
+                    $($field: {
+                        let address = video.gl_get_proc_address($name);
+                        if address.is_null() {
+                            return Err(Error::Unsupported(format!("OpenGL has no {}", $name)));
+                        }
+                        // The address is of the function with this signature,
+                        // by the OpenGL specification.
+                        unsafe {
+                            mem::transmute::<*const (), unsafe extern "system" fn($($arg),*) $(-> $ret)*>(
+                                address,
+                            )
+                        }
+                    },)*
+                })
+            }
+        }
+    };
+}
+
+gl_functions! {
+    create_shader: "glCreateShader", fn(c_uint) -> c_uint;
+    shader_source: "glShaderSource", fn(c_uint, c_int, *const *const c_char, *const c_int);
+    compile_shader: "glCompileShader", fn(c_uint);
+    get_shader_iv: "glGetShaderiv", fn(c_uint, c_uint, *mut c_int);
+    get_shader_info_log: "glGetShaderInfoLog", fn(c_uint, c_int, *mut c_int, *mut c_char);
+    delete_shader: "glDeleteShader", fn(c_uint);
+    create_program: "glCreateProgram", fn() -> c_uint;
+    attach_shader: "glAttachShader", fn(c_uint, c_uint);
+    link_program: "glLinkProgram", fn(c_uint);
+    get_program_iv: "glGetProgramiv", fn(c_uint, c_uint, *mut c_int);
+    get_program_info_log: "glGetProgramInfoLog", fn(c_uint, c_int, *mut c_int, *mut c_char);
+    delete_program: "glDeleteProgram", fn(c_uint);
+    use_program: "glUseProgram", fn(c_uint);
+    get_integer_v: "glGetIntegerv", fn(c_uint, *mut c_int);
+    get_uniform_location: "glGetUniformLocation", fn(c_uint, *const c_char) -> c_int;
+    uniform_1i: "glUniform1i", fn(c_int, c_int);
+    uniform_1f: "glUniform1f", fn(c_int, c_float);
+    uniform_2f: "glUniform2f", fn(c_int, c_float, c_float);
+    begin: "glBegin", fn(c_uint);
+    end: "glEnd", fn();
+    tex_coord_2f: "glTexCoord2f", fn(c_float, c_float);
+    vertex_2f: "glVertex2f", fn(c_float, c_float);
+}
+
+/// The GL functions once they're loaded, and the post-processing pass.
+#[derive(Default)]
+pub(crate) struct GlState {
+    gl: Option<Gl>,
+    /// The program of each shader, by id, or 0 once it's unloaded.
+    programs: Vec<c_uint>,
+    post_process: Option<Shader>,
+    /// What the frame is drawn on before it's post-processed.
+    frame: Option<Graphics>,
+}
+
+/// A handle to a compiled fragment shader, made with `Renderer::load_shader`
+/// or `load_shader_file`, and the values of its uniforms.
+///
+/// Shaders are given the image being drawn as `uniform sampler2D frame`,
+/// where it is in the image as `varying vec2 uv`, the size it's drawn at
+/// in pixels as `uniform vec2 resolution`, and `elapsed_seconds` as
+/// `uniform float time`. Other float uniforms are set with `set_uniform`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shader {
+    id: usize,
+    uniforms: Vec<(String, f32)>,
+}
+
+impl Shader {
+    /// Sets a `uniform float` of the shader, which it gets the next time
+    /// it's drawn with.
+    pub fn set_uniform(&mut self, name: &str, value: f32) {
+        match self.uniforms.iter_mut().find(|u| u.0 == name) {
+            Some(uniform) => uniform.1 = value,
+            None => self.uniforms.push((name.to_owned(), value)),
+        }
+    }
+}
+
+/// Reads a shader's or program's info log, which holds compile errors.
+unsafe fn info_log(
+    object: c_uint,
+    get_iv: unsafe extern "system" fn(c_uint, c_uint, *mut c_int),
+    get_log: unsafe extern "system" fn(c_uint, c_int, *mut c_int, *mut c_char),
+) -> String {
+    let mut length = 0;
+    get_iv(object, GL_INFO_LOG_LENGTH, &mut length);
+    let mut log = vec![0u8; length.max(1) as usize];
+    get_log(object, length, ptr::null_mut(), log.as_mut_ptr() as *mut c_char);
+    String::from_utf8_lossy(&log).trim_end_matches('\0').trim().to_owned()
+}
+
+impl Gl {
+    unsafe fn compile(&self, kind: c_uint, source: &str) -> Result<c_uint> {
+        let source =
+            CString::new(source).map_err(|_| Error::Error("shaders can't contain nul bytes".to_owned()))?;
+        let shader = (self.create_shader)(kind);
+        (self.shader_source)(shader, 1, &source.as_ptr(), ptr::null());
+        (self.compile_shader)(shader);
+        let mut compiled = 0;
+        (self.get_shader_iv)(shader, GL_COMPILE_STATUS, &mut compiled);
+        if compiled == 0 {
+            let log = info_log(shader, self.get_shader_iv, self.get_shader_info_log);
+            (self.delete_shader)(shader);
+            return Err(Error::Error(format!("the shader didn't compile: {}", log)));
+        }
+        Ok(shader)
+    }
+    unsafe fn set_uniform_f(&self, program: c_uint, name: &str, value: f32) {
+        if let Ok(name) = CString::new(name) {
+            let location = (self.get_uniform_location)(program, name.as_ptr());
+            if location >= 0 {
+                (self.uniform_1f)(location, value);
+            }
+        }
+    }
+}
+
+impl Renderer {
+    /// Returns true if SDL is drawing with its desktop OpenGL driver, so
+    /// shaders and raw GL calls can be used.
+    pub fn is_opengl(&self) -> bool {
+        self.canvas.info().name == "opengl"
+    }
+    /// Returns the address of an OpenGL function, or null if there's no
+    /// such function, for loading GL with a crate like `gl`.
+    ///
+    /// This is for advanced use. GL calls share their state with SDL's
+    /// renderer, so anything they change has to be put back before doodle
+    /// draws again, and what doodle has drawn so far might not have
+    /// reached GL yet. Drawing with `draw_with_shader` takes care of both.
+    #[inline]
+    pub fn gl_proc_address(&self, name: &str) -> *const c_void {
+        self.canvas.window().subsystem().gl_get_proc_address(name) as *const c_void
+    }
+    /// Returns the GL functions doodle uses, loading them the first time.
+    fn load_gl(&mut self) -> Result<Gl> {
+        if let Some(gl) = self.gl.gl {
+            return Ok(gl);
+        }
+        if !self.is_opengl() {
+            return Err(Error::Unsupported(format!(
+                "shaders need the opengl render driver, not {}",
+                self.canvas.info().name
+            )));
+        }
+        let gl = Gl::load(self)?;
+        self.gl.gl = Some(gl);
+        Ok(gl)
+    }
+    /// Compiles a GLSL 1.10 fragment shader. See `Shader` for what it's
+    /// given, and `set_post_process` for an example.
+    pub fn load_shader(&mut self, fragment_source: &str) -> Result<Shader> {
+        let program = self.compile_program(fragment_source)?;
+        self.gl.programs.push(program);
+        Ok(Shader {
+            id: self.gl.programs.len() - 1,
+            uniforms: Vec::new(),
+        })
+    }
+    /// Compiles a GLSL 1.10 fragment shader from a file. With hot reloading
+    /// turned on, the shader is compiled again when the file changes.
+    pub fn load_shader_file<P: AsRef<Path>>(&mut self, path: P) -> Result<Shader> {
+        let source = fs::read_to_string(&path)?;
+        let shader = self.load_shader(&source)?;
+        self.hot_reload.watch_shader(shader.id, path.as_ref());
+        Ok(shader)
+    }
+    /// Returns true if the shader with an id hasn't been unloaded.
+    pub(crate) fn is_shader_loaded(&self, id: usize) -> bool {
+        self.gl.programs.get(id).is_some_and(|&program| program != 0)
+    }
+    /// Compiles the shader in a file again, keeping the old program if the
+    /// new one doesn't compile. Unloaded shaders are left unloaded.
+    pub(crate) fn reload_shader(&mut self, id: usize, path: &Path) -> Result<()> {
+        if !self.is_shader_loaded(id) {
+            return Ok(());
+        }
+        let source = fs::read_to_string(path)?;
+        let program = self.compile_program(&source)?;
+        let gl = self.load_gl()?;
+        if let Some(old) = self.gl.programs.get_mut(id) {
+            unsafe { (gl.delete_program)(*old) };
+            *old = program;
+        }
+        Ok(())
+    }
+    /// Links a fragment shader with the vertex shader into a program.
+    fn compile_program(&mut self, fragment_source: &str) -> Result<c_uint> {
+        let gl = self.load_gl()?;
+        unsafe {
+            let vertex = gl.compile(GL_VERTEX_SHADER, VERTEX_SHADER)?;
+            let fragment = match gl.compile(GL_FRAGMENT_SHADER, fragment_source) {
+                Ok(fragment) => fragment,
+                Err(error) => {
+                    (gl.delete_shader)(vertex);
+                    return Err(error);
+                }
+            };
+            let program = (gl.create_program)();
+            (gl.attach_shader)(program, vertex);
+            (gl.attach_shader)(program, fragment);
+            (gl.link_program)(program);
+            // The program keeps the shaders until it's deleted itself.
+            (gl.delete_shader)(vertex);
+            (gl.delete_shader)(fragment);
+            let mut linked = 0;
+            (gl.get_program_iv)(program, GL_LINK_STATUS, &mut linked);
+            if linked == 0 {
+                let log = info_log(program, gl.get_program_iv, gl.get_program_info_log);
+                (gl.delete_program)(program);
+                return Err(Error::Error(format!("the shader didn't link: {}", log)));
+            }
+            Ok(program)
+        }
+    }
+    /// Frees a shader's GL program. Drawing with it after this returns an
+    /// error.
+    pub fn unload_shader(&mut self, shader: Shader) {
+        let gl = match self.load_gl() {
+            Ok(gl) => gl,
+            Err(_) => return,
+        };
+        if let Some(program) = self.gl.programs.get_mut(shader.id) {
+            // Deleting program 0 does nothing, so unloading twice is fine.
+            unsafe { (gl.delete_program)(*program) };
+            *program = 0;
+        }
+        self.hot_reload.unwatch_shader(shader.id);
+    }
+    /// Draws an image stretched over a rectangle through a shader. The
+    /// rectangle is in pixels from the top left corner of the drawing area,
+    /// ignoring the transformation.
+    pub fn draw_with_shader(
+        &mut self,
+        image: Image,
+        shader: &Shader,
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+    ) -> Result<()> {
+        let gl = self.load_gl()?;
+        let program = match self.gl.programs.get(shader.id) {
+            Some(&0) => return Err(Error::Error("the shader has been unloaded".to_owned())),
+            Some(&program) => program,
+            None => return Err(Error::Error("shader belongs to a different renderer".to_owned())),
+        };
+        self.dirty = true;
+        self.flush_batch();
+        let (scale_x, scale_y) = self.canvas.scale();
+        let time = self.elapsed_seconds() as f32;
+        let texture = match self.textures.get(image.id) {
+            Some(Some(texture)) => texture,
+            Some(None) => return Err(Error::Error("the image has been unloaded".to_owned())),
+            None => return Err(Error::Error("image belongs to a different renderer".to_owned())),
+        };
+        self.debug.count_copy(texture);
+        let (mut right, mut bottom) = (0.0, 0.0);
+        // Binding the texture also draws everything SDL has queued up, so
+        // the shader draws on top of it.
+        if unsafe { sys::SDL_GL_BindTexture(texture.raw(), &mut right, &mut bottom) } != 0 {
+            return Err(Error::Error(sdl2::get_error()));
+        }
+        unsafe {
+            // SDL remembers which program it left bound, so it's bound again
+            // after.
+            let mut previous = 0;
+            (gl.get_integer_v)(GL_CURRENT_PROGRAM, &mut previous);
+            (gl.use_program)(program);
+            if let Ok(name) = CString::new("frame") {
+                let location = (gl.get_uniform_location)(program, name.as_ptr());
+                if location >= 0 {
+                    (gl.uniform_1i)(location, 0);
+                }
+            }
+            if let Ok(name) = CString::new("resolution") {
+                let location = (gl.get_uniform_location)(program, name.as_ptr());
+                if location >= 0 {
+                    (gl.uniform_2f)(location, w * scale_x, h * scale_y);
+                }
+            }
+            gl.set_uniform_f(program, "time", time);
+            for &(ref name, value) in &shader.uniforms {
+                gl.set_uniform_f(program, name, value);
+            }
+            // SDL's projection is in pixels of the viewport, with its scale
+            // left for the vertices.
+            let (left, top) = (x * scale_x, y * scale_y);
+            let (x2, y2) = ((x + w) * scale_x, (y + h) * scale_y);
+            (gl.begin)(GL_QUADS);
+            (gl.tex_coord_2f)(0.0, 0.0);
+            (gl.vertex_2f)(left, top);
+            (gl.tex_coord_2f)(right, 0.0);
+            (gl.vertex_2f)(x2, top);
+            (gl.tex_coord_2f)(right, bottom);
+            (gl.vertex_2f)(x2, y2);
+            (gl.tex_coord_2f)(0.0, bottom);
+            (gl.vertex_2f)(left, y2);
+            (gl.end)();
+            (gl.use_program)(previous as c_uint);
+            sys::SDL_GL_UnbindTexture(texture.raw());
+        }
+        Ok(())
+    }
+    /// Runs every frame through a shader before it's shown, or stops
+    /// post-processing with None. Returns the shader that was being used.
+    ///
+    /// While a shader is set, the background and the draw callback draw on
+    /// an image the size of the drawing area, which is then drawn on the
+    /// window through the shader. The image is kept from frame to frame
+    /// like the window is, but starts out empty, so what setup draws isn't
+    /// post-processed. The parameter panel and debug overlay are drawn
+    /// after, so the shader doesn't warp them.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// // Split the red and blue apart towards the edges, like a cheap lens.
+    /// const ABERRATION: &str = "
+    ///     uniform sampler2D frame;
+    ///     uniform float amount;
+    ///     varying vec2 uv;
+    ///     void main() {
+    ///         vec2 offset = (uv - 0.5) * amount;
+    ///         float r = texture2D(frame, uv + offset).r;
+    ///         float g = texture2D(frame, uv).g;
+    ///         float b = texture2D(frame, uv - offset).b;
+    ///         gl_FragColor = vec4(r, g, b, 1.0);
+    ///     }
+    /// ";
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .opengl(true)
+    ///     .setup(Box::new(|_, r| {
+    ///         let mut shader = r.load_shader(ABERRATION).unwrap();
+    ///         shader.set_uniform("amount", 0.02);
+    ///         r.set_post_process(Some(shader));
+    ///     }));
+    /// ```
+    pub fn set_post_process(&mut self, shader: Option<Shader>) -> Option<Shader> {
+        mem::replace(&mut self.gl.post_process, shader)
+    }
+    /// Returns the post-processing shader, for changing its uniforms.
+    #[inline]
+    pub fn post_process_mut(&mut self) -> Option<&mut Shader> {
+        self.gl.post_process.as_mut()
+    }
+    /// Returns the image the frame should be drawn on if it's going to be
+    /// post-processed, making a new one when the drawing area changes
+    /// size.
+    pub(crate) fn post_process_target(&mut self) -> Result<Option<Graphics>> {
+        if self.gl.post_process.is_none() {
+            return Ok(None);
+        }
+        let size = self.size();
+        match self.gl.frame {
+            Some(frame) if frame.size() == size => return Ok(Some(frame)),
+            Some(frame) => self.unload_image(frame.image()),
+            None => (),
+        }
+        let frame = self.create_graphics(size.0, size.1)?;
+        self.gl.frame = Some(frame);
+        Ok(Some(frame))
+    }
+    /// Draws the frame on the window through the post-processing shader.
+    pub(crate) fn finish_post_process(&mut self, frame: Graphics) -> Result<()> {
+        let shader = match self.gl.post_process.take() {
+            Some(shader) => shader,
+            None => return Ok(()),
+        };
+        self.background(Color::RGB(0, 0, 0));
+        let (width, height) = frame.size();
+        let result = self.draw_with_shader(frame.image(), &shader, 0.0, 0.0, width as f32, height as f32);
+        // The frame draws something whether the shader worked or not.
+        if result.is_err() {
+            self.draw_image(frame.image(), 0.0, 0.0);
+        }
+        if self.gl.post_process.is_none() {
+            self.gl.post_process = Some(shader);
+        }
+        result
+    }
+}
+
+impl<'a, T> DoodleBuilder<'a, T> {
+    /// Sets whether SDL is asked to draw with OpenGL, which shaders need.
+    /// SDL picks the best driver for the platform otherwise, which isn't
+    /// OpenGL on every platform. Headless doodles never use OpenGL.
+    #[inline]
+    pub fn opengl(mut self, opengl: bool) -> Self {
+        self.opengl = opengl;
+        self
+    }
+}
@@ -0,0 +1,194 @@
+//! Comparing frames against golden images, for rendering tests.
+
+use std::env;
+use std::path::Path;
+
+use capture::Frame;
+use testing::Tolerance;
+use {Error, Renderer, Result};
+
+/// The environment variable that makes `assert_frame_matches` and
+/// `testing::assert_image_matches` save the new golden image instead of
+/// comparing with it.
+const BLESS_VAR: &str = "DOODLE_BLESS";
+
+fn blessing() -> bool {
+    match env::var(BLESS_VAR) {
+        Ok(value) => !value.is_empty() && value != "0",
+        Err(_) => false,
+    }
+}
+
+/// Returns an FNV-1a hash of a frame. It is the same on every platform and
+/// Rust version, so hashes can be stored in tests.
+fn hash_frame(frame: &Frame) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let size = [frame.width.to_le_bytes(), frame.height.to_le_bytes()];
+    for &byte in size.iter().flat_map(|b| b.iter()).chain(frame.pixels.iter()) {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Returns true if two colors are no more than `channel` apart in each of
+/// red, green and blue.
+fn close(a: (u8, u8, u8), b: (u8, u8, u8), channel: u8) -> bool {
+    let near = |a: u8, b: u8| (i32::from(a) - i32::from(b)).unsigned_abs() <= u32::from(channel);
+    near(a.0, b.0) && near(a.1, b.1) && near(a.2, b.2)
+}
+
+/// Builds an image that shows the pixels that differ by more than
+/// `channel` in red on top of a darkened copy of the golden image. Returns
+/// it with the number of pixels that differ.
+pub(crate) fn diff_frames(actual: &Frame, golden: &Frame, channel: u8) -> (Frame, usize) {
+    let mut diff = Frame {
+        width: golden.width,
+        height: golden.height,
+        pixels: Vec::with_capacity(golden.pixels.len()),
+    };
+    let mut differing = 0;
+    for y in 0..golden.height {
+        for x in 0..golden.width {
+            let (r, g, b) = golden.pixel(x, y);
+            if close(actual.pixel(x, y), (r, g, b), channel) {
+                diff.pixels.extend_from_slice(&[r / 4, g / 4, b / 4]);
+            } else {
+                differing += 1;
+                diff.pixels.extend_from_slice(&[255, 0, 0]);
+            }
+        }
+    }
+    (diff, differing)
+}
+
+/// Compares a frame with a golden PNG image, blessing it or saving the
+/// `.actual.png` and `.diff.png` files as `Renderer::assert_frame_matches`
+/// describes.
+pub(crate) fn check_golden(actual: &mut Frame, path: &Path, tolerance: Tolerance) -> Result<()> {
+    if blessing() {
+        return actual.save_png(path);
+    }
+    if !path.exists() {
+        return Err(Error::Error(format!(
+            "golden image {} doesn't exist, run with {}=1 to create it",
+            path.display(),
+            BLESS_VAR
+        )));
+    }
+    let golden = Frame::load(path)?;
+    if *actual == golden {
+        return Ok(());
+    }
+    let actual_path = path.with_extension("actual.png");
+    if (actual.width, actual.height) != (golden.width, golden.height) {
+        actual.save_png(&actual_path)?;
+        return Err(Error::Error(format!(
+            "frame is {}x{} but golden image {} is {}x{}, saved the frame as {}",
+            actual.width,
+            actual.height,
+            path.display(),
+            golden.width,
+            golden.height,
+            actual_path.display()
+        )));
+    }
+    let (mut diff, differing) = diff_frames(actual, &golden, tolerance.channel);
+    if differing <= tolerance.allowed(golden.width * golden.height) {
+        return Ok(());
+    }
+    actual.save_png(&actual_path)?;
+    let diff_path = path.with_extension("diff.png");
+    diff.save_png(&diff_path)?;
+    Err(Error::Error(format!(
+        "{} of {} pixels don't match golden image {}, see {} and {}",
+        differing,
+        golden.width * golden.height,
+        path.display(),
+        actual_path.display(),
+        diff_path.display()
+    )))
+}
+
+impl Renderer {
+    /// Returns a hash of everything that has been drawn so far this frame.
+    ///
+    /// Two frames with the same size and pixels always have the same hash,
+    /// so this is a cheap way to check that a drawing hasn't changed.
+    pub fn frame_hash(&mut self) -> Result<u64> {
+        Ok(hash_frame(&self.read_frame()?))
+    }
+    /// Compares everything that has been drawn so far this frame with a
+    /// golden PNG image, returning an error if any pixel is different.
+    ///
+    /// On a mismatch the frame is saved next to the golden image with
+    /// `.actual.png` at the end of its name, together with a `.diff.png`
+    /// that shows the pixels that differ in red. Running with the
+    /// `DOODLE_BLESS` environment variable set to 1 saves the frame as the
+    /// new golden image instead, which is also how golden images are
+    /// created in the first place.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// let mut doodle = DoodleBuilder::<State>::new()
+    ///     .draw(Box::new(|_, r| {
+    ///         r.set_draw_color(Color::RGB(0, 0, 0));
+    ///         r.clear();
+    ///         r.set_draw_color(Color::RGB(255, 0, 0));
+    ///         r.fill_rect(10.0, 10.0, 100.0, 50.0);
+    ///         r.assert_frame_matches("tests/golden/rect.png").unwrap();
+    ///     }))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn assert_frame_matches<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let mut actual = self.read_frame()?;
+        check_golden(&mut actual, path.as_ref(), Tolerance::exact())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(width: u32, height: u32, pixels: &[(u8, u8, u8)]) -> Frame {
+        Frame {
+            width,
+            height,
+            pixels: pixels.iter().flat_map(|&(r, g, b)| vec![r, g, b]).collect(),
+        }
+    }
+
+    #[test]
+    fn channels_match_up_to_the_threshold() {
+        assert!(close((10, 20, 30), (10, 20, 30), 0));
+        assert!(!close((10, 20, 30), (10, 20, 31), 0));
+        assert!(close((10, 20, 30), (13, 17, 30), 3));
+        assert!(!close((10, 20, 30), (14, 20, 30), 3));
+        assert!(!close((10, 20, 30), (10, 16, 30), 3));
+        assert!(close((0, 255, 0), (255, 0, 255), 255));
+    }
+
+    #[test]
+    fn differences_are_highlighted_in_red() {
+        let golden = frame(2, 2, &[(40, 80, 120), (0, 0, 0), (255, 255, 255), (8, 8, 8)]);
+        let actual = frame(2, 2, &[(40, 80, 121), (0, 0, 0), (250, 255, 255), (8, 8, 8)]);
+        let (diff, differing) = diff_frames(&actual, &golden, 0);
+        assert_eq!(differing, 2);
+        assert_eq!(diff, frame(2, 2, &[(255, 0, 0), (0, 0, 0), (255, 0, 0), (2, 2, 2)]));
+        // Matching pixels show the golden image darkened.
+        let (diff, differing) = diff_frames(&actual, &golden, 5);
+        assert_eq!(differing, 0);
+        assert_eq!(diff, frame(2, 2, &[(10, 20, 30), (0, 0, 0), (63, 63, 63), (2, 2, 2)]));
+    }
+
+    #[test]
+    fn hashes_depend_on_size_and_pixels() {
+        let a = frame(2, 1, &[(1, 2, 3), (4, 5, 6)]);
+        assert_eq!(hash_frame(&a), hash_frame(&a.clone()));
+        assert_ne!(hash_frame(&a), hash_frame(&frame(1, 2, &[(1, 2, 3), (4, 5, 6)])));
+        assert_ne!(hash_frame(&a), hash_frame(&frame(2, 1, &[(1, 2, 3), (4, 5, 7)])));
+    }
+}
@@ -0,0 +1,119 @@
+//! Offscreen images that can be drawn on like the window.
+
+use std::mem;
+use std::path::Path;
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::BlendMode;
+use sdl2::sys;
+
+use capture::Frame;
+use image::into_static;
+use {Error, Image, Renderer, Result};
+
+/// An offscreen image that everything can be drawn on, created with
+/// `Renderer::create_graphics`.
+///
+/// Graphics are drawn on with `Renderer::draw_to`, and drawn on the window
+/// or on other graphics through `image()`, like any other image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Graphics {
+    image: Image,
+    width: u32,
+    height: u32,
+}
+
+impl Graphics {
+    /// Returns the image that shows what has been drawn on the graphics.
+    #[inline]
+    pub fn image(&self) -> Image {
+        self.image
+    }
+    /// Returns the width and height of the graphics in pixels.
+    #[inline]
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+}
+
+impl Renderer {
+    /// Creates an offscreen image of the given size to draw on, which
+    /// starts out transparent.
+    ///
+    /// Graphics don't have to be the size of the window, so a drawing can
+    /// be made at a higher resolution than the screen and saved with
+    /// `save_graphics`.
+    pub fn create_graphics(&mut self, width: u32, height: u32) -> Result<Graphics> {
+        let mut texture = self
+            .texture_creator
+            .create_texture_target(PixelFormatEnum::RGBA8888, width, height)?;
+        texture.set_blend_mode(BlendMode::Blend);
+        let image = self.push_texture(Some(into_static(texture)));
+        let graphics = Graphics { image, width, height };
+        self.draw_to(graphics, |r| {
+            r.canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+            r.draw_call();
+            r.canvas.clear();
+        })?;
+        Ok(graphics)
+    }
+    /// Runs `draw` with everything it draws going onto `graphics` instead
+    /// of the window.
+    ///
+    /// While drawing on graphics, `size` returns the size of the graphics
+    /// and the transformation starts out reset. The transformation that
+    /// was set before is brought back afterwards, while the colors and
+    /// other settings changed by `draw` are kept.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer, trails: Graphics) -> Result<()> {
+    /// // Dots pile up on the graphics, since it's never cleared.
+    /// let t = r.elapsed_seconds() as f32;
+    /// r.draw_to(trails, |r| {
+    ///     r.set_draw_color(Color::RGB(255, 100, 0));
+    ///     r.fill_circle(200.0 + 150.0 * t.cos(), 200.0 + 150.0 * t.sin(), 4.0);
+    /// })?;
+    /// r.clear();
+    /// r.draw_image(trails.image(), 0.0, 0.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn draw_to<F: FnOnce(&mut Renderer)>(&mut self, graphics: Graphics, draw: F) -> Result<()> {
+        let target = match self.textures.get(graphics.image.id) {
+            Some(Some(texture)) => texture.raw(),
+            Some(None) => return Err(Error::Error("graphics has been unloaded".to_owned())),
+            None => return Err(Error::Error("graphics belongs to a different renderer".to_owned())),
+        };
+        self.flush_batch();
+        let renderer = self.canvas.raw();
+        let previous = unsafe { sys::SDL_GetRenderTarget(renderer) };
+        if unsafe { sys::SDL_SetRenderTarget(renderer, target) } != 0 {
+            return Err(Error::Error(sdl2::get_error()));
+        }
+        let size = self.target_size.replace(graphics.size());
+        let transform = mem::take(&mut self.transform);
+        draw(self);
+        self.flush_batch();
+        self.transform = transform;
+        self.target_size = size;
+        self.dirty = true;
+        if unsafe { sys::SDL_SetRenderTarget(renderer, previous) } != 0 {
+            return Err(Error::Error(sdl2::get_error()));
+        }
+        Ok(())
+    }
+    /// Saves what has been drawn on graphics as a PNG file.
+    pub fn save_graphics<P: AsRef<Path>>(&mut self, graphics: Graphics, path: P) -> Result<()> {
+        let (width, height) = graphics.size();
+        let mut result = Ok(());
+        let mut pixels = Vec::new();
+        self.draw_to(graphics, |r| {
+            result = r.read_pixels_into(Some(Rect::new(0, 0, width, height)), &mut pixels).map(|_| ());
+        })?;
+        result?;
+        Frame { width, height, pixels }.save_png(path)
+    }
+}
@@ -0,0 +1,245 @@
+//! Reloading images, fonts and shaders when their files change.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use sdl2::image::LoadTexture;
+
+use image::into_static;
+use log::{Level, Logger};
+use text::font_into_static;
+use Renderer;
+
+/// How often the files behind loaded assets are checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The file an asset was loaded from.
+struct WatchedFile {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    // The modification time of the last version that failed to load,
+    // so a broken file is only complained about once.
+    failed: Option<SystemTime>,
+}
+
+impl WatchedFile {
+    fn new(path: &Path) -> Self {
+        WatchedFile {
+            path: path.to_owned(),
+            modified: modified_time(path),
+            failed: None,
+        }
+    }
+    /// Returns the new modification time if the file has changed since
+    /// it was last loaded.
+    fn changed(&self) -> Option<SystemTime> {
+        match modified_time(&self.path) {
+            // Editors often delete a file before writing its new version,
+            // so a missing file is treated as unchanged.
+            Some(modified) if Some(modified) != self.modified => Some(modified),
+            _ => None,
+        }
+    }
+    /// Records the result of reloading the file and reports it, logging each
+    /// version of the file that fails only once. Returns true if it was
+    /// reloaded.
+    fn reloaded<E: ::std::fmt::Display>(
+        &mut self,
+        modified: SystemTime,
+        result: ::std::result::Result<(), E>,
+        logger: &Logger,
+    ) -> bool {
+        match result {
+            Ok(()) => {
+                self.modified = Some(modified);
+                self.failed = None;
+                logger.log(Level::Info, || format!("reloaded {}", self.path.display()));
+                true
+            }
+            // The old asset is kept, and loading is tried again at the next
+            // check in case the file was only half written.
+            Err(error) => {
+                if self.failed != Some(modified) {
+                    logger.warn_or_print(|| format!("couldn't reload {}: {}", self.path.display(), error));
+                    self.failed = Some(modified);
+                }
+                false
+            }
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// A watched file and the assets that were loaded from it, so a file that's
+/// loaded more than once is only checked and reported once.
+type Watched<K> = Vec<(Vec<K>, WatchedFile)>;
+
+/// Adds an asset to the watcher of its file, starting one if the file
+/// isn't watched yet.
+fn watch<K: PartialEq>(watched: &mut Watched<K>, key: K, path: &Path) {
+    match watched.iter_mut().find(|(_, file)| file.path == path) {
+        Some((keys, _)) if keys.contains(&key) => (),
+        Some((keys, _)) => keys.push(key),
+        None => watched.push((vec![key], WatchedFile::new(path))),
+    }
+}
+
+/// Stops watching the assets `unload` is true for, and the files that no
+/// assets are left for.
+fn unwatch<K, F: Fn(&K) -> bool>(watched: &mut Watched<K>, unload: F) {
+    for (keys, _) in watched.iter_mut() {
+        keys.retain(|key| !unload(key));
+    }
+    watched.retain(|(keys, _)| !keys.is_empty());
+}
+
+/// Keeps track of the files behind loaded images, fonts and shaders.
+#[derive(Default)]
+pub(crate) struct HotReload {
+    pub(crate) enabled: bool,
+    last_check: Option<Instant>,
+    images: Watched<usize>,
+    /// Fonts by id and the size they were loaded at.
+    fonts: Watched<(usize, u16)>,
+    shaders: Watched<usize>,
+}
+
+impl HotReload {
+    pub(crate) fn watch_image(&mut self, id: usize, path: &Path) {
+        if self.enabled {
+            watch(&mut self.images, id, path);
+        }
+    }
+    /// Stops reloading an image once it has been unloaded.
+    pub(crate) fn unwatch_image(&mut self, id: usize) {
+        unwatch(&mut self.images, |&image| image == id);
+    }
+    pub(crate) fn watch_font(&mut self, id: usize, size: u16, path: &Path) {
+        if self.enabled {
+            watch(&mut self.fonts, (id, size), path);
+        }
+    }
+    /// Stops reloading a font once it has been closed.
+    pub(crate) fn unwatch_font(&mut self, id: usize) {
+        unwatch(&mut self.fonts, |&(font, _)| font == id);
+    }
+    pub(crate) fn watch_shader(&mut self, id: usize, path: &Path) {
+        if self.enabled {
+            watch(&mut self.shaders, id, path);
+        }
+    }
+    /// Stops reloading a shader once it has been unloaded.
+    pub(crate) fn unwatch_shader(&mut self, id: usize) {
+        unwatch(&mut self.shaders, |&shader| shader == id);
+    }
+}
+
+impl Renderer {
+    /// Reloads any images, fonts and shaders whose files have changed, if
+    /// hot reloading is turned on and it's time to check again. Returns the
+    /// paths of the files that were reloaded.
+    pub(crate) fn reload_changed_assets(&mut self) -> Vec<PathBuf> {
+        let mut reloaded = Vec::new();
+        if !self.hot_reload.enabled {
+            return reloaded;
+        }
+        let now = Instant::now();
+        if let Some(last_check) = self.hot_reload.last_check {
+            if now.duration_since(last_check) < CHECK_INTERVAL {
+                return reloaded;
+            }
+        }
+        self.hot_reload.last_check = Some(now);
+
+        for i in 0..self.hot_reload.images.len() {
+            let modified = match self.hot_reload.images[i].1.changed() {
+                Some(modified) => modified,
+                None => continue,
+            };
+            let (ref ids, ref file) = self.hot_reload.images[i];
+            let (ids, path) = (ids.clone(), file.path.clone());
+            // Images stay watched while they're the placeholder, so a file
+            // that failed to load shows up once it's fixed. Unloaded images
+            // aren't watched any more, so they're never brought back.
+            let mut result = Ok(());
+            for id in ids {
+                match self.texture_creator.load_texture(&path) {
+                    Ok(texture) => self.textures[id] = Some(into_static(texture)),
+                    Err(error) => {
+                        result = Err(error);
+                        break;
+                    }
+                }
+            }
+            self.dirty = true;
+            if self.hot_reload.images[i].1.reloaded(modified, result, &self.logger) {
+                reloaded.push(path);
+            }
+        }
+        for i in 0..self.hot_reload.fonts.len() {
+            let modified = match self.hot_reload.fonts[i].1.changed() {
+                Some(modified) => modified,
+                None => continue,
+            };
+            let path = self.hot_reload.fonts[i].1.path.clone();
+            let live: Vec<(usize, u16)> = self.hot_reload.fonts[i]
+                .0
+                .iter()
+                .cloned()
+                .filter(|&(id, _)| matches!(self.fonts.get(id), Some(Some(_))))
+                .collect();
+            if live.is_empty() {
+                continue;
+            }
+            let mut result = Ok(());
+            for (id, size) in live {
+                match self.ttf_context.load_font(&path, size) {
+                    Ok(font) => {
+                        self.fonts[id] = Some(font_into_static(font));
+                        self.text_cache.forget_font(id);
+                    }
+                    Err(error) => {
+                        result = Err(error);
+                        break;
+                    }
+                }
+            }
+            self.dirty = true;
+            if self.hot_reload.fonts[i].1.reloaded(modified, result, &self.logger) {
+                reloaded.push(path);
+            }
+        }
+        for i in 0..self.hot_reload.shaders.len() {
+            let modified = match self.hot_reload.shaders[i].1.changed() {
+                Some(modified) => modified,
+                None => continue,
+            };
+            let path = self.hot_reload.shaders[i].1.path.clone();
+            let live: Vec<usize> = self.hot_reload.shaders[i]
+                .0
+                .iter()
+                .cloned()
+                .filter(|&id| self.is_shader_loaded(id))
+                .collect();
+            if live.is_empty() {
+                continue;
+            }
+            let mut result = Ok(());
+            for id in live {
+                if let Err(error) = self.reload_shader(id, &path) {
+                    result = Err(error);
+                    break;
+                }
+            }
+            self.dirty = true;
+            if self.hot_reload.shaders[i].1.reloaded(modified, result, &self.logger) {
+                reloaded.push(path);
+            }
+        }
+        reloaded
+    }
+}
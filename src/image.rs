@@ -0,0 +1,278 @@
+//! Image loading.
+
+use std::mem;
+use std::path::Path;
+
+use sdl2::image::{ImageRWops, LoadTexture};
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::Texture;
+use sdl2::rwops::RWops;
+use sdl2::surface::Surface;
+use sdl2::sys;
+
+use shapes::to_pixel;
+use transform::copy_texture;
+use {Error, Renderer, Result};
+
+/// A handle to an image that has been loaded by a Renderer.
+///
+/// The pixels of the image live inside the Renderer, so an Image is
+/// cheap to copy and can be stored anywhere, including the doodle's state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Image {
+    pub(crate) id: usize,
+}
+
+/// Extends the lifetime of a texture to `'static`.
+///
+/// This is sound because textures are only ever stored inside the Renderer
+/// in fields that are declared before `canvas` and `texture_creator`, so
+/// they are always destroyed before the SDL renderer they belong to.
+pub(crate) fn into_static(texture: Texture) -> Texture<'static> {
+    unsafe { mem::transmute(texture) }
+}
+
+/// Sets the color and alpha a texture's pixels are multiplied by when it's
+/// drawn.
+///
+/// Like the blend mode in copy_texture, this goes through SDL directly
+/// because the textures shared through the renderer are only borrowed.
+pub(crate) fn set_texture_tint(texture: &Texture, tint: Color) -> Result<()> {
+    let raw = texture.raw();
+    if unsafe { sys::SDL_SetTextureColorMod(raw, tint.r, tint.g, tint.b) } != 0
+        || unsafe { sys::SDL_SetTextureAlphaMod(raw, tint.a) } != 0
+    {
+        return Err(::sdl2::get_error().into());
+    }
+    Ok(())
+}
+
+/// The number of squares along each side of the placeholder image.
+const PLACEHOLDER_SQUARES: u32 = 8;
+/// The size the placeholder image is drawn at by draw_image, since an
+/// image that couldn't be loaded doesn't have a size of its own.
+const PLACEHOLDER_SIZE: u32 = 32;
+
+impl Renderer {
+    /// Loads an image from a file.
+    pub fn load_image<P: AsRef<Path>>(&mut self, path: P) -> Result<Image> {
+        let texture = self.texture_creator.load_texture(&path)?;
+        let image = self.push_texture(Some(into_static(texture)));
+        self.hot_reload.watch_image(image.id, path.as_ref());
+        Ok(image)
+    }
+    /// Loads an image from the contents of an image file, in any format
+    /// `load_image` can read. Together with `embed!`, this puts images in
+    /// the executable instead of next to it.
+    ///
+    /// Images loaded from bytes aren't hot reloaded, since there's no file
+    /// to watch.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn setup(r: &mut Renderer, downloaded: Vec<u8>) -> Result<Image> {
+    /// // Bytes that came from anywhere, like a download, work too.
+    /// let avatar = r.load_image_bytes(&downloaded)?;
+    /// # Ok(avatar)
+    /// # }
+    /// ```
+    pub fn load_image_bytes(&mut self, bytes: &[u8]) -> Result<Image> {
+        let rwops = RWops::from_bytes(bytes)?;
+        let surface = rwops.load()?;
+        let texture = self.texture_creator.create_texture_from_surface(&surface)?;
+        Ok(self.push_texture(Some(into_static(texture))))
+    }
+    /// Stores a texture and returns a handle to it. If the texture is None,
+    /// the handle draws the placeholder image instead.
+    pub(crate) fn push_texture(&mut self, texture: Option<Texture<'static>>) -> Image {
+        self.textures.push(texture);
+        Image {
+            id: self.textures.len() - 1,
+        }
+    }
+    /// Returns a handle that draws the placeholder image for a file that
+    /// couldn't be loaded. With hot reloading turned on, the real image
+    /// replaces the placeholder once the file can be loaded.
+    pub(crate) fn missing_image(&mut self, path: &Path) -> Image {
+        let image = self.push_texture(None);
+        self.hot_reload.watch_image(image.id, path);
+        image
+    }
+    /// Takes the texture of an image out of the renderer, leaving the
+    /// placeholder in its place until replace_texture is called.
+    pub(crate) fn take_texture(&mut self, image: Image) -> Result<Option<Texture<'static>>> {
+        self.textures
+            .get_mut(image.id)
+            .map(|texture| texture.take())
+            .ok_or_else(|| Error::Error("image belongs to a different renderer".to_owned()))
+    }
+    /// Gives an image a new texture, or the placeholder if it's None.
+    pub(crate) fn replace_texture(&mut self, image: Image, texture: Option<Texture<'static>>) {
+        if let Some(slot) = self.textures.get_mut(image.id) {
+            *slot = texture;
+        }
+    }
+    /// Frees the pixels of an image. The handle draws the placeholder
+    /// image from then on.
+    pub(crate) fn unload_image(&mut self, image: Image) {
+        if let Some(texture) = self.textures.get_mut(image.id) {
+            *texture = None;
+        }
+        self.hot_reload.unwatch_image(image.id);
+    }
+    /// Returns the width and height of an image in pixels.
+    pub fn image_size(&self, image: Image) -> (u32, u32) {
+        match self.textures.get(image.id) {
+            Some(Some(texture)) => {
+                let query = texture.query();
+                (query.width, query.height)
+            }
+            _ => (0, 0),
+        }
+    }
+    /// Draws an image at its own size with its top left corner at (x, y).
+    ///
+    /// Images that failed to load or have been unloaded are drawn as a
+    /// magenta and black checkerboard instead, so they're easy to spot.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer, player: Image, x: f32, y: f32) {
+    /// r.draw_image(player, x, y);
+    /// // The same image at twice the size.
+    /// let (width, height) = r.image_size(player);
+    /// r.draw_image_rect(player, Rect::new(10, 10, width * 2, height * 2));
+    /// # }
+    /// ```
+    pub fn draw_image(&mut self, image: Image, x: f32, y: f32) {
+        let (width, height) = match self.image_size(image) {
+            (0, 0) => (PLACEHOLDER_SIZE, PLACEHOLDER_SIZE),
+            size => size,
+        };
+        self.draw_image_rect(image, Rect::new(to_pixel(x), to_pixel(y), width, height));
+    }
+    /// Draws an image stretched to cover `dst`.
+    pub fn draw_image_rect(&mut self, image: Image, dst: Rect) {
+        let result = self.copy_image(image, None, dst);
+        self.log_failure("draw an image", result);
+    }
+    /// Draws an image stretched to cover `dst`, rotated clockwise by
+    /// `angle` degrees around an anchor point and flipped horizontally
+    /// and/or vertically.
+    ///
+    /// The anchor is a point of `dst` given as fractions of its width and
+    /// height, so (0.5, 0.5) spins the image around its middle and (0, 0)
+    /// around its top left corner. The anchor stays where it is and the
+    /// rest of the image turns around it. Flipping happens before the
+    /// rotation, mirroring the image across the anchor.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer, ship: Image, x: i32, y: i32, heading: f32, facing_left: bool) {
+    /// let (width, height) = r.image_size(ship);
+    /// let dst = Rect::new(x, y, width, height);
+    /// r.draw_image_ex(ship, dst, heading, (0.5, 0.5), facing_left, false);
+    /// # }
+    /// ```
+    pub fn draw_image_ex(
+        &mut self,
+        image: Image,
+        dst: Rect,
+        angle: f32,
+        anchor: (f32, f32),
+        flip_horizontal: bool,
+        flip_vertical: bool,
+    ) {
+        let x = dst.x() as f32 + anchor.0 * dst.width() as f32;
+        let y = dst.y() as f32 + anchor.1 * dst.height() as f32;
+        let mirror = |flip| if flip { -1.0 } else { 1.0 };
+        // The texture is drawn at the rotation and flip of the current
+        // transformation, so going through it handles everything at once.
+        self.push_matrix();
+        self.translate(x, y);
+        self.rotate(angle.to_radians());
+        self.scale(mirror(flip_horizontal), mirror(flip_vertical));
+        self.translate(-x, -y);
+        let result = self.copy_image(image, None, dst);
+        self.pop_matrix();
+        self.log_failure("draw an image", result);
+    }
+    /// Multiplies the colors of the images drawn after this call by `color`,
+    /// and their alpha by its alpha, like holding colored glass in front
+    /// of them. White leaves images as they are, and a white tint with
+    /// some alpha fades them out.
+    ///
+    /// The tint stays until it's changed or turned off with `no_tint`. It
+    /// doesn't change the image itself, so the same image can be drawn
+    /// with different tints in the same frame.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer, enemy: Image, x: f32, y: f32, hurt: bool, fade: f32) {
+    /// // Flash red when hurt, and fade out while dying.
+    /// let alpha = (fade * 255.0) as u8;
+    /// if hurt {
+    ///     r.tint(Color::RGBA(255, 80, 80, alpha));
+    /// } else {
+    ///     r.tint(Color::RGBA(255, 255, 255, alpha));
+    /// }
+    /// r.draw_image(enemy, x, y);
+    /// r.no_tint();
+    /// # }
+    /// ```
+    #[inline]
+    pub fn tint(&mut self, color: Color) {
+        self.tint = color;
+    }
+    /// Draws images with their own colors again after `tint`.
+    #[inline]
+    pub fn no_tint(&mut self) {
+        self.tint = Color::RGBA(255, 255, 255, 255);
+    }
+    /// Copies the `src` part of an image into `dst` on the screen.
+    ///
+    /// Images that failed to load or have been unloaded are drawn as a
+    /// magenta and black checkerboard covering all of `dst`.
+    pub(crate) fn copy_image(&mut self, image: Image, src: Option<Rect>, dst: Rect) -> Result<()> {
+        self.dirty = true;
+        self.flush_batch();
+        match self.textures.get(image.id) {
+            Some(Some(texture)) => {
+                set_texture_tint(texture, self.tint)?;
+                self.debug.count_copy(texture);
+                copy_texture(&mut self.canvas, &self.transform, texture, src, dst)?;
+            }
+            Some(None) => {
+                if self.placeholder.is_none() {
+                    self.placeholder = Some(self.create_placeholder()?);
+                }
+                if let Some(ref placeholder) = self.placeholder {
+                    set_texture_tint(placeholder, self.tint)?;
+                    self.debug.count_copy(placeholder);
+                    copy_texture(&mut self.canvas, &self.transform, placeholder, None, dst)?;
+                }
+            }
+            None => return Err(Error::Error("image belongs to a different renderer".to_owned())),
+        }
+        Ok(())
+    }
+    fn create_placeholder(&self) -> Result<Texture<'static>> {
+        let size = PLACEHOLDER_SQUARES;
+        let mut surface = Surface::new(size, size, PixelFormatEnum::RGB24)?;
+        surface.fill_rect(None, Color::RGB(0, 0, 0))?;
+        for y in 0..size {
+            for x in 0..size {
+                if (x + y) % 2 == 0 {
+                    surface.fill_rect(Rect::new(x as i32, y as i32, 1, 1), Color::RGB(255, 0, 255))?;
+                }
+            }
+        }
+        let texture = self.texture_creator.create_texture_from_surface(&surface)?;
+        Ok(into_static(texture))
+    }
+}
@@ -0,0 +1,126 @@
+//! Information about the renderer SDL picked, for bug reports.
+
+use std::fmt;
+
+use sdl2::render;
+use sdl2::sys;
+use sdl2::version;
+
+use Renderer;
+
+/// Describes an SDL render driver and the SDL library it's running on.
+///
+/// The Display impl prints everything on one line, which is handy to log
+/// when a doodle starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RendererInfo {
+    /// The name of the render driver, like "opengl", "direct3d" or "software".
+    pub driver: &'static str,
+    /// Whether the driver draws with the GPU.
+    pub accelerated: bool,
+    /// Whether presenting waits for the display's vertical sync.
+    pub vsync: bool,
+    /// The widest texture the driver can create, or 0 if it's unknown.
+    pub max_texture_width: u32,
+    /// The tallest texture the driver can create, or 0 if it's unknown.
+    pub max_texture_height: u32,
+    /// The version of the SDL library that is linked, as (major, minor, patch).
+    pub sdl_version: (u8, u8, u8),
+}
+
+impl RendererInfo {
+    fn new(info: render::RendererInfo) -> Self {
+        let version = version::version();
+        RendererInfo::with_version(info, (version.major, version.minor, version.patch))
+    }
+    fn with_version(info: render::RendererInfo, sdl_version: (u8, u8, u8)) -> Self {
+        RendererInfo {
+            driver: info.name,
+            accelerated: info.flags & sys::SDL_RENDERER_ACCELERATED != 0,
+            vsync: info.flags & sys::SDL_RENDERER_PRESENTVSYNC != 0,
+            max_texture_width: info.max_texture_width,
+            max_texture_height: info.max_texture_height,
+            sdl_version,
+        }
+    }
+}
+
+impl fmt::Display for RendererInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = if self.accelerated { "accelerated" } else { "software" };
+        write!(f, "{} ({}", self.driver, kind)?;
+        if self.vsync {
+            write!(f, ", vsync")?;
+        }
+        write!(f, "), max texture {}x{}", self.max_texture_width, self.max_texture_height)?;
+        let (major, minor, patch) = self.sdl_version;
+        write!(f, ", SDL {}.{}.{}", major, minor, patch)
+    }
+}
+
+/// Returns the render drivers that SDL was built with, in the order SDL
+/// tries them. This can be called before a doodle is built.
+///
+/// For these, `accelerated` and `vsync` say what each driver supports
+/// rather than what is in use.
+///
+/// # Example
+/// ```no_run
+/// for driver in doodle::drivers() {
+///     println!("{}", driver);
+/// }
+/// ```
+pub fn drivers() -> Vec<RendererInfo> {
+    render::drivers().map(RendererInfo::new).collect()
+}
+
+impl Renderer {
+    /// Returns information about the render driver that this renderer uses.
+    pub fn info(&self) -> RendererInfo {
+        RendererInfo::new(self.canvas.info())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::pixels::PixelFormatEnum;
+
+    fn sdl_info(flags: u32) -> render::RendererInfo {
+        render::RendererInfo {
+            name: "opengl",
+            flags,
+            texture_formats: vec![PixelFormatEnum::ARGB8888, PixelFormatEnum::RGB888],
+            max_texture_width: 16384,
+            max_texture_height: 8192,
+        }
+    }
+
+    #[test]
+    fn flags_are_mapped() {
+        let info = RendererInfo::with_version(sdl_info(0), (2, 30, 1));
+        assert!(!info.accelerated);
+        assert!(!info.vsync);
+        let flags = sys::SDL_RENDERER_ACCELERATED | sys::SDL_RENDERER_PRESENTVSYNC;
+        let info = RendererInfo::with_version(sdl_info(flags), (2, 30, 1));
+        assert!(info.accelerated);
+        assert!(info.vsync);
+        let info = RendererInfo::with_version(sdl_info(sys::SDL_RENDERER_SOFTWARE), (2, 30, 1));
+        assert!(!info.accelerated);
+        assert_eq!(info.driver, "opengl");
+        assert_eq!((info.max_texture_width, info.max_texture_height), (16384, 8192));
+        assert_eq!(info.sdl_version, (2, 30, 1));
+    }
+
+    #[test]
+    fn display_is_one_line() {
+        let flags = sys::SDL_RENDERER_ACCELERATED | sys::SDL_RENDERER_PRESENTVSYNC;
+        let info = RendererInfo::with_version(sdl_info(flags), (2, 30, 1));
+        assert_eq!(
+            info.to_string(),
+            "opengl (accelerated, vsync), max texture 16384x8192, SDL 2.30.1"
+        );
+        let info = RendererInfo::with_version(sdl_info(sys::SDL_RENDERER_SOFTWARE), (2, 0, 22));
+        assert_eq!(info.to_string(), "opengl (software), max texture 16384x8192, SDL 2.0.22");
+    }
+}
@@ -0,0 +1,323 @@
+//! Input state tracking, named actions and text input.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+
+use sdl2::controller::{Axis, Button};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Keycode, Scancode};
+use sdl2::mouse::MouseButton;
+use sdl2::rect::Rect;
+
+use log::{Level, Logger};
+use Renderer;
+
+/// How far a controller axis has to move before it counts as held,
+/// out of 32767. This is the value recommended by the SDL documentation.
+pub const AXIS_DEADZONE: i16 = 8000;
+
+/// A physical input that can trigger an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    /// A key on the keyboard. Counts as 1 on an axis.
+    Key(Keycode),
+    /// A pair of keys that act like an axis, going to -1 while the first
+    /// key is held and 1 while the second is.
+    KeyAxis(Keycode, Keycode),
+    /// A button on any connected game controller. Counts as 1 on an axis.
+    ControllerButton(Button),
+    /// An axis on any connected game controller. Counts as held once it
+    /// leaves the deadzone.
+    ControllerAxis(Axis),
+}
+
+/// The state of the keyboard, mouse and controllers, updated from events.
+#[derive(Default)]
+pub(crate) struct InputState {
+    keys_down: HashSet<Keycode>,
+    keys_pressed: HashSet<Keycode>,
+    buttons_down: HashSet<(i32, Button)>,
+    buttons_pressed: HashSet<Button>,
+    axes: HashMap<(i32, Axis), i16>,
+    axes_pressed: HashSet<Axis>,
+    mouse_down: HashSet<MouseButton>,
+    mouse_pressed: HashSet<MouseButton>,
+    mouse_released: HashSet<MouseButton>,
+    actions: HashMap<String, Vec<Binding>>,
+    warned: RefCell<HashSet<String>>,
+}
+
+impl InputState {
+    /// Forgets which inputs were pressed during the previous frame.
+    pub(crate) fn begin_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.buttons_pressed.clear();
+        self.axes_pressed.clear();
+        self.mouse_pressed.clear();
+        self.mouse_released.clear();
+    }
+    /// Updates the input state from an event, returning false if the
+    /// event has nothing to do with input.
+    pub(crate) fn handle_event(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::KeyDown { keycode: Some(key), repeat, .. } => {
+                if !repeat {
+                    self.keys_pressed.insert(key);
+                }
+                self.keys_down.insert(key);
+            }
+            Event::KeyUp { keycode: Some(key), .. } => {
+                self.keys_down.remove(&key);
+            }
+            Event::MouseButtonDown { mouse_btn, .. } => {
+                self.mouse_pressed.insert(mouse_btn);
+                self.mouse_down.insert(mouse_btn);
+            }
+            Event::MouseButtonUp { mouse_btn, .. } => {
+                self.mouse_released.insert(mouse_btn);
+                self.mouse_down.remove(&mouse_btn);
+            }
+            Event::ControllerButtonDown { which, button, .. } => {
+                self.buttons_pressed.insert(button);
+                self.buttons_down.insert((which, button));
+            }
+            Event::ControllerButtonUp { which, button, .. } => {
+                self.buttons_down.remove(&(which, button));
+            }
+            Event::ControllerAxisMotion { which, axis, value, .. } => {
+                let old = self.axes.insert((which, axis), value).unwrap_or(0);
+                if !outside_deadzone(old) && outside_deadzone(value) {
+                    self.axes_pressed.insert(axis);
+                }
+            }
+            Event::ControllerDeviceRemoved { which, .. } => {
+                self.buttons_down.retain(|&(id, _)| id != which);
+                self.axes.retain(|&(id, _), _| id != which);
+            }
+            // Key up events never arrive for keys that are released while
+            // the window doesn't have focus.
+            Event::Window { win_event: WindowEvent::FocusLost, .. } => {
+                self.keys_down.clear();
+            }
+            _ => return false,
+        }
+        true
+    }
+    /// Returns true if a key started being held this frame.
+    #[inline]
+    pub(crate) fn key_pressed(&self, key: Keycode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+    /// Returns true if a mouse button is being held.
+    #[inline]
+    pub(crate) fn mouse_down(&self, button: MouseButton) -> bool {
+        self.mouse_down.contains(&button)
+    }
+    /// Returns true if a mouse button started being held this frame.
+    #[inline]
+    pub(crate) fn mouse_pressed(&self, button: MouseButton) -> bool {
+        self.mouse_pressed.contains(&button)
+    }
+    /// Returns true if a mouse button was let go of this frame.
+    #[inline]
+    pub(crate) fn mouse_released(&self, button: MouseButton) -> bool {
+        self.mouse_released.contains(&button)
+    }
+    fn axis_value(&self, axis: Axis) -> i16 {
+        self.axes
+            .iter()
+            .filter(|&(&(_, a), _)| a == axis)
+            .map(|(_, &value)| value)
+            .max_by_key(|&value| (i32::from(value)).abs())
+            .unwrap_or(0)
+    }
+    fn binding_down(&self, binding: Binding) -> bool {
+        match binding {
+            Binding::Key(key) => self.keys_down.contains(&key),
+            Binding::KeyAxis(negative, positive) => {
+                self.keys_down.contains(&negative) || self.keys_down.contains(&positive)
+            }
+            Binding::ControllerButton(button) => self.buttons_down.iter().any(|&(_, b)| b == button),
+            Binding::ControllerAxis(axis) => outside_deadzone(self.axis_value(axis)),
+        }
+    }
+    fn binding_pressed(&self, binding: Binding) -> bool {
+        match binding {
+            Binding::Key(key) => self.keys_pressed.contains(&key),
+            Binding::KeyAxis(negative, positive) => {
+                self.keys_pressed.contains(&negative) || self.keys_pressed.contains(&positive)
+            }
+            Binding::ControllerButton(button) => self.buttons_pressed.contains(&button),
+            Binding::ControllerAxis(axis) => self.axes_pressed.contains(&axis),
+        }
+    }
+    fn binding_axis(&self, binding: Binding) -> f32 {
+        let key = |key| if self.keys_down.contains(&key) { 1.0 } else { 0.0 };
+        match binding {
+            Binding::Key(k) => key(k),
+            Binding::KeyAxis(negative, positive) => key(positive) - key(negative),
+            Binding::ControllerButton(_) => {
+                if self.binding_down(binding) {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Binding::ControllerAxis(axis) => {
+                let value = self.axis_value(axis);
+                if outside_deadzone(value) {
+                    (f32::from(value) / 32767.0).max(-1.0)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+    /// Returns the bindings of an action, warning once about unknown names.
+    /// Without a log handler the warning is only printed in debug builds.
+    fn bindings(&self, action: &str, logger: &Logger) -> &[Binding] {
+        match self.actions.get(action) {
+            Some(bindings) => bindings,
+            None => {
+                if self.warned.borrow_mut().insert(action.to_owned()) {
+                    let message = || format!("unknown action {:?}", action);
+                    if cfg!(debug_assertions) {
+                        logger.warn_or_print(message);
+                    } else {
+                        logger.log(Level::Warn, message);
+                    }
+                }
+                &[]
+            }
+        }
+    }
+}
+
+#[inline]
+fn outside_deadzone(value: i16) -> bool {
+    i32::from(value).abs() >= i32::from(AXIS_DEADZONE)
+}
+
+impl Renderer {
+    /// Binds an action to a list of inputs, replacing its old bindings.
+    /// This can be used at any time, for example from a key rebinding screen.
+    pub fn bind_action(&mut self, action: &str, bindings: &[Binding]) {
+        self.input.actions.insert(action.to_owned(), bindings.to_vec());
+    }
+    /// Removes an action and all of its bindings.
+    pub fn unbind_action(&mut self, action: &str) {
+        self.input.actions.remove(action);
+    }
+    /// Returns the inputs bound to an action, or None if there is no such action.
+    pub fn action_bindings(&self, action: &str) -> Option<&[Binding]> {
+        self.input.actions.get(action).map(|b| &b[..])
+    }
+    /// Returns where the mouse is, in drawing coordinates. The position can
+    /// be outside of the drawing area when the window has borders around
+    /// a logical size, or while a button is held and the mouse is dragged
+    /// out of the window.
+    pub fn mouse_position(&self) -> (i32, i32) {
+        if let Some(position) = self.replayed_mouse_position() {
+            return position;
+        }
+        let mouse = self.pump.mouse_state();
+        let ((scale_x, scale_y), viewport) = (self.canvas.scale(), self.canvas.viewport());
+        // The mouse is in the window's units, and the scale is from pixels.
+        let pixels = self.scale_factor();
+        (
+            (mouse.x() as f32 * pixels / scale_x).floor() as i32 - viewport.x(),
+            (mouse.y() as f32 * pixels / scale_y).floor() as i32 - viewport.y(),
+        )
+    }
+    /// Returns true if a key is being held.
+    ///
+    /// This asks SDL for the state of the keyboard as of the last events,
+    /// so it can be polled every frame for smooth movement instead of
+    /// keeping track of key events.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # struct Player { x: f32, y: f32 }
+    /// # fn draw(player: &mut Player, r: &mut Renderer) {
+    /// let speed = 200.0 * r.delta_seconds() as f32;
+    /// if r.is_key_down(Keycode::A) { player.x -= speed; }
+    /// if r.is_key_down(Keycode::D) { player.x += speed; }
+    /// if r.is_key_down(Keycode::W) { player.y -= speed; }
+    /// if r.is_key_down(Keycode::S) { player.y += speed; }
+    /// # }
+    /// ```
+    pub fn is_key_down(&self, key: Keycode) -> bool {
+        // SDL only knows about the live keyboard.
+        if self.is_replaying() {
+            return self.input.keys_down.contains(&key);
+        }
+        match Scancode::from_keycode(key) {
+            Some(scancode) => self.pump.keyboard_state().is_scancode_pressed(scancode),
+            None => false,
+        }
+    }
+    /// Returns true if a mouse button is being held.
+    #[inline]
+    pub fn is_mouse_button_down(&self, button: MouseButton) -> bool {
+        self.input.mouse_down(button)
+    }
+    /// Returns all the keys that are being held, like `is_key_down`.
+    pub fn keys_down(&self) -> HashSet<Keycode> {
+        if self.is_replaying() {
+            return self.input.keys_down.clone();
+        }
+        self.pump
+            .keyboard_state()
+            .pressed_scancodes()
+            .filter_map(Keycode::from_scancode)
+            .collect()
+    }
+    /// Turns on text input, so typed text is sent to the text input
+    /// callback. On devices without a keyboard this shows the on-screen
+    /// one. Text input is off when the doodle starts.
+    #[inline]
+    pub fn start_text_input(&mut self) {
+        self.canvas.window().subsystem().text_input().start();
+    }
+    /// Turns text input off again. Key events keep arriving either way.
+    #[inline]
+    pub fn stop_text_input(&mut self) {
+        self.canvas.window().subsystem().text_input().stop();
+    }
+    /// Returns true if text input is on.
+    #[inline]
+    pub fn is_text_input_active(&self) -> bool {
+        self.canvas.window().subsystem().text_input().is_active()
+    }
+    /// Tells input methods where the text is being typed, in window
+    /// pixels, so they can show their windows next to it instead of over
+    /// it.
+    #[inline]
+    pub fn set_text_input_rect(&mut self, rect: Rect) {
+        self.canvas.window().subsystem().text_input().set_rect(rect);
+    }
+    /// Returns true if any input bound to the action started being held this frame.
+    ///
+    /// Unknown actions are never pressed.
+    pub fn action_pressed(&self, action: &str) -> bool {
+        let input = &self.input;
+        input.bindings(action, &self.logger).iter().any(|&b| input.binding_pressed(b))
+    }
+    /// Returns true if any input bound to the action is being held.
+    ///
+    /// Unknown actions are never down.
+    pub fn action_down(&self, action: &str) -> bool {
+        let input = &self.input;
+        input.bindings(action, &self.logger).iter().any(|&b| input.binding_down(b))
+    }
+    /// Returns the combined value of all the inputs bound to the action,
+    /// between -1 and 1.
+    ///
+    /// Unknown actions are always 0.
+    pub fn action_axis(&self, action: &str) -> f32 {
+        let input = &self.input;
+        let sum: f32 = input.bindings(action, &self.logger).iter().map(|&b| input.binding_axis(b)).sum();
+        sum.clamp(-1.0, 1.0)
+    }
+}
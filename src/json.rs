@@ -0,0 +1,490 @@
+//! A small JSON parser for the data files doodle reads, like texture
+//! atlases.
+
+use std::fmt;
+
+use {Error, Result};
+
+/// How deeply arrays and objects can be nested. Parsing goes one level of
+/// recursion deeper for each, so without a limit a file of nothing but
+/// `[` would overflow the stack.
+const MAX_DEPTH: usize = 128;
+
+/// A parsed JSON value. Objects keep their keys in the order they are
+/// written in, since some formats number things by it.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// Parses a whole JSON document.
+    pub(crate) fn parse(text: &str) -> Result<Json> {
+        let mut parser = Parser { text, offset: 0, depth: 0 };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.offset < text.len() {
+            return Err(parser.error("expected the end of the file"));
+        }
+        Ok(value)
+    }
+    /// Returns the value of a key if this is an object that has it.
+    pub(crate) fn get(&self, key: &str) -> Option<&Json> {
+        match *self {
+            Json::Object(ref members) => members.iter().find(|m| m.0 == key).map(|m| &m.1),
+            _ => None,
+        }
+    }
+    #[inline]
+    pub(crate) fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Json::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+    #[inline]
+    pub(crate) fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Json::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+    #[inline]
+    pub(crate) fn as_str(&self) -> Option<&str> {
+        match *self {
+            Json::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the value as compact JSON on one line.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Json::Number(_) => f.write_str("null"),
+            Json::String(ref s) => write_string(f, s),
+            Json::Array(ref items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(ref members) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    offset: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: &str) -> Error {
+        Error::JsonError {
+            offset: self.offset,
+            message: message.to_owned(),
+        }
+    }
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.offset).cloned()
+    }
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.offset += 1;
+        }
+    }
+    /// Skips `expected` if it's next, after any whitespace.
+    fn eat(&mut self, expected: u8) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.offset += 1;
+            true
+        } else {
+            false
+        }
+    }
+    fn expect(&mut self, expected: u8, message: &str) -> Result<()> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+    fn value(&mut self) -> Result<Json> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{' | b'[') if self.depth == MAX_DEPTH => Err(self.error("nested too deeply")),
+            Some(b'{') => self.nested(Parser::object),
+            Some(b'[') => self.nested(Parser::array),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            _ => {
+                for &(word, ref value) in &[
+                    ("true", Json::Bool(true)),
+                    ("false", Json::Bool(false)),
+                    ("null", Json::Null),
+                ] {
+                    if self.text[self.offset..].starts_with(word) {
+                        self.offset += word.len();
+                        return Ok(value.clone());
+                    }
+                }
+                Err(self.error("expected a value"))
+            }
+        }
+    }
+    /// Parses an array or an object one level deeper.
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Json>) -> Result<Json> {
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+        value
+    }
+    fn object(&mut self) -> Result<Json> {
+        self.offset += 1;
+        let mut members = Vec::new();
+        if self.eat(b'}') {
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error("expected a key"));
+            }
+            let key = self.string()?;
+            self.expect(b':', "expected `:` after the key")?;
+            members.push((key, self.value()?));
+            if self.eat(b'}') {
+                return Ok(Json::Object(members));
+            }
+            self.expect(b',', "expected `,` or `}`")?;
+        }
+    }
+    fn array(&mut self) -> Result<Json> {
+        self.offset += 1;
+        let mut items = Vec::new();
+        if self.eat(b']') {
+            return Ok(Json::Array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            if self.eat(b']') {
+                return Ok(Json::Array(items));
+            }
+            self.expect(b',', "expected `,` or `]`")?;
+        }
+    }
+    fn number(&mut self) -> Result<Json> {
+        let start = self.offset;
+        while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+            self.offset += 1;
+        }
+        match self.text[start..self.offset].parse() {
+            Ok(n) => Ok(Json::Number(n)),
+            Err(_) => {
+                self.offset = start;
+                Err(self.error("invalid number"))
+            }
+        }
+    }
+    fn string(&mut self) -> Result<String> {
+        self.offset += 1;
+        let mut string = String::new();
+        loop {
+            let rest = &self.text[self.offset..];
+            let c = match rest.chars().next() {
+                Some(c) => c,
+                None => return Err(self.error("unterminated string")),
+            };
+            self.offset += c.len_utf8();
+            match c {
+                '"' => return Ok(string),
+                '\\' => string.push(self.escape()?),
+                _ => string.push(c),
+            }
+        }
+    }
+    fn escape(&mut self) -> Result<char> {
+        let c = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                self.offset += 1;
+                let high = self.hex4()?;
+                if (0xD800..0xDC00).contains(&high) && self.text[self.offset..].starts_with("\\u") {
+                    let start = self.offset;
+                    self.offset += 2;
+                    let low = self.hex4()?;
+                    if (0xDC00..0xE000).contains(&low) {
+                        let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                        return Ok(::std::char::from_u32(code).unwrap_or('\u{FFFD}'));
+                    }
+                    // Not the second half of a pair, so it's read on its own.
+                    self.offset = start;
+                }
+                // Lone halves of surrogate pairs aren't characters.
+                return Ok(::std::char::from_u32(high).unwrap_or('\u{FFFD}'));
+            }
+            _ => return Err(self.error("invalid escape")),
+        };
+        self.offset += 1;
+        Ok(c)
+    }
+    fn hex4(&mut self) -> Result<u32> {
+        let digits = self
+            .text
+            .get(self.offset..self.offset + 4)
+            .filter(|d| d.bytes().all(|b| b.is_ascii_hexdigit()));
+        match digits.and_then(|d| u32::from_str_radix(d, 16).ok()) {
+            Some(n) => {
+                self.offset += 4;
+                Ok(n)
+            }
+            None => Err(self.error("invalid \\u escape")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error_offset(text: &str) -> usize {
+        match Json::parse(text) {
+            Err(Error::JsonError { offset, .. }) => offset,
+            other => panic!("{:?} should have been rejected", other),
+        }
+    }
+
+    fn string(text: &str) -> String {
+        match Json::parse(text).unwrap() {
+            Json::String(s) => s,
+            other => panic!("{:?} isn't a string", other),
+        }
+    }
+
+    #[test]
+    fn objects_keep_their_order() {
+        let json = Json::parse(r#" { "b": 1, "a": [true, false, null], "c": {} } "#).unwrap();
+        let expected = Json::Object(vec![
+            ("b".to_owned(), Json::Number(1.0)),
+            ("a".to_owned(), Json::Array(vec![Json::Bool(true), Json::Bool(false), Json::Null])),
+            ("c".to_owned(), Json::Object(vec![])),
+        ]);
+        assert_eq!(json, expected);
+        assert_eq!(json.get("b").and_then(Json::as_f64), Some(1.0));
+        assert_eq!(json.get("missing"), None);
+    }
+
+    #[test]
+    fn arrays() {
+        assert_eq!(Json::parse("[]").unwrap(), Json::Array(vec![]));
+        let nested = Json::parse("[[1], [[2]], \"3\"]").unwrap();
+        assert_eq!(nested.to_string(), "[[1],[[2]],\"3\"]");
+    }
+
+    #[test]
+    fn numbers() {
+        let numbers = [("0", 0.0), ("-12", -12.0), ("3.25", 3.25), ("1e3", 1000.0), ("-2.5E-2", -0.025)];
+        for &(text, n) in &numbers {
+            assert_eq!(Json::parse(text).unwrap(), Json::Number(n), "{}", text);
+        }
+        assert_eq!(error_offset("[1, 2-3]"), 4);
+        assert_eq!(error_offset("-"), 0);
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(string(r#""a\"b\\c\/d\b\f\n\r\t""#), "a\"b\\c/d\u{8}\u{c}\n\r\t");
+        assert_eq!(string(r#""\u0041\u00e9\u4E2D""#), "Aé中");
+        assert_eq!(string("\"naïve ☃\""), "naïve ☃");
+        assert_eq!(error_offset(r#""\x""#), 2);
+        assert_eq!(error_offset(r#""\u12""#), 3);
+        assert_eq!(error_offset(r#""\u+123""#), 3);
+        assert_eq!(error_offset("\"open"), 5);
+    }
+
+    #[test]
+    fn surrogate_pairs() {
+        assert_eq!(string(r#""\ud83d\ude00""#), "😀");
+        assert_eq!(string(r#""\uD834\uDD1E!""#), "𝄞!");
+        // Halves that aren't part of a pair are replaced.
+        assert_eq!(string(r#""\ud83d""#), "\u{FFFD}");
+        assert_eq!(string(r#""\ude00x""#), "\u{FFFD}x");
+        assert_eq!(string(r#""\ud83dA""#), "\u{FFFD}A");
+        assert_eq!(string(r#""\ud83d\u0041""#), "\u{FFFD}A");
+    }
+
+    #[test]
+    fn strings_round_trip() {
+        let original = Json::String("say \"hi\"\n\u{1}\\ ünïcödé".to_owned());
+        assert_eq!(Json::parse(&original.to_string()).unwrap(), original);
+    }
+
+    #[test]
+    fn trailing_garbage_is_rejected() {
+        assert_eq!(error_offset("{} x"), 3);
+        assert_eq!(error_offset("[1] [2]"), 4);
+        assert_eq!(error_offset("truex"), 4);
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        assert_eq!(error_offset(""), 0);
+        assert_eq!(error_offset("{\"a\" 1}"), 5);
+        assert_eq!(error_offset("{\"a\": 1,}"), 8);
+        assert_eq!(error_offset("{1: 2}"), 1);
+        assert_eq!(error_offset("[1 2]"), 3);
+        assert_eq!(error_offset("[1,\n  nope]"), 6);
+    }
+
+    #[test]
+    fn deep_nesting_is_an_error() {
+        let fine = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(Json::parse(&fine).is_ok());
+        let deep = "[".repeat(MAX_DEPTH + 1);
+        assert_eq!(error_offset(&deep), MAX_DEPTH);
+        // Far too deep to parse by recursing, if there were no limit.
+        let objects = "{\"a\":".repeat(100_000);
+        assert_eq!(error_offset(&objects), MAX_DEPTH * 5);
+    }
+
+    #[test]
+    fn texture_packer_hash() {
+        let text = r#"{"frames": {
+"run_01.png":
+{
+	"frame": {"x":2,"y":2,"w":30,"h":42},
+	"rotated": false,
+	"trimmed": true,
+	"spriteSourceSize": {"x":1,"y":3,"w":30,"h":42},
+	"sourceSize": {"w":32,"h":48},
+	"pivot": {"x":0.5,"y":0.5}
+},
+"run_02.png":
+{
+	"frame": {"x":34,"y":2,"w":28,"h":44},
+	"rotated": false,
+	"trimmed": true,
+	"spriteSourceSize": {"x":2,"y":2,"w":28,"h":44},
+	"sourceSize": {"w":32,"h":48},
+	"pivot": {"x":0.5,"y":0.5}
+}},
+"meta": {
+	"app": "https://www.codeandweb.com/texturepacker",
+	"version": "1.0",
+	"image": "hero.png",
+	"format": "RGBA8888",
+	"size": {"w":64,"h":48},
+	"scale": "1",
+	"smartupdate": "$TexturePacker:SmartInfo:0a1b2c3d$"
+}
+}
+"#;
+        let atlas = Json::parse(text).unwrap();
+        assert_eq!(atlas.get("meta").and_then(|m| m.get("image")).and_then(Json::as_str), Some("hero.png"));
+        let frames = match atlas.get("frames") {
+            Some(Json::Object(frames)) => frames,
+            other => panic!("{:?}", other),
+        };
+        let names: Vec<&str> = frames.iter().map(|f| &f.0[..]).collect();
+        assert_eq!(names, ["run_01.png", "run_02.png"]);
+        let second = &frames[1].1;
+        assert_eq!(second.get("frame").and_then(|f| f.get("x")).and_then(Json::as_f64), Some(34.0));
+        assert_eq!(second.get("rotated").and_then(Json::as_bool), Some(false));
+        assert_eq!(second.get("pivot").and_then(|p| p.get("y")).and_then(Json::as_f64), Some(0.5));
+    }
+
+    #[test]
+    fn texture_packer_array() {
+        let text = r#"{"frames": [
+
+{
+	"filename": "coin/0001",
+	"frame": {"x":0,"y":0,"w":16,"h":16},
+	"rotated": false,
+	"trimmed": false,
+	"spriteSourceSize": {"x":0,"y":0,"w":16,"h":16},
+	"sourceSize": {"w":16,"h":16}
+},
+{
+	"filename": "coin/0002",
+	"frame": {"x":16,"y":0,"w":12,"h":16},
+	"rotated": true,
+	"trimmed": true,
+	"spriteSourceSize": {"x":2,"y":0,"w":12,"h":16},
+	"sourceSize": {"w":16,"h":16}
+}],
+"meta": {
+	"app": "https://www.codeandweb.com/texturepacker",
+	"version": "1.0",
+	"image": "coin sheet.png",
+	"format": "RGBA8888",
+	"size": {"w":28,"h":16},
+	"scale": "0.5"
+}
+}
+"#;
+        let atlas = Json::parse(text).unwrap();
+        let frames = match atlas.get("frames") {
+            Some(Json::Array(frames)) => frames,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].get("filename").and_then(Json::as_str), Some("coin/0002"));
+        assert_eq!(frames[1].get("rotated").and_then(Json::as_bool), Some(true));
+        let source = frames[1].get("spriteSourceSize").unwrap();
+        assert_eq!(source.get("x").and_then(Json::as_f64), Some(2.0));
+        let meta = atlas.get("meta").unwrap();
+        assert_eq!(meta.get("image").and_then(Json::as_str), Some("coin sheet.png"));
+        assert_eq!(meta.get("scale").and_then(Json::as_str), Some("0.5"));
+    }
+}
@@ -1,29 +1,146 @@
 extern crate fps_clock;
-extern crate sdl2;
+/// The sdl2 crate doodle is built on, for naming the types returned by
+/// `Renderer::canvas_mut` without depending on the same version of it.
+pub extern crate sdl2;
 extern crate ludomath;
 
+mod assets;
+mod atlas;
+#[cfg(feature = "audio")]
+mod audio;
+mod batch;
+mod camera;
+mod capture;
+mod clipboard;
+mod color;
+mod config;
+mod controller;
+mod cursor;
+mod debug;
+mod display;
+mod embed;
+pub mod geometry;
+mod golden;
+mod gif;
+mod gl;
+mod graphics;
+mod hot_reload;
+mod image;
+mod info;
+mod input;
+mod json;
+mod loader;
+mod log;
+pub mod math;
+pub mod noise;
+mod params;
+mod particles;
+mod path;
+mod pixels;
+mod random;
+mod record;
+mod replay;
+mod scene;
+mod shapes;
+mod sketch;
+mod sprite;
+mod style;
+mod system_font;
+pub mod testing;
+mod text;
+mod tilemap;
+mod time;
+mod timer;
+mod transform;
+pub mod tween;
+mod ui;
+mod user_event;
+mod vec2;
+mod video;
+mod window;
+mod windows;
+
+pub use assets::Assets;
+pub use atlas::AtlasBuilder;
+#[cfg(feature = "audio")]
+pub use audio::{Audio, Sound};
+pub use camera::Camera2D;
+pub use color::{lerp_color, ColorExt};
+pub use display::DisplayMode;
+pub use gl::Shader;
+pub use graphics::Graphics;
+pub use image::Image;
+pub use info::{drivers, RendererInfo};
+pub use input::{Binding, AXIS_DEADZONE};
+pub use log::{Level, LogHandler};
+pub use params::ParamField;
+pub use particles::ParticleEmitter;
+pub use path::{Path, SubPath, CURVE_SEGMENTS};
+pub use pixels::PixelBuffer;
+pub use shapes::{to_pixel, CornerRadii};
+pub use scene::{Scene, SceneStack, Transition};
+pub use sketch::Sketch;
+pub use sprite::{AnimatedSprite, SpriteSheet};
+pub use style::ArcMode;
+pub use system_font::find_system_font;
+pub use text::{Font, TextEffect, TextSpan, TextStyle, DEFAULT_MISSING_GLYPH, DEFAULT_TEXT_SIZE};
+pub use tilemap::TileMap;
+pub use timer::{TimerCallback, TimerId};
+pub use ui::Ui;
+pub use user_event::{DoodleEventSender, UserEvent};
+pub use vec2::Vec2;
+pub use video::VideoSettings;
+pub use window::FlashOperation;
+
+use std::borrow::Cow;
+use std::collections::HashMap;
 use std::default::Default;
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
 
-use sdl2::video::Window;
-use sdl2::render::Canvas;
+use sdl2::video::{Window, WindowContext};
+pub use sdl2::video::FullscreenType;
+use sdl2::render::{Canvas, Texture, TextureCreator};
+pub use sdl2::render::BlendMode;
 use sdl2::EventPump;
 pub use sdl2::pixels::Color;
-use sdl2::event::Event;
-use sdl2::keyboard::Keycode;
-use sdl2::image::INIT_PNG;
-use sdl2::rect::{Point, Rect};
+pub use sdl2::event::Event;
+use sdl2::event::WindowEvent;
+pub use sdl2::keyboard::Keycode;
+pub use sdl2::controller::{Axis, Button};
+pub use sdl2::mouse::{MouseButton, SystemCursor};
+use sdl2::mouse::MouseWheelDirection;
+use sdl2::{GameControllerSubsystem, HapticSubsystem, Sdl};
+use sdl2::image::{Sdl2ImageContext, INIT_PNG};
+pub use sdl2::rect::{Point, Rect};
 use sdl2::ttf::Sdl2TtfContext;
 use sdl2::surface::Surface;
 use sdl2::pixels::PixelFormatEnum;
-use sdl2::rwops::RWops;
-use sdl2::image::ImageRWops;
 
 pub type Result<T> = ::std::result::Result<T, Error>;
 
 /// All of the different errors that can happen in the running of
 /// a doodle.
+///
+/// Errors can be printed, and work with `?` in functions that return
+/// `Box<dyn std::error::Error>`.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// # #[derive(Default)] struct State;
+/// fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+///     let mut doodle = DoodleBuilder::<State>::new().build()?;
+///     doodle.run()?;
+///     Ok(())
+/// }
+/// ```
 #[derive(Debug)]
 pub enum Error {
+    /// SDL reported an error.
     SdlError(sdl2::Error),
     /// Integer Overflow happened in the SDL2 library.
     IntegerOrSdlError(sdl2::IntegerOrSdlError),
@@ -33,9 +150,68 @@ pub enum Error {
     FontError(sdl2::ttf::FontError),
     /// An error occurred while building the window.
     WindowBuildError(sdl2::video::WindowBuildError),
+    /// A texture couldn't be created.
+    TextureValueError(sdl2::render::TextureValueError),
+    /// SVG path data couldn't be parsed. `offset` is the byte offset
+    /// of the problem in the path data.
+    PathError { offset: usize, message: String },
+    /// A config file, asset manifest or environment variable couldn't be
+    /// parsed. `line` is the line of the file the problem is on, or 0 for
+    /// environment variables, and `key` is the setting, asset or variable
+    /// that was wrong.
+    ConfigError { line: usize, key: String, message: String },
+    /// A JSON file, like a texture atlas, couldn't be parsed. `offset` is
+    /// the byte offset of the problem in the file.
+    JsonError { offset: usize, message: String },
+    /// A file couldn't be read or written.
+    IoError(io::Error),
+    /// Something isn't supported on this platform or by this version of SDL.
+    Unsupported(String),
+    /// An event was sent to a doodle that no longer exists.
+    Disconnected,
     /// Something bad happened.
     Error(String),
 }
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::SdlError(ref error) => write!(f, "SDL error: {}", error),
+            Error::IntegerOrSdlError(ref error) => write!(f, "{}", error),
+            Error::TtfInitError(ref error) => write!(f, "couldn't start SDL_ttf: {}", error),
+            Error::FontError(ref error) => write!(f, "font error: {}", error),
+            Error::WindowBuildError(ref error) => write!(f, "couldn't create the window: {}", error),
+            Error::TextureValueError(ref error) => write!(f, "couldn't create a texture: {}", error),
+            Error::PathError { offset, ref message } => {
+                write!(f, "invalid path data at byte {}: {}", offset, message)
+            }
+            Error::ConfigError { line: 0, ref key, ref message } => write!(f, "{}: {}", key, message),
+            Error::ConfigError { line, ref key, ref message } => {
+                write!(f, "line {}: {}: {}", line, key, message)
+            }
+            Error::JsonError { offset, ref message } => {
+                write!(f, "invalid JSON at byte {}: {}", offset, message)
+            }
+            Error::IoError(ref error) => write!(f, "{}", error),
+            Error::Unsupported(ref message) => write!(f, "unsupported: {}", message),
+            Error::Disconnected => write!(f, "the doodle no longer exists"),
+            Error::Error(ref message) => write!(f, "{}", message),
+        }
+    }
+}
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::SdlError(ref error) => Some(error),
+            Error::IntegerOrSdlError(ref error) => Some(error),
+            Error::TtfInitError(ref error) => Some(error),
+            Error::FontError(ref error) => Some(error),
+            Error::WindowBuildError(ref error) => Some(error),
+            Error::TextureValueError(ref error) => Some(error),
+            Error::IoError(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
 impl From<sdl2::Error> for Error {
     fn from(error: sdl2::Error) -> Self {
         Error::SdlError(error)
@@ -66,10 +242,159 @@ impl From<sdl2::video::WindowBuildError> for Error {
         Error::WindowBuildError(error)
     }
 }
+impl From<sdl2::render::TextureValueError> for Error {
+    fn from(error: sdl2::render::TextureValueError) -> Self {
+        Error::TextureValueError(error)
+    }
+}
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::IoError(error)
+    }
+}
 
 /// A Handler is a callback function that takes no arguments
 /// other than the state and renderer.
-pub type Handler<T> = Box<Fn(&mut T, &mut Renderer) -> ()>;
+///
+/// Handlers can change the variables they capture, so things that don't
+/// belong in the state can live in the closure instead.
+///
+/// # Example
+/// ```
+/// # use doodle::*;
+/// # #[derive(Default)] struct State;
+/// let mut frames = 0;
+/// let builder = DoodleBuilder::<State>::new().draw(Box::new(move |_, r| {
+///     frames += 1;
+///     r.debug(format!("frame {}", frames));
+/// }));
+/// ```
+pub type Handler<T> = Box<dyn FnMut(&mut T, &mut Renderer)>;
+
+/// A TryHandler is a Handler that can fail. An error stops the doodle,
+/// and `Doodle::run` returns it.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// #[derive(Default)]
+/// struct State {
+///     logo: Option<Image>,
+/// }
+/// let mut doodle = DoodleBuilder::<State>::new()
+///     .try_setup(Box::new(|state, r| {
+///         state.logo = Some(r.load_image("logo.png")?);
+///         Ok(())
+///     }))
+///     .build()
+///     .unwrap();
+/// if let Err(error) = doodle.run() {
+///     eprintln!("the doodle stopped: {}", error);
+/// }
+/// ```
+pub type TryHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer) -> Result<()>>;
+
+/// A TeardownHandler is a callback function that is run when the doodle
+/// stops, and receives how it stopped.
+pub type TeardownHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, RunOutcome)>;
+
+/// An UpdateHandler is a callback function that updates the state, and
+/// receives the number of seconds since the previous frame.
+pub type UpdateHandler<T> = Box<dyn FnMut(&mut T, f64)>;
+
+/// A TryUpdateHandler is an UpdateHandler that can fail, stopping the
+/// doodle like a TryHandler.
+pub type TryUpdateHandler<T> = Box<dyn FnMut(&mut T, f64) -> Result<()>>;
+
+/// A Handler or TryHandler, whichever the callback was set with.
+enum Callback<T> {
+    Plain(Handler<T>),
+    Fallible(TryHandler<T>),
+}
+
+impl<T> Callback<T> {
+    fn call(&mut self, state: &mut T, renderer: &mut Renderer) -> Result<()> {
+        match *self {
+            Callback::Plain(ref mut handler) => {
+                handler(state, renderer);
+                Ok(())
+            }
+            Callback::Fallible(ref mut handler) => handler(state, renderer),
+        }
+    }
+}
+
+/// An UpdateHandler or TryUpdateHandler, whichever the callback was set
+/// with.
+enum UpdateCallback<T> {
+    Plain(UpdateHandler<T>),
+    Fallible(TryUpdateHandler<T>),
+}
+
+impl<T> UpdateCallback<T> {
+    fn call(&mut self, state: &mut T, dt: f64) -> Result<()> {
+        match *self {
+            UpdateCallback::Plain(ref mut handler) => {
+                handler(state, dt);
+                Ok(())
+            }
+            UpdateCallback::Fallible(ref mut handler) => handler(state, dt),
+        }
+    }
+}
+
+/// A KeyHandler is a callback function that receives a key that was
+/// pressed or released.
+pub type KeyHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, Keycode)>;
+
+/// A MouseButtonHandler is a callback function that receives a mouse button
+/// that was pressed or released, and where the mouse was at the time.
+pub type MouseButtonHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, MouseButton, i32, i32)>;
+
+/// A MouseMoveHandler is a callback function that receives the position
+/// the mouse moved to, and how far it moved since the last time.
+pub type MouseMoveHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, i32, i32, i32, i32)>;
+
+/// A ScrollHandler is a callback function that receives how far the mouse
+/// wheel or touchpad scrolled, across and down.
+pub type ScrollHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, i32, i32)>;
+
+/// A TextInputHandler is a callback function that receives text that was
+/// typed while text input is on.
+pub type TextInputHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, &str)>;
+
+/// A TextEditingHandler is a callback function that receives text that is
+/// still being put together by an input method, along with where the
+/// cursor is in it and how many characters are selected.
+pub type TextEditingHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, &str, i32, i32)>;
+
+/// A FileDropHandler is a callback function that receives the path of a
+/// file that was dropped onto the window.
+pub type FileDropHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, PathBuf)>;
+
+/// An AssetReloadHandler is a callback function that receives the path of
+/// an image, font or shader file that was reloaded because it changed.
+pub type AssetReloadHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, &std::path::Path)>;
+
+/// A ControllerButtonHandler is a callback function that receives the id
+/// of a game controller and a button on it that was pressed or released.
+pub type ControllerButtonHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, i32, Button)>;
+
+/// A ControllerHandler is a callback function that receives the id of a
+/// game controller that was plugged in or unplugged.
+pub type ControllerHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, i32)>;
+
+/// A ResizeHandler is a callback function that receives the new width and
+/// height of the drawing area after the window was resized.
+pub type ResizeHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, u32, u32)>;
+
+/// An EventHandler is a callback function that receives every event the
+/// doodle receives, before the other callbacks for it are run.
+pub type EventHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, &Event)>;
+
+/// A UserEventHandler is a callback function that receives the events
+/// sent through a DoodleEventSender.
+pub type UserEventHandler<T> = Box<dyn FnMut(&mut T, &mut Renderer, UserEvent)>;
 
 /// A DoodleBuilder provides a simple api to create a Doodle
 ///
@@ -91,37 +416,141 @@ pub type Handler<T> = Box<Fn(&mut T, &mut Renderer) -> ()>;
 ///         .build()
 ///         .unwrap();
 /// ```
-pub struct DoodleBuilder<'a, T: Default> {
-    name: &'a str,
+pub struct DoodleBuilder<'a, T> {
+    name: Cow<'a, str>,
     width: u32,
     height: u32,
     state: T,
     fps: u32,
-    setup: Handler<T>,
-    draw: Handler<T>,
+    fullscreen: FullscreenType,
+    resizable: bool,
+    vsync: bool,
+    logical_size: Option<(u32, u32)>,
+    integer_scale: bool,
+    high_dpi: bool,
+    headless: bool,
+    opengl: bool,
+    env_overrides: bool,
+    setup: Callback<T>,
+    update: UpdateCallback<T>,
+    draw: Callback<T>,
+    user_event: UserEventHandler<T>,
+    debug_overlay: bool,
+    hot_reload_assets: bool,
+    asset_reload: AssetReloadHandler<T>,
+    actions: Vec<(String, Vec<Binding>)>,
+    log_handler: Option<LogHandler>,
+    teardown: TeardownHandler<T>,
+    quit_key: Option<Keycode>,
+    key_down: KeyHandler<T>,
+    key_up: KeyHandler<T>,
+    mouse_down: MouseButtonHandler<T>,
+    mouse_up: MouseButtonHandler<T>,
+    mouse_move: MouseMoveHandler<T>,
+    resize: ResizeHandler<T>,
+    scroll: ScrollHandler<T>,
+    text_input: TextInputHandler<T>,
+    text_editing: TextEditingHandler<T>,
+    file_drop: FileDropHandler<T>,
+    controller_button_down: ControllerButtonHandler<T>,
+    controller_button_up: ControllerButtonHandler<T>,
+    controller_connected: ControllerHandler<T>,
+    controller_disconnected: ControllerHandler<T>,
+    event: EventHandler<T>,
+    seed: Option<u64>,
+    replay: Option<replay::ReplayMode>,
+    params: params::ParamPanel<T>,
+    windows: Vec<windows::WindowSpec<T>>,
+    background: Option<Color>,
 }
 
 impl<'a, T: Default> DoodleBuilder<'a, T> {
-    /// Creates a DoodleBuilder with the default settings.
+    /// Creates a DoodleBuilder with the default settings, and the default
+    /// value of the state.
     #[inline]
     pub fn new() -> Self {
+        DoodleBuilder::with_state(Default::default())
+    }
+}
+
+impl<'a, T> DoodleBuilder<'a, T> {
+    /// Creates a DoodleBuilder with the default settings and a state. The
+    /// state doesn't need to implement Default, so it can hold things that
+    /// have no sensible default.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// # use std::net::TcpStream;
+    /// struct State {
+    ///     server: TcpStream,
+    /// }
+    /// # fn connect() -> TcpStream { unimplemented!() }
+    /// # fn run() {
+    /// let builder = DoodleBuilder::with_state(State { server: connect() })
+    ///     .draw(Box::new(|state, r| {
+    ///         // Draw what the server sent.
+    ///     }));
+    /// # }
+    /// ```
+    #[inline]
+    pub fn with_state(state: T) -> Self {
         DoodleBuilder {
-            name: "Doodle",
-            state: Default::default(),
+            name: Cow::Borrowed("Doodle"),
+            state,
             fps: 30,
+            fullscreen: FullscreenType::Off,
+            resizable: false,
+            vsync: false,
+            logical_size: None,
+            integer_scale: false,
+            high_dpi: false,
+            headless: false,
+            opengl: false,
+            env_overrides: false,
             width: 800,
             height: 600,
-            setup: Box::new(|_, _| ()),
-            draw: Box::new(|_, _| ()),
+            setup: Callback::Plain(Box::new(|_, _| ())),
+            update: UpdateCallback::Plain(Box::new(|_, _| ())),
+            draw: Callback::Plain(Box::new(|_, _| ())),
+            user_event: Box::new(|_, _, _| ()),
+            debug_overlay: false,
+            hot_reload_assets: false,
+            asset_reload: Box::new(|_, _, _| ()),
+            actions: Vec::new(),
+            log_handler: None,
+            teardown: Box::new(|_, _, _| ()),
+            quit_key: None,
+            key_down: Box::new(|_, _, _| ()),
+            key_up: Box::new(|_, _, _| ()),
+            mouse_down: Box::new(|_, _, _, _, _| ()),
+            mouse_up: Box::new(|_, _, _, _, _| ()),
+            mouse_move: Box::new(|_, _, _, _, _, _| ()),
+            resize: Box::new(|_, _, _, _| ()),
+            scroll: Box::new(|_, _, _, _| ()),
+            text_input: Box::new(|_, _, _| ()),
+            text_editing: Box::new(|_, _, _, _, _| ()),
+            file_drop: Box::new(|_, _, _| ()),
+            controller_button_down: Box::new(|_, _, _, _| ()),
+            controller_button_up: Box::new(|_, _, _, _| ()),
+            controller_connected: Box::new(|_, _, _| ()),
+            controller_disconnected: Box::new(|_, _, _| ()),
+            event: Box::new(|_, _, _| ()),
+            seed: None,
+            replay: None,
+            params: Default::default(),
+            windows: Vec::new(),
+            background: None,
         }
     }
     /// Sets the name of the doodle.
     #[inline]
     pub fn name(mut self, name: &'a str) -> Self {
-        self.name = name;
+        self.name = Cow::Borrowed(name);
         self
     }
-    /// Sets the fps of the doodle.
+    /// Sets the fps of the doodle. See `vsync` for how the two work
+    /// together.
     #[inline]
     pub fn fps(mut self, fps: u32) -> Self {
         self.fps = fps;
@@ -139,6 +568,197 @@ impl<'a, T: Default> DoodleBuilder<'a, T> {
         self.height = height;
         self
     }
+    /// Sets whether the doodle runs fullscreen, switching the display to
+    /// the size of the window.
+    #[inline]
+    pub fn fullscreen(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = if fullscreen { FullscreenType::True } else { FullscreenType::Off };
+        self
+    }
+    /// Sets whether the doodle runs fullscreen at the size of the desktop,
+    /// without changing the display's resolution. This is quicker to switch
+    /// to and from than `fullscreen`, and is what most programs use.
+    #[inline]
+    pub fn fullscreen_desktop(mut self, fullscreen: bool) -> Self {
+        self.fullscreen = if fullscreen { FullscreenType::Desktop } else { FullscreenType::Off };
+        self
+    }
+    /// Sets whether the window can be resized by the user.
+    #[inline]
+    pub fn resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+    /// Draws in a fixed coordinate space of `width` by `height` that is
+    /// scaled to fit the window, whatever size the window is. When the
+    /// window's shape doesn't match, the drawing is kept in the middle with
+    /// bars down the sides or along the top and bottom, which are cleared
+    /// along with the rest of the window.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// // A 320 by 180 pixel art game, drawn at 4 times the size.
+    /// let mut doodle = DoodleBuilder::<State>::new()
+    ///     .width(1280)
+    ///     .height(720)
+    ///     .resizable(true)
+    ///     .logical_size(320, 180)
+    ///     .integer_scale(true)
+    ///     .draw(Box::new(|_, r| {
+    ///         r.clear();
+    ///         // Always the bottom right corner of the game, in any window.
+    ///         r.fill_rect(310.0, 170.0, 10.0, 10.0);
+    ///     }))
+    ///     .build()
+    ///     .unwrap();
+    /// doodle.run().unwrap();
+    /// ```
+    #[inline]
+    pub fn logical_size(mut self, width: u32, height: u32) -> Self {
+        self.logical_size = Some((width, height));
+        self
+    }
+    /// Sets whether a logical size is only ever scaled up a whole number
+    /// of times, so every pixel of pixel art ends up the same size on the
+    /// screen. The bars around the drawing get wider to make up the
+    /// difference.
+    #[inline]
+    pub fn integer_scale(mut self, integer_scale: bool) -> Self {
+        self.integer_scale = integer_scale;
+        self
+    }
+    /// Sets whether the window uses every pixel of high-DPI screens, like
+    /// Retina displays, instead of being drawn at a lower resolution and
+    /// blown up by the operating system. Off by default.
+    ///
+    /// Drawing coordinates stay in the same units as the window's size and
+    /// mouse positions, so a doodle looks the same size on any screen, just
+    /// sharper. `Renderer::scale_factor` says how many pixels there are to
+    /// each unit.
+    #[inline]
+    pub fn high_dpi(mut self, high_dpi: bool) -> Self {
+        self.high_dpi = high_dpi;
+        self
+    }
+    /// Sets whether the doodle runs without a window, for drawing pictures
+    /// on a server or testing drawing code where there's no display. Off
+    /// by default.
+    ///
+    /// A headless doodle draws in software to an offscreen surface the
+    /// size of the window, and gets no keyboard or mouse events. Frames
+    /// are drawn as fast as they can be, and time moves on by exactly
+    /// `1 / fps` seconds each frame, so the same doodle always draws the
+    /// same pictures. Use `Doodle::run_frames` to stop after a number of
+    /// frames, and `Renderer::save_frame` to keep them.
+    ///
+    /// SDL's `dummy` video driver is used unless the `SDL_VIDEODRIVER`
+    /// environment variable picks another one.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// // Render two seconds of animation as numbered PNGs.
+    /// let mut doodle = DoodleBuilder::<State>::new()
+    ///     .headless(true)
+    ///     .fps(30)
+    ///     .draw(Box::new(|_, r| {
+    ///         let t = r.elapsed_seconds() as f32;
+    ///         r.clear();
+    ///         r.fill_circle(400.0 + 200.0 * t.cos(), 300.0, 40.0);
+    ///         r.save_frame_sequence("frames/frame").unwrap();
+    ///     }))
+    ///     .build()
+    ///     .unwrap();
+    /// doodle.run_frames(60).unwrap();
+    /// ```
+    #[inline]
+    pub fn headless(mut self, headless: bool) -> Self {
+        self.headless = headless;
+        self
+    }
+    /// Sets whether presenting a frame waits for the display's vertical sync,
+    /// which stops fast-moving shapes from tearing.
+    ///
+    /// The doodle still sleeps to keep to its `fps`, so with vsync on a
+    /// frame takes whichever is longer: the time `fps` allows, or the time
+    /// until the next refresh after it's drawn. Setting `fps` to the
+    /// display's refresh rate, usually 60, gives one frame per refresh. A
+    /// lower `fps` is rounded up to whole refreshes, so frames can come
+    /// unevenly, and a higher one is capped at the refresh rate. Some
+    /// drivers can't do vsync; `Renderer::info` tells whether it
+    /// was turned on.
+    #[inline]
+    pub fn vsync(mut self, vsync: bool) -> Self {
+        self.vsync = vsync;
+        self
+    }
+    /// Sets whether the `DOODLE_*` environment variables override the
+    /// builder's settings when the doodle is built. This is turned on by
+    /// `from_config`; see it for the list of variables.
+    #[inline]
+    pub fn env_overrides(mut self, env_overrides: bool) -> Self {
+        self.env_overrides = env_overrides;
+        self
+    }
+    /// Sets the seed for the doodle's random numbers and noise, so every
+    /// run comes out the same. See `Renderer::random_seed`.
+    #[inline]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+    /// Records the doodle's keyboard, mouse and controller input to a file,
+    /// along with its random seed and when each frame started, so the run
+    /// can be played back exactly with `replay_input`. This makes bugs
+    /// that depend on what the player did easy to report and reproduce.
+    ///
+    /// The seed is the one given to `seed`, or a new one if there isn't
+    /// one. Other inputs, like user events, files and the clock that
+    /// `now_millis` reads, aren't recorded, so doodles that depend on
+    /// them can play back differently.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct Game;
+    /// # impl Sketch for Game {}
+    /// // Keep the last run, in case it needs to be watched again.
+    /// let mut builder = DoodleBuilder::from_sketch(Game::default());
+    /// builder = match std::env::args().nth(1) {
+    ///     Some(ref path) => builder.replay_input(path),
+    ///     None => builder.record_input("last_run.replay"),
+    /// };
+    /// builder.build().unwrap().run().unwrap();
+    /// ```
+    #[inline]
+    pub fn record_input<P: AsRef<std::path::Path>>(mut self, path: P) -> Self {
+        self.replay = Some(replay::ReplayMode::Record(path.as_ref().to_owned()));
+        self
+    }
+    /// Plays back input recorded with `record_input` instead of listening
+    /// to the keyboard, mouse and controllers, and starts the random
+    /// numbers with the recorded seed. Every frame is given the time it
+    /// had when it was recorded, so the doodle does what it did then.
+    /// Once the recording ends, the live input takes over again;
+    /// `Renderer::is_replaying` tells when.
+    ///
+    /// Building the doodle fails if the file can't be read.
+    #[inline]
+    pub fn replay_input<P: AsRef<std::path::Path>>(mut self, path: P) -> Self {
+        self.replay = Some(replay::ReplayMode::Play(path.as_ref().to_owned()));
+        self
+    }
+    /// Sets a color the screen is cleared with before setup and before
+    /// each frame is drawn, so the draw callback doesn't have to clear it.
+    /// Without one, the screen keeps what was drawn on it before.
+    #[inline]
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
     /// Sets the object used to store the doodle's state.
     #[inline]
     pub fn state(mut self, state: T) -> Self {
@@ -148,80 +768,1014 @@ impl<'a, T: Default> DoodleBuilder<'a, T> {
     /// Sets the callback used to setup the doodle.
     #[inline]
     pub fn setup(mut self, setup: Handler<T>) -> Self {
-        self.setup = setup;
+        self.setup = Callback::Plain(setup);
+        self
+    }
+    /// Sets a callback that can fail to setup the doodle. If it returns an
+    /// error, the doodle stops before the first frame and `run` returns
+    /// the error.
+    #[inline]
+    pub fn try_setup(mut self, setup: TryHandler<T>) -> Self {
+        self.setup = Callback::Fallible(setup);
+        self
+    }
+    /// Sets the callback used to update the state before each frame is
+    /// drawn. It receives the number of seconds since the previous frame,
+    /// sped up or slowed down by the time scale, so moving things by their
+    /// speed times it keeps them at the same speed whatever the frame rate.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// #[derive(Default)]
+    /// struct Ball {
+    ///     x: f64,
+    /// }
+    /// let builder = DoodleBuilder::<Ball>::new()
+    ///     // 120 pixels per second.
+    ///     .update(Box::new(|ball, dt| ball.x += 120.0 * dt))
+    ///     .draw(Box::new(|ball, r| r.fill_circle(ball.x as f32, 100.0, 10.0)));
+    /// ```
+    #[inline]
+    pub fn update(mut self, update: UpdateHandler<T>) -> Self {
+        self.update = UpdateCallback::Plain(update);
+        self
+    }
+    /// Sets a callback that can fail to update the state before each
+    /// frame. If it returns an error, the doodle stops and `run` returns
+    /// the error.
+    #[inline]
+    pub fn try_update(mut self, update: TryUpdateHandler<T>) -> Self {
+        self.update = UpdateCallback::Fallible(update);
         self
     }
     /// Sets the callback used to draw each frame of the doodle.
     #[inline]
     pub fn draw(mut self, draw: Handler<T>) -> Self {
-        self.draw = draw;
+        self.draw = Callback::Plain(draw);
+        self
+    }
+    /// Sets a callback that can fail to draw each frame. If it returns an
+    /// error, the doodle stops without showing the frame and `run` returns
+    /// the error.
+    #[inline]
+    pub fn try_draw(mut self, draw: TryHandler<T>) -> Self {
+        self.draw = Callback::Fallible(draw);
+        self
+    }
+    /// Sets the callback that is run when the doodle stops without an error.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// # fn save(_: &State) {}
+    /// let builder = DoodleBuilder::<State>::new().teardown(Box::new(|state, _, outcome| {
+    ///     // Only autosave when the player closed the window themselves.
+    ///     if outcome == RunOutcome::WindowClosed {
+    ///         save(state);
+    ///     }
+    /// }));
+    /// ```
+    #[inline]
+    pub fn teardown(mut self, teardown: TeardownHandler<T>) -> Self {
+        self.teardown = teardown;
+        self
+    }
+    /// Sets a key that stops the doodle when it is pressed, as if
+    /// `Renderer::quit` had been called.
+    #[inline]
+    pub fn quit_key(mut self, key: Keycode) -> Self {
+        self.quit_key = Some(key);
+        self
+    }
+    /// Sets the callback that is run when a key is pressed. Keys that repeat
+    /// while they're held down don't run it again.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// #[derive(Default)]
+    /// struct State {
+    ///     paused: bool,
+    /// }
+    /// let builder = DoodleBuilder::<State>::new().on_key_down(Box::new(|state, _, key| {
+    ///     if key == Keycode::P {
+    ///         state.paused = !state.paused;
+    ///     }
+    /// }));
+    /// ```
+    #[inline]
+    pub fn on_key_down(mut self, key_down: KeyHandler<T>) -> Self {
+        self.key_down = key_down;
+        self
+    }
+    /// Sets the callback that is run when a key is released.
+    #[inline]
+    pub fn on_key_up(mut self, key_up: KeyHandler<T>) -> Self {
+        self.key_up = key_up;
+        self
+    }
+    /// Sets the callback that is run when a mouse button is pressed. It
+    /// receives the button and where the mouse is, in drawing coordinates.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// #[derive(Default)]
+    /// struct State {
+    ///     dots: Vec<(i32, i32)>,
+    /// }
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .on_mouse_down(Box::new(|state, _, button, x, y| {
+    ///         if button == MouseButton::Left {
+    ///             state.dots.push((x, y));
+    ///         }
+    ///     }))
+    ///     .draw(Box::new(|state, r| {
+    ///         for &(x, y) in &state.dots {
+    ///             r.fill_circle(x as f32, y as f32, 5.0);
+    ///         }
+    ///     }));
+    /// ```
+    #[inline]
+    pub fn on_mouse_down(mut self, mouse_down: MouseButtonHandler<T>) -> Self {
+        self.mouse_down = mouse_down;
+        self
+    }
+    /// Sets the callback that is run when a mouse button is released.
+    #[inline]
+    pub fn on_mouse_up(mut self, mouse_up: MouseButtonHandler<T>) -> Self {
+        self.mouse_up = mouse_up;
+        self
+    }
+    /// Sets the callback that is run when the mouse moves inside the
+    /// window. It receives where the mouse moved to, in drawing coordinates,
+    /// and how far it moved along x and y.
+    ///
+    /// In relative mouse mode the position stays put, and only how far the
+    /// mouse moved is useful. See `Renderer::set_relative_mouse`.
+    #[inline]
+    pub fn on_mouse_move(mut self, mouse_move: MouseMoveHandler<T>) -> Self {
+        self.mouse_move = mouse_move;
+        self
+    }
+    /// Sets the callback that is run when the window is resized, with the
+    /// new size of the drawing area.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// #[derive(Default)]
+    /// struct State {
+    ///     columns: u32,
+    /// }
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .resizable(true)
+    ///     .on_resize(Box::new(|state, _, width, _| {
+    ///         // Fit as many 100 pixel columns as there is room for.
+    ///         state.columns = (width / 100).max(1);
+    ///     }));
+    /// ```
+    #[inline]
+    pub fn on_resize(mut self, resize: ResizeHandler<T>) -> Self {
+        self.resize = resize;
+        self
+    }
+    /// Sets the callback that is run when the mouse wheel or a touchpad
+    /// scrolls. It receives how far it scrolled to the right and up, in
+    /// steps of the wheel; most mice can only scroll up and down. Scrolling
+    /// that the system has set to go the other way, like macOS's natural
+    /// scrolling, is turned back around so the numbers always say which way
+    /// the wheel or fingers moved.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// #[derive(Default)]
+    /// struct State {
+    ///     zoom: f32,
+    /// }
+    /// let builder = DoodleBuilder::<State>::new().on_scroll(Box::new(|state, _, _, y| {
+    ///     // Scrolling up zooms in.
+    ///     state.zoom = (state.zoom * 1.1f32.powi(y)).clamp(0.1, 10.0);
+    /// }));
+    /// ```
+    #[inline]
+    pub fn on_scroll(mut self, scroll: ScrollHandler<T>) -> Self {
+        self.scroll = scroll;
+        self
+    }
+    /// Sets the callback that receives typed text while text input is on,
+    /// which `Renderer::start_text_input` turns on.
+    ///
+    /// Unlike key events, this receives the characters the user meant to
+    /// type: shifted and accented ones, ones from other keyboard layouts,
+    /// and whole words from input methods for languages like Japanese.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// #[derive(Default)]
+    /// struct State {
+    ///     name: String,
+    /// }
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .setup(Box::new(|_, r| r.start_text_input()))
+    ///     .on_text_input(Box::new(|state, _, text| state.name.push_str(text)))
+    ///     .on_key_down(Box::new(|state, r, key| match key {
+    ///         Keycode::Backspace => {
+    ///             state.name.pop();
+    ///         }
+    ///         Keycode::Return => r.stop_text_input(),
+    ///         _ => (),
+    ///     }));
+    /// ```
+    #[inline]
+    pub fn on_text_input(mut self, text_input: TextInputHandler<T>) -> Self {
+        self.text_input = text_input;
+        self
+    }
+    /// Sets the callback that receives the text an input method is putting
+    /// together, before it's finished and sent to the text input callback.
+    /// Showing it where the text is being typed, with the cursor at the
+    /// position it receives, lets users of input methods see what they're
+    /// typing. It receives an empty string once the text is finished.
+    #[inline]
+    pub fn on_text_editing(mut self, text_editing: TextEditingHandler<T>) -> Self {
+        self.text_editing = text_editing;
+        self
+    }
+    /// Sets the callback that is run when a file is dragged from another
+    /// program and dropped onto the window. Dropping several files at once
+    /// runs it once for each of them.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// #[derive(Default)]
+    /// struct State {
+    ///     photo: Option<Image>,
+    /// }
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .on_file_drop(Box::new(|state, r, path| match r.load_image(&path) {
+    ///         Ok(image) => state.photo = Some(image),
+    ///         Err(error) => eprintln!("couldn't open {}: {}", path.display(), error),
+    ///     }))
+    ///     .draw(Box::new(|state, r| {
+    ///         r.clear();
+    ///         if let Some(photo) = state.photo {
+    ///             r.draw_image(photo, 0.0, 0.0);
+    ///         }
+    ///     }));
+    /// ```
+    #[inline]
+    pub fn on_file_drop(mut self, file_drop: FileDropHandler<T>) -> Self {
+        self.file_drop = file_drop;
+        self
+    }
+    /// Sets the callback that is run when a button on a game controller is
+    /// pressed. It receives the controller's id and the button.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// #[derive(Default)]
+    /// struct State {
+    ///     jumps: Vec<i32>,
+    /// }
+    /// let builder = DoodleBuilder::<State>::new().on_controller_button_down(Box::new(|state, r, id, button| {
+    ///     if button == Button::A {
+    ///         state.jumps.push(id);
+    ///         let _ = r.rumble(id, 0.5, 100);
+    ///     }
+    /// }));
+    /// ```
+    #[inline]
+    pub fn on_controller_button_down(mut self, button_down: ControllerButtonHandler<T>) -> Self {
+        self.controller_button_down = button_down;
+        self
+    }
+    /// Sets the callback that is run when a button on a game controller is
+    /// released.
+    #[inline]
+    pub fn on_controller_button_up(mut self, button_up: ControllerButtonHandler<T>) -> Self {
+        self.controller_button_up = button_up;
+        self
+    }
+    /// Sets the callback that is run when a game controller is plugged in,
+    /// with its id. It's also run for every controller that is already
+    /// plugged in when the doodle starts.
+    #[inline]
+    pub fn on_controller_connected(mut self, connected: ControllerHandler<T>) -> Self {
+        self.controller_connected = connected;
+        self
+    }
+    /// Sets the callback that is run when a game controller is unplugged,
+    /// with the id it had.
+    #[inline]
+    pub fn on_controller_disconnected(mut self, disconnected: ControllerHandler<T>) -> Self {
+        self.controller_disconnected = disconnected;
+        self
+    }
+    /// Sets the callback that receives every event, for events that don't
+    /// have a callback of their own, like touches.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// #[derive(Default)]
+    /// struct State {
+    ///     touches: Vec<(f32, f32)>,
+    /// }
+    /// let builder = DoodleBuilder::<State>::new().on_event(Box::new(|state, r, event| {
+    ///     // Touch positions go from 0 to 1 across the window.
+    ///     if let Event::FingerDown { x, y, .. } = *event {
+    ///         state.touches.push((x * r.width() as f32, y * r.height() as f32));
+    ///     }
+    /// }));
+    /// ```
+    #[inline]
+    pub fn on_event(mut self, event: EventHandler<T>) -> Self {
+        self.event = event;
+        self
+    }
+    /// Sets the callback that receives events sent with a DoodleEventSender.
+    #[inline]
+    pub fn user_event(mut self, user_event: UserEventHandler<T>) -> Self {
+        self.user_event = user_event;
+        self
+    }
+    /// Sets whether the debug overlay is shown when the doodle starts.
+    /// It can always be toggled with F3 while the doodle is running.
+    #[inline]
+    pub fn debug_overlay(mut self, visible: bool) -> Self {
+        self.debug_overlay = visible;
+        self
+    }
+    /// Sets whether images, fonts and shaders loaded from files are
+    /// reloaded when their files change.
+    ///
+    /// The files are checked once a second. Image, Font and Shader handles
+    /// keep working after a reload, and if a file can't be loaded (because
+    /// it's only half written, for example) the old version is kept.
+    #[inline]
+    pub fn hot_reload_assets(mut self, hot_reload: bool) -> Self {
+        self.hot_reload_assets = hot_reload;
+        self
+    }
+    /// Sets the callback that is run after an asset is hot reloaded,
+    /// with the path of its file, for redoing anything that was worked
+    /// out from it. A file is reported once however many times it was
+    /// loaded, and not at all once everything loaded from it is unloaded.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State { level: Option<Image>, walls: Vec<(u32, u32)> }
+    /// # fn find_walls(r: &mut Renderer, level: Image) -> Vec<(u32, u32)> { Vec::new() }
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .hot_reload_assets(true)
+    ///     .on_asset_reload(Box::new(|state, r, path| {
+    ///         if let (true, Some(level)) = (path.ends_with("level.png"), state.level) {
+    ///             state.walls = find_walls(r, level);
+    ///         }
+    ///     }));
+    /// ```
+    #[inline]
+    pub fn on_asset_reload(mut self, asset_reload: AssetReloadHandler<T>) -> Self {
+        self.asset_reload = asset_reload;
+        self
+    }
+    /// Binds a named action to a list of inputs, which can then be checked
+    /// with `Renderer::action_pressed`, `action_down` and `action_axis`.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .action("jump", &[Binding::Key(Keycode::Space), Binding::ControllerButton(Button::A)])
+    ///     .action("move_x", &[Binding::KeyAxis(Keycode::A, Keycode::D), Binding::ControllerAxis(Axis::LeftX)]);
+    /// ```
+    #[inline]
+    pub fn action(mut self, name: &str, bindings: &[Binding]) -> Self {
+        self.actions.push((name.to_owned(), bindings.to_vec()));
+        self
+    }
+    /// Sets the callback that receives the warnings and other messages
+    /// doodle logs while it runs, like images that couldn't be loaded or
+    /// display modes other than the one asked for.
+    ///
+    /// Without a handler, warnings that doodle has always printed still go
+    /// to stderr and everything else is dropped without being built.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// let builder = DoodleBuilder::<State>::new().on_log(Box::new(|level, message| {
+    ///     if level >= Level::Info {
+    ///         println!("[{}] {}", level, message);
+    ///     }
+    /// }));
+    /// ```
+    #[inline]
+    pub fn on_log(mut self, handler: LogHandler) -> Self {
+        self.log_handler = Some(handler);
         self
     }
     /// Builds the doodle using the settings stored in this DoodleBuilder.
     #[inline]
-    pub fn build(self) -> Result<Doodle<T>> {
+    pub fn build(mut self) -> Result<Doodle<T>> {
+        let logger = log::Logger::new(self.log_handler.take());
+        if self.env_overrides {
+            self = config::Config::from_env()?.apply(self);
+            if logger.enabled() {
+                for var in config::set_env_vars() {
+                    logger.log(Level::Info, || format!("{} overrides the builder's setting", var));
+                }
+            }
+        }
+        self.params.load(&mut self.state)?;
+        let mut renderer = Renderer::new(&WindowSettings {
+            title: &self.name,
+            width: self.width,
+            height: self.height,
+            fullscreen: self.fullscreen,
+            resizable: self.resizable,
+            vsync: self.vsync,
+            high_dpi: self.high_dpi,
+            headless: self.headless,
+            opengl: self.opengl,
+        })?;
+        renderer.logger = logger;
+        renderer.integer_scale = self.integer_scale;
+        renderer.high_dpi = self.high_dpi;
+        renderer.headless = self.headless;
+        match self.logical_size {
+            Some((width, height)) => renderer.set_logical_size(width, height)?,
+            None => renderer.clear_logical_size()?,
+        }
+        renderer.frame_rate = self.fps;
+        renderer.quit_key = self.quit_key;
+        let seed = match self.replay {
+            Some(ref mode) => Some(renderer.start_replay(mode, self.seed)?),
+            None => self.seed,
+        };
+        if let Some(seed) = seed {
+            renderer.random_seed(seed);
+        }
+        renderer.set_debug_overlay(self.debug_overlay);
+        renderer.hot_reload.enabled = self.hot_reload_assets;
+        for (name, bindings) in self.actions {
+            renderer.bind_action(&name, &bindings);
+        }
+        let mut window_draws = Vec::new();
+        for spec in self.windows {
+            let (width, height) = spec.size.ok_or_else(|| {
+                Error::Error(format!("draw_window was given {:?}, which extra_window didn't add", spec.name))
+            })?;
+            renderer.open_window(&spec.name, width, height)?;
+            window_draws.push(spec.draw.unwrap_or_else(|| Callback::Plain(Box::new(|_, _| ()))));
+        }
+        let (user_event_sender, user_events) = mpsc::channel();
         Ok(Doodle {
             state: self.state,
-            fps: self.fps,
+            params: self.params,
+            window_draws,
+            background: self.background,
             setup: self.setup,
+            update: self.update,
             draw: self.draw,
-            renderer: Renderer::new(self.name, self.width, self.height)?,
+            teardown: self.teardown,
+            key_down: self.key_down,
+            key_up: self.key_up,
+            mouse_down: self.mouse_down,
+            mouse_up: self.mouse_up,
+            mouse_move: self.mouse_move,
+            resize: self.resize,
+            scroll: self.scroll,
+            text_input: self.text_input,
+            text_editing: self.text_editing,
+            file_drop: self.file_drop,
+            asset_reload: self.asset_reload,
+            controller_button_down: self.controller_button_down,
+            controller_button_up: self.controller_button_up,
+            controller_connected: self.controller_connected,
+            controller_disconnected: self.controller_disconnected,
+            event: self.event,
+            user_event: self.user_event,
+            set_up: false,
+            user_event_sender,
+            user_events,
+            renderer,
         })
     }
 }
 
+/// The settings used to create the window and canvas.
+struct WindowSettings<'a> {
+    title: &'a str,
+    width: u32,
+    height: u32,
+    fullscreen: FullscreenType,
+    resizable: bool,
+    vsync: bool,
+    high_dpi: bool,
+    headless: bool,
+    opengl: bool,
+}
+
+/// How many milliseconds the doodle waits for an event at a time while it
+/// isn't drawing frames.
+const IDLE_WAIT_MS: u32 = 50;
+
+/// How a doodle stopped running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RunOutcome {
+    /// The user closed the window.
+    WindowClosed,
+    /// The doodle called `Renderer::quit` or the quit key was pressed.
+    QuitRequested,
+    /// `run_frames` drew all of the frames it was asked to.
+    FramesCompleted(u64),
+    /// The operating system asked the doodle to stop without the window
+    /// being closed, for example because Ctrl-C was pressed in the
+    /// terminal or the user is logging out.
+    Interrupted,
+}
+
 /// A Doodle is responsible for actually starting the doodle and running the
 /// proper callbacks in response to events.
 /// 
 /// The only way to create a Doodle is with a DoodleBuilder.
 pub struct Doodle<T> {
     state: T,
-    fps: u32,
-    setup: Handler<T>,
-    draw: Handler<T>,
+    params: params::ParamPanel<T>,
+    window_draws: Vec<Callback<T>>,
+    background: Option<Color>,
+    setup: Callback<T>,
+    update: UpdateCallback<T>,
+    draw: Callback<T>,
+    teardown: TeardownHandler<T>,
+    key_down: KeyHandler<T>,
+    key_up: KeyHandler<T>,
+    mouse_down: MouseButtonHandler<T>,
+    mouse_up: MouseButtonHandler<T>,
+    mouse_move: MouseMoveHandler<T>,
+    resize: ResizeHandler<T>,
+    scroll: ScrollHandler<T>,
+    text_input: TextInputHandler<T>,
+    text_editing: TextEditingHandler<T>,
+    file_drop: FileDropHandler<T>,
+    asset_reload: AssetReloadHandler<T>,
+    controller_button_down: ControllerButtonHandler<T>,
+    controller_button_up: ControllerButtonHandler<T>,
+    controller_connected: ControllerHandler<T>,
+    controller_disconnected: ControllerHandler<T>,
+    event: EventHandler<T>,
+    user_event: UserEventHandler<T>,
+    set_up: bool,
+    user_event_sender: Sender<UserEvent>,
+    user_events: Receiver<UserEvent>,
     renderer: Renderer,
 }
 
 impl<T> Doodle<T> {
+    /// Returns a sender that other threads can use to send events to
+    /// this doodle's user event handler.
+    #[inline]
+    pub fn event_sender(&self) -> DoodleEventSender {
+        DoodleEventSender::new(self.user_event_sender.clone())
+    }
     /// Starts running the doodle.
     /// This method only returns if an error occurs or the doodle
-    /// has finished executing.
-    pub fn run(&mut self) -> Result<()> {
-        let mut clock = fps_clock::FpsClock::new(self.fps);
-        (self.setup)(&mut self.state, &mut self.renderer);
-        self.renderer.present();
-        'main: loop {
-            for event in self.renderer.pump.poll_iter() {
+    /// has finished executing, and the outcome says how it finished.
+    /// The setup callback is only run the first time the doodle runs, or
+    /// until it succeeds if it was set with `try_setup`.
+    ///
+    /// Errors returned by the `try_` callbacks stop the doodle straight
+    /// away and are returned here, without running the teardown callback.
+    pub fn run(&mut self) -> Result<RunOutcome> {
+        self.run_until(None)
+    }
+    /// Runs the doodle for at most `frames` frames, which is useful for
+    /// tests and recording. Returns `RunOutcome::FramesCompleted` if all of
+    /// them were drawn, or how the doodle stopped early.
+    pub fn run_frames(&mut self, frames: u64) -> Result<RunOutcome> {
+        self.run_until(Some(frames))
+    }
+    fn run_until(&mut self, frames: Option<u64>) -> Result<RunOutcome> {
+        let outcome = self.run_loop(frames)?;
+        (self.teardown)(&mut self.state, &mut self.renderer, outcome);
+        Ok(outcome)
+    }
+    /// Runs the callbacks for an event, returning false if no callback
+    /// wants it.
+    fn dispatch_callbacks(&mut self, event: &Event) -> bool {
+        match *event {
+            Event::KeyDown { keycode: Some(key), repeat: false, .. } => {
+                (self.key_down)(&mut self.state, &mut self.renderer, key);
+            }
+            Event::KeyUp { keycode: Some(key), .. } => {
+                (self.key_up)(&mut self.state, &mut self.renderer, key);
+            }
+            // SDL has already turned the positions of mouse events into
+            // drawing coordinates.
+            Event::MouseButtonDown { mouse_btn, x, y, .. } => {
+                (self.mouse_down)(&mut self.state, &mut self.renderer, mouse_btn, x, y);
+            }
+            Event::MouseButtonUp { mouse_btn, x, y, .. } => {
+                (self.mouse_up)(&mut self.state, &mut self.renderer, mouse_btn, x, y);
+            }
+            Event::MouseMotion { x, y, xrel, yrel, .. } => {
+                (self.mouse_move)(&mut self.state, &mut self.renderer, x, y, xrel, yrel);
+            }
+            Event::MouseWheel { x, y, direction, .. } => {
+                let (x, y) = if direction == MouseWheelDirection::Flipped { (-x, -y) } else { (x, y) };
+                (self.scroll)(&mut self.state, &mut self.renderer, x, y);
+            }
+            Event::TextInput { ref text, .. } => {
+                (self.text_input)(&mut self.state, &mut self.renderer, text);
+            }
+            Event::TextEditing { ref text, start, length, .. } => {
+                (self.text_editing)(&mut self.state, &mut self.renderer, text, start, length);
+            }
+            Event::DropFile { ref filename, .. } => {
+                (self.file_drop)(&mut self.state, &mut self.renderer, PathBuf::from(filename));
+            }
+            Event::ControllerButtonDown { which, button, .. } => {
+                (self.controller_button_down)(&mut self.state, &mut self.renderer, which, button);
+            }
+            Event::ControllerButtonUp { which, button, .. } => {
+                (self.controller_button_up)(&mut self.state, &mut self.renderer, which, button);
+            }
+            Event::Window { win_event: WindowEvent::SizeChanged(..), .. } => {
+                let result = self.renderer.window_resized();
+                self.renderer.log_failure("scale the drawing", result);
+                let (width, height) = self.renderer.size();
+                self.renderer.dirty = true;
+                (self.resize)(&mut self.state, &mut self.renderer, width, height);
+            }
+            _ => return false,
+        }
+        true
+    }
+    /// Clears the screen with the background color, if there is one.
+    fn clear_background(&mut self) {
+        if let Some(color) = self.background {
+            self.renderer.background(color);
+        }
+    }
+    /// Clears the background and calls the draw callback, on the image
+    /// that's post-processed if there's a post-processing shader.
+    fn draw_frame(&mut self) -> Result<()> {
+        let frame = match self.renderer.post_process_target() {
+            Ok(frame) => frame,
+            Err(error) => {
+                self.renderer.log_failure("make an image to post-process", Err(error));
+                None
+            }
+        };
+        let frame = match frame {
+            Some(frame) => frame,
+            None => {
+                self.clear_background();
+                return self.draw.call(&mut self.state, &mut self.renderer);
+            }
+        };
+        let (draw, state, background) = (&mut self.draw, &mut self.state, self.background);
+        let mut result = Ok(());
+        self.renderer.draw_to(frame, |r| {
+            if let Some(color) = background {
+                r.background(color);
+            }
+            result = draw.call(state, r);
+        })?;
+        result?;
+        let result = self.renderer.finish_post_process(frame);
+        self.renderer.log_failure("post-process the frame", result);
+        Ok(())
+    }
+    /// Draws the extra windows.
+    fn draw_windows(&mut self) -> Result<()> {
+        for (index, draw) in self.window_draws.iter_mut().enumerate() {
+            let state = &mut self.state;
+            let mut result = Ok(());
+            let shown = self.renderer.draw_window(index, |r| result = draw.call(state, r));
+            self.renderer.log_failure("draw an extra window", shown);
+            result?;
+        }
+        Ok(())
+    }
+    fn run_loop(&mut self, frames: Option<u64>) -> Result<RunOutcome> {
+        let mut fps = self.renderer.frame_rate;
+        let mut clock = fps_clock::FpsClock::new(fps);
+        let mut idle = false;
+        self.renderer.quit_requested = false;
+        if !self.set_up {
+            self.renderer.clock.start();
+            self.clear_background();
+            self.setup.call(&mut self.state, &mut self.renderer)?;
+            self.set_up = true;
+            self.renderer.transform.reset();
+            self.renderer.finish_debug_overlay()?;
+            self.renderer.present();
+        }
+        // SDL sends a close event for the window before it sends quit, so
+        // a quit on its own came from somewhere else.
+        let mut window_closed = false;
+        let mut drawn = 0;
+        loop {
+            if self.renderer.quit_requested {
+                return Ok(RunOutcome::QuitRequested);
+            }
+            if frames.is_some_and(|frames| drawn >= frames) {
+                return Ok(RunOutcome::FramesCompleted(drawn));
+            }
+            let drawing = self.renderer.looping || self.renderer.redraw_requested;
+            let mut events = Vec::new();
+            if !drawing {
+                // Sleep until something happens instead of spinning. The
+                // timeout keeps user events and asset reloading going.
+                events.extend(self.renderer.pump.wait_event_timeout(IDLE_WAIT_MS));
+            }
+            events.extend(self.renderer.pump.poll_iter());
+            self.renderer.replay_events(&mut events);
+            self.renderer.input.begin_frame();
+            for event in events {
+                // Mouse positions from other windows would confuse the input
+                // tracking and the callbacks, so only `event` sees them.
+                if windows::pointer_window_id(&event).is_some_and(|id| self.renderer.is_extra_window(id)) {
+                    (self.event)(&mut self.state, &mut self.renderer, &event);
+                    self.renderer.extra_window_event(&event);
+                    continue;
+                }
+                let tracked = self.renderer.input.handle_event(&event);
+                (self.event)(&mut self.state, &mut self.renderer, &event);
+                let handled = self.dispatch_callbacks(&event) || tracked;
                 match event {
-                    Event::Quit{..} => break 'main,
-                    _ => (),
+                    Event::Quit { .. } if window_closed => return Ok(RunOutcome::WindowClosed),
+                    Event::Quit { .. } => return Ok(RunOutcome::Interrupted),
+                    // SDL only quits once every window is closed.
+                    Event::Window { win_event: WindowEvent::Close, .. }
+                        if self.renderer.has_extra_windows() =>
+                    {
+                        return Ok(RunOutcome::WindowClosed)
+                    }
+                    Event::Window { win_event: WindowEvent::Close, .. } => window_closed = true,
+                    Event::KeyDown { keycode: Some(key), repeat: false, .. }
+                        if Some(key) == self.renderer.quit_key =>
+                    {
+                        self.renderer.quit();
+                    }
+                    Event::KeyDown { keycode: Some(Keycode::F3), repeat: false, .. } => {
+                        let visible = self.renderer.debug_overlay();
+                        self.renderer.set_debug_overlay(!visible);
+                    }
+                    Event::KeyDown { keycode: Some(Keycode::F2), repeat: false, .. } => {
+                        self.params.visible = !self.params.visible;
+                    }
+                    Event::ControllerDeviceAdded { which, .. } => {
+                        if let Some(id) = self.renderer.open_controller(which as u32) {
+                            (self.controller_connected)(&mut self.state, &mut self.renderer, id);
+                        }
+                    }
+                    Event::ControllerDeviceRemoved { which, .. } => {
+                        self.renderer.close_controller(which);
+                        (self.controller_disconnected)(&mut self.state, &mut self.renderer, which);
+                    }
+                    _ if handled => (),
+                    _ => self.renderer.logger.log(Level::Debug, || format!("ignored {:?}", event)),
                 }
             }
-            (self.draw)(&mut self.state, &mut self.renderer);
+            while let Ok(event) = self.user_events.try_recv() {
+                (self.user_event)(&mut self.state, &mut self.renderer, event);
+            }
+            if self.renderer.quit_requested {
+                return Ok(RunOutcome::QuitRequested);
+            }
+            for path in self.renderer.reload_changed_assets() {
+                (self.asset_reload)(&mut self.state, &mut self.renderer, &path);
+            }
+            self.renderer.finish_loads(None);
+            if !(self.renderer.looping || self.renderer.redraw_requested) {
+                idle = true;
+                continue;
+            }
+            self.renderer.redraw_requested = false;
+            // Idling leaves the clock behind, so it restarts instead of
+            // letting the next frame look late.
+            if idle || fps != self.renderer.frame_rate {
+                idle = false;
+                fps = self.renderer.frame_rate;
+                clock = fps_clock::FpsClock::new(fps);
+            }
+            let step = time::frame_step(self.renderer.headless, fps);
+            let elapsed = self.renderer.clock.next_elapsed(step);
+            // Replays go by the times the frames were recorded at.
+            let elapsed = self.renderer.replay_frame_time(elapsed).unwrap_or(elapsed);
+            self.renderer.clock.begin_frame(elapsed);
+            self.renderer.run_timers();
+            self.update.call(&mut self.state, self.renderer.delta_seconds())?;
+            self.draw_frame()?;
+            self.renderer.transform.reset();
+            self.renderer.record_frame();
+            self.params.draw(&mut self.state, &mut self.renderer);
+            self.renderer.finish_debug_overlay()?;
+            self.draw_windows()?;
             self.renderer.present();
-            clock.tick();
+            drawn += 1;
+            if self.renderer.headless {
+                continue;
+            }
+            // tick returns how many nanoseconds were left of the frame's
+            // time, which is negative when the frame took too long.
+            let spare = clock.tick();
+            if spare < 0.0 {
+                self.renderer.logger.log(Level::Debug, || {
+                    format!("frame took {:.1} ms longer than {} fps allows", -spare / 1e6, fps)
+                });
+            }
         }
-        Ok(())
     }
 }
 
 /// A Renderer is responsible for drawing to the screen.
 pub struct Renderer {
+    // Textures and fonts must be declared before canvas, texture_creator
+    // and ttf_context so that they are destroyed first.
+    textures: Vec<Option<Texture<'static>>>,
+    placeholder: Option<Texture<'static>>,
+    pixel_texture: Option<Texture<'static>>,
+    capture_buffer: Vec<u8>,
+    recorder: Option<record::Recorder>,
+    replay: replay::Replay,
+    text_cache: text::TextCache,
+    fonts: Vec<Option<sdl2::ttf::Font<'static, 'static>>>,
+    #[cfg(feature = "audio")]
+    audio: Option<audio::Audio>,
+    windows: Vec<windows::ExtraWindow>,
+    texture_creator: TextureCreator<WindowContext>,
     canvas: Canvas<Window>,
+    cursor: Option<sdl2::mouse::Cursor>,
+    controllers: Vec<controller::Controller>,
+    controller_subsystem: GameControllerSubsystem,
+    haptic_subsystem: Option<HapticSubsystem>,
     pump: EventPump,
     ttf_context: Sdl2TtfContext,
+    _image_context: Sdl2ImageContext,
+    _sdl: Sdl,
+    default_fonts: HashMap<u16, Font>,
+    text_effect: Option<TextEffect>,
+    text_state: text::TextState,
+    debug: debug::DebugOverlay,
+    batch: batch::Batch,
+    hot_reload: hot_reload::HotReload,
+    assets: assets::AssetCache,
+    loader: loader::Loader,
+    input: input::InputState,
+    ui: ui::UiState,
+    gl: gl::GlState,
+    clock: time::Clock,
+    timers: timer::Timers,
+    transform: transform::TransformState,
+    style: style::Style,
+    noise: noise::Noise,
+    rng: ludomath::rng::Rng,
+    target_size: Option<(u32, u32)>,
+    integer_scale: bool,
+    logical_size_set: bool,
+    high_dpi: bool,
+    headless: bool,
+    tint: Color,
+    frame_rate: u32,
+    looping: bool,
+    redraw_requested: bool,
+    logger: log::Logger,
+    quit_key: Option<Keycode>,
+    quit_requested: bool,
     dirty: bool,
 }
 impl Renderer {
-    fn new(app_name: &str, width: u32, height: u32) -> Result<Renderer> {
-        let (canvas, pump, ttf_context) = init_sdl(app_name, width, height)?;
+    fn new(settings: &WindowSettings) -> Result<Renderer> {
+        let (sdl, canvas, pump, ttf_context, image_context) = init_sdl(settings)?;
+        let controller_subsystem = sdl.game_controller()?;
+        // Rumble is optional, so controllers still work without it.
+        let haptic_subsystem = sdl.haptic().ok();
         Ok(Renderer {
+            textures: Vec::new(),
+            placeholder: None,
+            pixel_texture: None,
+            capture_buffer: Vec::new(),
+            recorder: None,
+            replay: Default::default(),
+            text_cache: Default::default(),
+            fonts: Vec::new(),
+            #[cfg(feature = "audio")]
+            audio: None,
+            windows: Vec::new(),
+            texture_creator: canvas.texture_creator(),
             canvas,
+            cursor: None,
+            controllers: Vec::new(),
+            controller_subsystem,
+            haptic_subsystem,
             pump,
             ttf_context,
+            _image_context: image_context,
+            _sdl: sdl,
+            default_fonts: HashMap::new(),
+            text_effect: None,
+            text_state: Default::default(),
+            debug: Default::default(),
+            batch: Default::default(),
+            hot_reload: Default::default(),
+            assets: Default::default(),
+            loader: Default::default(),
+            input: Default::default(),
+            ui: Default::default(),
+            gl: Default::default(),
+            clock: time::Clock::new(),
+            timers: Default::default(),
+            transform: Default::default(),
+            style: Default::default(),
+            noise: Default::default(),
+            rng: ludomath::rng::Rng::new(),
+            target_size: None,
+            integer_scale: false,
+            logical_size_set: false,
+            high_dpi: false,
+            headless: false,
+            tint: Color::RGBA(255, 255, 255, 255),
+            frame_rate: 30,
+            looping: true,
+            redraw_requested: false,
+            logger: Default::default(),
+            quit_key: None,
+            quit_requested: false,
             dirty: true,
         })
     }
+    /// Stops the doodle after the current callback returns, making `run`
+    /// return `RunOutcome::QuitRequested`. Calling this while drawing still
+    /// shows the frame being drawn.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// // Render a two second animation to disk and stop.
+    /// let mut doodle = DoodleBuilder::<State>::new()
+    ///     .fps(30)
+    ///     .draw(Box::new(|_, r| {
+    ///         r.clear();
+    ///         r.fill_circle(r.frame_count() as f32 * 5.0, 100.0, 20.0);
+    ///         r.save_frame_sequence("frames/").unwrap();
+    ///         if r.frame_count() == 60 {
+    ///             r.quit();
+    ///         }
+    ///     }))
+    ///     .build()
+    ///     .unwrap();
+    /// doodle.run().unwrap();
+    /// ```
+    #[inline]
+    pub fn quit(&mut self) {
+        self.quit_requested = true;
+    }
+    /// Returns the width and height of the drawing area.
+    ///
+    /// This is the logical size if one has been set, or else the size of
+    /// the window in pixels, so it always matches the coordinates used for
+    /// drawing and follows the window when it is resized. While drawing on
+    /// graphics it's the size of the graphics.
+    pub fn size(&self) -> (u32, u32) {
+        if let Some(size) = self.target_size {
+            return size;
+        }
+        match self.canvas.logical_size() {
+            (0, 0) => self.canvas.output_size().unwrap_or((0, 0)),
+            size => size,
+        }
+    }
+    /// Returns the width of the drawing area.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.size().0
+    }
+    /// Returns the height of the drawing area.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.size().1
+    }
+    /// Returns the point in the middle of the drawing area.
+    #[inline]
+    pub fn center(&self) -> Point {
+        let (width, height) = self.size();
+        Point::new((width / 2) as i32, (height / 2) as i32)
+    }
     /// Sets the color that the renderer uses for drawing shapes, text, background, etc.
     #[inline]
     pub fn set_draw_color(&mut self, color: Color) {
@@ -233,41 +1787,186 @@ impl Renderer {
     pub fn draw_color(&self) -> Color {
         self.canvas.draw_color()
     }
+    /// Sets how shapes, text and images are combined with what is already
+    /// drawn.
+    ///
+    /// The default is `BlendMode::Blend`, which uses the alpha of colors
+    /// and images to draw them see-through. `BlendMode::Add` adds the
+    /// colors together, which is good for glows, `BlendMode::Mod`
+    /// multiplies them, and `BlendMode::None` ignores alpha and draws
+    /// everything opaque. Clearing always ignores the blend mode.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) {
+    /// // Overlapping lights get brighter where they meet.
+    /// r.clear();
+    /// r.set_blend_mode(BlendMode::Add);
+    /// r.set_draw_color(Color::RGBA(255, 60, 20, 128));
+    /// r.fill_circle(180.0, 200.0, 80.0);
+    /// r.set_draw_color(Color::RGBA(20, 60, 255, 128));
+    /// r.fill_circle(260.0, 200.0, 80.0);
+    /// r.set_blend_mode(BlendMode::Blend);
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_blend_mode(&mut self, blend_mode: BlendMode) {
+        self.canvas.set_blend_mode(blend_mode);
+    }
+    /// Returns the current blend mode.
+    #[inline]
+    pub fn blend_mode(&self) -> BlendMode {
+        self.canvas.blend_mode()
+    }
     /// Clears the screen with the current drawing color.
     #[inline]
     pub fn clear(&mut self) {
         self.dirty = true;
+        self.draw_call();
         self.canvas.clear();
     }
+    /// Clears the screen with `color`, leaving the drawing color alone.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) {
+    /// r.set_draw_color(Color::RGB(255, 0, 0));
+    /// r.background(Color::RGB(20, 20, 30));
+    /// // Still red.
+    /// r.fill_rect(10.0, 10.0, 50.0, 50.0);
+    /// # }
+    /// ```
+    pub fn background(&mut self, color: Color) {
+        let old = self.canvas.draw_color();
+        self.canvas.set_draw_color(color);
+        self.clear();
+        self.canvas.set_draw_color(old);
+    }
+    /// Covers everything drawn so far with `color`, `amount` out of 255 of
+    /// the way. Fading a little each frame instead of clearing leaves
+    /// motion trails behind moving shapes.
+    ///
+    /// An amount of 255 is the same as clearing with `color` and 0 does
+    /// nothing. The alpha of `color`, the drawing color and the blend mode
+    /// are all left alone.
+    pub fn fade(&mut self, color: Color, amount: u8) {
+        if amount == 0 {
+            return;
+        }
+        self.dirty = true;
+        let (draw_color, blend_mode) = (self.canvas.draw_color(), self.canvas.blend_mode());
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        self.canvas.set_draw_color(Color::RGBA(color.r, color.g, color.b, amount));
+        self.draw_call();
+        let result = self.canvas.fill_rect(None);
+        self.log_failure("fade", result);
+        self.canvas.set_blend_mode(blend_mode);
+        self.canvas.set_draw_color(draw_color);
+    }
     /// Calling this function makes all the drawing operations
     /// performed actually appear on the screen.
     #[inline]
     pub fn present(&mut self) {
+        self.flush_batch();
+        let evicted = self.text_cache.end_frame();
+        if evicted > 0 {
+            self.logger
+                .log(Level::Debug, || format!("evicted {} strings from the text cache", evicted));
+        }
         if self.dirty {
             self.dirty = false;
             self.canvas.present();
         }
     }
+    /// Returns the SDL canvas that the doodle draws on, for using parts of
+    /// sdl2 that doodle doesn't wrap yet. The sdl2 crate is re-exported as
+    /// `doodle::sdl2`.
+    ///
+    /// This is for advanced use. Whatever is drawn on the canvas ignores
+    /// the transformation and style, and settings changed on it, like the
+    /// render target, scale or viewport, can confuse doodle if they're not
+    /// put back before returning. Drawing colors and blend modes can be
+    /// changed freely, since they're the same ones `set_draw_color` and
+    /// `set_blend_mode` change.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) -> Result<()> {
+    /// let canvas = r.canvas_mut();
+    /// let clip = canvas.clip_rect();
+    /// canvas.set_clip_rect(Rect::new(0, 0, 100, 100));
+    /// // ... draw with sdl2 ...
+    /// canvas.set_clip_rect(clip);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn canvas_mut(&mut self) -> &mut Canvas<Window> {
+        // Shapes batched before this have to be drawn before anything
+        // drawn straight on the canvas.
+        self.flush_batch();
+        self.dirty = true;
+        &mut self.canvas
+    }
+    /// Returns the SDL texture creator for the canvas, for making textures
+    /// to draw on `canvas_mut`. Like `canvas_mut`, this is for advanced use.
+    #[inline]
+    pub fn texture_creator(&self) -> &TextureCreator<WindowContext> {
+        &self.texture_creator
+    }
 }
 
 fn init_sdl(
-    app_name: &str,
-    width: u32,
-    height: u32,
-) -> Result<(Canvas<Window>, EventPump, Sdl2TtfContext)> {
+    settings: &WindowSettings,
+) -> Result<(Sdl, Canvas<Window>, EventPump, Sdl2TtfContext, Sdl2ImageContext)> {
+    if settings.headless {
+        // Hints set here give way to the environment variable.
+        sdl2::hint::set("SDL_VIDEODRIVER", "dummy");
+    } else if settings.opengl {
+        sdl2::hint::set("SDL_RENDER_DRIVER", "opengl");
+    }
     let sdl_context = sdl2::init()?;
-    let _image_context = sdl2::image::init(INIT_PNG)?;
+    let image_context = sdl2::image::init(INIT_PNG)?;
     let video_subsystem = sdl_context.video()?;
 
-    let window = video_subsystem
-        .window(app_name, width, height)
-        .position_centered()
-        .opengl()
-        .build()?;
+    let mut window = video_subsystem.window(settings.title, settings.width, settings.height);
+    if settings.headless {
+        window.hidden();
+    } else {
+        window.position_centered().opengl();
+    }
+    match settings.fullscreen {
+        FullscreenType::Off => (),
+        FullscreenType::True => {
+            window.fullscreen();
+        }
+        FullscreenType::Desktop => {
+            window.fullscreen_desktop();
+        }
+    }
+    if settings.resizable {
+        window.resizable();
+    }
+    if settings.high_dpi && !settings.headless {
+        window.allow_highdpi();
+    }
+    let window = window.build()?;
 
-    let canvas = window.into_canvas().build()?;
+    let mut canvas = window.into_canvas();
+    if settings.headless {
+        canvas = canvas.software();
+    } else if settings.vsync {
+        canvas = canvas.present_vsync();
+    }
+    let mut canvas = canvas.build()?;
+    canvas.set_blend_mode(BlendMode::Blend);
+    // SDL starts with text input on, which can pop up input method windows
+    // and on-screen keyboards in doodles that never read text.
+    video_subsystem.text_input().stop();
     let event_pump = sdl_context.event_pump()?;
     let ttf_context = sdl2::ttf::init()?;
-    Ok((canvas, event_pump, ttf_context))
+    Ok((sdl_context, canvas, event_pump, ttf_context, image_context))
 }
 
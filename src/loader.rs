@@ -0,0 +1,272 @@
+//! Loading images on worker threads, so a doodle with lots of them can
+//! show a loading screen instead of freezing while they're read.
+//!
+//! Only decoding files happens on the workers. SDL's textures have to be
+//! made on the thread that owns the renderer, so decoded pixels are sent
+//! back and turned into textures between frames.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use sdl2::image::LoadSurface;
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+use sdl2::surface::Surface;
+
+use assets::Assets;
+use image::into_static;
+use pixels::FORMAT;
+use {Error, Image, Renderer};
+
+/// The most worker threads that load images at once.
+const MAX_WORKERS: usize = 4;
+/// How long `wait_for_loads` waits for a worker before checking for events
+/// again.
+const WAIT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// An image for a worker to decode.
+struct Job {
+    id: usize,
+    path: PathBuf,
+}
+
+/// The pixels a worker decoded, as a width, a height, the bytes in each
+/// row and the rows.
+type Decoded = ::std::result::Result<(u32, u32, usize, Vec<u8>), String>;
+
+/// The worker threads and the images they haven't finished yet.
+#[derive(Default)]
+pub(crate) struct Loader {
+    /// Started with the first image that's loaded in the background.
+    workers: Option<Workers>,
+    /// The paths of the images being loaded, by image id.
+    pending: HashMap<usize, PathBuf>,
+    loaded: usize,
+    total: usize,
+}
+
+struct Workers {
+    jobs: Sender<Job>,
+    results: Receiver<(usize, Decoded)>,
+}
+
+impl Workers {
+    fn start() -> Workers {
+        let (jobs, job_receiver) = mpsc::channel::<Job>();
+        let (result_sender, results) = mpsc::channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+        let count = thread::available_parallelism().map_or(1, |n| n.get()).min(MAX_WORKERS);
+        for _ in 0..count {
+            let (job_receiver, result_sender) = (job_receiver.clone(), result_sender.clone());
+            // The workers stop once the Loader, and with it the sender of
+            // their jobs, is dropped.
+            thread::spawn(move || loop {
+                let job = match job_receiver.lock() {
+                    Ok(receiver) => receiver.recv(),
+                    Err(_) => return,
+                };
+                let job = match job {
+                    Ok(job) => job,
+                    Err(_) => return,
+                };
+                if result_sender.send((job.id, decode(&job.path))).is_err() {
+                    return;
+                }
+            });
+        }
+        Workers { jobs, results }
+    }
+}
+
+/// Reads an image file into pixels in the format textures are made from.
+fn decode(path: &Path) -> Decoded {
+    let surface = Surface::from_file(path)?;
+    // SDL needs a PixelFormat to convert to, which only comes from a
+    // surface, like in `Frame::load`.
+    let format = Surface::new(1, 1, FORMAT)?.pixel_format();
+    let surface = surface.convert(&format)?;
+    let (width, height, pitch) = (surface.width(), surface.height(), surface.pitch() as usize);
+    let pixels = surface.with_lock(|pixels| pixels.to_vec());
+    Ok((width, height, pitch, pixels))
+}
+
+impl Renderer {
+    /// Makes textures of the images the workers have finished, waiting up
+    /// to `timeout` for the first one if there's one to wait for.
+    pub(crate) fn finish_loads(&mut self, timeout: Option<Duration>) {
+        let mut finished = Vec::new();
+        if let Some(ref workers) = self.loader.workers {
+            if let Some(timeout) = timeout {
+                finished.extend(workers.results.recv_timeout(timeout).ok());
+            }
+            finished.extend(workers.results.try_iter());
+        }
+        for (id, decoded) in finished {
+            // Images unloaded while they were loading are left unloaded.
+            let path = match self.loader.pending.remove(&id) {
+                Some(path) => path,
+                None => continue,
+            };
+            self.loader.loaded += 1;
+            let result = decoded.map_err(Error::Error).and_then(|(width, height, pitch, pixels)| {
+                let mut texture = self.texture_creator.create_texture_static(FORMAT, width, height)?;
+                texture.update(None, &pixels, pitch).map_err(|e| Error::Error(e.to_string()))?;
+                texture.set_blend_mode(BlendMode::Blend);
+                Ok(into_static(texture))
+            });
+            match result {
+                Ok(texture) => self.replace_texture(Image { id }, Some(texture)),
+                Err(error) => self
+                    .logger
+                    .warn_or_print(|| format!("couldn't load {}: {:?}", path.display(), error)),
+            }
+            // Sketches that aren't looping still show the image.
+            self.dirty = true;
+            self.redraw_requested = true;
+        }
+    }
+    /// Stops waiting for an image that was unloaded while it was loading.
+    pub(crate) fn forget_load(&mut self, image: Image) {
+        if self.loader.pending.remove(&image.id).is_some() {
+            self.loader.total -= 1;
+        }
+    }
+    /// Draws a simple loading screen: a bar across the middle of the
+    /// drawing area that fills up as `loaded` gets to `total`, and the
+    /// count under it.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State { tiles: Vec<Image> }
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .setup(Box::new(|state, r| {
+    ///         for i in 0..200 {
+    ///             let tile = r.assets().load_image_async(format!("tiles/{}.png", i));
+    ///             state.tiles.push(tile);
+    ///         }
+    ///     }))
+    ///     .draw(Box::new(|state, r| {
+    ///         let (loaded, total) = r.assets().loading_progress();
+    ///         if loaded < total {
+    ///             r.draw_loading_screen(loaded, total);
+    ///             return;
+    ///         }
+    ///         r.clear();
+    ///         for (i, &tile) in state.tiles.iter().enumerate() {
+    ///             r.draw_image(tile, (i % 20) as f32 * 40.0, (i / 20) as f32 * 40.0);
+    ///         }
+    ///     }));
+    /// ```
+    pub fn draw_loading_screen(&mut self, loaded: usize, total: usize) {
+        let color = self.canvas.draw_color();
+        let (width, height) = self.size();
+        let (width, height) = (width as f32, height as f32);
+        let fraction = if total > 0 { (loaded as f32 / total as f32).min(1.0) } else { 1.0 };
+        let (bar_width, bar_height) = (width * 0.6, 12.0);
+        let (x, y) = ((width - bar_width) / 2.0, (height - bar_height) / 2.0);
+        self.push_matrix();
+        self.reset_matrix();
+        self.background(Color::RGB(0, 0, 0));
+        self.canvas.set_draw_color(Color::RGB(60, 60, 60));
+        self.fill_rect(x, y, bar_width, bar_height);
+        self.canvas.set_draw_color(Color::RGB(255, 255, 255));
+        self.fill_rect(x, y, bar_width * fraction, bar_height);
+        let text = format!("Loading {} of {}", loaded, total);
+        let result = self.text(&text, x, y + bar_height * 2.0);
+        self.log_failure("draw the loading screen", result);
+        self.pop_matrix();
+        self.canvas.set_draw_color(color);
+    }
+}
+
+impl<'a> Assets<'a> {
+    /// Starts loading an image on a worker thread, or returns the already
+    /// loaded copy of it like `load_image`.
+    ///
+    /// The image draws as the placeholder checkerboard until it has loaded,
+    /// which `is_ready` says, and finished images are turned into textures
+    /// between frames. Images that can't be loaded stay the placeholder,
+    /// with a message printed like `load_image`. See
+    /// `Renderer::draw_loading_screen` for an example.
+    pub fn load_image_async<P: AsRef<Path>>(&mut self, path: P) -> Image {
+        let path = path.as_ref();
+        if let Some(image) = self.cached_image(path) {
+            return image;
+        }
+        // The placeholder is watched like an image that failed to load, so
+        // hot reloading still works.
+        let image = self.renderer.missing_image(path);
+        self.insert_image(path, image);
+        let r = &mut *self.renderer;
+        let loader = &mut r.loader;
+        if loader.pending.is_empty() {
+            loader.loaded = 0;
+            loader.total = 0;
+        }
+        loader.total += 1;
+        loader.pending.insert(image.id, path.to_owned());
+        let workers = loader.workers.get_or_insert_with(Workers::start);
+        let job = Job {
+            id: image.id,
+            path: path.to_owned(),
+        };
+        if workers.jobs.send(job).is_err() {
+            // Sending only fails once every worker has panicked.
+            loader.pending.remove(&image.id);
+            loader.loaded += 1;
+            let message = || format!("couldn't load {}: the loading threads have stopped", path.display());
+            r.logger.warn_or_print(message);
+        }
+        image
+    }
+    /// Returns true once an image loaded with `load_image_async` has
+    /// finished loading, whether it worked or not. Images loaded any other
+    /// way are always ready.
+    #[inline]
+    pub fn is_ready(&self, image: Image) -> bool {
+        !self.renderer.loader.pending.contains_key(&image.id)
+    }
+    /// Returns how many images loaded with `load_image_async` have finished
+    /// and how many there are, counting from when the last of the images
+    /// before them finished.
+    #[inline]
+    pub fn loading_progress(&self) -> (usize, usize) {
+        (self.renderer.loader.loaded, self.renderer.loader.total)
+    }
+    /// Waits for every image loaded with `load_image_async` to finish,
+    /// calling `progress` with the renderer, the number finished and the
+    /// total each time one does, like `preload_manifest`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn setup(r: &mut Renderer, paths: &[String]) {
+    /// for path in paths {
+    ///     r.assets().load_image_async(path);
+    /// }
+    /// r.assets().wait_for_loads(|r, loaded, total| {
+    ///     r.draw_loading_screen(loaded, total);
+    ///     r.present();
+    /// });
+    /// # }
+    /// ```
+    pub fn wait_for_loads<F: FnMut(&mut Renderer, usize, usize)>(&mut self, mut progress: F) {
+        let r = &mut *self.renderer;
+        progress(r, r.loader.loaded, r.loader.total);
+        while !r.loader.pending.is_empty() {
+            let loaded = r.loader.loaded;
+            r.finish_loads(Some(WAIT_INTERVAL));
+            // Events are left queued for the doodle, but pumping them stops
+            // the window being marked as not responding.
+            r.pump.pump_events();
+            if r.loader.loaded != loaded {
+                progress(r, r.loader.loaded, r.loader.total);
+            }
+        }
+    }
+}
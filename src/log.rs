@@ -0,0 +1,77 @@
+//! Reporting the decisions doodle makes behind the scenes.
+
+use std::fmt::{self, Debug, Display};
+
+use Renderer;
+
+/// How important a logged message is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// Details that are only interesting while tracking down a problem,
+    /// like events nothing listens to.
+    Debug,
+    /// Something doodle decided on its own that works but might not be
+    /// what was wanted, like a display mode other than the one asked for.
+    Info,
+    /// Something went wrong and doodle carried on anyway, like an image
+    /// that couldn't be loaded and is drawn as a checkerboard.
+    Warn,
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Level::Debug => "debug",
+            Level::Info => "info",
+            Level::Warn => "warning",
+        })
+    }
+}
+
+/// A LogHandler is a callback function that receives the messages doodle
+/// logs, set with `DoodleBuilder::on_log`.
+pub type LogHandler = Box<dyn Fn(Level, &str)>;
+
+/// Sends messages to the log handler, if there is one.
+#[derive(Default)]
+pub(crate) struct Logger {
+    handler: Option<LogHandler>,
+}
+
+impl Logger {
+    pub(crate) fn new(handler: Option<LogHandler>) -> Self {
+        Logger { handler }
+    }
+    /// Returns true if messages go anywhere.
+    #[inline]
+    pub(crate) fn enabled(&self) -> bool {
+        self.handler.is_some()
+    }
+    /// Sends a message to the handler. Without a handler the message is
+    /// dropped without being built.
+    #[inline]
+    pub(crate) fn log<F: FnOnce() -> String>(&self, level: Level, message: F) {
+        if let Some(ref handler) = self.handler {
+            handler(level, &message());
+        }
+    }
+    /// Sends a warning to the handler, or prints it to stderr if there is
+    /// no handler, for the problems doodle has always printed.
+    pub(crate) fn warn_or_print<F: FnOnce() -> String>(&self, message: F) {
+        match self.handler {
+            Some(ref handler) => handler(Level::Warn, &message()),
+            None => eprintln!("doodle: {}", message()),
+        }
+    }
+}
+
+impl Renderer {
+    /// Logs a warning if something that can't return an error failed, like
+    /// a drawing operation. This is the only place those errors show up.
+    #[inline]
+    pub(crate) fn log_failure<E: Debug>(&self, what: &str, result: ::std::result::Result<(), E>) {
+        if let Err(error) = result {
+            self.logger.log(Level::Warn, || format!("couldn't {}: {:?}", what, error));
+        }
+    }
+}
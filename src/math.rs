@@ -0,0 +1,64 @@
+//! Small number helpers for turning one range of values into another.
+//!
+//! These work like the functions of the same names in Processing.
+//!
+//! # Example
+//! ```no_run
+//! # use doodle::*;
+//! use doodle::math::{constrain, map};
+//! # fn draw(r: &mut Renderer) {
+//! // The further right the mouse is, the bigger the circle.
+//! let (x, _) = r.mouse_position();
+//! let radius = map(x as f32, 0.0, r.width() as f32, 5.0, 100.0);
+//! r.fill_circle(200.0, 200.0, constrain(radius, 5.0, 100.0));
+//! # }
+//! ```
+
+/// Maps a value from one range to another, so `in_min` becomes `out_min`
+/// and `in_max` becomes `out_max`. Values outside of the first range end
+/// up outside of the second one; use `constrain` to keep them in.
+///
+/// If `in_min` and `in_max` are the same, there is no way to map the
+/// value and `out_min` is returned.
+#[inline]
+pub fn map(value: f32, in_min: f32, in_max: f32, out_min: f32, out_max: f32) -> f32 {
+    lerp(out_min, out_max, norm(value, in_min, in_max))
+}
+
+/// Returns how far `value` is from `min` to `max`, from 0 at `min` to 1 at
+/// `max`, or 0 if `min` and `max` are the same.
+#[inline]
+pub fn norm(value: f32, min: f32, max: f32) -> f32 {
+    if max == min {
+        0.0
+    } else {
+        (value - min) / (max - min)
+    }
+}
+
+/// Returns the value `t` of the way from `a` to `b`, so 0 gives `a` and
+/// 1 gives `b`. `t` isn't clamped.
+#[inline]
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Keeps a value between `min` and `max`. Unlike `f32::clamp`, this doesn't
+/// panic when `min` is bigger than `max`, and gives `min` instead.
+#[inline]
+pub fn constrain(value: f32, min: f32, max: f32) -> f32 {
+    value.min(max).max(min)
+}
+
+/// Turns an angle in radians into degrees.
+#[inline]
+pub fn degrees(radians: f32) -> f32 {
+    radians.to_degrees()
+}
+
+/// Turns an angle in degrees into radians, which `Renderer::rotate` and
+/// `Vec2::from_angle` take.
+#[inline]
+pub fn radians(degrees: f32) -> f32 {
+    degrees.to_radians()
+}
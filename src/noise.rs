@@ -0,0 +1,306 @@
+//! Perlin and simplex noise, for randomness that changes smoothly.
+//!
+//! Noise gives a value between -1 and 1 for every point, and points close
+//! to each other get close values, so it's good for anything that should
+//! look natural: terrain, clouds, wobbly lines, or movement that wanders.
+//! The values change about once per unit, so coordinates are usually
+//! scaled down first, and moving through one of the coordinates over time
+//! animates it.
+//!
+//! Every doodle has its own `Noise`, returned by `Renderer::noise`, which
+//! always gives the same values until it is reseeded with
+//! `Renderer::noise_seed`.
+//!
+//! # Example
+//! ```no_run
+//! # use doodle::*;
+//! # fn draw(r: &mut Renderer) {
+//! // A hilly horizon that scrolls to the left.
+//! let t = r.elapsed_seconds() as f32;
+//! let (width, height) = r.size();
+//! r.clear();
+//! for x in 0..width {
+//!     let hill = r.noise().fbm1(x as f32 * 0.005 + t, 4, 0.5);
+//!     let top = height as f32 * (0.6 + 0.25 * hill);
+//!     r.fill_rect(x as f32, top, 1.0, height as f32 - top);
+//! }
+//! # }
+//! ```
+
+use ludomath::rng::Rng;
+
+use math::lerp;
+use Renderer;
+
+/// A source of Perlin and simplex noise, which gives the same values for
+/// the same seed.
+#[derive(Clone)]
+pub struct Noise {
+    seed: u64,
+    // The numbers 0 to 255 shuffled, twice over, so indexing with a hash
+    // plus another coordinate never runs off the end.
+    perm: [u8; 512],
+}
+
+impl Default for Noise {
+    fn default() -> Self {
+        Noise::new(0)
+    }
+}
+
+impl Noise {
+    /// Creates noise from a seed. Different seeds give unrelated noise.
+    pub fn new(seed: u64) -> Noise {
+        let mut table: Vec<u8> = (0..=255).collect();
+        Rng::new_seeded(seed, 0x9e37_79b9_7f4a_7c15).shuffle_mut(&mut table);
+        let mut perm = [0; 512];
+        for (i, p) in perm.iter_mut().enumerate() {
+            *p = table[i & 255];
+        }
+        Noise { seed, perm }
+    }
+    /// Returns the seed this noise was created from.
+    #[inline]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    #[inline]
+    fn hash(&self, i: i32) -> usize {
+        usize::from(self.perm[(i & 255) as usize])
+    }
+    #[inline]
+    fn hash2(&self, i: i32, j: i32) -> usize {
+        usize::from(self.perm[self.hash(i) + (j & 255) as usize])
+    }
+    #[inline]
+    fn hash3(&self, i: i32, j: i32, k: i32) -> usize {
+        usize::from(self.perm[self.hash2(i, j) + (k & 255) as usize])
+    }
+    /// Returns Perlin noise along a line, between -1 and 1.
+    pub fn perlin1(&self, x: f32) -> f32 {
+        let x0 = x.floor();
+        let (i, x) = (x0 as i32, x - x0);
+        let n0 = grad1(self.hash(i), x);
+        let n1 = grad1(self.hash(i + 1), x - 1.0);
+        clamp(lerp(n0, n1, fade(x)) * 2.0)
+    }
+    /// Returns Perlin noise on a plane, between -1 and 1.
+    pub fn perlin2(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (i, j) = (x0 as i32, y0 as i32);
+        let (x, y) = (x - x0, y - y0);
+        let (u, v) = (fade(x), fade(y));
+        let n00 = grad2(self.hash2(i, j), x, y);
+        let n10 = grad2(self.hash2(i + 1, j), x - 1.0, y);
+        let n01 = grad2(self.hash2(i, j + 1), x, y - 1.0);
+        let n11 = grad2(self.hash2(i + 1, j + 1), x - 1.0, y - 1.0);
+        clamp(lerp(lerp(n00, n10, u), lerp(n01, n11, u), v))
+    }
+    /// Returns Perlin noise in space, between -1 and 1. Using time as the
+    /// third coordinate animates 2D noise smoothly.
+    pub fn perlin3(&self, x: f32, y: f32, z: f32) -> f32 {
+        let (x0, y0, z0) = (x.floor(), y.floor(), z.floor());
+        let (i, j, k) = (x0 as i32, y0 as i32, z0 as i32);
+        let (x, y, z) = (x - x0, y - y0, z - z0);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+        let corner = |di: i32, dj: i32, dk: i32| {
+            let hash = self.hash3(i + di, j + dj, k + dk);
+            grad3(hash, x - di as f32, y - dj as f32, z - dk as f32)
+        };
+        let near = lerp(
+            lerp(corner(0, 0, 0), corner(1, 0, 0), u),
+            lerp(corner(0, 1, 0), corner(1, 1, 0), u),
+            v,
+        );
+        let far = lerp(
+            lerp(corner(0, 0, 1), corner(1, 0, 1), u),
+            lerp(corner(0, 1, 1), corner(1, 1, 1), u),
+            v,
+        );
+        clamp(lerp(near, far, w))
+    }
+    /// Returns simplex noise along a line, between -1 and 1.
+    pub fn simplex1(&self, x: f32) -> f32 {
+        let x0 = x.floor();
+        let i = x0 as i32;
+        let contribution = |i: i32, x: f32| {
+            let t = (1.0 - x * x).max(0.0);
+            let t = t * t;
+            t * t * grad1(self.hash(i), x)
+        };
+        let x = x - x0;
+        clamp((contribution(i, x) + contribution(i + 1, x - 1.0)) * 3.16)
+    }
+    /// Returns simplex noise on a plane, between -1 and 1.
+    ///
+    /// Simplex noise is a little quicker than Perlin noise and doesn't
+    /// line up with the axes as much.
+    pub fn simplex2(&self, x: f32, y: f32) -> f32 {
+        const SKEW: f32 = 0.366_025_4; // (sqrt(3) - 1) / 2
+        const UNSKEW: f32 = 0.211_324_87; // (3 - sqrt(3)) / 6
+        let s = (x + y) * SKEW;
+        let (i, j) = ((x + s).floor(), (y + s).floor());
+        let t = (i + j) * UNSKEW;
+        let (x0, y0) = (x - (i - t), y - (j - t));
+        let (i, j) = (i as i32, j as i32);
+        let (di, dj) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let corners = [
+            (0, 0, x0, y0),
+            (di, dj, x0 - di as f32 + UNSKEW, y0 - dj as f32 + UNSKEW),
+            (1, 1, x0 - 1.0 + 2.0 * UNSKEW, y0 - 1.0 + 2.0 * UNSKEW),
+        ];
+        let mut total = 0.0;
+        for &(ci, cj, x, y) in &corners {
+            let t = 0.5 - x * x - y * y;
+            if t > 0.0 {
+                total += t * t * t * t * grad2(self.hash2(i + ci, j + cj), x, y);
+            }
+        }
+        clamp(total * 70.0)
+    }
+    /// Returns simplex noise in space, between -1 and 1.
+    pub fn simplex3(&self, x: f32, y: f32, z: f32) -> f32 {
+        const SKEW: f32 = 1.0 / 3.0;
+        const UNSKEW: f32 = 1.0 / 6.0;
+        let s = (x + y + z) * SKEW;
+        let (i, j, k) = ((x + s).floor(), (y + s).floor(), (z + s).floor());
+        let t = (i + j + k) * UNSKEW;
+        let (x0, y0, z0) = (x - (i - t), y - (j - t), z - (k - t));
+        let (i, j, k) = (i as i32, j as i32, k as i32);
+        // Which of the six tetrahedra in the cube the point is in decides
+        // the two corners between the first and last.
+        let ((i1, j1, k1), (i2, j2, k2)) = if x0 >= y0 {
+            if y0 >= z0 {
+                ((1, 0, 0), (1, 1, 0))
+            } else if x0 >= z0 {
+                ((1, 0, 0), (1, 0, 1))
+            } else {
+                ((0, 0, 1), (1, 0, 1))
+            }
+        } else if y0 < z0 {
+            ((0, 0, 1), (0, 1, 1))
+        } else if x0 < z0 {
+            ((0, 1, 0), (0, 1, 1))
+        } else {
+            ((0, 1, 0), (1, 1, 0))
+        };
+        let offset = |ci: i32, cj: i32, ck: i32, n: f32| {
+            (ci, cj, ck, x0 - ci as f32 + n * UNSKEW, y0 - cj as f32 + n * UNSKEW, z0 - ck as f32 + n * UNSKEW)
+        };
+        let corners = [
+            offset(0, 0, 0, 0.0),
+            offset(i1, j1, k1, 1.0),
+            offset(i2, j2, k2, 2.0),
+            offset(1, 1, 1, 3.0),
+        ];
+        let mut total = 0.0;
+        for &(ci, cj, ck, x, y, z) in &corners {
+            let t = 0.6 - x * x - y * y - z * z;
+            if t > 0.0 {
+                total += t * t * t * t * grad3(self.hash3(i + ci, j + cj, k + ck), x, y, z);
+            }
+        }
+        clamp(total * 32.0)
+    }
+    /// Returns fractal Brownian motion along a line: Perlin noise layered
+    /// `octaves` times, each layer twice as detailed as the one before and
+    /// weighted by `falloff` times as much. The result is between -1 and 1.
+    ///
+    /// More octaves give rougher, more natural looking noise. A falloff of
+    /// 0.5 is a good start; higher values make the fine details stronger.
+    pub fn fbm1(&self, x: f32, octaves: u32, falloff: f32) -> f32 {
+        fbm(octaves, falloff, |frequency| self.perlin1(x * frequency))
+    }
+    /// Returns fractal Brownian motion on a plane, like `fbm1`.
+    pub fn fbm2(&self, x: f32, y: f32, octaves: u32, falloff: f32) -> f32 {
+        fbm(octaves, falloff, |frequency| self.perlin2(x * frequency, y * frequency))
+    }
+    /// Returns fractal Brownian motion in space, like `fbm1`.
+    pub fn fbm3(&self, x: f32, y: f32, z: f32, octaves: u32, falloff: f32) -> f32 {
+        fbm(octaves, falloff, |frequency| {
+            self.perlin3(x * frequency, y * frequency, z * frequency)
+        })
+    }
+}
+
+/// Adds up octaves of noise and scales the sum back to between -1 and 1.
+fn fbm<F: Fn(f32) -> f32>(octaves: u32, falloff: f32, noise: F) -> f32 {
+    let (mut total, mut weights) = (0.0, 0.0);
+    let (mut frequency, mut weight) = (1.0, 1.0);
+    for _ in 0..octaves.max(1) {
+        total += noise(frequency) * weight;
+        weights += weight;
+        frequency *= 2.0;
+        weight *= falloff;
+    }
+    if weights == 0.0 {
+        0.0
+    } else {
+        clamp(total / weights)
+    }
+}
+
+/// Eases from 0 to 1 so the noise is smooth across grid lines.
+#[inline]
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+#[inline]
+fn clamp(value: f32) -> f32 {
+    value.clamp(-1.0, 1.0)
+}
+
+/// Returns the dot product of a gradient picked by the hash with (x).
+/// Gradients are between -1 and 1, other than 0.
+#[inline]
+fn grad1(hash: usize, x: f32) -> f32 {
+    let gradient = 1.0 + (hash & 7) as f32 / 7.0;
+    let gradient = if hash & 8 == 0 { gradient } else { -gradient };
+    gradient * x / 2.0
+}
+
+/// Returns the dot product of one of eight gradients around the square,
+/// picked by the hash, with (x, y).
+#[inline]
+fn grad2(hash: usize, x: f32, y: f32) -> f32 {
+    match hash & 7 {
+        0 => x + y,
+        1 => -x + y,
+        2 => x - y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+/// Returns the dot product of one of the twelve gradients towards the
+/// edges of a cube, picked by the hash, with (x, y, z).
+#[inline]
+fn grad3(hash: usize, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = match h {
+        0..=3 => y,
+        12 | 14 => x,
+        _ => z,
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+impl Renderer {
+    /// Returns the doodle's noise. See the `noise` module for an example.
+    #[inline]
+    pub fn noise(&self) -> &Noise {
+        &self.noise
+    }
+    /// Replaces the doodle's noise with noise from a different seed. The
+    /// noise starts out with the seed 0, or the one given to
+    /// `DoodleBuilder::seed`, so it's the same every run unless it's
+    /// reseeded. `random_seed` reseeds it too.
+    pub fn noise_seed(&mut self, seed: u64) {
+        self.noise = Noise::new(seed);
+    }
+}
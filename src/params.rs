@@ -0,0 +1,256 @@
+//! A panel of controls for fields of the state, saved to a file between runs.
+
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use sdl2::mouse::MouseButton;
+
+use config::{config_error, parse_bool, parse_string, strip_comment};
+use {Color, ColorExt, DoodleBuilder, Renderer, Result};
+
+/// How wide the panel is, in pixels.
+const WIDTH: f32 = 220.0;
+/// The space between the panel and the edges of the drawing area, in pixels.
+const MARGIN: f32 = 10.0;
+
+/// Gets a field of the state for a control to change.
+pub type ParamField<T, V> = fn(&mut T) -> &mut V;
+
+enum Kind<T> {
+    Float(ParamField<T, f32>, Range<f32>),
+    Int(ParamField<T, i32>, Range<i32>),
+    Bool(ParamField<T, bool>),
+    Color(ParamField<T, Color>),
+}
+
+struct Param<T> {
+    name: String,
+    kind: Kind<T>,
+}
+
+/// The parameters a doodle shows controls for.
+pub(crate) struct ParamPanel<T> {
+    params: Vec<Param<T>>,
+    path: Option<PathBuf>,
+    pub(crate) visible: bool,
+    /// Set when a control changes a value, until the values are saved.
+    unsaved: bool,
+}
+
+impl<T> Default for ParamPanel<T> {
+    fn default() -> Self {
+        ParamPanel {
+            params: Vec::new(),
+            path: None,
+            visible: true,
+            unsaved: false,
+        }
+    }
+}
+
+impl<T> ParamPanel<T> {
+    fn add(&mut self, name: &str, kind: Kind<T>) {
+        self.params.retain(|p| p.name != name);
+        self.params.push(Param {
+            name: name.to_owned(),
+            kind,
+        });
+    }
+    /// Sets the state's fields to the values saved in the params file, if
+    /// there is one. A file that doesn't exist yet is left for `save`.
+    pub(crate) fn load(&self, state: &mut T) -> Result<()> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(ref error) if error.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(error) => return Err(error.into()),
+        };
+        for (index, line) in text.lines().enumerate() {
+            let line_number = index + 1;
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = split_key(line)
+                .ok_or_else(|| config_error(line_number, line, "expected `key = value`"))?;
+            // Parameters that were removed from the sketch are ignored.
+            if let Some(param) = self.params.iter().find(|p| p.name == key) {
+                param
+                    .set(state, value)
+                    .map_err(|message| config_error(line_number, &key, message))?;
+            }
+        }
+        Ok(())
+    }
+    /// Writes the values of the state's fields to the params file.
+    fn save(&self, state: &mut T, path: &Path) -> Result<()> {
+        let mut text = String::from("# The values of a doodle's parameters, saved by its panel.\n");
+        for param in &self.params {
+            let value = match param.kind {
+                Kind::Float(field, _) => format!("{:?}", *field(state)),
+                Kind::Int(field, _) => field(state).to_string(),
+                Kind::Bool(field) => field(state).to_string(),
+                Kind::Color(field) => {
+                    let c = *field(state);
+                    format!("\"#{:02x}{:02x}{:02x}{:02x}\"", c.r, c.g, c.b, c.a)
+                }
+            };
+            text.push_str(&format!("{} = {}\n", key(&param.name), value));
+        }
+        fs::write(path, text)?;
+        Ok(())
+    }
+    /// Draws the panel in the top right corner, and saves the values once
+    /// they have been changed and the mouse has let go of the controls.
+    pub(crate) fn draw(&mut self, state: &mut T, r: &mut Renderer) {
+        if !self.visible || self.params.is_empty() {
+            return;
+        }
+        let x = (r.width() as f32 - WIDTH - MARGIN).max(0.0);
+        let mut ui = r.ui(x, MARGIN).width(WIDTH);
+        ui.label("Parameters");
+        let mut changed = false;
+        for param in &self.params {
+            let name = &param.name;
+            changed |= match param.kind {
+                Kind::Float(field, ref range) => ui.slider(name, field(state), range.clone()),
+                Kind::Int(field, ref range) => ui.slider_int(name, field(state), range.clone()),
+                Kind::Bool(field) => ui.checkbox(name, field(state)),
+                Kind::Color(field) => {
+                    let color = field(state);
+                    ui.label(name);
+                    let mut changed = false;
+                    let channels = [("red", &mut color.r), ("green", &mut color.g), ("blue", &mut color.b)];
+                    for (channel, value) in channels {
+                        let mut int = i32::from(*value);
+                        if ui.slider_int(&format!("{} {}", name, channel), &mut int, 0..255) {
+                            *value = int as u8;
+                            changed = true;
+                        }
+                    }
+                    changed
+                }
+            };
+        }
+        self.unsaved |= changed;
+        if let Some(ref path) = self.path {
+            if self.unsaved && !r.is_mouse_button_down(MouseButton::Left) {
+                let result = self.save(state, path);
+                r.log_failure("save the parameters", result);
+                self.unsaved = false;
+            }
+        }
+    }
+}
+
+impl<T> Param<T> {
+    /// Sets the parameter's field from a value in the params file.
+    fn set(&self, state: &mut T, value: &str) -> ::std::result::Result<(), &'static str> {
+        match self.kind {
+            Kind::Float(field, _) => *field(state) = value.parse().map_err(|_| "must be a number")?,
+            Kind::Int(field, _) => *field(state) = value.parse().map_err(|_| "must be a whole number")?,
+            Kind::Bool(field) => *field(state) = parse_bool(value)?,
+            Kind::Color(field) => {
+                let hex = parse_string(value)?;
+                *field(state) = Color::from_hex(&hex).map_err(|_| "must be a hex color, like \"#ff8800\"")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Splits a line into its key, which can be quoted, and its value.
+fn split_key(line: &str) -> Option<(String, &str)> {
+    if !line.starts_with('"') {
+        let eq = line.find('=')?;
+        return Some((line[..eq].trim().to_owned(), line[eq + 1..].trim()));
+    }
+    // Find the end of the quoted key, skipping escaped quotes.
+    let mut escaped = false;
+    let end = line.char_indices().skip(1).find(|&(_, c)| {
+        let end = c == '"' && !escaped;
+        escaped = c == '\\' && !escaped;
+        end
+    })?;
+    let key = parse_string(&line[..end.0 + 1]).ok()?;
+    let rest = line[end.0 + 1..].trim_start();
+    if !rest.starts_with('=') {
+        return None;
+    }
+    Some((key, rest[1..].trim()))
+}
+
+/// Returns a name as a key for the params file, quoting it unless it's
+/// only letters, digits, `_` and `-`.
+fn key(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        return name.to_owned();
+    }
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl<'a, T> DoodleBuilder<'a, T> {
+    /// Adds a slider to the parameter panel that changes a number in the
+    /// state, between the ends of `range`.
+    ///
+    /// The panel is shown in the top right corner of a doodle with any
+    /// parameters, on top of what's drawn, and F2 shows and hides it.
+    /// Each parameter needs its own name, and adding one with the same
+    /// name as another replaces it.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// struct Spiral {
+    ///     turns: f32,
+    ///     dots: i32,
+    ///     hollow: bool,
+    ///     color: Color,
+    /// }
+    /// let spiral = Spiral { turns: 3.0, dots: 200, hollow: false, color: Color::RGB(255, 136, 0) };
+    /// let builder = DoodleBuilder::with_state(spiral)
+    ///     .float_param("turns", 1.0..20.0, |s| &mut s.turns)
+    ///     .int_param("dots", 10..1000, |s| &mut s.dots)
+    ///     .bool_param("hollow", |s| &mut s.hollow)
+    ///     .color_param("color", |s| &mut s.color)
+    ///     .params_file("spiral.toml");
+    /// ```
+    pub fn float_param(mut self, name: &str, range: Range<f32>, field: ParamField<T, f32>) -> Self {
+        self.params.add(name, Kind::Float(field, range));
+        self
+    }
+    /// Adds a slider to the parameter panel that changes a whole number in
+    /// the state, between the ends of `range`.
+    pub fn int_param(mut self, name: &str, range: Range<i32>, field: ParamField<T, i32>) -> Self {
+        self.params.add(name, Kind::Int(field, range));
+        self
+    }
+    /// Adds a checkbox to the parameter panel that turns a bool in the
+    /// state on and off.
+    pub fn bool_param(mut self, name: &str, field: ParamField<T, bool>) -> Self {
+        self.params.add(name, Kind::Bool(field));
+        self
+    }
+    /// Adds sliders to the parameter panel for the red, green and blue of
+    /// a color in the state.
+    pub fn color_param(mut self, name: &str, field: ParamField<T, Color>) -> Self {
+        self.params.add(name, Kind::Color(field));
+        self
+    }
+    /// Keeps the parameters in a file, so tweaks last from one run to the
+    /// next. The values in the file are put in the state when the doodle
+    /// is built, and the file is written whenever the panel changes them.
+    ///
+    /// The file is TOML, with a `name = value` line for each parameter and
+    /// colors as hex strings, so it can be edited by hand too. Values for
+    /// parameters that no longer exist are ignored.
+    pub fn params_file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.params.path = Some(path.as_ref().to_owned());
+        self
+    }
+}
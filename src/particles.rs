@@ -0,0 +1,306 @@
+//! Particle effects, like sparks, smoke and rain.
+
+use std::f32::consts::PI;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+use {lerp_color, to_pixel, Image, Renderer, Vec2};
+
+/// A particle that has been emitted and hasn't died yet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+}
+
+/// Emits particles that fly out from a point, fall with gravity, and
+/// change size and color as they age.
+///
+/// An emitter is set up with its builder methods, then moved on every
+/// frame with `Renderer::update_particles` and drawn with
+/// `Renderer::draw_particles`. Particles are squares of color unless the
+/// emitter has an image, and are drawn through the transformation, so an
+/// emitter's position can be in world coordinates.
+///
+/// Squares of the same color are drawn together, so thousands of them are
+/// cheap as long as their colors are about the same.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// struct State {
+///     sparks: ParticleEmitter,
+/// }
+/// let sparks = ParticleEmitter::new(Vec2::new(400.0, 300.0))
+///     .rate(200.0)
+///     .lifetime(0.5, 1.5)
+///     .speed(100.0, 250.0)
+///     .direction(-std::f32::consts::FRAC_PI_2, 0.6)
+///     .gravity(Vec2::new(0.0, 300.0))
+///     .size(4.0, 1.0)
+///     .color(Color::RGB(255, 220, 80), Color::RGBA(255, 40, 0, 0));
+/// let builder = DoodleBuilder::with_state(State { sparks }).draw(Box::new(|state, r| {
+///     let (x, y) = r.mouse_position();
+///     state.sparks.position = Vec2::new(x as f32, y as f32);
+///     r.update_particles(&mut state.sparks);
+///     r.clear();
+///     r.draw_particles(&state.sparks);
+/// }));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParticleEmitter {
+    /// Where new particles start.
+    pub position: Vec2,
+    rate: f64,
+    lifetime: (f32, f32),
+    speed: (f32, f32),
+    direction: f32,
+    spread: f32,
+    radius: f32,
+    gravity: Vec2,
+    size: (f32, f32),
+    color: (Color, Color),
+    image: Option<Image>,
+    max_particles: usize,
+    emitting: bool,
+    particles: Vec<Particle>,
+    /// The part of a particle that is owed from the frames so far, since
+    /// the rate rarely gives a whole number of particles a frame.
+    owed: f64,
+}
+
+impl ParticleEmitter {
+    /// Creates an emitter at `position` that emits 50 white particles a
+    /// second in every direction, each living for a second.
+    pub fn new(position: Vec2) -> ParticleEmitter {
+        ParticleEmitter {
+            position,
+            rate: 50.0,
+            lifetime: (1.0, 1.0),
+            speed: (50.0, 100.0),
+            direction: 0.0,
+            spread: PI,
+            radius: 0.0,
+            gravity: Vec2::ZERO,
+            size: (4.0, 4.0),
+            color: (Color::RGB(255, 255, 255), Color::RGBA(255, 255, 255, 0)),
+            image: None,
+            max_particles: 10_000,
+            emitting: true,
+            particles: Vec::new(),
+            owed: 0.0,
+        }
+    }
+    /// Sets how many particles are emitted a second. A rate of 0 only
+    /// emits particles with `Renderer::emit_particles`.
+    #[inline]
+    pub fn rate(mut self, per_second: f64) -> Self {
+        self.rate = per_second.max(0.0);
+        self
+    }
+    /// Sets how many seconds particles live, picked at random between
+    /// `min` and `max` for each one.
+    #[inline]
+    pub fn lifetime(mut self, min: f32, max: f32) -> Self {
+        self.lifetime = (min, max);
+        self
+    }
+    /// Sets how many pixels a second particles start moving at, picked at
+    /// random between `min` and `max` for each one.
+    #[inline]
+    pub fn speed(mut self, min: f32, max: f32) -> Self {
+        self.speed = (min, max);
+        self
+    }
+    /// Sets which way particles fly, as an angle in radians like
+    /// `Vec2::from_angle`, and how many radians to either side of it they
+    /// can stray. A spread of PI sends them every way.
+    #[inline]
+    pub fn direction(mut self, angle: f32, spread: f32) -> Self {
+        self.direction = angle;
+        self.spread = spread.abs();
+        self
+    }
+    /// Sets how far from the position particles can start, so they come
+    /// out of a circle instead of a point.
+    #[inline]
+    pub fn radius(mut self, radius: f32) -> Self {
+        self.radius = radius.abs();
+        self
+    }
+    /// Sets how much particles speed up each second, which is usually
+    /// downwards.
+    #[inline]
+    pub fn gravity(mut self, gravity: Vec2) -> Self {
+        self.gravity = gravity;
+        self
+    }
+    /// Sets how wide particles are when they're emitted and when they die.
+    /// They grow or shrink smoothly in between.
+    #[inline]
+    pub fn size(mut self, start: f32, end: f32) -> Self {
+        self.size = (start, end);
+        self
+    }
+    /// Sets the color of particles when they're emitted and when they die.
+    /// Fading the alpha to 0 makes them disappear smoothly instead of
+    /// popping out. Images are tinted with the color.
+    #[inline]
+    pub fn color(mut self, start: Color, end: Color) -> Self {
+        self.color = (start, end);
+        self
+    }
+    /// Draws each particle as an image stretched to its size, instead of
+    /// a square.
+    #[inline]
+    pub fn image(mut self, image: Image) -> Self {
+        self.image = Some(image);
+        self
+    }
+    /// Sets the most particles that can be alive at once, 10000 by default.
+    /// No more are emitted until some die.
+    #[inline]
+    pub fn max_particles(mut self, max: usize) -> Self {
+        self.max_particles = max;
+        self
+    }
+    /// Starts or stops emitting particles at the emitter's rate. The ones
+    /// already emitted carry on until they die.
+    #[inline]
+    pub fn set_emitting(&mut self, emitting: bool) {
+        self.emitting = emitting;
+        self.owed = 0.0;
+    }
+    /// Returns true if the emitter is emitting particles at its rate.
+    #[inline]
+    pub fn is_emitting(&self) -> bool {
+        self.emitting
+    }
+    /// Returns how many particles are alive.
+    #[inline]
+    pub fn particle_count(&self) -> usize {
+        self.particles.len()
+    }
+    /// Removes all of the particles.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.particles.clear();
+    }
+}
+
+/// Returns a random number between `min` and `max`, or `min` if they are
+/// the same.
+fn random_between(r: &mut Renderer, (min, max): (f32, f32)) -> f32 {
+    if max > min {
+        r.random_range(min, max)
+    } else {
+        min
+    }
+}
+
+impl Renderer {
+    /// Emits `count` particles at once from an emitter, on top of the ones
+    /// it emits at its rate, for bursts like explosions.
+    pub fn emit_particles(&mut self, emitter: &mut ParticleEmitter, count: usize) {
+        let count = count.min(emitter.max_particles.saturating_sub(emitter.particles.len()));
+        for _ in 0..count {
+            let angle = emitter.direction + random_between(self, (-emitter.spread, emitter.spread));
+            let speed = random_between(self, emitter.speed);
+            let offset = if emitter.radius > 0.0 {
+                // The square root spreads particles evenly over the circle
+                // instead of bunching them in the middle.
+                let distance = emitter.radius * self.random_range(0.0, 1.0).sqrt();
+                Vec2::from_angle(self.random_range(0.0, 2.0 * PI)) * distance
+            } else {
+                Vec2::ZERO
+            };
+            let lifetime = random_between(self, emitter.lifetime);
+            emitter.particles.push(Particle {
+                position: emitter.position + offset,
+                velocity: Vec2::from_angle(angle) * speed,
+                age: 0.0,
+                lifetime,
+            });
+        }
+    }
+    /// Moves an emitter's particles on by `delta_seconds`, removing the
+    /// ones that have died and emitting new ones at its rate.
+    pub fn update_particles(&mut self, emitter: &mut ParticleEmitter) {
+        let (dt, gravity) = (self.delta_seconds() as f32, emitter.gravity);
+        emitter.particles.retain_mut(|p| {
+            p.age += dt;
+            p.velocity += gravity * dt;
+            p.position += p.velocity * dt;
+            p.age < p.lifetime
+        });
+        if emitter.emitting {
+            emitter.owed += emitter.rate * f64::from(dt);
+            let count = emitter.owed.floor();
+            emitter.owed -= count;
+            self.emit_particles(emitter, count as usize);
+        }
+    }
+    /// Draws an emitter's particles, with the size and color each one has
+    /// got to for its age.
+    pub fn draw_particles(&mut self, emitter: &ParticleEmitter) {
+        self.dirty = true;
+        let (start_size, end_size) = emitter.size;
+        let (start_color, end_color) = emitter.color;
+        let particles = emitter.particles.iter().map(|p| {
+            let t = if p.lifetime > 0.0 { p.age / p.lifetime } else { 1.0 };
+            let size = start_size + (end_size - start_size) * t;
+            (p.position, size.max(0.0), lerp_color(start_color, end_color, t))
+        });
+        if let Some(image) = emitter.image {
+            let tint = self.tint;
+            for (position, size, color) in particles {
+                self.tint = color;
+                let half = size / 2.0;
+                let dst = Rect::new(
+                    to_pixel(position.x - half),
+                    to_pixel(position.y - half),
+                    size.ceil().max(1.0) as u32,
+                    size.ceil().max(1.0) as u32,
+                );
+                let result = self.copy_image(image, None, dst);
+                self.log_failure("draw a particle", result);
+            }
+            self.tint = tint;
+            return;
+        }
+        let (scale_x, scale_y) = self.transform.axis_scale();
+        let mut squares: Vec<(Color, Rect)> = particles
+            .filter(|&(_, size, color)| size > 0.0 && color.a > 0)
+            .map(|(position, size, color)| {
+                let (x, y) = self.transform.apply(position.x, position.y);
+                let (width, height) = (size * scale_x, size * scale_y);
+                let rect = Rect::new(
+                    to_pixel(x - width / 2.0),
+                    to_pixel(y - height / 2.0),
+                    width.ceil().max(1.0) as u32,
+                    height.ceil().max(1.0) as u32,
+                );
+                (color, rect)
+            })
+            .collect();
+        // Sorting by color lets every square of a color be drawn at once.
+        // The sort is stable, so squares of the same color keep their order.
+        squares.sort_by_key(|&(c, _)| (c.r, c.g, c.b, c.a));
+        let color = self.canvas.draw_color();
+        let mut rects = Vec::new();
+        for (i, &(c, rect)) in squares.iter().enumerate() {
+            rects.push(rect);
+            if squares.get(i + 1).map(|&(next, _)| next) != Some(c) {
+                self.canvas.set_draw_color(c);
+                self.draw_call();
+                let result = self.canvas.fill_rects(&rects);
+                self.log_failure("draw particles", result);
+                rects.clear();
+            }
+        }
+        self.canvas.set_draw_color(color);
+    }
+}
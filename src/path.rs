@@ -0,0 +1,470 @@
+//! Paths built from SVG path data.
+
+use {Error, Renderer, Result};
+
+/// The number of line segments each curve is flattened into.
+pub const CURVE_SEGMENTS: usize = 16;
+
+/// One connected run of vertices inside a Path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubPath {
+    /// The vertices of the flattened subpath, in drawing order.
+    pub points: Vec<(f32, f32)>,
+    /// Whether the last vertex connects back to the first one.
+    pub closed: bool,
+}
+
+/// A shape made of straight line segments, usually created from the
+/// `d` attribute of an SVG `<path>` element.
+///
+/// Curves are flattened into `CURVE_SEGMENTS` line segments each when
+/// the path is parsed, so drawing a Path is as cheap as drawing lines.
+///
+/// # Example
+/// ```
+/// use doodle::Path;
+///
+/// let square = Path::from_svg_path("M 10 10 H 90 V 90 H 10 Z").unwrap();
+/// assert_eq!(square.vertex_count(), 4);
+/// assert_eq!(square.bounds(), Some((10.0, 10.0, 90.0, 90.0)));
+///
+/// // Relative commands and implicit repeats, as exported by Inkscape.
+/// let wave = Path::from_svg_path("m 0,50 c 25,-50 75,-50 100,0 s 75,50 100,0").unwrap();
+/// assert_eq!(wave.vertex_count(), 1 + 2 * 16);
+/// let (min_x, min_y, max_x, max_y) = wave.bounds().unwrap();
+/// assert_eq!((min_x, max_x), (0.0, 200.0));
+/// assert!(min_y > 12.0 && max_y < 88.0);
+///
+/// let triangles = Path::from_svg_path("M0 0L10 0L5 8zm20 0l10 0l-5 8z").unwrap();
+/// assert_eq!(triangles.subpaths().len(), 2);
+/// assert_eq!(triangles.vertex_count(), 6);
+/// assert_eq!(triangles.bounds(), Some((0.0, 0.0, 30.0, 8.0)));
+///
+/// let quad = Path::from_svg_path("M 0 0 Q 50 100 100 0 T 200 0").unwrap();
+/// assert_eq!(quad.vertex_count(), 1 + 2 * 16);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Path {
+    subpaths: Vec<SubPath>,
+}
+
+impl Path {
+    /// Parses SVG path data into a Path.
+    ///
+    /// The M, L, H, V, C, S, Q, T and Z commands are supported, in both
+    /// their absolute and relative forms. Arcs (A) are not supported yet.
+    ///
+    /// # Example
+    /// ```
+    /// use doodle::{Error, Path};
+    ///
+    /// match Path::from_svg_path("M 0 0 A 5 5 0 0 1 10 10") {
+    ///     Err(Error::PathError { offset, .. }) => assert_eq!(offset, 6),
+    ///     _ => panic!("arcs should be rejected"),
+    /// }
+    /// ```
+    pub fn from_svg_path(d: &str) -> Result<Path> {
+        Parser::new(d).parse()
+    }
+    /// Returns the subpaths that make up this path.
+    #[inline]
+    pub fn subpaths(&self) -> &[SubPath] {
+        &self.subpaths
+    }
+    /// Returns the total number of vertices in all of the subpaths.
+    pub fn vertex_count(&self) -> usize {
+        self.subpaths.iter().map(|s| s.points.len()).sum()
+    }
+    /// Returns the bounding box of the path as (min_x, min_y, max_x, max_y),
+    /// or None if the path has no vertices.
+    pub fn bounds(&self) -> Option<(f32, f32, f32, f32)> {
+        let mut points = self.subpaths.iter().flat_map(|s| s.points.iter());
+        let &(x, y) = points.next()?;
+        Some(points.fold((x, y, x, y), |(x0, y0, x1, y1), &(x, y)| {
+            (x0.min(x), y0.min(y), x1.max(x), y1.max(y))
+        }))
+    }
+}
+
+/// Returns the points of a cubic bezier curve from p0 to p3, not including p0.
+pub(crate) fn flatten_cubic(
+    p0: (f32, f32),
+    p1: (f32, f32),
+    p2: (f32, f32),
+    p3: (f32, f32),
+    segments: usize,
+) -> Vec<(f32, f32)> {
+    (1..segments + 1)
+        .map(|i| {
+            let t = i as f32 / segments as f32;
+            let u = 1.0 - t;
+            let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+            (
+                a * p0.0 + b * p1.0 + c * p2.0 + d * p3.0,
+                a * p0.1 + b * p1.1 + c * p2.1 + d * p3.1,
+            )
+        })
+        .collect()
+}
+
+/// Returns the points of a quadratic bezier curve from p0 to p2, not including p0.
+pub(crate) fn flatten_quadratic(
+    p0: (f32, f32),
+    p1: (f32, f32),
+    p2: (f32, f32),
+    segments: usize,
+) -> Vec<(f32, f32)> {
+    (1..segments + 1)
+        .map(|i| {
+            let t = i as f32 / segments as f32;
+            let u = 1.0 - t;
+            let (a, b, c) = (u * u, 2.0 * u * t, t * t);
+            (
+                a * p0.0 + b * p1.0 + c * p2.0,
+                a * p0.1 + b * p1.1 + c * p2.1,
+            )
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Curve {
+    Cubic,
+    Quadratic,
+}
+
+struct Parser<'a> {
+    data: &'a [u8],
+    pos: usize,
+    subpaths: Vec<SubPath>,
+    current: (f32, f32),
+    start: (f32, f32),
+    // The last control point of the previous command if it was a curve,
+    // used by S and T.
+    last_control: Option<(Curve, (f32, f32))>,
+}
+
+impl<'a> Parser<'a> {
+    fn new(d: &'a str) -> Self {
+        Parser {
+            data: d.as_bytes(),
+            pos: 0,
+            subpaths: Vec::new(),
+            current: (0.0, 0.0),
+            start: (0.0, 0.0),
+            last_control: None,
+        }
+    }
+    fn error<T>(&self, offset: usize, message: &str) -> Result<T> {
+        Err(Error::PathError {
+            offset,
+            message: message.to_owned(),
+        })
+    }
+    fn skip_separators(&mut self) {
+        while self.pos < self.data.len() {
+            match self.data[self.pos] {
+                b' ' | b'\t' | b'\r' | b'\n' | b',' => self.pos += 1,
+                _ => break,
+            }
+        }
+    }
+    fn at_number(&mut self) -> bool {
+        self.skip_separators();
+        match self.data.get(self.pos) {
+            Some(&c) => c.is_ascii_digit() || c == b'-' || c == b'+' || c == b'.',
+            None => false,
+        }
+    }
+    fn number(&mut self) -> Result<f32> {
+        if !self.at_number() {
+            return self.error(self.pos, "expected a number");
+        }
+        let start = self.pos;
+        let mut end = self.pos;
+        if self.data[end] == b'-' || self.data[end] == b'+' {
+            end += 1;
+        }
+        let mut seen_dot = false;
+        while end < self.data.len() {
+            match self.data[end] {
+                b'0'..=b'9' => end += 1,
+                b'.' if !seen_dot => {
+                    seen_dot = true;
+                    end += 1;
+                }
+                _ => break,
+            }
+        }
+        if end < self.data.len() && (self.data[end] == b'e' || self.data[end] == b'E') {
+            let mut exp_end = end + 1;
+            if exp_end < self.data.len() && (self.data[exp_end] == b'-' || self.data[exp_end] == b'+') {
+                exp_end += 1;
+            }
+            if exp_end < self.data.len() && self.data[exp_end].is_ascii_digit() {
+                while exp_end < self.data.len() && self.data[exp_end].is_ascii_digit() {
+                    exp_end += 1;
+                }
+                end = exp_end;
+            }
+        }
+        // The slice only contains ASCII characters, so this can't fail.
+        let text = ::std::str::from_utf8(&self.data[start..end]).unwrap();
+        match text.parse() {
+            Ok(n) => {
+                self.pos = end;
+                Ok(n)
+            }
+            Err(_) => self.error(start, "invalid number"),
+        }
+    }
+    fn point(&mut self, relative: bool) -> Result<(f32, f32)> {
+        let x = self.number()?;
+        let y = self.number()?;
+        Ok(if relative {
+            (self.current.0 + x, self.current.1 + y)
+        } else {
+            (x, y)
+        })
+    }
+    fn push_points(&mut self, points: &[(f32, f32)]) {
+        if self.subpaths.last().map(|s| s.closed) != Some(false) {
+            let start = self.current;
+            self.start = start;
+            self.subpaths.push(SubPath {
+                points: vec![start],
+                closed: false,
+            });
+        }
+        if let Some(subpath) = self.subpaths.last_mut() {
+            subpath.points.extend_from_slice(points);
+        }
+        if let Some(&last) = points.last() {
+            self.current = last;
+        }
+    }
+    fn reflected_control(&self, curve: Curve) -> (f32, f32) {
+        match self.last_control {
+            Some((kind, (x, y))) if kind == curve => {
+                (2.0 * self.current.0 - x, 2.0 * self.current.1 - y)
+            }
+            _ => self.current,
+        }
+    }
+    fn parse(mut self) -> Result<Path> {
+        let mut command: Option<u8> = None;
+        loop {
+            self.skip_separators();
+            if self.pos >= self.data.len() {
+                break;
+            }
+            let offset = self.pos;
+            let c = self.data[self.pos];
+            if c.is_ascii_alphabetic() {
+                self.pos += 1;
+                command = Some(c);
+            } else if !self.at_number() {
+                return self.error(offset, "unexpected character");
+            } else {
+                command = match command {
+                    // Extra coordinate pairs after a moveto are treated as linetos.
+                    Some(b'M') => Some(b'L'),
+                    Some(b'm') => Some(b'l'),
+                    Some(b'Z') | Some(b'z') | None => {
+                        return self.error(offset, "expected a command")
+                    }
+                    other => other,
+                };
+            }
+            let c = command.unwrap();
+            let relative = c.is_ascii_lowercase();
+            let upper = c.to_ascii_uppercase();
+            let mut control = None;
+            match upper {
+                b'M' => {
+                    let p = self.point(relative)?;
+                    self.current = p;
+                    self.start = p;
+                    self.subpaths.push(SubPath {
+                        points: vec![p],
+                        closed: false,
+                    });
+                }
+                b'L' => {
+                    let p = self.point(relative)?;
+                    self.push_points(&[p]);
+                }
+                b'H' => {
+                    let x = self.number()?;
+                    let x = if relative { self.current.0 + x } else { x };
+                    let p = (x, self.current.1);
+                    self.push_points(&[p]);
+                }
+                b'V' => {
+                    let y = self.number()?;
+                    let y = if relative { self.current.1 + y } else { y };
+                    let p = (self.current.0, y);
+                    self.push_points(&[p]);
+                }
+                b'C' | b'S' => {
+                    let p0 = self.current;
+                    let p1 = if upper == b'C' {
+                        self.point(relative)?
+                    } else {
+                        self.reflected_control(Curve::Cubic)
+                    };
+                    let p2 = self.point(relative)?;
+                    let p3 = self.point(relative)?;
+                    let points = flatten_cubic(p0, p1, p2, p3, CURVE_SEGMENTS);
+                    self.push_points(&points);
+                    control = Some((Curve::Cubic, p2));
+                }
+                b'Q' | b'T' => {
+                    let p0 = self.current;
+                    let p1 = if upper == b'Q' {
+                        self.point(relative)?
+                    } else {
+                        self.reflected_control(Curve::Quadratic)
+                    };
+                    let p2 = self.point(relative)?;
+                    let points = flatten_quadratic(p0, p1, p2, CURVE_SEGMENTS);
+                    self.push_points(&points);
+                    control = Some((Curve::Quadratic, p1));
+                }
+                b'Z' => {
+                    if let Some(subpath) = self.subpaths.last_mut() {
+                        subpath.closed = true;
+                    }
+                    self.current = self.start;
+                }
+                b'A' => return self.error(offset, "arc commands are not supported"),
+                _ => return self.error(offset, "unknown command"),
+            }
+            self.last_control = control;
+        }
+        Ok(Path {
+            subpaths: self.subpaths,
+        })
+    }
+}
+
+impl Renderer {
+    /// Draws a path with the current fill and stroke, like `end_shape`.
+    ///
+    /// Every subpath with at least three vertices is filled, closed or not,
+    /// and they're filled together with the even-odd rule, so a subpath
+    /// inside another one cuts a hole in it. Each subpath is then outlined
+    /// with the stroke color and weight, joining the last vertex back to
+    /// the first for closed ones.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) -> Result<()> {
+    /// let ring = Path::from_svg_path("M 0 0 H 100 V 100 H 0 Z M 25 25 V 75 H 75 V 25 Z")?;
+    /// r.fill(Color::RGB(40, 120, 220));
+    /// r.stroke(Color::RGB(0, 0, 0));
+    /// r.stroke_weight(3.0);
+    /// r.draw_path(&ring);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn draw_path(&mut self, path: &Path) {
+        let polygons: Vec<Vec<(f32, f32)>> = path
+            .subpaths()
+            .iter()
+            .filter(|subpath| subpath.points.len() >= 3)
+            .map(|subpath| subpath.points.clone())
+            .collect();
+        if !polygons.is_empty() {
+            self.fill_styled(&polygons, "fill a path");
+        }
+        for subpath in path.subpaths() {
+            if subpath.closed && subpath.points.len() > 2 {
+                let mut points = subpath.points.clone();
+                points.push(points[0]);
+                self.stroke_open(&points, "draw a path");
+            } else {
+                self.stroke_open(&subpath.points, "draw a path");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_bounds(path: &Path, expected: (f32, f32, f32, f32)) {
+        let bounds = path.bounds().unwrap();
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+        assert!(
+            close(bounds.0, expected.0)
+                && close(bounds.1, expected.1)
+                && close(bounds.2, expected.2)
+                && close(bounds.3, expected.3),
+            "{:?} isn't {:?}",
+            bounds,
+            expected
+        );
+    }
+
+    fn arc_offset(d: &str) -> usize {
+        match Path::from_svg_path(d) {
+            Err(Error::PathError { offset, .. }) => offset,
+            other => panic!("{:?} should have been rejected", other),
+        }
+    }
+
+    #[test]
+    fn rounded_rect() {
+        // Inkscape's output for a rectangle with rounded corners.
+        let d = "m 10,10 h 80 c 5.5,0 10,4.5 10,10 v 60 c 0,5.5 -4.5,10 -10,10 H 10 \
+                 C 4.5,90 0,85.5 0,80 V 20 C 0,14.5 4.5,10 10,10 Z";
+        let path = Path::from_svg_path(d).unwrap();
+        assert_eq!(path.subpaths().len(), 1);
+        assert!(path.subpaths()[0].closed);
+        assert_eq!(path.vertex_count(), 1 + 4 + 4 * CURVE_SEGMENTS);
+        assert_bounds(&path, (0.0, 10.0, 100.0, 90.0));
+    }
+
+    #[test]
+    fn quadratic_wave() {
+        let path = Path::from_svg_path("M 0,0 Q 50,-40 100,0 t 100,0 V 30 h -200 z").unwrap();
+        assert_eq!(path.vertex_count(), 1 + 2 * CURVE_SEGMENTS + 2);
+        // The middle of each curve is halfway to its control point.
+        assert_bounds(&path, (0.0, -20.0, 200.0, 30.0));
+    }
+
+    #[test]
+    fn heart_with_repeated_curves() {
+        // Inkscape leaves out the command letter of repeated curves.
+        let d = "m 50,30 c -10,-20 -40,-20 -40,5 0,20 25,30 40,45 \
+                 15,-15 40,-25 40,-45 0,-25 -30,-25 -40,-5 z";
+        let path = Path::from_svg_path(d).unwrap();
+        assert_eq!(path.vertex_count(), 1 + 4 * CURVE_SEGMENTS);
+        let (min_x, min_y, max_x, max_y) = path.bounds().unwrap();
+        assert_eq!((min_x, max_x, max_y), (10.0, 90.0, 80.0));
+        // The top lobes dip between the curves' ends and their controls.
+        assert!(min_y > 10.0 && min_y < 20.0, "{}", min_y);
+        let last = *path.subpaths()[0].points.last().unwrap();
+        assert!((last.0 - 50.0).abs() < 1e-4 && (last.1 - 30.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn subpaths_after_close_start_from_the_last_start() {
+        let path = Path::from_svg_path("M 10 10 h 10 v 10 z m 5 5 h 1 V 40").unwrap();
+        let subpaths = path.subpaths();
+        assert_eq!(subpaths.len(), 2);
+        assert!(subpaths[0].closed && !subpaths[1].closed);
+        assert_eq!(subpaths[1].points, vec![(15.0, 15.0), (16.0, 15.0), (16.0, 40.0)]);
+        assert_bounds(&path, (10.0, 10.0, 20.0, 40.0));
+    }
+
+    #[test]
+    fn arcs_are_rejected_where_they_start() {
+        assert_eq!(arc_offset("M 10 10 L 20 20 a 5 5 0 0 1 10 10"), 16);
+        assert_eq!(arc_offset("m 0,0 h 10 a 5,5 0 0 1 5,5"), 11);
+        assert_eq!(arc_offset("M0 0A5 5 0 0 1 10 10"), 4);
+    }
+}
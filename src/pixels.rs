@@ -0,0 +1,219 @@
+//! Working on the pixels of the drawing directly.
+
+use sdl2::pixels::{Color, PixelFormatEnum};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, Texture};
+use sdl2::surface::Surface;
+use sdl2::sys;
+use sdl2::video::Window;
+
+use image::{into_static, set_texture_tint};
+use {Error, Image, Renderer, Result};
+
+/// The SDL format with the bytes of each pixel in the order red, green,
+/// blue, alpha, which SDL names by the order of the bits in a u32.
+#[cfg(target_endian = "little")]
+pub(crate) const FORMAT: PixelFormatEnum = PixelFormatEnum::ABGR8888;
+#[cfg(target_endian = "big")]
+pub(crate) const FORMAT: PixelFormatEnum = PixelFormatEnum::RGBA8888;
+pub(crate) const BYTES_PER_PIXEL: usize = 4;
+
+/// A grid of pixels in memory, which can be read and changed one pixel at
+/// a time and then drawn with `Renderer::update_pixels`.
+///
+/// Pixels are stored as red, green, blue and alpha bytes, row by row from
+/// the top left, with no padding between rows.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// # fn draw(r: &mut Renderer) {
+/// // Turn everything drawn so far into shades of grey.
+/// let mut pixels = r.load_pixels().unwrap();
+/// for y in 0..pixels.height() {
+///     for x in 0..pixels.width() {
+///         let c = pixels.get(x, y).unwrap();
+///         let grey = ((u32::from(c.r) * 3 + u32::from(c.g) * 6 + u32::from(c.b)) / 10) as u8;
+///         pixels.set(x, y, Color::RGB(grey, grey, grey));
+///     }
+/// }
+/// r.update_pixels(&pixels).unwrap();
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PixelBuffer {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl PixelBuffer {
+    /// Creates a buffer with every pixel transparent black.
+    pub fn new(width: u32, height: u32) -> PixelBuffer {
+        PixelBuffer {
+            width,
+            height,
+            pixels: vec![0; width as usize * height as usize * BYTES_PER_PIXEL],
+        }
+    }
+    /// Returns the width of the buffer in pixels.
+    #[inline]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    /// Returns the height of the buffer in pixels.
+    #[inline]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    /// Returns where the bytes of the pixel at (x, y) start, if it's inside
+    /// the buffer.
+    #[inline]
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some((y as usize * self.width as usize + x as usize) * BYTES_PER_PIXEL)
+        } else {
+            None
+        }
+    }
+    /// Returns the color of the pixel at (x, y), or None if it's outside of
+    /// the buffer.
+    #[inline]
+    pub fn get(&self, x: u32, y: u32) -> Option<Color> {
+        self.index(x, y).map(|i| {
+            let p = &self.pixels[i..i + BYTES_PER_PIXEL];
+            Color::RGBA(p[0], p[1], p[2], p[3])
+        })
+    }
+    /// Sets the color of the pixel at (x, y). Pixels outside of the buffer
+    /// are ignored, so shapes can be drawn across its edges.
+    #[inline]
+    pub fn set(&mut self, x: u32, y: u32, color: Color) {
+        if let Some(i) = self.index(x, y) {
+            self.pixels[i..i + BYTES_PER_PIXEL].copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+    /// Sets every pixel to the same color.
+    pub fn fill(&mut self, color: Color) {
+        for pixel in self.pixels.chunks_mut(BYTES_PER_PIXEL) {
+            pixel.copy_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+    }
+    /// Returns the bytes of all of the pixels, four for each.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.pixels
+    }
+    /// Returns the bytes of all of the pixels to change them, which is
+    /// quicker than `set` for changing every pixel.
+    #[inline]
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+    /// Copies the buffer into a new SDL surface, for the things SDL only
+    /// takes surfaces for, like window icons and cursors.
+    pub(crate) fn to_surface(&self) -> Result<Surface<'static>> {
+        let mut surface = Surface::new(self.width, self.height, FORMAT)?;
+        let pitch = surface.pitch() as usize;
+        let row = self.width as usize * BYTES_PER_PIXEL;
+        surface.with_lock_mut(|bytes| {
+            for (dst, src) in bytes.chunks_mut(pitch).zip(self.pixels.chunks(row)) {
+                dst[..row].copy_from_slice(src);
+            }
+        });
+        Ok(surface)
+    }
+}
+
+/// Copies a texture over the whole render target and reads the target back.
+fn copy_and_read(canvas: &mut Canvas<Window>, texture: &Texture) -> Result<Vec<u8>> {
+    set_texture_tint(texture, Color::RGBA(255, 255, 255, 255))?;
+    canvas.copy(texture, None, None)?;
+    Ok(canvas.read_pixels(None, FORMAT)?)
+}
+
+impl Renderer {
+    /// Reads everything drawn so far this frame into a pixel buffer the
+    /// size of the drawing area. Every pixel read back is opaque.
+    pub fn load_pixels(&mut self) -> Result<PixelBuffer> {
+        let (width, height) = self.size();
+        let frame = self.read_region(Rect::new(0, 0, width, height))?;
+        let mut buffer = PixelBuffer::new(frame.width, frame.height);
+        for (rgba, rgb) in buffer.pixels.chunks_mut(BYTES_PER_PIXEL).zip(frame.pixels.chunks(3)) {
+            rgba.copy_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+        }
+        Ok(buffer)
+    }
+    /// Draws a pixel buffer over the whole drawing area, replacing what was
+    /// there, alpha included. A buffer smaller than the drawing area is
+    /// stretched to cover it, each of its pixels becoming a solid block,
+    /// which is a quick way to draw chunky, low resolution effects.
+    ///
+    /// The buffer is copied to a texture that is kept for the next call, so
+    /// updating every frame doesn't create a new one each time.
+    pub fn update_pixels(&mut self, buffer: &PixelBuffer) -> Result<()> {
+        if buffer.width == 0 || buffer.height == 0 {
+            return Ok(());
+        }
+        let reusable = match self.pixel_texture {
+            Some(ref texture) => {
+                let query = texture.query();
+                (query.width, query.height) == (buffer.width, buffer.height)
+            }
+            None => false,
+        };
+        if !reusable {
+            let mut texture = self
+                .texture_creator
+                .create_texture_streaming(FORMAT, buffer.width, buffer.height)?;
+            texture.set_blend_mode(BlendMode::None);
+            self.pixel_texture = Some(into_static(texture));
+        }
+        self.flush_batch();
+        let (width, height) = self.size();
+        let texture = self.pixel_texture.as_mut().expect("the pixel texture was just created");
+        let pitch = buffer.width as usize * BYTES_PER_PIXEL;
+        texture
+            .update(None, &buffer.pixels, pitch)
+            .map_err(|e| Error::Error(e.to_string()))?;
+        self.dirty = true;
+        self.debug.count_copy(texture);
+        self.canvas.copy(texture, None, Rect::new(0, 0, width, height))?;
+        Ok(())
+    }
+    /// Reads back the pixels of an image, alpha included, by copying it
+    /// onto a texture that is drawn on and reading that.
+    pub(crate) fn image_pixels(&mut self, image: Image) -> Result<PixelBuffer> {
+        let (width, height) = self.image_size(image);
+        if width == 0 || height == 0 {
+            return Err(Error::Error("the image isn't loaded".to_owned()));
+        }
+        let target = into_static(self.texture_creator.create_texture_target(FORMAT, width, height)?);
+        self.flush_batch();
+        let renderer = self.canvas.raw();
+        let previous = unsafe { sys::SDL_GetRenderTarget(renderer) };
+        if unsafe { sys::SDL_SetRenderTarget(renderer, target.raw()) } != 0 {
+            return Err(Error::Error(::sdl2::get_error()));
+        }
+        let color = self.canvas.draw_color();
+        let result = match self.textures.get_mut(image.id) {
+            Some(Some(texture)) => {
+                // Without blending the image's alpha is copied as it is,
+                // rather than mixed with the transparent target.
+                let blend_mode = texture.blend_mode();
+                texture.set_blend_mode(BlendMode::None);
+                self.canvas.set_draw_color(Color::RGBA(0, 0, 0, 0));
+                self.canvas.clear();
+                let result = copy_and_read(&mut self.canvas, texture);
+                texture.set_blend_mode(blend_mode);
+                result
+            }
+            _ => Err(Error::Error("the image isn't loaded".to_owned())),
+        };
+        self.canvas.set_draw_color(color);
+        if unsafe { sys::SDL_SetRenderTarget(renderer, previous) } != 0 {
+            return Err(Error::Error(::sdl2::get_error()));
+        }
+        Ok(PixelBuffer { width, height, pixels: result? })
+    }
+}
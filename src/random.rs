@@ -0,0 +1,72 @@
+//! Random numbers that can be made the same every run.
+
+use ludomath::rng::Rng;
+
+use noise::Noise;
+use Renderer;
+
+/// A second half of the seed for the random number generator, so seeds
+/// that differ only a little still give unrelated numbers.
+const SEED_MIX: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// Returns the random number generator for a seed.
+#[inline]
+pub(crate) fn seeded(seed: u64) -> Rng {
+    Rng::new_seeded(seed, SEED_MIX)
+}
+
+impl Renderer {
+    /// Reseeds the doodle's random numbers and noise, so everything random
+    /// after this call comes out the same every time for the same seed.
+    /// `DoodleBuilder::seed` does the same before setup.
+    ///
+    /// Without a seed, random numbers are different every run.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer, seed: u64) {
+    /// // The same seed always draws the same constellation, so a good
+    /// // one can be drawn again by writing down its seed.
+    /// r.random_seed(seed);
+    /// r.clear();
+    /// for _ in 0..100 {
+    ///     let x = r.random_range(0.0, r.width() as f32);
+    ///     let y = r.random_range(0.0, r.height() as f32);
+    ///     let size = 2.0 + r.random_gaussian().abs();
+    ///     r.fill_circle(x, y, size);
+    /// }
+    /// # }
+    /// ```
+    pub fn random_seed(&mut self, seed: u64) {
+        self.rng = seeded(seed);
+        self.noise = Noise::new(seed);
+    }
+    /// Returns a random number that is at least `min` and less than `max`.
+    #[inline]
+    pub fn random_range(&mut self, min: f32, max: f32) -> f32 {
+        self.rng.rand_float(min, max)
+    }
+    /// Returns a random number from a normal distribution with a mean of 0
+    /// and a standard deviation of 1. About two thirds of the numbers are
+    /// between -1 and 1, and almost all of them are between -3 and 3.
+    pub fn random_gaussian(&mut self) -> f32 {
+        // The Box-Muller transform, leaving out 0 so the log is finite.
+        let u = 1.0 - self.rng.rand_float(0.0, 1.0);
+        let v = self.rng.rand_float(0.0, 1.0);
+        (-2.0 * u.ln()).sqrt() * (2.0 * ::std::f32::consts::PI * v).cos()
+    }
+    /// Puts the items of a slice in a random order.
+    #[inline]
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        self.rng.shuffle_mut(items);
+    }
+    /// Returns a random item from a slice, or None if it's empty.
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        if items.is_empty() {
+            None
+        } else {
+            Some(self.rng.rand_el(items))
+        }
+    }
+}
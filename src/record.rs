@@ -0,0 +1,206 @@
+//! Recording the frames a doodle draws into an animated GIF or a video.
+
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::thread::{self, JoinHandle};
+
+use sdl2::rect::Rect;
+
+use capture::Frame;
+use gif::GifWriter;
+use video::FfmpegWriter;
+use {Error, Renderer, Result, VideoSettings};
+
+/// The most frames a second a GIF can show, since most viewers treat
+/// shorter delays than 2 hundredths of a second as much longer ones.
+const MAX_GIF_FPS: u32 = 50;
+/// How many frames can wait to be encoded before the doodle waits for the
+/// encoder to catch up, which keeps long recordings from filling memory.
+const QUEUE_FRAMES: usize = 30;
+
+/// Frames being recorded. They are encoded on a thread of their own so
+/// recording doesn't slow the doodle down too much.
+pub(crate) struct Recorder {
+    frames: Option<SyncSender<Frame>>,
+    encoder: Option<JoinHandle<Result<()>>>,
+    size: (u32, u32),
+    interval: f64,
+    next_frame: f64,
+}
+
+impl Recorder {
+    fn start<F>(size: (u32, u32), fps: u32, start: f64, encode: F) -> Recorder
+    where
+        F: FnOnce(Receiver<Frame>) -> Result<()> + Send + 'static,
+    {
+        let (frames, receiver) = mpsc::sync_channel(QUEUE_FRAMES);
+        Recorder {
+            frames: Some(frames),
+            encoder: Some(thread::spawn(move || encode(receiver))),
+            size,
+            interval: 1.0 / f64::from(fps.max(1)),
+            next_frame: start,
+        }
+    }
+    /// Stops taking frames and waits for the ones already taken to be
+    /// written.
+    fn finish(&mut self) -> Result<()> {
+        self.frames = None;
+        match self.encoder.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(Error::Error("the recording thread panicked".to_owned())),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        // Finish the file when the doodle ends while it's still recording.
+        let _ = self.finish();
+    }
+}
+
+impl Renderer {
+    /// Starts recording what the doodle draws into an animated GIF that
+    /// loops forever, taking `fps` frames a second of the doodle's running
+    /// time, up to 50. The GIF isn't finished until `stop_recording` is
+    /// called or the doodle ends.
+    ///
+    /// Frames are the size of the drawing area when recording starts, and
+    /// the debug overlay is left out of them. Each frame gets its own 256
+    /// colors, so smooth gradients come out banded.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// // Record a three second loop of a spinning square.
+    /// let mut doodle = DoodleBuilder::<State>::new()
+    ///     .setup(Box::new(|_, r| r.start_recording_gif("spin.gif", 30).unwrap()))
+    ///     .draw(Box::new(|_, r| {
+    ///         let t = r.elapsed_seconds() as f32;
+    ///         r.clear();
+    ///         r.translate(400.0, 300.0);
+    ///         r.rotate(t * std::f32::consts::PI * 2.0 / 3.0);
+    ///         r.fill_rect(-50.0, -50.0, 100.0, 100.0);
+    ///         if t >= 3.0 {
+    ///             r.stop_recording().unwrap();
+    ///             r.quit();
+    ///         }
+    ///     }))
+    ///     .build()
+    ///     .unwrap();
+    /// doodle.run().unwrap();
+    /// ```
+    pub fn start_recording_gif<P: AsRef<Path>>(&mut self, path: P, fps: u32) -> Result<()> {
+        if self.recorder.is_some() {
+            return Err(Error::Error("the doodle is already recording".to_owned()));
+        }
+        let fps = fps.clamp(1, MAX_GIF_FPS);
+        let (width, height) = self.size();
+        let mut gif = GifWriter::create(path, width, height)?;
+        let delay = (100.0 / f64::from(fps)).round() as u16;
+        let start = self.elapsed_seconds();
+        self.recorder = Some(Recorder::start((width, height), fps, start, move |frames| {
+            for frame in frames {
+                gif.write_frame(&frame, delay)?;
+            }
+            gif.finish()
+        }));
+        Ok(())
+    }
+    /// Starts recording what the doodle draws into a video, by running
+    /// ffmpeg and handing it `fps` frames a second of the doodle's running
+    /// time. The kind of video depends on the file's extension, like
+    /// `.mp4` or `.webm`, and ffmpeg has to be installed.
+    ///
+    /// Frames are encoded while the doodle carries on running. If ffmpeg
+    /// falls 30 frames behind, the doodle waits for it to catch up. The video
+    /// isn't finished until `stop_recording` is called or the doodle ends.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// // R starts and stops recording.
+    /// let builder = DoodleBuilder::<State>::new().on_key_down(Box::new(|_, r, key| {
+    ///     if key == Keycode::R {
+    ///         if r.is_recording() {
+    ///             r.stop_recording().unwrap();
+    ///         } else {
+    ///             let settings = VideoSettings::new().bitrate("12M");
+    ///             r.start_recording_video("doodle.mp4", 60, &settings).unwrap();
+    ///         }
+    ///     }
+    /// }));
+    /// ```
+    pub fn start_recording_video<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        fps: u32,
+        settings: &VideoSettings,
+    ) -> Result<()> {
+        if self.recorder.is_some() {
+            return Err(Error::Error("the doodle is already recording".to_owned()));
+        }
+        let fps = fps.max(1);
+        let size = self.size();
+        let mut video = FfmpegWriter::spawn(settings, path.as_ref(), size, fps)?;
+        let start = self.elapsed_seconds();
+        self.recorder = Some(Recorder::start(size, fps, start, move |frames| {
+            for frame in frames {
+                video.write_frame(&frame)?;
+            }
+            video.finish()
+        }));
+        Ok(())
+    }
+    /// Stops recording and finishes the file, waiting for the frames that
+    /// haven't been written yet. Errors from writing any of the frames are
+    /// returned here. Stopping when nothing is being recorded does nothing.
+    pub fn stop_recording(&mut self) -> Result<()> {
+        match self.recorder.take() {
+            Some(mut recorder) => recorder.finish(),
+            None => Ok(()),
+        }
+    }
+    /// Returns true while the doodle is recording.
+    #[inline]
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+    /// Hands the frame that was just drawn to the recorder, if one is
+    /// recording and it's time for its next frame.
+    pub(crate) fn record_frame(&mut self) {
+        let elapsed = self.elapsed_seconds();
+        let size = match self.recorder {
+            Some(ref recorder) if elapsed >= recorder.next_frame => recorder.size,
+            _ => return,
+        };
+        let (width, height) = self.size();
+        let frame = match self.read_region(Rect::new(0, 0, width, height)) {
+            Ok(frame) => Some(frame.resized(size.0, size.1)),
+            Err(error) => {
+                self.log_failure("record a frame", Err(error));
+                None
+            }
+        };
+        let recorder = self.recorder.as_mut().expect("the recorder was checked above");
+        // Catch up without taking several frames at once when the doodle
+        // is running slower than the recording.
+        while recorder.next_frame <= elapsed {
+            recorder.next_frame += recorder.interval;
+        }
+        let frame = match frame {
+            Some(frame) => frame,
+            None => return,
+        };
+        let sent = recorder.frames.as_ref().is_some_and(|frames| frames.send(frame).is_ok());
+        if !sent {
+            // The encoder stopped because of an error, which stop_recording
+            // reports.
+            recorder.frames = None;
+        }
+    }
+}
@@ -0,0 +1,547 @@
+//! Recording the input a doodle gets, and playing it back.
+//!
+//! A replay file is JSON lines: a header with the random seed, then one
+//! array for each input event and for the time of each frame, in the order
+//! the doodle saw them.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use sdl2::controller::{Axis, Button};
+use sdl2::event::Event;
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+use sdl2::mouse::{MouseButton, MouseState, MouseWheelDirection};
+
+use json::Json;
+use log::Level;
+use {Error, Renderer, Result};
+
+/// The version of the replay format, written in the header.
+const FORMAT_VERSION: f64 = 1.0;
+
+/// What a doodle is asked to do with its input when it's built.
+pub(crate) enum ReplayMode {
+    Record(PathBuf),
+    Play(PathBuf),
+}
+
+/// Whether the doodle is recording its input or playing it back.
+#[derive(Default)]
+pub(crate) enum Replay {
+    #[default]
+    Off,
+    Recording(BufWriter<File>),
+    Playing(Player),
+}
+
+/// Something that happened in a replay.
+#[derive(Debug, PartialEq)]
+enum Item {
+    Event(Event),
+    /// A frame started at this many seconds into the doodle.
+    Frame(f64),
+}
+
+pub(crate) struct Player {
+    items: VecDeque<Item>,
+    /// Where the latest mouse event in the replay put the mouse.
+    mouse: (i32, i32),
+    /// The controllers the replay has had events from so far, in the
+    /// order they first appeared.
+    controllers: Vec<i32>,
+    /// Where each controller axis the replay has moved was left, by
+    /// controller id and axis.
+    axes: HashMap<(i32, i32), i16>,
+    /// The controller buttons held down in the replay, by controller id
+    /// and button.
+    buttons: HashSet<(i32, i32)>,
+}
+
+impl Player {
+    /// Keeps track of where the mouse and controllers are as recorded
+    /// events are played.
+    fn play(&mut self, event: &Event) {
+        match *event {
+            Event::MouseMotion { x, y, .. }
+            | Event::MouseButtonDown { x, y, .. }
+            | Event::MouseButtonUp { x, y, .. } => self.mouse = (x, y),
+            Event::ControllerAxisMotion { which, axis, value, .. } => {
+                self.saw_controller(which);
+                self.axes.insert((which, axis.to_ll()), value);
+            }
+            Event::ControllerButtonDown { which, button, .. } => {
+                self.saw_controller(which);
+                self.buttons.insert((which, button.to_ll()));
+            }
+            Event::ControllerButtonUp { which, button, .. } => {
+                self.saw_controller(which);
+                self.buttons.remove(&(which, button.to_ll()));
+            }
+            _ => (),
+        }
+    }
+    fn saw_controller(&mut self, id: i32) {
+        if !self.controllers.contains(&id) {
+            self.controllers.push(id);
+        }
+    }
+}
+
+/// Returns true for the events that are recorded. During playback, live
+/// events like these are ignored, so only the recorded ones are handled.
+fn is_input(event: &Event) -> bool {
+    matches!(
+        *event,
+        Event::KeyDown { .. }
+            | Event::KeyUp { .. }
+            | Event::TextInput { .. }
+            | Event::TextEditing { .. }
+            | Event::MouseMotion { .. }
+            | Event::MouseButtonDown { .. }
+            | Event::MouseButtonUp { .. }
+            | Event::MouseWheel { .. }
+            | Event::ControllerAxisMotion { .. }
+            | Event::ControllerButtonDown { .. }
+            | Event::ControllerButtonUp { .. }
+    )
+}
+
+fn number<N: Into<f64>>(n: N) -> Json {
+    Json::Number(n.into())
+}
+
+fn text(s: &str) -> Json {
+    Json::String(s.to_owned())
+}
+
+/// Turns an input event into the array written to a replay file.
+fn encode(event: &Event) -> Option<Json> {
+    let key = |kind, keycode: Option<Keycode>, scancode: Option<Scancode>, keymod: Mod, repeat| {
+        vec![
+            text(kind),
+            keycode.map_or(Json::Null, |k| number(k as i32)),
+            scancode.map_or(Json::Null, |s| number(s as i32)),
+            number(keymod.bits()),
+            Json::Bool(repeat),
+        ]
+    };
+    let items = match *event {
+        Event::KeyDown { keycode, scancode, keymod, repeat, .. } => {
+            key("key_down", keycode, scancode, keymod, repeat)
+        }
+        Event::KeyUp { keycode, scancode, keymod, repeat, .. } => {
+            key("key_up", keycode, scancode, keymod, repeat)
+        }
+        Event::TextInput { text: ref t, .. } => vec![text("text_input"), text(t)],
+        Event::TextEditing { text: ref t, start, length, .. } => {
+            vec![text("text_editing"), text(t), number(start), number(length)]
+        }
+        Event::MouseMotion { mousestate, x, y, xrel, yrel, .. } => vec![
+            text("mouse_move"),
+            number(x),
+            number(y),
+            number(xrel),
+            number(yrel),
+            number(mousestate.to_sdl_state()),
+        ],
+        Event::MouseButtonDown { mouse_btn, x, y, .. } => {
+            vec![text("mouse_down"), number(mouse_btn as u8), number(x), number(y)]
+        }
+        Event::MouseButtonUp { mouse_btn, x, y, .. } => {
+            vec![text("mouse_up"), number(mouse_btn as u8), number(x), number(y)]
+        }
+        Event::MouseWheel { x, y, direction, .. } => {
+            vec![text("scroll"), number(x), number(y), number(direction.to_ll())]
+        }
+        Event::ControllerAxisMotion { which, axis, value, .. } => {
+            vec![text("controller_axis"), number(which), number(axis.to_ll()), number(value)]
+        }
+        Event::ControllerButtonDown { which, button, .. } => {
+            vec![text("controller_down"), number(which), number(button.to_ll())]
+        }
+        Event::ControllerButtonUp { which, button, .. } => {
+            vec![text("controller_up"), number(which), number(button.to_ll())]
+        }
+        _ => return None,
+    };
+    Some(Json::Array(items))
+}
+
+/// Returns the first line of a replay file.
+fn header(seed: u64) -> Json {
+    Json::Object(vec![
+        ("doodle_replay".to_owned(), number(FORMAT_VERSION)),
+        ("seed".to_owned(), Json::String(seed.to_string())),
+    ])
+}
+
+/// Returns the line of a replay file for a frame starting `elapsed`
+/// seconds into the doodle.
+fn encode_frame(elapsed: f64) -> Json {
+    Json::Array(vec![text("frame"), number(elapsed)])
+}
+
+/// Turns an array read from a replay file back into an item.
+fn decode(items: &[Json], window_id: u32) -> ::std::result::Result<Item, &'static str> {
+    let kind = items.first().and_then(Json::as_str).ok_or("expected the kind of event")?;
+    let int = |i: usize| -> ::std::result::Result<i32, &'static str> {
+        match items.get(i).and_then(Json::as_f64) {
+            Some(n) if n.fract() == 0.0 => Ok(n as i32),
+            _ => Err("expected a whole number"),
+        }
+    };
+    let string = |i: usize| items.get(i).and_then(Json::as_str).map(str::to_owned).ok_or("expected a string");
+    let key = || -> ::std::result::Result<_, &'static str> {
+        let keycode = match items.get(1) {
+            Some(&Json::Null) => None,
+            _ => Keycode::from_i32(int(1)?),
+        };
+        let scancode = match items.get(2) {
+            Some(&Json::Null) => None,
+            _ => Scancode::from_i32(int(2)?),
+        };
+        let keymod = Mod::from_bits_truncate(int(3)? as u16);
+        let repeat = items.get(4).and_then(Json::as_bool).ok_or("expected true or false")?;
+        Ok((keycode, scancode, keymod, repeat))
+    };
+    // SDL panics on controller axes and buttons it doesn't know.
+    let axis = |i: usize| match int(i)? {
+        n @ 0..=5 => Axis::from_ll(n).ok_or("unknown controller axis"),
+        _ => Err("unknown controller axis"),
+    };
+    let button = |i: usize| match int(i)? {
+        n @ 0..=14 => Button::from_ll(n).ok_or("unknown controller button"),
+        _ => Err("unknown controller button"),
+    };
+    let (timestamp, which) = (0, 0);
+    let event = match kind {
+        "frame" => {
+            return match items.get(1).and_then(Json::as_f64) {
+                Some(elapsed) => Ok(Item::Frame(elapsed)),
+                None => Err("expected the time of the frame"),
+            }
+        }
+        "key_down" | "key_up" => {
+            let (keycode, scancode, keymod, repeat) = key()?;
+            if kind == "key_down" {
+                Event::KeyDown { timestamp, window_id, keycode, scancode, keymod, repeat }
+            } else {
+                Event::KeyUp { timestamp, window_id, keycode, scancode, keymod, repeat }
+            }
+        }
+        "text_input" => Event::TextInput { timestamp, window_id, text: string(1)? },
+        "text_editing" => Event::TextEditing {
+            timestamp,
+            window_id,
+            text: string(1)?,
+            start: int(2)?,
+            length: int(3)?,
+        },
+        "mouse_move" => Event::MouseMotion {
+            timestamp,
+            window_id,
+            which,
+            mousestate: MouseState::from_sdl_state(int(5)? as u32),
+            x: int(1)?,
+            y: int(2)?,
+            xrel: int(3)?,
+            yrel: int(4)?,
+        },
+        "mouse_down" | "mouse_up" => {
+            let (mouse_btn, x, y) = (MouseButton::from_ll(int(1)? as u8), int(2)?, int(3)?);
+            if kind == "mouse_down" {
+                Event::MouseButtonDown { timestamp, window_id, which, mouse_btn, x, y }
+            } else {
+                Event::MouseButtonUp { timestamp, window_id, which, mouse_btn, x, y }
+            }
+        }
+        "scroll" => Event::MouseWheel {
+            timestamp,
+            window_id,
+            which,
+            x: int(1)?,
+            y: int(2)?,
+            direction: MouseWheelDirection::from_ll(int(3)? as u32),
+        },
+        "controller_axis" => Event::ControllerAxisMotion {
+            timestamp,
+            which: int(1)?,
+            axis: axis(2)?,
+            value: int(3)? as i16,
+        },
+        "controller_down" => Event::ControllerButtonDown { timestamp, which: int(1)?, button: button(2)? },
+        "controller_up" => Event::ControllerButtonUp { timestamp, which: int(1)?, button: button(2)? },
+        _ => return Err("unknown kind of event"),
+    };
+    Ok(Item::Event(event))
+}
+
+fn replay_error(path: &Path, line: usize, message: &str) -> Error {
+    Error::Error(format!("{}:{}: {}", path.display(), line, message))
+}
+
+/// Reads a replay file, returning its seed and what happened in it.
+fn load(path: &Path, window_id: u32) -> Result<(u64, VecDeque<Item>)> {
+    parse(&fs::read_to_string(path)?, path, window_id)
+}
+
+/// Reads the contents of a replay file, which errors say came from `path`.
+fn parse(contents: &str, path: &Path, window_id: u32) -> Result<(u64, VecDeque<Item>)> {
+    let mut lines = contents.lines().enumerate().filter(|&(_, line)| !line.trim().is_empty());
+    let header = match lines.next() {
+        Some((_, line)) => Json::parse(line)?,
+        None => return Err(replay_error(path, 1, "the file is empty")),
+    };
+    match header.get("doodle_replay").and_then(Json::as_f64) {
+        Some(version) if version == FORMAT_VERSION => (),
+        Some(_) => return Err(replay_error(path, 1, "the replay was made by a newer version of doodle")),
+        None => return Err(replay_error(path, 1, "not a doodle replay")),
+    }
+    let seed = header.get("seed").and_then(Json::as_str).and_then(|s| s.parse().ok());
+    let seed = seed.ok_or_else(|| replay_error(path, 1, "expected the seed"))?;
+    let mut items = VecDeque::new();
+    for (index, line) in lines {
+        let item = match Json::parse(line) {
+            Ok(Json::Array(ref array)) => decode(array, window_id),
+            Ok(_) => Err("expected an array"),
+            Err(error) => return Err(replay_error(path, index + 1, &error.to_string())),
+        };
+        items.push_back(item.map_err(|message| replay_error(path, index + 1, message))?);
+    }
+    Ok((seed, items))
+}
+
+/// Returns a seed that's different every run.
+fn fresh_seed() -> u64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+    now.as_secs() ^ u64::from(now.subsec_nanos()).rotate_left(32)
+}
+
+impl Renderer {
+    /// Starts recording or playing back input when the doodle is built,
+    /// returning the seed its random numbers start with.
+    pub(crate) fn start_replay(&mut self, mode: &ReplayMode, seed: Option<u64>) -> Result<u64> {
+        match *mode {
+            ReplayMode::Record(ref path) => {
+                let seed = seed.unwrap_or_else(fresh_seed);
+                let mut out = BufWriter::new(File::create(path)?);
+                writeln!(out, "{}", header(seed))?;
+                self.replay = Replay::Recording(out);
+                Ok(seed)
+            }
+            ReplayMode::Play(ref path) => {
+                let (seed, items) = load(path, self.canvas.window().id())?;
+                let (width, height) = self.size();
+                self.replay = Replay::Playing(Player {
+                    items,
+                    mouse: (width as i32 / 2, height as i32 / 2),
+                    controllers: Vec::new(),
+                    axes: HashMap::new(),
+                    buttons: HashSet::new(),
+                });
+                Ok(seed)
+            }
+        }
+    }
+    /// Records the input events that are about to be handled, or swaps
+    /// them for the recorded ones that came before the next frame.
+    pub(crate) fn replay_events(&mut self, events: &mut Vec<Event>) {
+        let result = match self.replay {
+            Replay::Off => return,
+            Replay::Recording(ref mut out) => events
+                .iter()
+                .filter_map(encode)
+                .try_for_each(|event| writeln!(out, "{}", event)),
+            Replay::Playing(ref mut player) => {
+                events.retain(|event| !is_input(event));
+                while let Some(&Item::Event(_)) = player.items.front() {
+                    if let Some(Item::Event(event)) = player.items.pop_front() {
+                        player.play(&event);
+                        events.push(event);
+                    }
+                }
+                if player.items.is_empty() {
+                    self.replay = Replay::Off;
+                    self.logger.log(Level::Info, || "finished replaying input".to_owned());
+                }
+                Ok(())
+            }
+        };
+        self.stop_recording_input_on_error(result);
+    }
+    /// Returns when the next frame started in the replay being played, or
+    /// writes down when it started for the replay being recorded.
+    pub(crate) fn replay_frame_time(&mut self, elapsed: f64) -> Option<f64> {
+        let result = match self.replay {
+            Replay::Off => return None,
+            Replay::Playing(ref mut player) => {
+                if let Some(&Item::Frame(elapsed)) = player.items.front() {
+                    player.items.pop_front();
+                    return Some(elapsed);
+                }
+                return None;
+            }
+            // Flushing every frame keeps the file up to date when the
+            // doodle crashes, which is when it's most wanted.
+            Replay::Recording(ref mut out) => {
+                writeln!(out, "{}", encode_frame(elapsed)).and_then(|_| out.flush())
+            }
+        };
+        self.stop_recording_input_on_error(result);
+        None
+    }
+    fn stop_recording_input_on_error(&mut self, result: ::std::io::Result<()>) {
+        if let Err(error) = result {
+            self.replay = Replay::Off;
+            self.log_failure("record input", Err(error));
+        }
+    }
+    /// Returns where the mouse is in the replay being played, if there is
+    /// one.
+    pub(crate) fn replayed_mouse_position(&self) -> Option<(i32, i32)> {
+        match self.replay {
+            Replay::Playing(ref player) => Some(player.mouse),
+            _ => None,
+        }
+    }
+    /// Returns the controllers in the replay being played, if there is one.
+    pub(crate) fn replayed_controllers(&self) -> Option<Vec<i32>> {
+        match self.replay {
+            Replay::Playing(ref player) => Some(player.controllers.clone()),
+            _ => None,
+        }
+    }
+    /// Returns where an axis of a controller is in the replay being played,
+    /// if there is one.
+    pub(crate) fn replayed_controller_axis(&self, id: i32, axis: Axis) -> Option<i16> {
+        match self.replay {
+            Replay::Playing(ref player) => Some(player.axes.get(&(id, axis.to_ll())).cloned().unwrap_or(0)),
+            _ => None,
+        }
+    }
+    /// Returns whether a button of a controller is held in the replay being
+    /// played, if there is one.
+    pub(crate) fn replayed_controller_button(&self, id: i32, button: Button) -> Option<bool> {
+        match self.replay {
+            Replay::Playing(ref player) => Some(player.buttons.contains(&(id, button.to_ll()))),
+            _ => None,
+        }
+    }
+    /// Returns true while the doodle is recording its input with
+    /// `DoodleBuilder::record_input`.
+    #[inline]
+    pub fn is_recording_input(&self) -> bool {
+        matches!(self.replay, Replay::Recording(_))
+    }
+    /// Returns true while the doodle is playing back input with
+    /// `DoodleBuilder::replay_input`. It becomes false once the whole
+    /// replay has been played, and the doodle goes back to the live input.
+    #[inline]
+    pub fn is_replaying(&self) -> bool {
+        matches!(self.replay, Replay::Playing(_))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sdl2::keyboard;
+
+    const WINDOW: u32 = 3;
+
+    fn events() -> Vec<Event> {
+        let (timestamp, window_id, which) = (0, WINDOW, 0);
+        vec![
+            Event::KeyDown {
+                timestamp,
+                window_id,
+                keycode: Some(Keycode::Space),
+                scancode: Some(Scancode::Space),
+                keymod: keyboard::LSHIFTMOD | keyboard::NUMMOD,
+                repeat: false,
+            },
+            Event::KeyUp {
+                timestamp,
+                window_id,
+                keycode: None,
+                scancode: Some(Scancode::A),
+                keymod: keyboard::NOMOD,
+                repeat: true,
+            },
+            Event::TextInput { timestamp, window_id, text: "say \"héllo\" \\ 日本 😀".to_owned() },
+            Event::TextEditing { timestamp, window_id, text: ",\n\t]".to_owned(), start: 2, length: -1 },
+            Event::MouseMotion {
+                timestamp,
+                window_id,
+                which,
+                mousestate: MouseState::from_sdl_state(0b101),
+                x: 320,
+                y: -4,
+                xrel: -12,
+                yrel: 7,
+            },
+            Event::MouseButtonDown { timestamp, window_id, which, mouse_btn: MouseButton::Right, x: 1, y: 2 },
+            Event::MouseButtonUp { timestamp, window_id, which, mouse_btn: MouseButton::X2, x: 3, y: 4 },
+            Event::MouseWheel {
+                timestamp,
+                window_id,
+                which,
+                x: -1,
+                y: 3,
+                direction: MouseWheelDirection::Flipped,
+            },
+            Event::ControllerAxisMotion { timestamp, which: 2, axis: Axis::TriggerRight, value: -32768 },
+            Event::ControllerButtonDown { timestamp, which: 2, button: Button::DPadLeft },
+            Event::ControllerButtonUp { timestamp, which: 5, button: Button::Guide },
+        ]
+    }
+
+    #[test]
+    fn recordings_decode_to_the_same_events() {
+        let seed = u64::MAX - 12;
+        let mut expected = Vec::new();
+        let mut lines = vec![header(seed).to_string()];
+        for (i, event) in events().into_iter().enumerate() {
+            let elapsed = i as f64 / 60.0 + 0.001;
+            lines.push(encode_frame(elapsed).to_string());
+            expected.push(Item::Frame(elapsed));
+            lines.push(encode(&event).expect("the event should be recorded").to_string());
+            expected.push(Item::Event(event));
+        }
+        let contents = lines.join("\n") + "\n";
+        let (decoded_seed, items) = parse(&contents, Path::new("test.replay"), WINDOW).unwrap();
+        assert_eq!(decoded_seed, seed);
+        assert_eq!(items.len(), expected.len());
+        for (item, expected) in items.into_iter().zip(expected) {
+            assert_eq!(item, expected);
+        }
+    }
+
+    #[test]
+    fn only_input_is_recorded() {
+        assert!(events().iter().all(is_input));
+        let other = Event::Window {
+            timestamp: 0,
+            window_id: WINDOW,
+            win_event: ::sdl2::event::WindowEvent::FocusGained,
+        };
+        assert!(!is_input(&other));
+        assert!(encode(&other).is_none());
+    }
+
+    #[test]
+    fn bad_lines_are_reported_with_their_number() {
+        let seed = header(1).to_string();
+        let path = Path::new("a.replay");
+        let error = |contents: &str| parse(contents, path, WINDOW).err().unwrap().to_string();
+        assert_eq!(error(""), "a.replay:1: the file is empty");
+        assert_eq!(error("{\"seed\": \"1\"}"), "a.replay:1: not a doodle replay");
+        let contents = format!("{}\n\n[\"frame\", 0]\n[\"controller_axis\", 0, 9, 0]\n", seed);
+        assert_eq!(error(&contents), "a.replay:4: unknown controller axis");
+        let contents = format!("{}\n[\"mouse_down\", 1, 2.5, 0]\n", seed);
+        assert_eq!(error(&contents), "a.replay:2: expected a whole number");
+    }
+}
@@ -0,0 +1,240 @@
+//! Splitting a doodle into scenes, like a menu, the game and a game over
+//! screen, that take turns running.
+
+use std::mem;
+
+use {DoodleBuilder, Event, Renderer};
+
+/// A part of a doodle that runs while it's on top of a `SceneStack`.
+///
+/// Scenes share the doodle's state `T`, for things that outlive any one
+/// scene like a high score, and keep what only they need in themselves.
+/// `update` and `event` return a `Transition` to move to another scene.
+/// Every method does nothing unless it's implemented.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// #[derive(Default)]
+/// struct Scores {
+///     best: u32,
+/// }
+///
+/// struct Menu;
+/// struct Game {
+///     score: u32,
+/// }
+/// struct GameOver {
+///     score: u32,
+/// }
+///
+/// impl Scene<Scores> for Menu {
+///     fn draw(&mut self, scores: &mut Scores, r: &mut Renderer) {
+///         r.clear();
+///         r.text(&format!("Best: {}. Click to play", scores.best), 250.0, 280.0).unwrap();
+///     }
+///     fn event(&mut self, _: &mut Scores, _: &mut Renderer, event: &Event) -> Transition<Scores> {
+///         match *event {
+///             Event::MouseButtonDown { .. } => Transition::Replace(Box::new(Game { score: 0 })),
+///             _ => Transition::Stay,
+///         }
+///     }
+/// }
+///
+/// impl Scene<Scores> for Game {
+///     fn update(&mut self, _: &mut Scores, _: f64) -> Transition<Scores> {
+///         self.score += 1;
+///         if self.score == 300 {
+///             return Transition::Replace(Box::new(GameOver { score: self.score }));
+///         }
+///         Transition::Stay
+///     }
+///     fn draw(&mut self, _: &mut Scores, r: &mut Renderer) {
+///         r.clear();
+///         r.fill_circle(self.score as f32 * 2.0, 300.0, 20.0);
+///     }
+/// }
+///
+/// impl Scene<Scores> for GameOver {
+///     fn enter(&mut self, scores: &mut Scores, _: &mut Renderer) {
+///         scores.best = scores.best.max(self.score);
+///     }
+///     fn event(&mut self, _: &mut Scores, _: &mut Renderer, event: &Event) -> Transition<Scores> {
+///         match *event {
+///             Event::MouseButtonDown { .. } => Transition::Replace(Box::new(Menu)),
+///             _ => Transition::Stay,
+///         }
+///     }
+/// }
+///
+/// DoodleBuilder::from_scene(Scores::default(), Box::new(Menu)).build().unwrap().run().unwrap();
+/// ```
+pub trait Scene<T> {
+    /// Run when the scene is put on top of the stack.
+    fn enter(&mut self, _state: &mut T, _r: &mut Renderer) {}
+    /// Run when the scene is taken off the stack, or when the doodle stops
+    /// while it's still on it.
+    fn exit(&mut self, _state: &mut T, _r: &mut Renderer) {}
+    /// Run before each frame while the scene is on top, with the number of
+    /// seconds since the previous one.
+    fn update(&mut self, _state: &mut T, _dt: f64) -> Transition<T> {
+        Transition::Stay
+    }
+    /// Run to draw each frame while the scene is on top, or under an
+    /// overlay that's on top.
+    fn draw(&mut self, _state: &mut T, _r: &mut Renderer) {}
+    /// Run for every event the doodle receives while the scene is on top.
+    fn event(&mut self, _state: &mut T, _r: &mut Renderer, _event: &Event) -> Transition<T> {
+        Transition::Stay
+    }
+    /// Returns true if the scene under this one should be drawn first,
+    /// like the game under a pause menu. The scene under it doesn't update
+    /// or get events either way.
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+/// What a `SceneStack` should do after a scene's `update` or `event`.
+pub enum Transition<T> {
+    /// Keep running the same scene.
+    Stay,
+    /// Put a scene on top of this one, which carries on when it's popped.
+    Push(Box<dyn Scene<T>>),
+    /// Take this scene off the stack, going back to the one under it. The
+    /// doodle stops when the last scene is popped.
+    Pop,
+    /// Swap this scene for another.
+    Replace(Box<dyn Scene<T>>),
+}
+
+/// The state of a doodle made with `DoodleBuilder::from_scene`: the scenes,
+/// with the one running on top, and the state they share.
+pub struct SceneStack<T> {
+    state: T,
+    scenes: Vec<Box<dyn Scene<T>>>,
+    /// Transitions that haven't been made yet, since entering and exiting
+    /// scenes needs the Renderer.
+    pending: Vec<Transition<T>>,
+}
+
+impl<T> SceneStack<T> {
+    /// Creates a stack that starts with one scene. The scene is entered
+    /// when the doodle is set up.
+    pub fn new(state: T, scene: Box<dyn Scene<T>>) -> SceneStack<T> {
+        SceneStack {
+            state,
+            scenes: Vec::new(),
+            pending: vec![Transition::Push(scene)],
+        }
+    }
+    /// Returns the state the scenes share.
+    #[inline]
+    pub fn state(&self) -> &T {
+        &self.state
+    }
+    /// Returns the state the scenes share, for changing it.
+    #[inline]
+    pub fn state_mut(&mut self) -> &mut T {
+        &mut self.state
+    }
+    /// Returns how many scenes are on the stack.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.scenes.len()
+    }
+    /// Returns true if there are no scenes on the stack, which is only the
+    /// case before setup and after the last scene is popped.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.scenes.is_empty()
+    }
+    /// Puts a scene on top of the stack the next time the Renderer can
+    /// enter it, for callbacks outside the scenes.
+    #[inline]
+    pub fn push(&mut self, scene: Box<dyn Scene<T>>) {
+        self.pending.push(Transition::Push(scene));
+    }
+    /// Takes the top scene off the stack, like `push`.
+    #[inline]
+    pub fn pop(&mut self) {
+        self.pending.push(Transition::Pop);
+    }
+    /// Swaps the top scene for another, like `push`.
+    #[inline]
+    pub fn replace(&mut self, scene: Box<dyn Scene<T>>) {
+        self.pending.push(Transition::Replace(scene));
+    }
+    /// Makes the transitions that are waiting, in order.
+    fn apply(&mut self, r: &mut Renderer) {
+        for transition in mem::take(&mut self.pending) {
+            match transition {
+                Transition::Stay => (),
+                Transition::Push(scene) => self.enter(scene, r),
+                Transition::Pop => self.exit(r),
+                Transition::Replace(scene) => {
+                    self.exit(r);
+                    self.enter(scene, r);
+                }
+            }
+        }
+        if self.scenes.is_empty() {
+            r.quit();
+        }
+    }
+    fn enter(&mut self, mut scene: Box<dyn Scene<T>>, r: &mut Renderer) {
+        scene.enter(&mut self.state, r);
+        self.scenes.push(scene);
+    }
+    fn exit(&mut self, r: &mut Renderer) {
+        if let Some(mut scene) = self.scenes.pop() {
+            scene.exit(&mut self.state, r);
+        }
+    }
+    fn update(&mut self, dt: f64) {
+        if let Some(scene) = self.scenes.last_mut() {
+            let transition = scene.update(&mut self.state, dt);
+            self.pending.push(transition);
+        }
+    }
+    fn draw(&mut self, r: &mut Renderer) {
+        self.apply(r);
+        // Draw from the lowest scene that shows, up to the top.
+        let first = self.scenes.iter().rposition(|s| !s.is_overlay()).unwrap_or(0);
+        for scene in &mut self.scenes[first..] {
+            scene.draw(&mut self.state, r);
+        }
+    }
+    fn event(&mut self, r: &mut Renderer, event: &Event) {
+        self.apply(r);
+        if let Some(scene) = self.scenes.last_mut() {
+            let transition = scene.event(&mut self.state, r, event);
+            self.pending.push(transition);
+        }
+        self.apply(r);
+    }
+    fn teardown(&mut self, r: &mut Renderer) {
+        while !self.scenes.is_empty() {
+            self.exit(r);
+        }
+    }
+}
+
+impl<'a, T> DoodleBuilder<'a, SceneStack<T>> {
+    /// Creates a DoodleBuilder that runs scenes, starting with `scene`,
+    /// with the default settings. See `Scene` for an example.
+    ///
+    /// Like with `from_sketch`, the other settings can be changed before
+    /// the doodle is built, but setting the setup, update, draw, event or
+    /// teardown callback stops the scenes getting it. The other callbacks
+    /// get the `SceneStack`, and can change scenes with its `push`, `pop`
+    /// and `replace`.
+    pub fn from_scene(state: T, scene: Box<dyn Scene<T>>) -> Self {
+        DoodleBuilder::with_state(SceneStack::new(state, scene))
+            .setup(Box::new(|stack, r| stack.apply(r)))
+            .update(Box::new(|stack, dt| stack.update(dt)))
+            .draw(Box::new(|stack, r| stack.draw(r)))
+            .on_event(Box::new(|stack, r, event| stack.event(r, event)))
+            .teardown(Box::new(|stack, r, _| stack.teardown(r)))
+    }
+}
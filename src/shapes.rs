@@ -0,0 +1,575 @@
+//! Shape drawing methods for the Renderer.
+//!
+//! All coordinates are `f32`. SDL only draws on whole pixels, so every
+//! coordinate is snapped with `to_pixel` right before it's handed to SDL.
+//! Rectangles are snapped by their edges instead of by their size, so a
+//! rectangle that slowly moves across the screen never changes width by
+//! a pixel from one frame to the next.
+//!
+//! Coordinates go through the transformation set up with `translate`,
+//! `rotate` and `scale` before they're snapped.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::f32::consts::PI;
+
+use sdl2::pixels::Color;
+use sdl2::rect::{Point, Rect};
+
+use Renderer;
+
+/// Converts a coordinate to the pixel that contains it.
+///
+/// Pixel `n` covers the range `[n, n + 1)`, so this is simply the floor
+/// of the coordinate. Flooring (instead of rounding to nearest) means
+/// that a shape moving by less than a pixel per frame always steps in
+/// the same direction it is moving.
+#[inline]
+pub fn to_pixel(v: f32) -> i32 {
+    v.floor() as i32
+}
+
+/// How round each corner of a rounded rectangle is, as the radius of the
+/// circle it's cut from.
+///
+/// A single number gives every corner the same radius, and an array gives
+/// the corners clockwise from the top left, like CSS's `border-radius`.
+/// When two corners on the same side add up to more than the side is long,
+/// all of the corners are shrunk by the same amount until they fit.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct CornerRadii {
+    /// The radius of the top left corner.
+    pub top_left: f32,
+    /// The radius of the top right corner.
+    pub top_right: f32,
+    /// The radius of the bottom right corner.
+    pub bottom_right: f32,
+    /// The radius of the bottom left corner.
+    pub bottom_left: f32,
+}
+
+impl From<f32> for CornerRadii {
+    #[inline]
+    fn from(radius: f32) -> CornerRadii {
+        CornerRadii::from([radius; 4])
+    }
+}
+
+impl From<[f32; 4]> for CornerRadii {
+    #[inline]
+    fn from([top_left, top_right, bottom_right, bottom_left]: [f32; 4]) -> CornerRadii {
+        CornerRadii {
+            top_left,
+            top_right,
+            bottom_right,
+            bottom_left,
+        }
+    }
+}
+
+impl CornerRadii {
+    /// Returns the radii clockwise from the top left, made to fit a
+    /// rectangle of the given size.
+    fn fit(self, w: f32, h: f32) -> [f32; 4] {
+        let radii = [self.top_left, self.top_right, self.bottom_right, self.bottom_left].map(|r| r.max(0.0));
+        let sides = [(w, radii[0] + radii[1]), (h, radii[1] + radii[2]), (w, radii[2] + radii[3]), (h, radii[3] + radii[0])];
+        let shrink = sides
+            .iter()
+            .filter(|&&(_, needed)| needed > 0.0)
+            .fold(1.0f32, |shrink, &(side, needed)| shrink.min(side / needed));
+        radii.map(|r| r * shrink)
+    }
+}
+
+/// Snaps a rectangle to the pixel grid by its edges.
+/// Returns None if the rectangle doesn't cover any pixels.
+pub(crate) fn pixel_rect(x: f32, y: f32, w: f32, h: f32) -> Option<Rect> {
+    let (left, right) = if w < 0.0 { (x + w, x) } else { (x, x + w) };
+    let (top, bottom) = if h < 0.0 { (y + h, y) } else { (y, y + h) };
+    let (left, top) = (to_pixel(left), to_pixel(top));
+    let (right, bottom) = (to_pixel(right), to_pixel(bottom));
+    if right <= left || bottom <= top {
+        None
+    } else {
+        Some(Rect::new(left, top, (right - left) as u32, (bottom - top) as u32))
+    }
+}
+
+/// Returns the pixels inside an ellipse and `bounds` as one
+/// `(y, left, right)` span per row, from top to bottom. A pixel is inside
+/// if its center is.
+fn ellipse_spans(cx: f32, cy: f32, rx: f32, ry: f32, bounds: PixelBounds) -> Vec<(i32, i32, i32)> {
+    let (rx, ry) = (rx.abs(), ry.abs());
+    let mut spans = Vec::new();
+    if rx == 0.0 || ry == 0.0 {
+        return spans;
+    }
+    for y in to_pixel(cy - ry).max(bounds.1)..=to_pixel(cy + ry).min(bounds.3) {
+        let dy = (y as f32 + 0.5 - cy) / ry;
+        if dy.abs() > 1.0 {
+            continue;
+        }
+        let half_width = rx * (1.0 - dy * dy).sqrt();
+        let left = ((cx - half_width - 0.5).ceil() as i32).max(bounds.0);
+        let right = ((cx + half_width - 0.5).floor() as i32).min(bounds.2);
+        if left <= right {
+            spans.push((y, left, right));
+        }
+    }
+    spans
+}
+
+/// Returns the rows of pixels on the edge of an ellipse: the pixels of
+/// each span that don't have a pixel of the ellipse on all four sides.
+fn ellipse_outline(spans: &[(i32, i32, i32)]) -> Vec<Rect> {
+    let mut rects = Vec::new();
+    for (i, &(y, left, right)) in spans.iter().enumerate() {
+        let whole = Rect::new(left, y, (right - left + 1) as u32, 1);
+        let (above, below) = match (i.checked_sub(1).map(|i| spans[i]), spans.get(i + 1)) {
+            (Some(above), Some(&below)) => (above, below),
+            _ => {
+                rects.push(whole);
+                continue;
+            }
+        };
+        let inner_left = (left + 1).max(above.1).max(below.1);
+        let inner_right = (right - 1).min(above.2).min(below.2);
+        if inner_left > inner_right {
+            rects.push(whole);
+        } else {
+            rects.push(Rect::new(left, y, (inner_left - left) as u32, 1));
+            rects.push(Rect::new(inner_right + 1, y, (right - inner_right) as u32, 1));
+        }
+    }
+    rects
+}
+
+/// Which parts of overlapping polygons are filled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FillRule {
+    /// Parts inside an odd number of polygons are filled, so a polygon
+    /// inside another one cuts a hole in it.
+    EvenOdd,
+    /// Parts inside any of the polygons are filled, as long as they all go
+    /// around the same way.
+    NonZero,
+}
+
+/// How many lines each row of pixels is split into to work out how much of
+/// each pixel a smooth shape covers.
+const SUBSAMPLES: usize = 4;
+
+/// Returns the top and bottom of some polygons, or None if they have no
+/// points.
+fn vertical_bounds(contours: &[&[(f32, f32)]]) -> Option<(f32, f32)> {
+    let ys = contours.iter().flat_map(|c| c.iter()).map(|p| p.1);
+    let (top, bottom) = ys.fold((f32::INFINITY, f32::NEG_INFINITY), |(t, b), y| (t.min(y), b.max(y)));
+    if top > bottom {
+        None
+    } else {
+        Some((top, bottom))
+    }
+}
+
+/// Adds the parts of a horizontal line at `y` that are inside the polygons
+/// to `intervals`, as `(start, end)` pairs from left to right. `crossings`
+/// is scratch space that is reused between lines.
+fn inside_intervals(
+    contours: &[&[(f32, f32)]],
+    y: f32,
+    rule: FillRule,
+    crossings: &mut Vec<(f32, i32)>,
+    intervals: &mut Vec<(f32, f32)>,
+) {
+    crossings.clear();
+    for points in contours {
+        for (i, &(x0, y0)) in points.iter().enumerate() {
+            let (x1, y1) = points[(i + 1) % points.len()];
+            if (y0 <= y) != (y1 <= y) {
+                let direction = if y1 > y0 { 1 } else { -1 };
+                crossings.push((x0 + (y - y0) / (y1 - y0) * (x1 - x0), direction));
+            }
+        }
+    }
+    crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+    let inside = |winding: i32| match rule {
+        FillRule::EvenOdd => winding % 2 != 0,
+        FillRule::NonZero => winding != 0,
+    };
+    let (mut winding, mut start) = (0, 0.0);
+    for &(x, direction) in crossings.iter() {
+        let was_inside = inside(winding);
+        winding += direction;
+        match (was_inside, inside(winding)) {
+            (false, true) => start = x,
+            (true, false) => intervals.push((start, x)),
+            _ => (),
+        }
+    }
+}
+
+/// The pixels a shape can be drawn on, as the `(left, top, right, bottom)`
+/// pixels on its edges. Shapes only work out the pixels inside these, so
+/// huge shapes that are mostly off the canvas take no longer than ones
+/// that fit on it.
+pub(crate) type PixelBounds = (i32, i32, i32, i32);
+
+/// Returns the pixels inside one or more polygons and `bounds` as
+/// `(y, left, right)` spans. A pixel is inside if its center is.
+fn polygon_spans(contours: &[&[(f32, f32)]], rule: FillRule, bounds: PixelBounds) -> Vec<(i32, i32, i32)> {
+    let mut spans = Vec::new();
+    let (top, bottom) = match vertical_bounds(contours) {
+        Some(bounds) => bounds,
+        None => return spans,
+    };
+    let (mut crossings, mut intervals) = (Vec::new(), Vec::new());
+    for y in to_pixel(top).max(bounds.1)..=to_pixel(bottom).min(bounds.3) {
+        intervals.clear();
+        inside_intervals(contours, y as f32 + 0.5, rule, &mut crossings, &mut intervals);
+        for &(start, end) in &intervals {
+            let left = ((start - 0.5).ceil() as i32).max(bounds.0);
+            let right = ((end - 0.5).ceil() as i32 - 1).min(bounds.2);
+            if left <= right {
+                spans.push((y, left, right));
+            }
+        }
+    }
+    spans
+}
+
+/// Returns how much of each pixel one or more polygons cover, from 0 to 1,
+/// as one `(y, left, coverage)` row for every row of pixels inside
+/// `bounds` they touch.
+fn polygon_coverage(
+    contours: &[&[(f32, f32)]],
+    rule: FillRule,
+    bounds: PixelBounds,
+) -> Vec<(i32, i32, Vec<f32>)> {
+    let mut rows = Vec::new();
+    let (top, bottom) = match vertical_bounds(contours) {
+        Some(bounds) => bounds,
+        None => return rows,
+    };
+    let (mut crossings, mut intervals) = (Vec::new(), Vec::new());
+    for y in to_pixel(top).max(bounds.1)..=to_pixel(bottom).min(bounds.3) {
+        intervals.clear();
+        for k in 0..SUBSAMPLES {
+            let line = y as f32 + (k as f32 + 0.5) / SUBSAMPLES as f32;
+            inside_intervals(contours, line, rule, &mut crossings, &mut intervals);
+        }
+        let left = intervals.iter().map(|&(start, _)| to_pixel(start)).min();
+        let right = intervals.iter().map(|&(_, end)| to_pixel(end)).max();
+        let (left, right) = match (left, right) {
+            (Some(left), Some(right)) => (left.max(bounds.0), right.min(bounds.2)),
+            _ => continue,
+        };
+        if left > right {
+            continue;
+        }
+        let mut coverage = vec![0.0; (right - left + 1) as usize];
+        for &(start, end) in &intervals {
+            for x in to_pixel(start).max(left)..=to_pixel(end).min(right) {
+                let covered = end.min(x as f32 + 1.0) - start.max(x as f32);
+                if covered > 0.0 {
+                    coverage[(x - left) as usize] += covered / SUBSAMPLES as f32;
+                }
+            }
+        }
+        rows.push((y, left, coverage));
+    }
+    rows
+}
+
+/// Turns spans of pixels into rectangles one pixel high.
+fn span_rects(spans: &[(i32, i32, i32)]) -> Vec<Rect> {
+    spans
+        .iter()
+        .map(|&(y, left, right)| Rect::new(left, y, (right - left + 1) as u32, 1))
+        .collect()
+}
+
+impl Renderer {
+    /// Returns the pixels of the canvas, or of the image being drawn on.
+    pub(crate) fn pixel_bounds(&self) -> PixelBounds {
+        let (width, height) = self.size();
+        (0, 0, width as i32 - 1, height as i32 - 1)
+    }
+    /// Returns the corners of a rectangle after transforming them.
+    fn transformed_rect(&self, x: f32, y: f32, w: f32, h: f32) -> [(f32, f32); 4] {
+        let transform = &self.transform;
+        [
+            transform.apply(x, y),
+            transform.apply(x + w, y),
+            transform.apply(x + w, y + h),
+            transform.apply(x, y + h),
+        ]
+    }
+    /// Returns a rectangle in canvas pixels for a rectangle in drawing
+    /// coordinates, if the transformation keeps it a rectangle.
+    fn axis_aligned_rect(&self, x: f32, y: f32, w: f32, h: f32) -> Option<Option<Rect>> {
+        if !self.transform.axis_aligned() {
+            return None;
+        }
+        let (left, top) = self.transform.apply(x, y);
+        let (right, bottom) = self.transform.apply(x + w, y + h);
+        Some(pixel_rect(left, top, right - left, bottom - top))
+    }
+    /// Returns the points around an ellipse, with about one point for every
+    /// two pixels around it once it's transformed onto the canvas.
+    pub(crate) fn ellipse_points(&self, x: f32, y: f32, rx: f32, ry: f32) -> Vec<(f32, f32)> {
+        let (scale_x, scale_y) = self.transform.axis_scale();
+        let around = PI * (rx.abs() * scale_x + ry.abs() * scale_y);
+        let count = ((around / 2.0).ceil() as usize).clamp(8, 512);
+        (0..count)
+            .map(|i| {
+                let angle = i as f32 / count as f32 * 2.0 * PI;
+                (x + rx * angle.cos(), y + ry * angle.sin())
+            })
+            .collect()
+    }
+    /// Returns the points along an arc of a circle from `start` to `end`
+    /// radians, including both ends, spaced like `ellipse_points`.
+    pub(crate) fn arc_points(&self, x: f32, y: f32, radius: f32, start: f32, end: f32) -> Vec<(f32, f32)> {
+        let full = self.ellipse_points(x, y, radius, radius).len();
+        let sweep = end - start;
+        let count = ((full as f32 * sweep / (2.0 * PI)).ceil() as usize).max(2);
+        (0..=count)
+            .map(|i| {
+                let angle = start + i as f32 / count as f32 * sweep;
+                (x + radius * angle.cos(), y + radius * angle.sin())
+            })
+            .collect()
+    }
+    /// Returns the points around a rounded rectangle, clockwise from the
+    /// top left corner.
+    pub(crate) fn rounded_rect_points(&self, x: f32, y: f32, w: f32, h: f32, radii: CornerRadii) -> Vec<(f32, f32)> {
+        let (x, w) = if w < 0.0 { (x + w, -w) } else { (x, w) };
+        let (y, h) = if h < 0.0 { (y + h, -h) } else { (y, h) };
+        let [top_left, top_right, bottom_right, bottom_left] = radii.fit(w, h);
+        let corners = [
+            (x + top_left, y + top_left, top_left, PI),
+            (x + w - top_right, y + top_right, top_right, 1.5 * PI),
+            (x + w - bottom_right, y + h - bottom_right, bottom_right, 0.0),
+            (x + bottom_left, y + h - bottom_left, bottom_left, 0.5 * PI),
+        ];
+        let mut points = Vec::new();
+        for &(cx, cy, radius, start) in &corners {
+            if radius > 0.0 {
+                points.extend(self.arc_points(cx, cy, radius, start, start + 0.5 * PI));
+            } else {
+                points.push((cx, cy));
+            }
+        }
+        points.dedup();
+        points
+    }
+    /// Returns the points around an ellipse after transforming them.
+    fn transformed_ellipse(&self, x: f32, y: f32, rx: f32, ry: f32) -> Vec<(f32, f32)> {
+        let points = self.ellipse_points(x, y, rx, ry);
+        points.into_iter().map(|(x, y)| self.transform.apply(x, y)).collect()
+    }
+    /// Draws the outline of a polygon that is already in canvas pixels.
+    pub(crate) fn stroke_polygon(&mut self, points: &[(f32, f32)], what: &str) {
+        let mut points = points.to_vec();
+        if let Some(&first) = points.first() {
+            points.push(first);
+        }
+        self.stroke_polyline(&points, what);
+    }
+    /// Draws lines joining points that are already in canvas pixels,
+    /// without joining the last one back to the first.
+    pub(crate) fn stroke_polyline(&mut self, points: &[(f32, f32)], what: &str) {
+        let pixels: Vec<Point> = points.iter().map(|&(x, y)| Point::new(to_pixel(x), to_pixel(y))).collect();
+        self.draw_call();
+        let result = self.canvas.draw_lines(&pixels[..]);
+        self.log_failure(what, result);
+    }
+    /// Fills polygons that are already in canvas pixels, with the
+    /// even-odd rule.
+    #[inline]
+    pub(crate) fn fill_polygons(&mut self, contours: &[&[(f32, f32)]], what: &str) {
+        self.fill_polygons_with(contours, FillRule::EvenOdd, false, what);
+    }
+    /// Fills polygons that are already in canvas pixels. Smooth polygons
+    /// have their edge pixels drawn see-through by how much of each pixel
+    /// they cover.
+    pub(crate) fn fill_polygons_with(&mut self, contours: &[&[(f32, f32)]], rule: FillRule, smooth: bool, what: &str) {
+        if !smooth {
+            self.draw_call();
+            let spans = polygon_spans(contours, rule, self.pixel_bounds());
+            let result = self.canvas.fill_rects(&span_rects(&spans));
+            self.log_failure(what, result);
+            return;
+        }
+        let mut solid = Vec::new();
+        // Edge pixels are grouped by how see-through they are, so each
+        // group can be drawn in one call.
+        let mut edges: BTreeMap<u8, Vec<Point>> = BTreeMap::new();
+        for (y, left, coverage) in polygon_coverage(contours, rule, self.pixel_bounds()) {
+            let mut run_start = None;
+            for (i, &covered) in coverage.iter().enumerate() {
+                let x = left + i as i32;
+                if covered >= 0.999 {
+                    run_start = run_start.or(Some(x));
+                    continue;
+                }
+                if let Some(start) = run_start.take() {
+                    solid.push(Rect::new(start, y, (x - start) as u32, 1));
+                }
+                let alpha = (covered.min(1.0) * 255.0).round() as u8;
+                if alpha > 0 {
+                    edges.entry(alpha).or_default().push(Point::new(x, y));
+                }
+            }
+            if let Some(start) = run_start {
+                let end = left + coverage.len() as i32;
+                solid.push(Rect::new(start, y, (end - start) as u32, 1));
+            }
+        }
+        self.draw_call();
+        let result = self.canvas.fill_rects(&solid);
+        self.log_failure(what, result);
+        let color = self.canvas.draw_color();
+        for (alpha, points) in edges {
+            let alpha = (u32::from(color.a) * u32::from(alpha) / 255) as u8;
+            self.canvas.set_draw_color(Color::RGBA(color.r, color.g, color.b, alpha));
+            self.draw_call();
+            let result = self.canvas.draw_points(&points[..]);
+            self.log_failure(what, result);
+        }
+        self.canvas.set_draw_color(color);
+    }
+    /// Draws a single pixel at (x, y) with the current drawing color.
+    pub fn draw_point(&mut self, x: f32, y: f32) {
+        self.dirty = true;
+        let (x, y) = self.transform.apply(x, y);
+        self.batch_point(Point::new(to_pixel(x), to_pixel(y)));
+    }
+    /// Draws a line from (x1, y1) to (x2, y2) with the current drawing color.
+    pub fn draw_line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        self.dirty = true;
+        let ((x1, y1), (x2, y2)) = (self.transform.apply(x1, y1), self.transform.apply(x2, y2));
+        let start = Point::new(to_pixel(x1), to_pixel(y1));
+        let end = Point::new(to_pixel(x2), to_pixel(y2));
+        self.batch_line(start, end);
+    }
+    /// Draws the outline of a rectangle with its top left corner at (x, y).
+    pub fn draw_rect(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        self.dirty = true;
+        match self.axis_aligned_rect(x, y, w, h) {
+            Some(Some(rect)) => self.batch_outline(rect),
+            Some(None) => (),
+            None => {
+                let corners = self.transformed_rect(x, y, w, h);
+                self.stroke_polygon(&corners, "draw a rectangle");
+            }
+        }
+    }
+    /// Fills a rectangle with its top left corner at (x, y).
+    pub fn fill_rect(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        self.dirty = true;
+        match self.axis_aligned_rect(x, y, w, h) {
+            Some(Some(rect)) => self.batch_rect(rect),
+            Some(None) => (),
+            None => {
+                let corners = self.transformed_rect(x, y, w, h);
+                self.fill_polygons(&[&corners], "fill a rectangle");
+            }
+        }
+    }
+    /// Draws the outline of a rectangle with rounded corners, with its top
+    /// left corner at (x, y). `radii` is one radius for every corner, or
+    /// an array of four going clockwise from the top left.
+    pub fn draw_rounded_rect<R: Into<CornerRadii>>(&mut self, x: f32, y: f32, w: f32, h: f32, radii: R) {
+        self.dirty = true;
+        let points = self.rounded_rect_points(x, y, w, h, radii.into());
+        let points: Vec<(f32, f32)> = points.into_iter().map(|(x, y)| self.transform.apply(x, y)).collect();
+        self.stroke_polygon(&points, "draw a rounded rectangle");
+    }
+    /// Fills a rectangle with rounded corners, with its top left corner at
+    /// (x, y). `radii` is one radius for every corner, or an array of four
+    /// going clockwise from the top left.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) {
+    /// // A card with a tab sticking out of its top left.
+    /// r.set_draw_color(Color::RGB(240, 240, 240));
+    /// r.fill_rounded_rect(40.0, 60.0, 320.0, 200.0, [0.0, 16.0, 16.0, 16.0]);
+    /// r.fill_rounded_rect(40.0, 30.0, 100.0, 40.0, [12.0, 12.0, 0.0, 0.0]);
+    /// # }
+    /// ```
+    pub fn fill_rounded_rect<R: Into<CornerRadii>>(&mut self, x: f32, y: f32, w: f32, h: f32, radii: R) {
+        self.dirty = true;
+        let points = self.rounded_rect_points(x, y, w, h, radii.into());
+        let points: Vec<(f32, f32)> = points.into_iter().map(|(x, y)| self.transform.apply(x, y)).collect();
+        self.fill_polygons(&[&points], "fill a rounded rectangle");
+    }
+    /// Draws the outline of a circle centered on (x, y).
+    #[inline]
+    pub fn draw_circle(&mut self, x: f32, y: f32, radius: f32) {
+        self.draw_ellipse(x, y, radius, radius);
+    }
+    /// Fills a circle centered on (x, y).
+    #[inline]
+    pub fn fill_circle(&mut self, x: f32, y: f32, radius: f32) {
+        self.fill_ellipse(x, y, radius, radius);
+    }
+    /// Draws the outline of an ellipse centered on (x, y), with a
+    /// horizontal radius of `rx` and a vertical radius of `ry`.
+    ///
+    /// The outline covers exactly the edge pixels of `fill_ellipse` with
+    /// the same arguments, so the two can be drawn on top of each other.
+    /// Once the ellipse is rotated it is drawn as a polygon with enough
+    /// sides to look round instead.
+    pub fn draw_ellipse(&mut self, x: f32, y: f32, rx: f32, ry: f32) {
+        self.dirty = true;
+        if self.transform.axis_aligned() {
+            let (cx, cy) = self.transform.apply(x, y);
+            let (scale_x, scale_y) = self.transform.axis_scale();
+            // The spans go a pixel past the canvas, so where the ellipse is
+            // cut off isn't drawn as part of its edge.
+            let (left, top, right, bottom) = self.pixel_bounds();
+            let bounds = (left - 1, top - 1, right + 1, bottom + 1);
+            let rects = ellipse_outline(&ellipse_spans(cx, cy, rx * scale_x, ry * scale_y, bounds));
+            self.draw_call();
+            let result = self.canvas.fill_rects(&rects);
+            self.log_failure("draw an ellipse", result);
+        } else {
+            let points = self.transformed_ellipse(x, y, rx, ry);
+            self.stroke_polygon(&points, "draw an ellipse");
+        }
+    }
+    /// Fills an ellipse centered on (x, y), with a horizontal radius of `rx`
+    /// and a vertical radius of `ry`.
+    pub fn fill_ellipse(&mut self, x: f32, y: f32, rx: f32, ry: f32) {
+        self.dirty = true;
+        if self.transform.axis_aligned() {
+            let (cx, cy) = self.transform.apply(x, y);
+            let (scale_x, scale_y) = self.transform.axis_scale();
+            let spans = ellipse_spans(cx, cy, rx * scale_x, ry * scale_y, self.pixel_bounds());
+            let rects = span_rects(&spans);
+            self.draw_call();
+            let result = self.canvas.fill_rects(&rects);
+            self.log_failure("fill an ellipse", result);
+        } else {
+            let points = self.transformed_ellipse(x, y, rx, ry);
+            self.fill_polygons(&[&points], "fill an ellipse");
+        }
+    }
+    /// Draws the outline of the triangle with corners at (x1, y1), (x2, y2)
+    /// and (x3, y3).
+    pub fn draw_triangle(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x3: f32, y3: f32) {
+        self.draw_line(x1, y1, x2, y2);
+        self.draw_line(x2, y2, x3, y3);
+        self.draw_line(x3, y3, x1, y1);
+    }
+    /// Fills the triangle with corners at (x1, y1), (x2, y2) and (x3, y3).
+    pub fn fill_triangle(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x3: f32, y3: f32) {
+        self.dirty = true;
+        let transform = &self.transform;
+        let corners = [transform.apply(x1, y1), transform.apply(x2, y2), transform.apply(x3, y3)];
+        self.fill_polygons(&[&corners], "fill a triangle");
+    }
+}
@@ -0,0 +1,101 @@
+//! Writing a doodle as a type with methods instead of as closures.
+
+use {Doodle, DoodleBuilder, Event, Renderer, Result, RunOutcome};
+
+/// A doodle written as a type, as an alternative to handing closures to a
+/// DoodleBuilder.
+///
+/// The sketch is the doodle's state, so its methods can use everything it
+/// holds, like images loaded in `setup`, without sharing them between
+/// closures. Every method does nothing unless it's implemented.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// #[derive(Default)]
+/// struct Bounce {
+///     ball: Option<Image>,
+///     x: f32,
+///     speed: f32,
+/// }
+///
+/// impl Sketch for Bounce {
+///     fn setup(&mut self, r: &mut Renderer) {
+///         self.ball = r.load_image("ball.png").ok();
+///         self.speed = 200.0;
+///     }
+///     fn update(&mut self, dt: f64) {
+///         self.x += self.speed * dt as f32;
+///         if self.x < 0.0 || self.x > 760.0 {
+///             self.speed = -self.speed;
+///         }
+///     }
+///     fn draw(&mut self, r: &mut Renderer) {
+///         r.clear();
+///         if let Some(ball) = self.ball {
+///             r.draw_image(ball, self.x, 280.0);
+///         }
+///     }
+///     fn event(&mut self, _: &mut Renderer, event: &Event) {
+///         if let Event::MouseButtonDown { .. } = *event {
+///             self.speed *= 1.5;
+///         }
+///     }
+/// }
+///
+/// Doodle::from_sketch(Bounce::default()).unwrap().run().unwrap();
+/// ```
+pub trait Sketch {
+    /// Run once before the first frame, like `DoodleBuilder::setup`.
+    fn setup(&mut self, _r: &mut Renderer) {}
+    /// Run before each frame is drawn with the number of seconds since the
+    /// previous one, like `DoodleBuilder::update`.
+    fn update(&mut self, _dt: f64) {}
+    /// Run to draw each frame, like `DoodleBuilder::draw`.
+    fn draw(&mut self, _r: &mut Renderer) {}
+    /// Run for every event the doodle receives, like
+    /// `DoodleBuilder::on_event`.
+    fn event(&mut self, _r: &mut Renderer, _event: &Event) {}
+    /// Run when the doodle stops without an error, like
+    /// `DoodleBuilder::teardown`.
+    fn teardown(&mut self, _r: &mut Renderer, _outcome: RunOutcome) {}
+}
+
+impl<'a, S: Sketch> DoodleBuilder<'a, S> {
+    /// Creates a DoodleBuilder that runs a sketch, with the default
+    /// settings. The other settings can be changed before the doodle is
+    /// built, but setting one of the sketch's callbacks replaces the
+    /// sketch's method.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct Game;
+    /// # impl Sketch for Game {}
+    /// let mut doodle = DoodleBuilder::from_sketch(Game::default())
+    ///     .name("Game")
+    ///     .width(1280)
+    ///     .height(720)
+    ///     .fps(60)
+    ///     .build()
+    ///     .unwrap();
+    /// doodle.run().unwrap();
+    /// ```
+    pub fn from_sketch(sketch: S) -> Self {
+        DoodleBuilder::with_state(sketch)
+            .setup(Box::new(|sketch, r| sketch.setup(r)))
+            .update(Box::new(|sketch, dt| sketch.update(dt)))
+            .draw(Box::new(|sketch, r| sketch.draw(r)))
+            .on_event(Box::new(|sketch, r, event| sketch.event(r, event)))
+            .teardown(Box::new(|sketch, r, outcome| sketch.teardown(r, outcome)))
+    }
+}
+
+impl<S: Sketch> Doodle<S> {
+    /// Builds a doodle that runs a sketch, with the default settings. Use
+    /// `DoodleBuilder::from_sketch` to change them.
+    #[inline]
+    pub fn from_sketch(sketch: S) -> Result<Doodle<S>> {
+        DoodleBuilder::from_sketch(sketch).build()
+    }
+}
@@ -0,0 +1,315 @@
+//! Sprite sheets, texture atlases and frame by frame animation.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use sdl2::rect::{Point, Rect};
+
+use image::Image;
+use json::Json;
+use shapes::to_pixel;
+use {Error, Renderer, Result};
+
+/// An image split up into several frames, such as the tiles of a tile set
+/// or the frames of an animation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpriteSheet {
+    image: Image,
+    frames: Vec<Rect>,
+    /// How far each frame is drawn from where it's drawn at, for atlases
+    /// that trim the transparent edges off their frames.
+    offsets: Vec<Point>,
+    names: HashMap<String, usize>,
+}
+
+impl SpriteSheet {
+    /// Creates a sprite sheet from a list of frame rectangles inside `image`.
+    pub fn new(image: Image, frames: Vec<Rect>) -> Self {
+        let offsets = vec![Point::new(0, 0); frames.len()];
+        SpriteSheet {
+            image,
+            frames,
+            offsets,
+            names: HashMap::new(),
+        }
+    }
+    /// Splits an image into a grid of equally sized frames, numbered left to
+    /// right and then top to bottom. Partial frames at the right and bottom
+    /// edges are left out.
+    pub fn from_grid(renderer: &Renderer, image: Image, frame_width: u32, frame_height: u32) -> Self {
+        let (width, height) = renderer.image_size(image);
+        let mut frames = Vec::new();
+        if frame_width > 0 && frame_height > 0 {
+            for row in 0..height / frame_height {
+                for col in 0..width / frame_width {
+                    frames.push(Rect::new(
+                        (col * frame_width) as i32,
+                        (row * frame_height) as i32,
+                        frame_width,
+                        frame_height,
+                    ));
+                }
+            }
+        }
+        SpriteSheet::new(image, frames)
+    }
+    /// Loads a texture atlas saved by TexturePacker in its JSON format,
+    /// either the hash or the array variety, along with the image it names.
+    /// The image is looked for next to the JSON file.
+    ///
+    /// Frames are numbered in the order they appear in the file, and can
+    /// also be found by their names with `frame_index`. Trimmed frames are
+    /// drawn where they would be if they hadn't been trimmed. Rotated
+    /// frames aren't supported, so turn rotation off when packing.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn setup(r: &mut Renderer) -> Result<()> {
+    /// let sheet = SpriteSheet::load_texture_packer(r, "assets/hero.json")?;
+    /// let run = sheet.frames_starting_with("run");
+    /// let mut hero = AnimatedSprite::with_frames(sheet, run, 12.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_texture_packer<P: AsRef<Path>>(renderer: &mut Renderer, path: P) -> Result<SpriteSheet> {
+        let path = path.as_ref();
+        let atlas = Json::parse(&fs::read_to_string(path)?)?;
+        let image_name = atlas
+            .get("meta")
+            .and_then(|meta| meta.get("image"))
+            .and_then(Json::as_str)
+            .ok_or_else(|| atlas_error(path, "meta.image is missing"))?;
+        let named_frames: Vec<(&str, &Json)> = match atlas.get("frames") {
+            Some(Json::Object(frames)) => frames.iter().map(|f| (&f.0[..], &f.1)).collect(),
+            Some(Json::Array(frames)) => frames
+                .iter()
+                .map(|f| (f.get("filename").and_then(Json::as_str).unwrap_or(""), f))
+                .collect(),
+            _ => return Err(atlas_error(path, "frames is missing")),
+        };
+        let mut frames = Vec::with_capacity(named_frames.len());
+        let mut offsets = Vec::with_capacity(named_frames.len());
+        let mut names = HashMap::new();
+        for (name, frame) in named_frames {
+            if frame.get("rotated").and_then(Json::as_bool) == Some(true) {
+                return Err(Error::Unsupported(format!("{} is rotated in {}", name, path.display())));
+            }
+            let number = |object: &str, key: &str| {
+                frame
+                    .get(object)
+                    .and_then(|o| o.get(key))
+                    .and_then(Json::as_f64)
+                    .map(|n| n as i32)
+            };
+            let (x, y) = (number("frame", "x"), number("frame", "y"));
+            let rect = match (x, y, number("frame", "w"), number("frame", "h")) {
+                (Some(x), Some(y), Some(w), Some(h)) if w >= 0 && h >= 0 => Rect::new(x, y, w as u32, h as u32),
+                _ => return Err(atlas_error(path, &format!("{} has no frame rectangle", name))),
+            };
+            let offset = Point::new(
+                number("spriteSourceSize", "x").unwrap_or(0),
+                number("spriteSourceSize", "y").unwrap_or(0),
+            );
+            if !name.is_empty() {
+                names.insert(name.to_owned(), frames.len());
+            }
+            frames.push(rect);
+            offsets.push(offset);
+        }
+        let image_path = path.parent().unwrap_or_else(|| Path::new("")).join(image_name);
+        Ok(SpriteSheet {
+            image: renderer.load_image(image_path)?,
+            frames,
+            offsets,
+            names,
+        })
+    }
+    /// Returns the image the frames are taken from.
+    #[inline]
+    pub fn image(&self) -> Image {
+        self.image
+    }
+    /// Returns the rectangle of the frame at `index`, if there is one.
+    #[inline]
+    pub fn frame(&self, index: usize) -> Option<Rect> {
+        self.frames.get(index).cloned()
+    }
+    /// Returns how far the frame at `index` is drawn from the point it's
+    /// drawn at. This is only ever not (0, 0) for trimmed atlas frames.
+    #[inline]
+    pub fn frame_offset(&self, index: usize) -> Point {
+        self.offsets.get(index).cloned().unwrap_or_else(|| Point::new(0, 0))
+    }
+    /// Returns the number of frames in the sheet.
+    #[inline]
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+    /// Returns the index of the frame with a name, for sheets loaded from
+    /// an atlas.
+    #[inline]
+    pub fn frame_index(&self, name: &str) -> Option<usize> {
+        self.names.get(name).cloned()
+    }
+    /// Returns the indices of the frames whose names start with `prefix`,
+    /// sorted by name, which is how atlas frames like `run_01.png`,
+    /// `run_02.png` and so on are usually turned into an animation.
+    pub fn frames_starting_with(&self, prefix: &str) -> Vec<usize> {
+        let mut named: Vec<(&String, usize)> = self
+            .names
+            .iter()
+            .filter(|&(name, _)| name.starts_with(prefix))
+            .map(|(name, &index)| (name, index))
+            .collect();
+        named.sort();
+        named.into_iter().map(|(_, index)| index).collect()
+    }
+}
+
+fn atlas_error(path: &Path, message: &str) -> Error {
+    Error::Error(format!("invalid texture atlas {}: {}", path.display(), message))
+}
+
+/// An animation that steps through frames of a sprite sheet at a steady
+/// rate.
+///
+/// Call `update` every frame with the time since the last one, and draw
+/// it with `Renderer::draw_animation`.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// #[derive(Default)]
+/// struct State {
+///     coin: Option<AnimatedSprite>,
+/// }
+///
+/// let builder = DoodleBuilder::<State>::new()
+///     .setup(Box::new(|state, r| {
+///         let image = r.load_image("coin.png").unwrap();
+///         let sheet = SpriteSheet::from_grid(r, image, 16, 16);
+///         state.coin = Some(AnimatedSprite::new(sheet, 10.0));
+///     }))
+///     .update(Box::new(|state, dt| {
+///         if let Some(ref mut coin) = state.coin {
+///             coin.update(dt);
+///         }
+///     }))
+///     .draw(Box::new(|state, r| {
+///         r.clear();
+///         if let Some(ref coin) = state.coin {
+///             r.draw_animation(coin, 100.0, 100.0);
+///         }
+///     }));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimatedSprite {
+    sheet: SpriteSheet,
+    frames: Vec<usize>,
+    frame_seconds: f64,
+    elapsed: f64,
+    looping: bool,
+    playing: bool,
+}
+
+impl AnimatedSprite {
+    /// Creates an animation of every frame of a sheet, playing `fps`
+    /// frames a second over and over.
+    pub fn new(sheet: SpriteSheet, fps: f64) -> Self {
+        let frames = (0..sheet.frame_count()).collect();
+        AnimatedSprite::with_frames(sheet, frames, fps)
+    }
+    /// Creates an animation of some of the frames of a sheet, in the order
+    /// given. Frames can be repeated to hold them for longer.
+    pub fn with_frames(sheet: SpriteSheet, frames: Vec<usize>, fps: f64) -> Self {
+        AnimatedSprite {
+            sheet,
+            frames,
+            frame_seconds: 1.0 / fps.max(f64::EPSILON),
+            elapsed: 0.0,
+            looping: true,
+            playing: true,
+        }
+    }
+    /// Sets whether the animation starts over after its last frame, or
+    /// stops on it. Animations loop unless this turns it off.
+    #[inline]
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+    /// Moves the animation on by `dt` seconds, unless it's paused.
+    pub fn update(&mut self, dt: f64) {
+        if self.playing {
+            self.elapsed += dt;
+            let length = self.frame_seconds * self.frames.len() as f64;
+            if self.looping && length > 0.0 {
+                self.elapsed %= length;
+            }
+        }
+    }
+    /// Carries on after `pause`.
+    #[inline]
+    pub fn play(&mut self) {
+        self.playing = true;
+    }
+    /// Stops the animation on the frame it's on.
+    #[inline]
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+    /// Returns true unless the animation has been paused.
+    #[inline]
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+    /// Goes back to the first frame.
+    #[inline]
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+    }
+    /// Returns true if the animation doesn't loop and has reached the end
+    /// of its last frame.
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.elapsed >= self.frame_seconds * self.frames.len() as f64
+    }
+    /// Returns the index into the sheet of the frame being shown, or None
+    /// if the animation has no frames.
+    pub fn current_frame(&self) -> Option<usize> {
+        let step = (self.elapsed / self.frame_seconds) as usize;
+        self.frames.get(step.min(self.frames.len().saturating_sub(1))).cloned()
+    }
+    /// Returns the sheet the animation's frames are taken from.
+    #[inline]
+    pub fn sheet(&self) -> &SpriteSheet {
+        &self.sheet
+    }
+}
+
+impl Renderer {
+    /// Draws a frame of a sprite sheet at its own size with its top left
+    /// corner at (x, y). Frames that aren't in the sheet aren't drawn.
+    pub fn draw_sprite(&mut self, sheet: &SpriteSheet, index: usize, x: f32, y: f32) {
+        let src = match sheet.frame(index) {
+            Some(src) => src,
+            None => return,
+        };
+        let offset = sheet.frame_offset(index);
+        let dst = Rect::new(
+            to_pixel(x) + offset.x(),
+            to_pixel(y) + offset.y(),
+            src.width(),
+            src.height(),
+        );
+        let result = self.copy_image(sheet.image(), Some(src), dst);
+        self.log_failure("draw a sprite", result);
+    }
+    /// Draws the current frame of an animation with its top left corner at
+    /// (x, y).
+    pub fn draw_animation(&mut self, animation: &AnimatedSprite, x: f32, y: f32) {
+        if let Some(index) = animation.current_frame() {
+            self.draw_sprite(&animation.sheet, index, x, y);
+        }
+    }
+}
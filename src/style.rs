@@ -0,0 +1,753 @@
+//! Fill and stroke settings, and the shapes that are drawn with them.
+//!
+//! These work like shapes in Processing: each one is filled with the fill
+//! color and then outlined with the stroke color, either of which can be
+//! turned off. The `draw_*` and `fill_*` methods keep using the drawing
+//! color, so the two kinds of shapes can be mixed freely.
+
+use std::f32::consts::PI;
+
+use sdl2::pixels::Color;
+
+use path::{flatten_cubic, CURVE_SEGMENTS};
+use log::Level;
+use shapes::FillRule;
+use {CornerRadii, Renderer, Vec2};
+
+/// How many stroke weights a pointed corner can stick out before it is cut
+/// off flat.
+const MITER_LIMIT: f32 = 4.0;
+
+/// The number of points around each rounded end of a thick line.
+const CAP_POINTS: usize = 8;
+
+/// Which parts of a circle an arc covers, for `Renderer::arc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArcMode {
+    /// Just the curve is outlined. The fill still covers the part of
+    /// the circle cut off by a straight line between the ends.
+    Open,
+    /// The ends of the curve are joined with a straight line, like a
+    /// circle with a slice cut off.
+    Chord,
+    /// The ends of the curve are joined to the center, like a slice of
+    /// pie.
+    Pie,
+}
+
+/// The fill and stroke used by the shapes in this module.
+pub(crate) struct Style {
+    fill: Option<Color>,
+    stroke: Option<Color>,
+    weight: f32,
+    smooth: bool,
+    curve_detail: usize,
+    /// The vertices added since begin_shape, or None outside of a shape.
+    shape: Option<Vec<(f32, f32)>>,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            fill: Some(Color::RGB(255, 255, 255)),
+            stroke: Some(Color::RGB(0, 0, 0)),
+            weight: 1.0,
+            smooth: false,
+            curve_detail: CURVE_SEGMENTS,
+            shape: None,
+        }
+    }
+}
+
+/// Returns twice the area of a polygon, which is positive if its points go
+/// clockwise on the screen.
+fn signed_area(points: &[(f32, f32)]) -> f32 {
+    let mut area = 0.0;
+    for (i, &(x0, y0)) in points.iter().enumerate() {
+        let (x1, y1) = points[(i + 1) % points.len()];
+        area += x0 * y1 - x1 * y0;
+    }
+    area
+}
+
+/// Moves every edge of a convex polygon `distance` outwards, or inwards if
+/// it's negative, joining the edges with pointed corners unless they would
+/// stick out further than MITER_LIMIT allows.
+fn offset_polygon(points: &[(f32, f32)], distance: f32) -> Vec<(f32, f32)> {
+    let points = corners(points);
+    let count = points.len();
+    let orientation = if signed_area(&points) >= 0.0 { 1.0 } else { -1.0 };
+    // The outward normal of the edge from point i to the next one.
+    let normal = |i: usize| {
+        let ((x0, y0), (x1, y1)) = (points[i], points[(i + 1) % count]);
+        let length = (x1 - x0).hypot(y1 - y0);
+        (orientation * (y1 - y0) / length, orientation * (x0 - x1) / length)
+    };
+    let mut offset = Vec::with_capacity(count);
+    for (i, &(x, y)) in points.iter().enumerate() {
+        let (before, after) = (normal((i + count - 1) % count), normal(i));
+        let dot = before.0 * after.0 + before.1 * after.1;
+        if 1.0 + dot > 2.0 / (MITER_LIMIT * MITER_LIMIT) {
+            let miter = distance / (1.0 + dot);
+            offset.push((x + (before.0 + after.0) * miter, y + (before.1 + after.1) * miter));
+        } else {
+            offset.push((x + before.0 * distance, y + before.1 * distance));
+            offset.push((x + after.0 * distance, y + after.1 * distance));
+        }
+    }
+    offset
+}
+
+/// Returns the corners of a polygon without repeated points, or a last
+/// point that repeats the first.
+fn corners(points: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let mut points = points.to_vec();
+    points.dedup();
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    points
+}
+
+/// Returns the sine of the angle the edges turn through at corner i, which
+/// has the same sign as the polygon's area if the corner bulges outwards.
+fn turn(points: &[(f32, f32)], i: usize) -> f32 {
+    let count = points.len();
+    let (x0, y0) = points[(i + count - 1) % count];
+    let (x1, y1) = points[i];
+    let (x2, y2) = points[(i + 1) % count];
+    let lengths = (x1 - x0).hypot(y1 - y0) * (x2 - x1).hypot(y2 - y1);
+    ((x1 - x0) * (y2 - y1) - (y1 - y0) * (x2 - x1)) / lengths
+}
+
+/// Returns whether none of a polygon's corners point inwards. Corners that
+/// are straight apart from rounding errors count as convex.
+fn is_convex(points: &[(f32, f32)]) -> bool {
+    let points = corners(points);
+    let orientation = signed_area(&points).signum();
+    (0..points.len()).all(|i| turn(&points, i) * orientation > -1e-4)
+}
+
+/// Returns the pieces of a thick outline around any polygon, one for each
+/// edge and one for the outside of each corner, all going clockwise so
+/// they can be filled together with the non-zero rule.
+fn outline_pieces(points: &[(f32, f32)], half: f32) -> Vec<Vec<(f32, f32)>> {
+    let points = corners(points);
+    let count = points.len();
+    // The normal of the edge from point i to the next one, half long.
+    let normal = |i: usize| {
+        let ((x0, y0), (x1, y1)) = (points[i], points[(i + 1) % count]);
+        let length = (x1 - x0).hypot(y1 - y0);
+        ((y0 - y1) / length * half, (x1 - x0) / length * half)
+    };
+    let mut pieces = Vec::with_capacity(2 * count);
+    for (i, &(x0, y0)) in points.iter().enumerate() {
+        let (x1, y1) = points[(i + 1) % count];
+        let (nx, ny) = normal(i);
+        pieces.push(vec![(x0 + nx, y0 + ny), (x1 + nx, y1 + ny), (x1 - nx, y1 - ny), (x0 - nx, y0 - ny)]);
+    }
+    for (i, &(x, y)) in points.iter().enumerate() {
+        let turn = turn(&points, i);
+        if turn == 0.0 {
+            continue;
+        }
+        // The corner sticks out on the side the outline turns away from.
+        let side = -turn.signum();
+        let (before, after) = (normal((i + count - 1) % count), normal(i));
+        let (before, after) = ((before.0 * side, before.1 * side), (after.0 * side, after.1 * side));
+        let dot = (before.0 * after.0 + before.1 * after.1) / (half * half);
+        let mut piece = vec![(x, y), (x + before.0, y + before.1)];
+        if 1.0 + dot > 2.0 / (MITER_LIMIT * MITER_LIMIT) {
+            let miter = 1.0 / (1.0 + dot);
+            piece.push((x + (before.0 + after.0) * miter, y + (before.1 + after.1) * miter));
+        }
+        piece.push((x + after.0, y + after.1));
+        pieces.push(piece);
+    }
+    for piece in &mut pieces {
+        if signed_area(piece) < 0.0 {
+            piece.reverse();
+        }
+    }
+    pieces
+}
+
+impl Renderer {
+    /// Sets the color shapes are filled with.
+    #[inline]
+    pub fn fill(&mut self, color: Color) {
+        self.style.fill = Some(color);
+    }
+    /// Stops shapes from being filled, so only their outlines are drawn.
+    #[inline]
+    pub fn no_fill(&mut self) {
+        self.style.fill = None;
+    }
+    /// Sets the color of lines, points and the outlines of shapes.
+    #[inline]
+    pub fn stroke(&mut self, color: Color) {
+        self.style.stroke = Some(color);
+    }
+    /// Stops lines, points and outlines from being drawn.
+    #[inline]
+    pub fn no_stroke(&mut self) {
+        self.style.stroke = None;
+    }
+    /// Sets how many pixels thick lines, points and outlines are. Outlines
+    /// are centered on the edges of shapes, and the thickness is scaled
+    /// along with everything else by `scale`.
+    #[inline]
+    pub fn stroke_weight(&mut self, weight: f32) {
+        self.style.weight = weight.max(0.0);
+    }
+    /// Sets how many straight lines each piece of a curve drawn with
+    /// `bezier` or `curve` is made of. The default is `CURVE_SEGMENTS`; big
+    /// curves may need more to look smooth.
+    #[inline]
+    pub fn curve_detail(&mut self, segments: usize) {
+        self.style.curve_detail = segments.max(1);
+    }
+    /// Turns on smoothing, so the edges of shapes and lines drawn with the
+    /// fill and stroke styles blend into what's behind them instead of
+    /// looking like staircases. Pixels on the edges are drawn see-through
+    /// by how much of them the shape covers, so this needs the `Blend`
+    /// blend mode, which is the default.
+    ///
+    /// Smoothing is off by default, because it's slower and makes shapes
+    /// less crisp when they line up with the pixels. The `draw_*` and
+    /// `fill_*` methods are never smoothed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) {
+    /// r.clear();
+    /// r.smooth();
+    /// r.stroke_weight(1.5);
+    /// for i in 0..12 {
+    ///     let end = Vec2::new(200.0, 200.0) + Vec2::from_angle(i as f32 * 0.52) * 150.0;
+    ///     r.line(200.0, 200.0, end.x, end.y);
+    /// }
+    /// # }
+    /// ```
+    #[inline]
+    pub fn smooth(&mut self) {
+        self.style.smooth = true;
+    }
+    /// Turns smoothing off again, so every pixel of a shape is either
+    /// drawn or not.
+    #[inline]
+    pub fn no_smooth(&mut self) {
+        self.style.smooth = false;
+    }
+    /// Returns true if strokes are thin enough to be drawn as plain lines.
+    /// Smooth strokes never are.
+    fn hairline(&self) -> bool {
+        let (scale_x, scale_y) = self.transform.axis_scale();
+        !self.style.smooth && self.style.weight * scale_x.max(scale_y) <= 1.0
+    }
+    /// Returns half the stroke weight. Smooth strokes are at least a pixel
+    /// wide, like plain ones, so thin lines don't fade away.
+    fn stroke_half(&self) -> f32 {
+        let weight = if self.style.smooth {
+            let (scale_x, scale_y) = self.transform.axis_scale();
+            self.style.weight.max(1.0 / scale_x.min(scale_y).max(f32::EPSILON))
+        } else {
+            self.style.weight
+        };
+        weight / 2.0
+    }
+    /// Runs `draw` with the drawing color set to `color`, if there is one.
+    fn with_color<F: FnOnce(&mut Renderer)>(&mut self, color: Option<Color>, draw: F) {
+        if let Some(color) = color {
+            let old = self.canvas.draw_color();
+            self.canvas.set_draw_color(color);
+            draw(self);
+            self.canvas.set_draw_color(old);
+        }
+    }
+    /// Fills polygons given in drawing coordinates with the fill color, if
+    /// there is one, and the even-odd rule.
+    pub(crate) fn fill_styled(&mut self, polygons: &[Vec<(f32, f32)>], what: &str) {
+        let fill = self.style.fill;
+        self.with_color(fill, |r| r.fill_shape(polygons, what));
+    }
+    /// Fills polygons given in drawing coordinates with the even-odd rule.
+    #[inline]
+    fn fill_shape(&mut self, polygons: &[Vec<(f32, f32)>], what: &str) {
+        self.fill_contours(polygons, FillRule::EvenOdd, what);
+    }
+    /// Fills polygons given in drawing coordinates, smoothly if smoothing
+    /// is on.
+    fn fill_contours(&mut self, polygons: &[Vec<(f32, f32)>], rule: FillRule, what: &str) {
+        self.dirty = true;
+        let transformed: Vec<Vec<(f32, f32)>> = polygons
+            .iter()
+            .map(|points| points.iter().map(|&(x, y)| self.transform.apply(x, y)).collect())
+            .collect();
+        let contours: Vec<&[(f32, f32)]> = transformed.iter().map(|points| &points[..]).collect();
+        let smooth = self.style.smooth;
+        self.fill_polygons_with(&contours, rule, smooth, what);
+    }
+    /// Draws the outline of a polygon with the stroke weight. Polygons with
+    /// corners that point inwards, like a pie slice bigger than half a
+    /// circle, are outlined edge by edge.
+    fn stroke_thick(&mut self, points: &[(f32, f32)], what: &str) {
+        let half = self.stroke_half();
+        if !is_convex(points) {
+            let pieces = outline_pieces(points, half);
+            self.fill_contours(&pieces, FillRule::NonZero, what);
+            return;
+        }
+        let outer = offset_polygon(points, half);
+        let inner = offset_polygon(points, -half);
+        // An inner edge that turned inside out means the outline is thick
+        // enough to cover the whole shape.
+        if signed_area(&inner) * signed_area(points) > 0.0 {
+            self.fill_shape(&[outer, inner], what);
+        } else {
+            self.fill_shape(&[outer], what);
+        }
+    }
+    /// Draws a point with the stroke color. Thick points are round.
+    pub fn point(&mut self, x: f32, y: f32) {
+        let stroke = self.style.stroke;
+        self.with_color(stroke, |r| {
+            if r.hairline() {
+                r.draw_point(x, y);
+            } else {
+                let radius = r.stroke_half();
+                let points = r.ellipse_points(x, y, radius, radius);
+                r.fill_shape(&[points], "draw a point");
+            }
+        });
+    }
+    /// Draws a line from (x1, y1) to (x2, y2) with the stroke color. Thick
+    /// lines have round ends.
+    pub fn line(&mut self, x1: f32, y1: f32, x2: f32, y2: f32) {
+        let stroke = self.style.stroke;
+        self.with_color(stroke, |r| {
+            if r.hairline() {
+                r.draw_line(x1, y1, x2, y2);
+                return;
+            }
+            let radius = r.stroke_half();
+            let direction = (y2 - y1).atan2(x2 - x1);
+            let mut points = Vec::with_capacity(2 * (CAP_POINTS + 1));
+            for &(x, y, start) in &[(x2, y2, direction - PI / 2.0), (x1, y1, direction + PI / 2.0)] {
+                for i in 0..=CAP_POINTS {
+                    let angle = start + i as f32 / CAP_POINTS as f32 * PI;
+                    points.push((x + radius * angle.cos(), y + radius * angle.sin()));
+                }
+            }
+            r.fill_shape(&[points], "draw a line");
+        });
+    }
+    /// Draws a rectangle with its top left corner at (x, y), filled with the
+    /// fill color and outlined with the stroke color.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) {
+    /// r.fill(Color::RGB(255, 200, 0));
+    /// r.stroke(Color::RGB(120, 60, 0));
+    /// r.stroke_weight(4.0);
+    /// r.rect(20.0, 20.0, 200.0, 100.0);
+    /// r.no_fill();
+    /// r.circle(300.0, 70.0, 50.0);
+    /// # }
+    /// ```
+    pub fn rect(&mut self, x: f32, y: f32, w: f32, h: f32) {
+        let (fill, stroke) = (self.style.fill, self.style.stroke);
+        self.with_color(fill, |r| {
+            if r.style.smooth {
+                r.fill_shape(&[vec![(x, y), (x + w, y), (x + w, y + h), (x, y + h)]], "fill a rectangle");
+            } else {
+                r.fill_rect(x, y, w, h);
+            }
+        });
+        self.with_color(stroke, |r| {
+            if r.hairline() {
+                r.draw_rect(x, y, w, h);
+            } else {
+                let corners = [(x, y), (x + w, y), (x + w, y + h), (x, y + h)];
+                r.stroke_thick(&corners, "draw a rectangle");
+            }
+        });
+    }
+    /// Draws a rectangle with rounded corners, with its top left corner at
+    /// (x, y), filled with the fill color and outlined with the stroke
+    /// color. `radii` is one radius for every corner, or an array of four
+    /// going clockwise from the top left.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) {
+    /// // A button that is only rounded on its right side.
+    /// r.fill(Color::RGB(60, 120, 220));
+    /// r.stroke(Color::RGB(255, 255, 255));
+    /// r.stroke_weight(2.0);
+    /// r.rounded_rect(20.0, 20.0, 160.0, 48.0, [0.0, 24.0, 24.0, 0.0]);
+    /// # }
+    /// ```
+    pub fn rounded_rect<R: Into<CornerRadii>>(&mut self, x: f32, y: f32, w: f32, h: f32, radii: R) {
+        let points = self.rounded_rect_points(x, y, w, h, radii.into());
+        let (fill, stroke) = (self.style.fill, self.style.stroke);
+        self.with_color(fill, |r| r.fill_shape(::std::slice::from_ref(&points), "fill a rounded rectangle"));
+        self.with_color(stroke, |r| {
+            if r.hairline() {
+                let points: Vec<(f32, f32)> = points.iter().map(|&(x, y)| r.transform.apply(x, y)).collect();
+                r.dirty = true;
+                r.stroke_polygon(&points, "draw a rounded rectangle");
+            } else {
+                r.stroke_thick(&points, "draw a rounded rectangle");
+            }
+        });
+    }
+    /// Draws an ellipse centered on (x, y) with radii `rx` and `ry`, filled
+    /// with the fill color and outlined with the stroke color.
+    pub fn ellipse(&mut self, x: f32, y: f32, rx: f32, ry: f32) {
+        let (fill, stroke) = (self.style.fill, self.style.stroke);
+        self.with_color(fill, |r| {
+            if r.style.smooth {
+                let points = r.ellipse_points(x, y, rx, ry);
+                r.fill_shape(&[points], "fill an ellipse");
+            } else {
+                r.fill_ellipse(x, y, rx, ry);
+            }
+        });
+        self.with_color(stroke, |r| {
+            if r.hairline() {
+                r.draw_ellipse(x, y, rx, ry);
+            } else {
+                let points = r.ellipse_points(x, y, rx, ry);
+                r.stroke_thick(&points, "draw an ellipse");
+            }
+        });
+    }
+    /// Draws a circle centered on (x, y), filled with the fill color and
+    /// outlined with the stroke color.
+    #[inline]
+    pub fn circle(&mut self, x: f32, y: f32, radius: f32) {
+        self.ellipse(x, y, radius, radius);
+    }
+    /// Draws part of the circle centered on (x, y), going clockwise from
+    /// `start` to `end` radians, where 0 is to the right of the center. It's
+    /// filled with the fill color and outlined with the stroke color.
+    ///
+    /// Nothing is drawn if `end` is before `start`, and arcs of more than
+    /// a whole turn are drawn as a whole circle.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # use std::f32::consts::PI;
+    /// # fn draw(r: &mut Renderer, progress: f32) {
+    /// // A progress ring that fills up clockwise from the top.
+    /// r.no_fill();
+    /// r.stroke_weight(12.0);
+    /// r.stroke(Color::RGB(60, 60, 60));
+    /// r.circle(200.0, 200.0, 80.0);
+    /// r.stroke(Color::RGB(80, 200, 120));
+    /// r.arc(200.0, 200.0, 80.0, -PI / 2.0, -PI / 2.0 + progress * 2.0 * PI, ArcMode::Open);
+    /// # }
+    /// ```
+    pub fn arc(&mut self, x: f32, y: f32, radius: f32, start: f32, end: f32, mode: ArcMode) {
+        if start.is_nan() || end.is_nan() || end < start {
+            return;
+        }
+        if end - start >= 2.0 * PI {
+            self.circle(x, y, radius);
+            return;
+        }
+        let curve = self.arc_points(x, y, radius, start, end);
+        let mut shape = curve.clone();
+        if mode == ArcMode::Pie {
+            shape.push((x, y));
+        }
+        let (fill, stroke) = (self.style.fill, self.style.stroke);
+        self.with_color(fill, |r| r.fill_shape(&[shape.clone()], "fill an arc"));
+        self.with_color(stroke, |r| {
+            if r.hairline() {
+                let points: Vec<(f32, f32)> = if mode == ArcMode::Open { &curve } else { &shape }
+                    .iter()
+                    .map(|&(x, y)| r.transform.apply(x, y))
+                    .collect();
+                r.dirty = true;
+                if mode == ArcMode::Open {
+                    r.stroke_polyline(&points, "draw an arc");
+                } else {
+                    r.stroke_polygon(&points, "draw an arc");
+                }
+            } else if mode == ArcMode::Open {
+                let band = r.arc_band(x, y, radius, start, end);
+                r.fill_shape(&[band], "draw an arc");
+            } else {
+                r.stroke_thick(&shape, "draw an arc");
+            }
+        });
+    }
+    /// Returns the outline of a thick arc with round ends.
+    fn arc_band(&self, x: f32, y: f32, radius: f32, start: f32, end: f32) -> Vec<(f32, f32)> {
+        let half = self.stroke_half();
+        let mut band = self.arc_points(x, y, radius + half, start, end);
+        let cap = |angle: f32, from: f32| {
+            let (cx, cy) = (x + radius * angle.cos(), y + radius * angle.sin());
+            (1..CAP_POINTS).map(move |i| {
+                let angle = from + i as f32 / CAP_POINTS as f32 * PI;
+                (cx + half * angle.cos(), cy + half * angle.sin())
+            })
+        };
+        band.extend(cap(end, end));
+        let mut inner = self.arc_points(x, y, (radius - half).max(0.0), start, end);
+        inner.reverse();
+        band.extend(inner);
+        band.extend(cap(start, start + PI));
+        band
+    }
+    /// Draws the triangle with corners at (x1, y1), (x2, y2) and (x3, y3),
+    /// filled with the fill color and outlined with the stroke color.
+    pub fn triangle(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x3: f32, y3: f32) {
+        let (fill, stroke) = (self.style.fill, self.style.stroke);
+        self.with_color(fill, |r| {
+            if r.style.smooth {
+                r.fill_shape(&[vec![(x1, y1), (x2, y2), (x3, y3)]], "fill a triangle");
+            } else {
+                r.fill_triangle(x1, y1, x2, y2, x3, y3);
+            }
+        });
+        self.with_color(stroke, |r| {
+            if r.hairline() {
+                r.draw_triangle(x1, y1, x2, y2, x3, y3);
+            } else {
+                r.stroke_thick(&[(x1, y1), (x2, y2), (x3, y3)], "draw a triangle");
+            }
+        });
+    }
+    /// Draws a cubic Bezier curve from `p0` to `p1` with the stroke color.
+    /// The curve starts out heading towards `c0` and arrives at `p1` from
+    /// the direction of `c1`, without going through either of them.
+    ///
+    /// Curves are only outlined, never filled.
+    pub fn bezier(&mut self, p0: Vec2, c0: Vec2, c1: Vec2, p1: Vec2) {
+        let mut points = vec![(p0.x, p0.y)];
+        points.extend(flatten_cubic(
+            (p0.x, p0.y),
+            (c0.x, c0.y),
+            (c1.x, c1.y),
+            (p1.x, p1.y),
+            self.style.curve_detail,
+        ));
+        self.stroke_open(&points, "draw a curve");
+    }
+    /// Draws a smooth Catmull-Rom curve that goes through all of the points,
+    /// with the stroke color.
+    ///
+    /// Curves are only outlined, never filled.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) {
+    /// // A wavy line through points that drift with noise.
+    /// let t = r.elapsed_seconds() as f32;
+    /// let points: Vec<Vec2> = (0..8)
+    ///     .map(|i| {
+    ///         let wobble = r.noise().perlin2(i as f32 * 0.7, t);
+    ///         Vec2::new(50.0 + i as f32 * 60.0, 200.0 + wobble * 100.0)
+    ///     })
+    ///     .collect();
+    /// r.clear();
+    /// r.stroke_weight(3.0);
+    /// r.curve(&points);
+    /// # }
+    /// ```
+    pub fn curve(&mut self, points: &[Vec2]) {
+        let (first, last) = match (points.first(), points.last()) {
+            (Some(&first), Some(&last)) => (first, last),
+            _ => return,
+        };
+        let mut flattened = vec![(first.x, first.y)];
+        for i in 0..points.len() - 1 {
+            // The curve is given a tangent at every point from its two
+            // neighbours, with the ends treated as their own neighbours.
+            let p0 = if i == 0 { first } else { points[i - 1] };
+            let (p1, p2) = (points[i], points[i + 1]);
+            let p3 = points.get(i + 2).cloned().unwrap_or(last);
+            let c0 = p1 + (p2 - p0) / 6.0;
+            let c1 = p2 - (p3 - p1) / 6.0;
+            flattened.extend(flatten_cubic(
+                (p1.x, p1.y),
+                (c0.x, c0.y),
+                (c1.x, c1.y),
+                (p2.x, p2.y),
+                self.style.curve_detail,
+            ));
+        }
+        self.stroke_open(&flattened, "draw a curve");
+    }
+    /// Draws lines joining points given in drawing coordinates with the
+    /// stroke style, without joining the last one to the first.
+    ///
+    /// Thick lines are drawn as one shape, a band around each line with a
+    /// circle at every point for round joins and ends, so see-through
+    /// strokes don't get darker where the lines overlap.
+    pub(crate) fn stroke_open(&mut self, points: &[(f32, f32)], what: &str) {
+        let stroke = self.style.stroke;
+        if stroke.is_none() || points.is_empty() {
+            return;
+        }
+        if self.hairline() {
+            self.with_color(stroke, |r| {
+                let points: Vec<(f32, f32)> = points.iter().map(|&(x, y)| r.transform.apply(x, y)).collect();
+                r.dirty = true;
+                r.stroke_polyline(&points, what);
+            });
+            return;
+        }
+        let half = self.stroke_half();
+        let mut pieces = Vec::with_capacity(2 * points.len());
+        for pair in points.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            let length = (x1 - x0).hypot(y1 - y0);
+            if length > 0.0 {
+                let (nx, ny) = ((y0 - y1) / length * half, (x1 - x0) / length * half);
+                pieces.push(vec![(x0 + nx, y0 + ny), (x1 + nx, y1 + ny), (x1 - nx, y1 - ny), (x0 - nx, y0 - ny)]);
+            }
+        }
+        for &(x, y) in points {
+            pieces.push(self.ellipse_points(x, y, half, half));
+        }
+        // The pieces only join together if they all go around the same way.
+        for piece in &mut pieces {
+            if signed_area(piece) < 0.0 {
+                piece.reverse();
+            }
+        }
+        self.with_color(stroke, |r| r.fill_contours(&pieces, FillRule::NonZero, what));
+    }
+    /// Starts a shape made of any number of corners, which are added with
+    /// `vertex` and drawn by `end_shape`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) {
+    /// // A five-pointed star, which has corners that point inwards.
+    /// r.fill(Color::RGB(255, 220, 0));
+    /// r.begin_shape();
+    /// for i in 0..10 {
+    ///     let radius = if i % 2 == 0 { 100.0 } else { 40.0 };
+    ///     let direction = Vec2::from_angle(math::radians(i as f32 * 36.0 - 90.0));
+    ///     let point = Vec2::new(200.0, 200.0) + direction * radius;
+    ///     r.vertex(point.x, point.y);
+    /// }
+    /// r.end_shape(true);
+    /// # }
+    /// ```
+    pub fn begin_shape(&mut self) {
+        if self.style.shape.is_some() {
+            self.logger
+                .log(Level::Warn, || "begin_shape was called again before end_shape".to_owned());
+        }
+        self.style.shape = Some(Vec::new());
+    }
+    /// Adds a corner to the shape started by `begin_shape`. Corners are
+    /// transformed by the transformation that is set when `end_shape` is
+    /// called.
+    pub fn vertex(&mut self, x: f32, y: f32) {
+        match self.style.shape {
+            Some(ref mut shape) => shape.push((x, y)),
+            None => self
+                .logger
+                .log(Level::Warn, || "vertex was called outside of begin_shape and end_shape".to_owned()),
+        }
+    }
+    /// Draws the shape started by `begin_shape`, filled with the fill color
+    /// and outlined with the stroke color. If `close` is true, the outline
+    /// goes from the last corner back to the first.
+    ///
+    /// The shape can have corners that point inwards and edges that cross.
+    /// Where edges cross, the parts of the shape that are inside an odd
+    /// number of times are filled.
+    pub fn end_shape(&mut self, close: bool) {
+        let mut points = match self.style.shape.take() {
+            Some(points) => points,
+            None => {
+                self.logger
+                    .log(Level::Warn, || "end_shape was called without begin_shape".to_owned());
+                return;
+            }
+        };
+        if points.len() >= 3 {
+            self.fill_styled(&[points.clone()], "fill a shape");
+        }
+        if close && points.len() >= 3 {
+            let first = points[0];
+            points.push(first);
+        }
+        self.stroke_open(&points, "draw a shape");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geometry::polygon_contains_point;
+
+    /// A pie slice around (0, 0) with a radius of 100, going clockwise on
+    /// the screen from `start` to `end`.
+    fn pie(start: f32, end: f32) -> Vec<(f32, f32)> {
+        let mut points: Vec<(f32, f32)> = (0..=32)
+            .map(|i| {
+                let angle = start + (end - start) * i as f32 / 32.0;
+                (100.0 * angle.cos(), 100.0 * angle.sin())
+            })
+            .collect();
+        points.push((0.0, 0.0));
+        points
+    }
+
+    fn covered(pieces: &[Vec<(f32, f32)>], x: f32, y: f32) -> bool {
+        pieces.iter().any(|piece| {
+            let piece: Vec<Vec2> = piece.iter().map(|&(x, y)| Vec2::new(x, y)).collect();
+            polygon_contains_point(&piece, Vec2::new(x, y))
+        })
+    }
+
+    #[test]
+    fn only_pies_over_half_a_circle_are_concave() {
+        assert!(is_convex(&pie(0.0, PI / 2.0)));
+        assert!(is_convex(&pie(0.0, PI)));
+        assert!(!is_convex(&pie(0.0, 1.5 * PI)));
+        assert!(is_convex(&[(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0), (0.0, 0.0)]));
+    }
+
+    #[test]
+    fn big_pies_are_outlined_edge_by_edge() {
+        let pieces = outline_pieces(&pie(0.0, 1.5 * PI), 5.0);
+        assert!(pieces.iter().all(|piece| signed_area(piece) > 0.0));
+        // Along the straight edges and the curve.
+        assert!(covered(&pieces, 50.0, 4.0));
+        assert!(covered(&pieces, 4.0, -50.0));
+        assert!(covered(&pieces, -70.0, 73.0));
+        // Both sides of the corner at the center, which points inwards.
+        assert!(covered(&pieces, -4.0, 4.0));
+        assert!(covered(&pieces, 4.0, -4.0));
+        // The middle of the slice, the quarter that was cut out and outside.
+        assert!(!covered(&pieces, -40.0, 40.0));
+        assert!(!covered(&pieces, 40.0, -40.0));
+        assert!(!covered(&pieces, 200.0, 0.0));
+    }
+
+    #[test]
+    fn sharp_corners_are_cut_off() {
+        // A long thin spike pointing right, with a corner much sharper than
+        // the miter limit allows.
+        let pieces = outline_pieces(&[(0.0, -1.0), (100.0, 0.0), (0.0, 1.0), (50.0, 0.0)], 2.0);
+        assert!(covered(&pieces, 99.0, 0.0));
+        assert!(!covered(&pieces, 102.0, 0.0));
+    }
+}
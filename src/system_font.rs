@@ -0,0 +1,155 @@
+//! Finding fonts that are installed on the system.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use {Error, Font, Renderer, Result, TextStyle};
+
+/// Font files found for the generic family names, in order of preference.
+const SANS: &[&str] = &[
+    "dejavusans", "liberationsans", "notosans", "ubuntu", "freesans", "arial", "helvetica", "segoeui",
+];
+const SERIF: &[&str] = &[
+    "dejavuserif", "liberationserif", "notoserif", "freeserif", "times", "timesnewroman", "georgia",
+];
+const MONO: &[&str] = &[
+    "dejavusansmono", "liberationmono", "notosansmono", "ubuntumono", "freemono", "menlo", "monaco",
+    "consola", "consolas", "cour", "couriernew",
+];
+
+/// The endings font files use for each style, in order of preference.
+const REGULAR: &[&str] = &["", "regular", "book", "roman", "r"];
+const BOLD: &[&str] = &["bold", "bd", "b"];
+const ITALIC: &[&str] = &["italic", "oblique", "it", "i"];
+const BOLD_ITALIC: &[&str] = &["bolditalic", "boldoblique", "bi", "z"];
+
+/// Returns the directories fonts are installed in on this platform.
+fn font_dirs() -> Vec<PathBuf> {
+    let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).map(PathBuf::from);
+    let mut dirs = Vec::new();
+    if cfg!(target_os = "windows") {
+        let windir = env::var_os("WINDIR").unwrap_or_else(|| "C:\\Windows".into());
+        dirs.push(Path::new(&windir).join("Fonts"));
+        if let Some(local) = env::var_os("LOCALAPPDATA") {
+            dirs.push(Path::new(&local).join("Microsoft").join("Windows").join("Fonts"));
+        }
+    } else if cfg!(target_os = "macos") {
+        dirs.push(PathBuf::from("/System/Library/Fonts"));
+        dirs.push(PathBuf::from("/Library/Fonts"));
+        if let Some(ref home) = home {
+            dirs.push(home.join("Library").join("Fonts"));
+        }
+    } else {
+        // The default directories of fontconfig.
+        dirs.push(PathBuf::from("/usr/share/fonts"));
+        dirs.push(PathBuf::from("/usr/local/share/fonts"));
+        match env::var_os("XDG_DATA_HOME") {
+            Some(data) => dirs.push(Path::new(&data).join("fonts")),
+            None => {
+                if let Some(ref home) = home {
+                    dirs.push(home.join(".local").join("share").join("fonts"));
+                }
+            }
+        }
+        if let Some(ref home) = home {
+            dirs.push(home.join(".fonts"));
+        }
+    }
+    dirs
+}
+
+/// Lowercases a name and removes spaces, dashes and underscores from it,
+/// so "DejaVu Sans", "DejaVuSans" and "dejavu_sans" are the same.
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Adds every font file under `dir` to `fonts`, by normalized file stem.
+/// The first file found with each stem wins.
+fn collect_fonts(dir: &Path, fonts: &mut HashMap<String, PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_fonts(&path, fonts);
+            continue;
+        }
+        let is_font = match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) => ["ttf", "otf", "ttc"].contains(&ext.to_lowercase().as_str()),
+            None => false,
+        };
+        if let (true, Some(stem)) = (is_font, path.file_stem().and_then(|s| s.to_str())) {
+            fonts.entry(normalize(stem)).or_insert_with(|| path.clone());
+        }
+    }
+}
+
+/// Finds the file of an installed font by its family name, like "DejaVu
+/// Sans" or "Arial". The generic names "sans", "serif" and "mono" find
+/// whichever common font of that kind is installed.
+///
+/// The standard font directories of the platform are searched, and fonts
+/// are matched by their file names, ignoring case, spaces and dashes. This
+/// isn't as thorough as fontconfig, but it finds the common fonts on
+/// Linux, macOS and Windows. If no font matches, the error lists the
+/// directories that were searched.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// let regular = doodle::find_system_font("mono", TextStyle::default()).unwrap();
+/// let bold = doodle::find_system_font("DejaVu Sans", TextStyle { bold: true, italic: false }).unwrap();
+/// # let _ = (regular, bold);
+/// ```
+pub fn find_system_font(family: &str, style: TextStyle) -> Result<PathBuf> {
+    let dirs = font_dirs();
+    let mut fonts = HashMap::new();
+    for dir in &dirs {
+        collect_fonts(dir, &mut fonts);
+    }
+    let normalized = normalize(family);
+    let families: Vec<&str> = match normalized.as_str() {
+        "sans" | "sansserif" => SANS.to_vec(),
+        "serif" => SERIF.to_vec(),
+        "mono" | "monospace" => MONO.to_vec(),
+        family => vec![family],
+    };
+    let endings = match (style.bold, style.italic) {
+        (false, false) => REGULAR,
+        (true, false) => BOLD,
+        (false, true) => ITALIC,
+        (true, true) => BOLD_ITALIC,
+    };
+    for name in families {
+        for ending in endings {
+            if let Some(path) = fonts.get(&format!("{}{}", name, ending)) {
+                return Ok(path.clone());
+            }
+        }
+    }
+    let searched: Vec<String> = dirs.iter().map(|d| d.display().to_string()).collect();
+    Err(Error::Error(format!(
+        "couldn't find a{}{} font named {:?} in {}",
+        if style.bold { " bold" } else { "" },
+        if style.italic { " italic" } else { "" },
+        family,
+        searched.join(", ")
+    )))
+}
+
+impl Renderer {
+    /// Loads the regular style of an installed font at the given point size.
+    /// See `find_system_font` for how fonts are found.
+    pub fn load_system_font(&mut self, family: &str, size: u16) -> Result<Font> {
+        let path = find_system_font(family, TextStyle::default())?;
+        self.load_font(path, size)
+    }
+}
@@ -0,0 +1,238 @@
+//! Helpers for testing drawing code by rendering frames and comparing them
+//! with images that are known to be right.
+//!
+//! Frames are rendered by headless doodles, so tests can run where there's
+//! no display. Comparisons that fail save what was drawn along with an
+//! image of the differences, and setting the `DOODLE_BLESS` environment
+//! variable to 1 saves the frames as the new expected images instead.
+//!
+//! # Example
+//! ```no_run
+//! # use doodle::*;
+//! # use doodle::testing::*;
+//! #[derive(Default)]
+//! struct Flag;
+//!
+//! impl Sketch for Flag {
+//!     fn draw(&mut self, r: &mut Renderer) {
+//!         r.clear();
+//!         r.set_draw_color(Color::RGB(200, 16, 46));
+//!         r.fill_rect(0.0, 0.0, 800.0, 200.0);
+//!     }
+//! }
+//!
+//! // In a test:
+//! let frame = render_frame_to_image(Flag::default(), 1).unwrap();
+//! assert_image_matches(&frame, "tests/golden/flag.png", Tolerance::new(2, 0.001)).unwrap();
+//! ```
+
+use std::cell::RefCell;
+use std::mem;
+use std::path::Path;
+use std::rc::Rc;
+
+use capture::Frame;
+use golden;
+use {Callback, Color, DoodleBuilder, Error, PixelBuffer, Result, RunOutcome, Sketch};
+
+/// How different an image can be from the expected one and still match.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// How far each of a pixel's red, green and blue can be from the
+    /// expected pixel's before the pixel counts as different.
+    pub channel: u8,
+    /// The fraction of pixels, from 0 to 1, that can be different.
+    pub fraction: f64,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        Tolerance::exact()
+    }
+}
+
+impl Tolerance {
+    /// A tolerance that only matches identical images.
+    #[inline]
+    pub fn exact() -> Tolerance {
+        Tolerance::new(0, 0.0)
+    }
+    /// Creates a tolerance that lets each channel be `channel` away from
+    /// the expected value, and `fraction` of the pixels be further away.
+    /// A little of both absorbs the differences in antialiasing between
+    /// graphics drivers.
+    #[inline]
+    pub fn new(channel: u8, fraction: f64) -> Tolerance {
+        Tolerance { channel, fraction }
+    }
+    /// Returns how many of `pixels` pixels can be different.
+    pub(crate) fn allowed(self, pixels: u32) -> usize {
+        (self.fraction.clamp(0.0, 1.0) * f64::from(pixels)) as usize
+    }
+}
+
+/// Runs a sketch in a headless doodle of the default size, and returns what
+/// it drew on frame `frame`, counting from 1 for the first frame.
+///
+/// Time moves on by exactly one frame at a time in headless doodles, so the
+/// result doesn't depend on how fast the computer is.
+pub fn render_frame_to_image<S: Sketch + 'static>(sketch: S, frame: u64) -> Result<PixelBuffer> {
+    render_frame(DoodleBuilder::from_sketch(sketch), frame)
+}
+
+/// Builds a headless doodle from a builder, and returns what it drew on
+/// frame `frame`, counting from 1. This is `render_frame_to_image` for
+/// doodles made of closures, or sketches that need other settings, like
+/// their size.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// # use doodle::testing::*;
+/// # #[derive(Default)] struct Game;
+/// # impl Sketch for Game {}
+/// let builder = DoodleBuilder::from_sketch(Game::default()).width(320).height(240).seed(7);
+/// let frame = render_frame(builder, 30).unwrap();
+/// assert_eq!(frame.get(0, 0), Some(Color::RGB(0, 0, 0)));
+/// ```
+pub fn render_frame<T: 'static>(mut builder: DoodleBuilder<T>, frame: u64) -> Result<PixelBuffer> {
+    if frame == 0 {
+        return Err(Error::Error("frames are counted from 1".to_owned()));
+    }
+    let captured = Rc::new(RefCell::new(None));
+    let slot = captured.clone();
+    let mut draw = mem::replace(&mut builder.draw, Callback::Plain(Box::new(|_, _| ())));
+    builder.draw = Callback::Fallible(Box::new(move |state, r| {
+        draw.call(state, r)?;
+        if r.frame_count() == frame {
+            *slot.borrow_mut() = Some(r.read_frame()?);
+        }
+        Ok(())
+    }));
+    let outcome = builder.headless(true).build()?.run_frames(frame)?;
+    let result = captured.borrow_mut().take();
+    match result {
+        Some(frame) => Ok(to_pixels(&frame)),
+        None => Err(Error::Error(match outcome {
+            RunOutcome::FramesCompleted(_) => "the frame wasn't drawn".to_owned(),
+            outcome => format!("the doodle stopped before frame {} ({:?})", frame, outcome),
+        })),
+    }
+}
+
+/// Compares an image with a golden PNG image, returning an error if they
+/// differ by more than `tolerance`.
+///
+/// On a mismatch the image is saved next to the golden image with
+/// `.actual.png` at the end of its name, together with a `.diff.png` that
+/// shows the pixels that differ in red. Running with the `DOODLE_BLESS`
+/// environment variable set to 1 saves the image as the new golden image
+/// instead, which is also how golden images are created in the first
+/// place. Only red, green and blue are compared.
+pub fn assert_image_matches<P: AsRef<Path>>(
+    image: &PixelBuffer,
+    path: P,
+    tolerance: Tolerance,
+) -> Result<()> {
+    golden::check_golden(&mut to_frame(image), path.as_ref(), tolerance)
+}
+
+/// Returns how many pixels of two images of the same size are more than
+/// `channel` apart in any of red, green and blue, or an error if their
+/// sizes are different.
+pub fn count_differences(actual: &PixelBuffer, expected: &PixelBuffer, channel: u8) -> Result<usize> {
+    if (actual.width(), actual.height()) != (expected.width(), expected.height()) {
+        return Err(Error::Error(format!(
+            "the image is {}x{} but the expected one is {}x{}",
+            actual.width(),
+            actual.height(),
+            expected.width(),
+            expected.height()
+        )));
+    }
+    let (_, differing) = golden::diff_frames(&to_frame(actual), &to_frame(expected), channel);
+    Ok(differing)
+}
+
+fn to_pixels(frame: &Frame) -> PixelBuffer {
+    let mut pixels = PixelBuffer::new(frame.width, frame.height);
+    for y in 0..frame.height {
+        for x in 0..frame.width {
+            let (r, g, b) = frame.pixel(x, y);
+            pixels.set(x, y, Color::RGB(r, g, b));
+        }
+    }
+    pixels
+}
+
+fn to_frame(pixels: &PixelBuffer) -> Frame {
+    let mut frame = Frame {
+        width: pixels.width(),
+        height: pixels.height(),
+        pixels: Vec::with_capacity(pixels.width() as usize * pixels.height() as usize * 3),
+    };
+    for y in 0..pixels.height() {
+        for x in 0..pixels.width() {
+            let color = pixels.get(x, y).unwrap_or(Color::RGB(0, 0, 0));
+            frame.pixels.extend_from_slice(&[color.r, color.g, color.b]);
+        }
+    }
+    frame
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(width: u32, height: u32, color: Color) -> PixelBuffer {
+        let mut pixels = PixelBuffer::new(width, height);
+        pixels.fill(color);
+        pixels
+    }
+
+    #[test]
+    fn allowed_pixels_round_down() {
+        assert_eq!(Tolerance::exact().allowed(1_000_000), 0);
+        assert_eq!(Tolerance::new(0, 0.001).allowed(999), 0);
+        assert_eq!(Tolerance::new(0, 0.001).allowed(1000), 1);
+        assert_eq!(Tolerance::new(0, 0.25).allowed(10), 2);
+        assert_eq!(Tolerance::new(0, 2.0).allowed(10), 10);
+        assert_eq!(Tolerance::new(0, -1.0).allowed(10), 0);
+    }
+
+    #[test]
+    fn channels_are_compared_up_to_the_threshold() {
+        let expected = filled(3, 2, Color::RGB(100, 100, 100));
+        let mut actual = expected.clone();
+        actual.set(0, 0, Color::RGB(102, 100, 100));
+        actual.set(1, 0, Color::RGB(100, 97, 100));
+        actual.set(2, 1, Color::RGB(100, 100, 103));
+        assert_eq!(count_differences(&actual, &expected, 0).unwrap(), 3);
+        assert_eq!(count_differences(&actual, &expected, 2).unwrap(), 2);
+        assert_eq!(count_differences(&actual, &expected, 3).unwrap(), 0);
+    }
+
+    #[test]
+    fn alpha_is_ignored() {
+        let expected = filled(2, 2, Color::RGBA(10, 20, 30, 255));
+        let actual = filled(2, 2, Color::RGBA(10, 20, 30, 0));
+        assert_eq!(count_differences(&actual, &expected, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn sizes_have_to_match() {
+        let error = count_differences(&PixelBuffer::new(4, 3), &PixelBuffer::new(3, 4), 255).unwrap_err();
+        assert_eq!(error.to_string(), "the image is 4x3 but the expected one is 3x4");
+    }
+
+    #[test]
+    fn frames_convert_to_opaque_pixels_and_back() {
+        let mut pixels = filled(2, 1, Color::RGBA(1, 2, 3, 4));
+        pixels.set(1, 0, Color::RGB(200, 100, 50));
+        let frame = to_frame(&pixels);
+        assert_eq!(frame.pixels, vec![1, 2, 3, 200, 100, 50]);
+        let back = to_pixels(&frame);
+        assert_eq!(back.get(0, 0), Some(Color::RGB(1, 2, 3)));
+        assert_eq!(back.get(1, 0), Some(Color::RGB(200, 100, 50)));
+    }
+}
@@ -0,0 +1,836 @@
+//! Font loading and text rendering.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Texture};
+use sdl2::rwops::RWops;
+use sdl2::surface::Surface;
+use sdl2::ttf::{self, Font as TtfFont, FontStyle};
+
+use image::{into_static, set_texture_tint};
+use log::Level;
+use pixels::FORMAT;
+use shapes::to_pixel;
+use transform::copy_texture;
+use {Error, Renderer, Result};
+
+/// DejaVu Sans Mono, used whenever the crate needs to draw text on
+/// its own. See assets/LICENSE-DejaVu.txt for its license.
+const DEFAULT_FONT: &[u8] = include_bytes!("../assets/DejaVuSansMono.ttf");
+
+/// The point size used by `text` when no font or size has been set.
+pub const DEFAULT_TEXT_SIZE: u16 = 16;
+
+/// The character drawn in place of characters a font has no glyph for,
+/// unless another one has been set with `set_missing_glyph`.
+pub const DEFAULT_MISSING_GLYPH: char = '\u{25a1}';
+
+/// How many frames the texture of a string is kept after it was last drawn,
+/// so text that blinks or comes back soon isn't rendered again.
+const TEXT_CACHE_FRAMES: u64 = 60;
+/// How many glyphs a side of a glyph atlas has room for.
+const ATLAS_GLYPHS_PER_SIDE: u32 = 16;
+/// The widest and tallest a glyph atlas gets, for large text.
+const MAX_ATLAS_SIZE: u32 = 2048;
+/// What text is rendered in before it's tinted to the color it's drawn in.
+const WHITE: Color = Color {
+    r: 255,
+    g: 255,
+    b: 255,
+    a: 255,
+};
+
+/// A handle to a font that has been loaded by a Renderer at a specific size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Font {
+    id: usize,
+}
+
+/// An effect that makes text easier to read on busy backgrounds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextEffect {
+    /// Draws a copy of the text in `color` behind it, moved by `offset` pixels.
+    Shadow { offset: (i32, i32), color: Color },
+    /// Draws an outline `px` pixels thick around every glyph.
+    Outline { px: u16, color: Color },
+}
+
+/// The style of a run of text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TextStyle {
+    /// Draws thicker glyphs.
+    pub bold: bool,
+    /// Draws slanted glyphs.
+    pub italic: bool,
+}
+
+impl TextStyle {
+    fn font_style(self) -> FontStyle {
+        let mut style = ttf::STYLE_NORMAL;
+        if self.bold {
+            style |= ttf::STYLE_BOLD;
+        }
+        if self.italic {
+            style |= ttf::STYLE_ITALIC;
+        }
+        style
+    }
+}
+
+/// A piece of text with its own color and style, drawn as part of a line
+/// by draw_rich_text.
+///
+/// # Example
+/// ```
+/// # use doodle::{Color, TextSpan};
+/// let score = [
+///     TextSpan::new("Lives: ", Color::RGB(255, 255, 255)),
+///     TextSpan::new("3", Color::RGB(255, 0, 0)).bold(),
+///     TextSpan::new(" Coins: ", Color::RGB(255, 255, 255)),
+///     TextSpan::new("12", Color::RGB(255, 255, 0)).bold(),
+/// ];
+/// # let _ = score;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextSpan<'a> {
+    /// The text of the span.
+    pub text: &'a str,
+    /// The color the text is drawn in.
+    pub color: Color,
+    /// The style the text is drawn in.
+    pub style: TextStyle,
+}
+
+impl<'a> TextSpan<'a> {
+    /// Creates a span of plain text.
+    #[inline]
+    pub fn new(text: &'a str, color: Color) -> Self {
+        TextSpan {
+            text,
+            color,
+            style: TextStyle::default(),
+        }
+    }
+    /// Makes the span bold.
+    #[inline]
+    pub fn bold(mut self) -> Self {
+        self.style.bold = true;
+        self
+    }
+    /// Makes the span italic.
+    #[inline]
+    pub fn italic(mut self) -> Self {
+        self.style.italic = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct TextKey {
+    font: usize,
+    text: String,
+    style: TextStyle,
+    outline: u16,
+}
+
+struct CachedText {
+    texture: Texture<'static>,
+    last_used: u64,
+}
+
+/// A glyph in a `GlyphAtlas`, and where it goes relative to the pen.
+/// Glyphs with no pixels, like spaces, only move the pen and have no cell.
+#[derive(Debug, Clone, Copy)]
+struct CachedGlyph {
+    cell: Option<usize>,
+    width: u32,
+    height: u32,
+    minx: i32,
+    advance: i32,
+    last_used: u64,
+}
+
+/// The glyphs of a fixed-width font, kept in one texture so strings that
+/// change every frame, like a score or a frame rate, are drawn from glyphs
+/// that were only rendered once.
+///
+/// The atlas is a grid of cells with room for any glyph of the font. Once
+/// every cell is taken, the glyph that was drawn longest ago gives up its
+/// cell.
+struct GlyphAtlas {
+    texture: Texture<'static>,
+    cell: (u32, u32),
+    columns: u32,
+    free: Vec<usize>,
+    glyphs: HashMap<char, CachedGlyph>,
+}
+
+impl GlyphAtlas {
+    fn cell_rect(&self, cell: usize, width: u32, height: u32) -> Rect {
+        let (column, row) = (cell as u32 % self.columns, cell as u32 / self.columns);
+        Rect::new((column * self.cell.0) as i32, (row * self.cell.1) as i32, width, height)
+    }
+    /// Returns a free cell, or the cell of the glyph drawn longest ago if it
+    /// wasn't drawn this frame.
+    fn take_cell(&mut self, frame: u64) -> Option<usize> {
+        if let Some(cell) = self.free.pop() {
+            return Some(cell);
+        }
+        let (c, cell, last_used) = self
+            .glyphs
+            .iter()
+            .filter_map(|(&c, glyph)| glyph.cell.map(|cell| (c, cell, glyph.last_used)))
+            .min_by_key(|&(_, _, last_used)| last_used)?;
+        if last_used == frame {
+            return None;
+        }
+        self.glyphs.remove(&c);
+        Some(cell)
+    }
+}
+
+/// Keeps the textures of recently drawn strings around, so text that is
+/// drawn every frame is only rendered by SDL_ttf once.
+///
+/// Strings are rendered in white and tinted when they're drawn, so text
+/// that only changes color, and the passes of a text effect, share their
+/// textures. Text in fixed-width fonts is drawn from a `GlyphAtlas` instead
+/// wherever it can be.
+#[derive(Default)]
+pub(crate) struct TextCache {
+    entries: HashMap<TextKey, CachedText>,
+    /// The atlas of each font, by id, or None for fonts that can't have one.
+    atlases: HashMap<usize, Option<GlyphAtlas>>,
+    frame: u64,
+}
+
+impl TextCache {
+    /// Throws away the textures of strings that haven't been drawn for
+    /// `TEXT_CACHE_FRAMES` frames, returning how many were thrown away.
+    pub(crate) fn end_frame(&mut self) -> usize {
+        let (frame, before) = (self.frame, self.entries.len());
+        self.entries.retain(|_, entry| frame - entry.last_used < TEXT_CACHE_FRAMES);
+        self.frame += 1;
+        before - self.entries.len()
+    }
+    /// Throws away the textures of strings and glyphs drawn with a font,
+    /// after the font has been reloaded.
+    pub(crate) fn forget_font(&mut self, font: usize) {
+        self.entries.retain(|key, _| key.font != font);
+        self.atlases.remove(&font);
+    }
+}
+
+/// Where a font was loaded from, so it can be loaded again at other sizes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum FontSource {
+    File(PathBuf),
+    Bytes(FontBytes),
+    Default,
+}
+
+/// The contents of a font file loaded with `load_font_bytes`, compared by
+/// where they are rather than what's in them, so looking up a font at
+/// another size doesn't read the whole file.
+#[derive(Debug, Clone, Copy)]
+struct FontBytes(&'static [u8]);
+
+impl PartialEq for FontBytes {
+    fn eq(&self, other: &FontBytes) -> bool {
+        self.0.as_ptr() == other.0.as_ptr() && self.0.len() == other.0.len()
+    }
+}
+
+impl Eq for FontBytes {}
+
+impl ::std::hash::Hash for FontBytes {
+    fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {
+        self.0.as_ptr().hash(state);
+        self.0.len().hash(state);
+    }
+}
+
+/// The current font and text size used by `text`, and what's needed to
+/// load the current font at other sizes.
+#[derive(Default)]
+pub(crate) struct TextState {
+    pub(crate) font: Option<Font>,
+    pub(crate) size: Option<u16>,
+    // The source and size of every font, by id.
+    sources: Vec<(FontSource, u16)>,
+    // Fonts loaded by set_text_size, by source and size.
+    sized: HashMap<(FontSource, u16), Font>,
+    missing_glyph: Option<char>,
+    // The missing glyphs that have been logged, by font id.
+    logged_glyphs: RefCell<HashSet<(usize, char)>>,
+}
+
+/// Extends the lifetime of a font to `'static`.
+///
+/// Fonts are stored before `ttf_context` in the Renderer, so they are
+/// always closed before TTF_Quit is called.
+pub(crate) fn font_into_static(font: TtfFont) -> TtfFont<'static, 'static> {
+    unsafe { mem::transmute(font) }
+}
+
+/// Returns true if `font` can draw `c`. SDL_ttf only handles characters
+/// in the Basic Multilingual Plane, so nothing past it can be drawn.
+fn has_glyph(font: &TtfFont, c: char) -> bool {
+    (c as u32) <= 0xffff && font.find_glyph(c).is_some()
+}
+
+/// Returns the extra space an effect needs around the text as
+/// (left, top, right, bottom).
+fn effect_margins(effect: Option<TextEffect>) -> (u32, u32, u32, u32) {
+    match effect {
+        Some(TextEffect::Shadow { offset: (x, y), .. }) => (
+            (-x).max(0) as u32,
+            (-y).max(0) as u32,
+            x.max(0) as u32,
+            y.max(0) as u32,
+        ),
+        Some(TextEffect::Outline { px, .. }) => {
+            let px = u32::from(px);
+            (px, px, px, px)
+        }
+        None => (0, 0, 0, 0),
+    }
+}
+
+impl Renderer {
+    /// Loads a TrueType font from a file at the given point size.
+    ///
+    /// Text drawn with the font is turned into textures behind the scenes
+    /// and cached, so drawing the same string every frame is cheap.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer, score: u32) -> Result<()> {
+    /// let font = r.load_font("assets/PressStart2P.ttf", 24)?;
+    /// let label = format!("Score: {}", score);
+    /// let (width, _) = r.measure_text(font, &label)?;
+    /// // Right-aligned in the top corner.
+    /// let x = (r.width() - width - 10) as f32;
+    /// r.draw_text(font, &label, x, 10.0)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_font<P: AsRef<Path>>(&mut self, path: P, size: u16) -> Result<Font> {
+        let font = font_into_static(self.ttf_context.load_font(&path, size)?);
+        let font = self.push_font(font, FontSource::File(path.as_ref().to_owned()), size);
+        self.hot_reload.watch_font(font.id, size, path.as_ref());
+        Ok(font)
+    }
+    /// Loads a font from the contents of a font file at the given point
+    /// size, like `load_font`. The bytes have to last as long as the
+    /// program, since SDL reads glyphs from them as they're drawn, which
+    /// is the case for the bytes `embed!` gives.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # #[macro_use] extern crate doodle;
+    /// # use doodle::*;
+    /// # fn main() {}
+    /// # fn setup(r: &mut Renderer) -> Result<()> {
+    /// let title = r.load_font_bytes(embed!("assets/DejaVuSansMono.ttf"), 48)?;
+    /// r.draw_text(title, "Embedded", 20.0, 20.0)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load_font_bytes(&mut self, bytes: &'static [u8], size: u16) -> Result<Font> {
+        let rwops = RWops::from_bytes(bytes)?;
+        let font = font_into_static(self.ttf_context.load_font_from_rwops(rwops, size)?);
+        Ok(self.push_font(font, FontSource::Bytes(FontBytes(bytes)), size))
+    }
+    fn push_font(&mut self, font: TtfFont<'static, 'static>, source: FontSource, size: u16) -> Font {
+        self.fonts.push(Some(font));
+        self.text_state.sources.push((source, size));
+        Font {
+            id: self.fonts.len() - 1,
+        }
+    }
+    /// Returns the bundled default font at the given point size,
+    /// loading it the first time each size is asked for.
+    pub(crate) fn default_font(&mut self, size: u16) -> Result<Font> {
+        if let Some(&font) = self.default_fonts.get(&size) {
+            return Ok(font);
+        }
+        let rwops = RWops::from_bytes(DEFAULT_FONT)?;
+        let font = font_into_static(self.ttf_context.load_font_from_rwops(rwops, size)?);
+        let font = self.push_font(font, FontSource::Default, size);
+        self.default_fonts.insert(size, font);
+        Ok(font)
+    }
+    /// Closes a font and throws away its cached text. The bundled default
+    /// fonts are never closed.
+    pub(crate) fn unload_font(&mut self, font: Font) {
+        if self.default_fonts.values().any(|&f| f == font) {
+            return;
+        }
+        if let Some(slot) = self.fonts.get_mut(font.id) {
+            *slot = None;
+            self.text_cache.forget_font(font.id);
+        }
+        self.hot_reload.unwatch_font(font.id);
+    }
+    /// Sets the font used by `text` and `text_extent`. The text size goes
+    /// back to the size the font was loaded at.
+    #[inline]
+    pub fn set_font(&mut self, font: Font) {
+        self.text_state.font = Some(font);
+        self.text_state.size = None;
+    }
+    /// Sets the point size used by `text` and `text_extent`. The current
+    /// font is loaded again at this size the first time it's needed.
+    #[inline]
+    pub fn set_text_size(&mut self, size: u16) {
+        self.text_state.size = Some(size);
+    }
+    /// Returns the point size used by `text` and `text_extent`.
+    pub fn text_size(&self) -> u16 {
+        match (self.text_state.size, self.text_state.font) {
+            (Some(size), _) => size,
+            (None, Some(font)) => self.text_state.sources[font.id].1,
+            (None, None) => DEFAULT_TEXT_SIZE,
+        }
+    }
+    /// Returns the current font at the current text size, which is the
+    /// bundled default font if no font has been set.
+    ///
+    /// The returned handle can be passed to the methods that take a font,
+    /// which share their cache with `text`.
+    pub fn current_font(&mut self) -> Result<Font> {
+        let size = self.text_size();
+        let font = match self.text_state.font {
+            Some(font) => font,
+            None => return self.default_font(size),
+        };
+        let (source, loaded_size) = self.text_state.sources[font.id].clone();
+        if size == loaded_size {
+            return Ok(font);
+        }
+        if let Some(&font) = self.text_state.sized.get(&(source.clone(), size)) {
+            return Ok(font);
+        }
+        let sized = match source {
+            FontSource::File(ref path) => self.load_font(path, size)?,
+            FontSource::Bytes(FontBytes(bytes)) => self.load_font_bytes(bytes, size)?,
+            FontSource::Default => self.default_font(size)?,
+        };
+        self.text_state.sized.insert((source, size), sized);
+        Ok(sized)
+    }
+    /// Draws `text` with the current font, text size, drawing color and
+    /// text effect, like draw_text.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer, title: Font, score: u32) -> Result<()> {
+    /// r.set_font(title);
+    /// r.text("Asteroids", 10.0, 10.0)?;
+    /// r.set_text_size(12);
+    /// r.text(&format!("Score: {}", score), 10.0, 60.0)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn text(&mut self, text: &str, x: f32, y: f32) -> Result<()> {
+        let font = self.current_font()?;
+        self.draw_text(font, text, x, y)
+    }
+    /// Draws `text` with the bundled default font at a point size, whatever
+    /// font and text size are set, like draw_text. The font is DejaVu Sans
+    /// Mono, which is built into the crate, so this works with no font
+    /// files at all.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer, score: u32) -> Result<()> {
+    /// r.draw_text_default(&format!("Score: {}", score), 10.0, 10.0, 24)?;
+    /// let fps = format!("{:.0} fps", r.current_fps());
+    /// r.draw_text_default(&fps, 10.0, 40.0, 12)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn draw_text_default(&mut self, text: &str, x: f32, y: f32, size: u16) -> Result<()> {
+        let font = self.default_font(size)?;
+        self.draw_text(font, text, x, y)
+    }
+    /// Returns the width and height of `text` when drawn with `text`,
+    /// like measure_text.
+    pub fn text_extent(&mut self, text: &str) -> Result<(u32, u32)> {
+        let font = self.current_font()?;
+        self.measure_text(font, text)
+    }
+    /// Returns true if `font` has a glyph for `c`, so it can be drawn.
+    pub fn font_has_glyph(&self, font: Font, c: char) -> bool {
+        match self.font(font) {
+            Ok(font) => has_glyph(font, c),
+            Err(_) => false,
+        }
+    }
+    /// Sets the character drawn in place of characters that a font has no
+    /// glyph for. If the font doesn't have this character either, '?' is
+    /// used, and if it doesn't have that the missing characters are left out.
+    ///
+    /// Text is measured with the same substitutions, so measurements always
+    /// match what is drawn.
+    #[inline]
+    pub fn set_missing_glyph(&mut self, c: char) {
+        self.text_state.missing_glyph = Some(c);
+    }
+    /// Returns the character drawn in place of missing glyphs.
+    #[inline]
+    pub fn missing_glyph(&self) -> char {
+        self.text_state.missing_glyph.unwrap_or(DEFAULT_MISSING_GLYPH)
+    }
+    /// Replaces the characters that `font` has no glyph for.
+    fn substitute_missing<'t>(&self, font: Font, text: &'t str) -> Result<Cow<'t, str>> {
+        let id = font.id;
+        let font = self.font(font)?;
+        let drawable = |c: char| c.is_control() || has_glyph(font, c);
+        if text.chars().all(&drawable) {
+            return Ok(Cow::Borrowed(text));
+        }
+        let fallback = [self.missing_glyph(), '?'].iter().cloned().find(|&c| drawable(c));
+        if self.logger.enabled() {
+            let mut logged = self.text_state.logged_glyphs.borrow_mut();
+            for c in text.chars().filter(|&c| !drawable(c)) {
+                if logged.insert((id, c)) {
+                    self.logger.log(Level::Info, || match fallback {
+                        Some(fallback) => format!("font {} has no glyph for {:?}, drawing {:?}", id, c, fallback),
+                        None => format!("font {} has no glyph for {:?}, leaving it out", id, c),
+                    });
+                }
+            }
+        }
+        Ok(Cow::Owned(
+            text.chars()
+                .filter_map(|c| if drawable(c) { Some(c) } else { fallback })
+                .collect(),
+        ))
+    }
+    /// Sets the effect applied to all text drawn after this call.
+    /// Pass None to draw plain text again.
+    #[inline]
+    pub fn set_text_effect(&mut self, effect: Option<TextEffect>) {
+        self.text_effect = effect;
+    }
+    /// Returns the current text effect.
+    #[inline]
+    pub fn text_effect(&self) -> Option<TextEffect> {
+        self.text_effect
+    }
+    /// Returns the width and height of `text` when drawn with `font`,
+    /// including the space taken up by the current text effect.
+    pub fn measure_text(&self, font: Font, text: &str) -> Result<(u32, u32)> {
+        let text = self.substitute_missing(font, text)?;
+        let (width, height) = self.font(font)?.size_of(&text)?;
+        let (left, top, right, bottom) = effect_margins(self.text_effect);
+        Ok((width + left + right, height + top + bottom))
+    }
+    /// Draws `text` with the current drawing color and text effect.
+    ///
+    /// (x, y) is the top left corner of the area returned by measure_text,
+    /// so text with an outline or shadow lines up with its measurements.
+    pub fn draw_text(&mut self, font: Font, text: &str, x: f32, y: f32) -> Result<()> {
+        let (left, top, _, _) = effect_margins(self.text_effect);
+        let x = to_pixel(x) + left as i32;
+        let y = to_pixel(y) + top as i32;
+        let color = self.canvas.draw_color();
+        self.draw_styled_text(font, text, color, TextStyle::default(), x, y)
+    }
+    /// Returns the width and height of a line of spans when drawn with `font`,
+    /// including the space taken up by the current text effect.
+    pub fn measure_rich_text(&mut self, font: Font, spans: &[TextSpan]) -> Result<(u32, u32)> {
+        let (mut width, mut height) = (0, self.font(font)?.height().max(0) as u32);
+        for span in spans {
+            let (w, h) = self.span_size(font, span)?;
+            width += w;
+            height = height.max(h);
+        }
+        let (left, top, right, bottom) = effect_margins(self.text_effect);
+        Ok((width + left + right, height + top + bottom))
+    }
+    /// Draws a line of spans next to each other, each with its own color and
+    /// style, using the current text effect.
+    ///
+    /// (x, y) is the top left corner of the area returned by measure_rich_text.
+    pub fn draw_rich_text(&mut self, font: Font, spans: &[TextSpan], x: f32, y: f32) -> Result<()> {
+        let (left, top, _, _) = effect_margins(self.text_effect);
+        let mut x = to_pixel(x) + left as i32;
+        let y = to_pixel(y) + top as i32;
+        for span in spans {
+            self.draw_styled_text(font, span.text, span.color, span.style, x, y)?;
+            x += self.span_size(font, span)?.0 as i32;
+        }
+        Ok(())
+    }
+    fn font(&self, font: Font) -> Result<&TtfFont<'static, 'static>> {
+        match self.fonts.get(font.id) {
+            Some(Some(font)) => Ok(font),
+            Some(None) => Err(Error::Error("font has been unloaded".to_owned())),
+            None => Err(Error::Error("font belongs to a different renderer".to_owned())),
+        }
+    }
+    fn font_mut(&mut self, font: Font) -> Result<&mut TtfFont<'static, 'static>> {
+        match self.fonts.get_mut(font.id) {
+            Some(Some(font)) => Ok(font),
+            Some(None) => Err(Error::Error("font has been unloaded".to_owned())),
+            None => Err(Error::Error("font belongs to a different renderer".to_owned())),
+        }
+    }
+    fn span_size(&mut self, font: Font, span: &TextSpan) -> Result<(u32, u32)> {
+        let text = self.substitute_missing(font, span.text)?;
+        let font = self.font_mut(font)?;
+        font.set_style(span.style.font_style());
+        let size = font.size_of(&text);
+        font.set_style(ttf::STYLE_NORMAL);
+        Ok(size?)
+    }
+    /// Draws text along with the current text effect. (x, y) is where the
+    /// top left corner of the plain text goes.
+    fn draw_styled_text(
+        &mut self,
+        font: Font,
+        text: &str,
+        color: Color,
+        style: TextStyle,
+        x: i32,
+        y: i32,
+    ) -> Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let text = &*self.substitute_missing(font, text)?;
+        match self.text_effect {
+            Some(TextEffect::Shadow { offset: (dx, dy), color }) => {
+                self.draw_text_pass(font, text, color, style, 0, x + dx, y + dy)?;
+            }
+            Some(TextEffect::Outline { px, color }) => {
+                let (ox, oy) = (x - px as i32, y - px as i32);
+                self.draw_text_pass(font, text, color, style, px, ox, oy)?;
+            }
+            None => (),
+        }
+        self.draw_text_pass(font, text, color, style, 0, x, y)
+    }
+    #[allow(clippy::too_many_arguments)]
+    fn draw_text_pass(
+        &mut self,
+        font: Font,
+        text: &str,
+        color: Color,
+        style: TextStyle,
+        outline: u16,
+        x: i32,
+        y: i32,
+    ) -> Result<()> {
+        if outline == 0 && style == TextStyle::default() && self.draw_atlas_text(font, text, color, x, y)? {
+            return Ok(());
+        }
+        let key = TextKey {
+            font: font.id,
+            text: text.to_owned(),
+            style,
+            outline,
+        };
+        if !self.text_cache.entries.contains_key(&key) {
+            let surface = {
+                let font = self.font_mut(font)?;
+                font.set_style(style.font_style());
+                font.set_outline_width(outline);
+                let surface = font.render(text).blended(WHITE);
+                font.set_outline_width(0);
+                font.set_style(ttf::STYLE_NORMAL);
+                surface?
+            };
+            let texture = self.texture_creator.create_texture_from_surface(&surface)?;
+            let entry = CachedText {
+                texture: into_static(texture),
+                last_used: 0,
+            };
+            self.text_cache.entries.insert(key.clone(), entry);
+        }
+        self.flush_batch();
+        let frame = self.text_cache.frame;
+        let entry = self.text_cache.entries.get_mut(&key).unwrap();
+        entry.last_used = frame;
+        let query = entry.texture.query();
+        self.dirty = true;
+        let dst = Rect::new(x, y, query.width, query.height);
+        set_texture_tint(&entry.texture, color)?;
+        self.debug.count_copy(&entry.texture);
+        copy_texture(&mut self.canvas, &self.transform, &entry.texture, None, dst)?;
+        Ok(())
+    }
+    /// Draws a plain pass of text glyph by glyph from the atlas of its font,
+    /// rendering the glyphs that aren't in it yet. Returns false without
+    /// drawing anything if the text can't be drawn that way, which leaves
+    /// it to the cache of whole strings.
+    fn draw_atlas_text(&mut self, font: Font, text: &str, color: Color, x: i32, y: i32) -> Result<bool> {
+        if text.chars().any(char::is_control) {
+            return Ok(false);
+        }
+        if !self.text_cache.atlases.contains_key(&font.id) {
+            let atlas = self.create_atlas(font)?;
+            self.text_cache.atlases.insert(font.id, atlas);
+        }
+        let frame = self.text_cache.frame;
+        for c in text.chars() {
+            let cached = match self.text_cache.atlases.get_mut(&font.id) {
+                Some(Some(atlas)) => match atlas.glyphs.get_mut(&c) {
+                    // Marking the glyphs as used right away stops the ones
+                    // after them taking their cells.
+                    Some(glyph) => {
+                        glyph.last_used = frame;
+                        true
+                    }
+                    None => false,
+                },
+                _ => return Ok(false),
+            };
+            if !cached && !self.cache_glyph(font, c)? {
+                return Ok(false);
+            }
+        }
+        self.flush_batch();
+        self.dirty = true;
+        let atlas = match self.text_cache.atlases.get(&font.id) {
+            Some(Some(atlas)) => atlas,
+            _ => return Ok(false),
+        };
+        // Like SDL_ttf, the text starts far enough right that no glyph hangs
+        // off its left edge.
+        let (mut pen, mut left) = (0, 0);
+        for c in text.chars() {
+            let glyph = atlas.glyphs[&c];
+            left = left.min(pen + glyph.minx);
+            pen += glyph.advance;
+        }
+        set_texture_tint(&atlas.texture, color)?;
+        let mut pen = x - left;
+        for c in text.chars() {
+            let glyph = atlas.glyphs[&c];
+            if let Some(cell) = glyph.cell {
+                let src = atlas.cell_rect(cell, glyph.width, glyph.height);
+                // Glyphs that start left of the pen are rendered that far in.
+                let dst = Rect::new(pen + glyph.minx.min(0), y, glyph.width, glyph.height);
+                self.debug.count_copy(&atlas.texture);
+                copy_texture(&mut self.canvas, &self.transform, &atlas.texture, Some(src), dst)?;
+            }
+            pen += glyph.advance;
+        }
+        Ok(true)
+    }
+    /// Makes the atlas for a font, or returns None if it's not a
+    /// fixed-width font, since only those can be drawn glyph by glyph
+    /// without losing kerning.
+    fn create_atlas(&mut self, font: Font) -> Result<Option<GlyphAtlas>> {
+        let cell = {
+            let font = self.font(font)?;
+            if !font.face_is_fixed_width() {
+                return Ok(None);
+            }
+            // Glyphs can reach past their advance, so the cells leave room.
+            let (width, height) = font.size_of_char('M')?;
+            (width * 2, height)
+        };
+        if cell.0 == 0 || cell.1 == 0 || cell.0 > MAX_ATLAS_SIZE || cell.1 > MAX_ATLAS_SIZE {
+            return Ok(None);
+        }
+        let columns = ATLAS_GLYPHS_PER_SIDE.min(MAX_ATLAS_SIZE / cell.0);
+        let rows = ATLAS_GLYPHS_PER_SIDE.min(MAX_ATLAS_SIZE / cell.1);
+        let mut texture = self
+            .texture_creator
+            .create_texture_static(FORMAT, columns * cell.0, rows * cell.1)?;
+        texture.set_blend_mode(BlendMode::Blend);
+        Ok(Some(GlyphAtlas {
+            texture: into_static(texture),
+            cell,
+            columns,
+            free: (0..(columns * rows) as usize).rev().collect(),
+            glyphs: HashMap::new(),
+        }))
+    }
+    /// Renders a glyph into the atlas of a font, returning false if it
+    /// doesn't fit in a cell or there's no cell it can have this frame.
+    fn cache_glyph(&mut self, font: Font, c: char) -> Result<bool> {
+        let (surface, metrics) = {
+            let font = self.font(font)?;
+            let metrics = match font.find_glyph_metrics(c) {
+                Some(metrics) => metrics,
+                None => return Ok(false),
+            };
+            // SDL_ttf can't render glyphs with no pixels, like spaces, on
+            // their own, and they don't need a cell anyway.
+            if metrics.maxx <= metrics.minx || metrics.maxy <= metrics.miny {
+                (None, metrics)
+            } else {
+                match font.render_char(c).blended(WHITE) {
+                    Ok(surface) => (Some(surface), metrics),
+                    Err(_) => return Ok(false),
+                }
+            }
+        };
+        let frame = self.text_cache.frame;
+        let surface = match surface {
+            Some(surface) => surface,
+            None => {
+                let glyph = CachedGlyph {
+                    cell: None,
+                    width: 0,
+                    height: 0,
+                    minx: metrics.minx,
+                    advance: metrics.advance,
+                    last_used: frame,
+                };
+                return Ok(match self.text_cache.atlases.get_mut(&font.id) {
+                    Some(Some(atlas)) => {
+                        atlas.glyphs.insert(c, glyph);
+                        true
+                    }
+                    _ => false,
+                });
+            }
+        };
+        let format = Surface::new(1, 1, FORMAT)?.pixel_format();
+        let surface = surface.convert(&format)?;
+        let (width, height, pitch) = (surface.width(), surface.height(), surface.pitch() as usize);
+        let atlas = match self.text_cache.atlases.get_mut(&font.id) {
+            Some(Some(atlas)) => atlas,
+            _ => return Ok(false),
+        };
+        if width == 0 || height == 0 || width > atlas.cell.0 || height > atlas.cell.1 {
+            return Ok(false);
+        }
+        let cell = match atlas.take_cell(frame) {
+            Some(cell) => cell,
+            None => return Ok(false),
+        };
+        let rect = atlas.cell_rect(cell, width, height);
+        let texture = &mut atlas.texture;
+        if let Err(error) = surface.with_lock(|pixels| texture.update(rect, pixels, pitch)) {
+            atlas.free.push(cell);
+            return Err(Error::Error(error.to_string()));
+        }
+        let glyph = CachedGlyph {
+            cell: Some(cell),
+            width,
+            height,
+            minx: metrics.minx,
+            advance: metrics.advance,
+            last_used: frame,
+        };
+        atlas.glyphs.insert(c, glyph);
+        Ok(true)
+    }
+}
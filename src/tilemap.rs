@@ -0,0 +1,133 @@
+//! Tile map rendering.
+
+use sdl2::rect::{Point, Rect};
+
+use sprite::SpriteSheet;
+use Renderer;
+
+/// A grid of tiles taken from a SpriteSheet.
+///
+/// Every tile is as big as the first frame of the sheet. Cells can be
+/// empty, in which case nothing is drawn there.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TileMap {
+    sheet: SpriteSheet,
+    columns: usize,
+    rows: usize,
+    tiles: Vec<Option<usize>>,
+}
+
+impl TileMap {
+    /// Creates an empty tile map with the given number of columns and rows.
+    pub fn new(sheet: SpriteSheet, columns: usize, rows: usize) -> Self {
+        TileMap {
+            sheet,
+            columns,
+            rows,
+            tiles: vec![None; columns * rows],
+        }
+    }
+    /// Creates a tile map from rows of frame indices, where a negative index
+    /// means the cell is empty. The map is as wide as its longest row.
+    pub fn from_grid(sheet: SpriteSheet, grid: &[Vec<i32>]) -> Self {
+        let columns = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut map = TileMap::new(sheet, columns, grid.len());
+        for (y, row) in grid.iter().enumerate() {
+            for (x, &index) in row.iter().enumerate() {
+                if index >= 0 {
+                    map.set_tile(x, y, Some(index as usize));
+                }
+            }
+        }
+        map
+    }
+    /// Sets the tile at column `x` and row `y`. Coordinates outside of the
+    /// map are ignored.
+    pub fn set_tile(&mut self, x: usize, y: usize, index: Option<usize>) {
+        if x < self.columns && y < self.rows {
+            self.tiles[y * self.columns + x] = index;
+        }
+    }
+    /// Returns the tile at column `x` and row `y`.
+    pub fn tile(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.columns && y < self.rows {
+            self.tiles[y * self.columns + x]
+        } else {
+            None
+        }
+    }
+    /// Returns the number of columns and rows in the map.
+    #[inline]
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+    /// Returns the width and height of a single tile in pixels.
+    pub fn tile_size(&self) -> (u32, u32) {
+        self.sheet
+            .frame(0)
+            .map_or((0, 0), |frame| (frame.width(), frame.height()))
+    }
+    /// Returns the sprite sheet the tiles are taken from.
+    #[inline]
+    pub fn sheet(&self) -> &SpriteSheet {
+        &self.sheet
+    }
+}
+
+/// Returns the range of cells of size `size` that overlap `[start, end)`,
+/// clamped to `[0, count)`.
+fn visible_cells(start: f32, end: f32, size: u32, count: usize) -> (usize, usize) {
+    let size = f64::from(size);
+    let first = (f64::from(start) / size).floor().max(0.0);
+    let last = (f64::from(end) / size).ceil().max(0.0);
+    (first.min(count as f64) as usize, last.min(count as f64) as usize)
+}
+
+impl Renderer {
+    /// Draws a tile map with its top left corner at `offset`.
+    ///
+    /// Only the tiles that are inside the window are drawn, so huge maps can
+    /// be scrolled around cheaply. Which tiles those are takes the current
+    /// transformation into account, so maps can be drawn through a camera.
+    pub fn draw_tilemap(&mut self, map: &TileMap, offset: Point) {
+        let (tile_width, tile_height) = map.tile_size();
+        if tile_width == 0 || tile_height == 0 {
+            return;
+        }
+        // The corners of the window in the map's pixels, which are a
+        // rotated rectangle when the transformation rotates.
+        let (width, height) = self.size();
+        let (width, height) = (width as f32, height as f32);
+        let (mut left, mut top) = (f32::INFINITY, f32::INFINITY);
+        let (mut right, mut bottom) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for &(x, y) in &[(0.0, 0.0), (width, 0.0), (width, height), (0.0, height)] {
+            let (x, y) = match self.transform.unapply(x, y) {
+                Some((x, y)) => (x - offset.x() as f32, y - offset.y() as f32),
+                None => return,
+            };
+            left = left.min(x);
+            top = top.min(y);
+            right = right.max(x);
+            bottom = bottom.max(y);
+        }
+        let (first_col, last_col) = visible_cells(left, right, tile_width, map.columns);
+        let (first_row, last_row) = visible_cells(top, bottom, tile_height, map.rows);
+        let image = map.sheet.image();
+        for y in first_row..last_row {
+            for x in first_col..last_col {
+                let src = match map.tile(x, y).and_then(|index| map.sheet.frame(index)) {
+                    Some(src) => src,
+                    None => continue,
+                };
+                let dst = Rect::new(
+                    offset.x() + (x as u32 * tile_width) as i32,
+                    offset.y() + (y as u32 * tile_height) as i32,
+                    tile_width,
+                    tile_height,
+                );
+                let result = self.copy_image(image, Some(src), dst);
+                self.log_failure("draw a tile", result);
+            }
+        }
+    }
+}
@@ -0,0 +1,384 @@
+//! Time keeping for the main loop.
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use {Error, Renderer, Result};
+
+/// How many of the latest frames the measured frame rate is averaged over.
+const FPS_FRAMES: usize = 30;
+
+/// Keeps track of how long the doodle has been running, both in real time
+/// and in scaled time.
+pub(crate) struct Clock {
+    start: Instant,
+    frame_elapsed: f64,
+    scale: f32,
+    scaled_elapsed: f64,
+    scaled_delta: f64,
+    frames: u64,
+    /// When the latest frames started, oldest first.
+    recent: VecDeque<f64>,
+}
+
+/// Returns how far apart frames are when time moves on by whole frames,
+/// which it does in headless doodles since they aren't watched as they run.
+pub(crate) fn frame_step(headless: bool, fps: u32) -> Option<f64> {
+    if headless {
+        Some(1.0 / f64::from(fps.max(1)))
+    } else {
+        None
+    }
+}
+
+impl Clock {
+    pub(crate) fn new() -> Self {
+        Clock {
+            start: Instant::now(),
+            frame_elapsed: 0.0,
+            scale: 1.0,
+            scaled_elapsed: 0.0,
+            scaled_delta: 0.0,
+            frames: 0,
+            recent: VecDeque::with_capacity(FPS_FRAMES),
+        }
+    }
+    /// Restarts the clock at zero. Called when the doodle starts running.
+    pub(crate) fn start(&mut self) {
+        self.start = Instant::now();
+        self.frame_elapsed = 0.0;
+        self.scaled_elapsed = 0.0;
+        self.scaled_delta = 0.0;
+        self.frames = 0;
+        self.recent.clear();
+    }
+    /// Returns the time a new frame starting now starts at, or the time
+    /// `fixed_step` seconds after the previous frame if there is a step.
+    pub(crate) fn next_elapsed(&self, fixed_step: Option<f64>) -> f64 {
+        match fixed_step {
+            Some(step) if self.frames > 0 => self.frame_elapsed + step,
+            Some(_) => 0.0,
+            None => self.start.elapsed().as_secs_f64(),
+        }
+    }
+    /// Starts a new frame at `elapsed` seconds.
+    pub(crate) fn begin_frame(&mut self, elapsed: f64) {
+        // Scaled time advances by each frame's real length times the scale
+        // at the time, so changing the scale never makes it jump.
+        self.scaled_delta = (elapsed - self.frame_elapsed) * f64::from(self.scale);
+        self.scaled_elapsed += self.scaled_delta;
+        self.frame_elapsed = elapsed;
+        self.frames += 1;
+        if self.recent.len() == FPS_FRAMES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(elapsed);
+    }
+    /// Sets how fast scaled time passes, rejecting negative, infinite and
+    /// NaN scales.
+    pub(crate) fn set_scale(&mut self, scale: f32) -> Result<()> {
+        if !scale.is_finite() || scale < 0.0 {
+            return Err(Error::Error(format!("time scale must be 0 or more, not {}", scale)));
+        }
+        self.scale = scale;
+        Ok(())
+    }
+}
+
+impl Renderer {
+    /// Returns the number of seconds between the doodle starting to run and
+    /// the start of the current frame.
+    ///
+    /// This only changes between frames, so animations driven by it stay in
+    /// sync no matter how many times it's called while drawing a frame.
+    #[inline]
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.clock.frame_elapsed
+    }
+    /// Returns the number of milliseconds since the doodle started running,
+    /// measured right now. Unlike `elapsed_seconds` this changes during a
+    /// frame, which makes it useful for timestamping events.
+    pub fn now_millis(&self) -> u64 {
+        self.clock.start.elapsed().as_millis() as u64
+    }
+    /// Returns the number of milliseconds between the doodle starting to
+    /// run and the start of the current frame, like `elapsed_seconds`.
+    #[inline]
+    pub fn millis(&self) -> u64 {
+        (self.clock.frame_elapsed * 1000.0) as u64
+    }
+    /// Returns how many frames a second are really being drawn, averaged
+    /// over the last 30 frames. This is lower than the `fps` the doodle was
+    /// built with when frames take too long to draw, and is 0 until two
+    /// frames have been drawn.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) {
+    /// r.debug_watch("fps", format!("{:.1}", r.current_fps()));
+    /// r.debug_watch("frame", r.frame_count());
+    /// # }
+    /// ```
+    pub fn current_fps(&self) -> f64 {
+        let recent = &self.clock.recent;
+        match (recent.front(), recent.back()) {
+            (Some(&first), Some(&last)) if last > first => (recent.len() - 1) as f64 / (last - first),
+            _ => 0.0,
+        }
+    }
+    /// Returns the number of the frame being drawn. It is 0 during setup
+    /// and 1 while the first frame is drawn.
+    #[inline]
+    pub fn frame_count(&self) -> u64 {
+        self.clock.frames
+    }
+    /// Changes how many frames a second the doodle tries to draw, starting
+    /// with the next frame. A frame rate of 0 is treated as 1.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer, menu_open: bool) {
+    /// // The menu doesn't animate, so save battery while it's open.
+    /// r.set_frame_rate(if menu_open { 10 } else { 60 });
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_frame_rate(&mut self, fps: u32) {
+        self.frame_rate = fps.max(1);
+    }
+    /// Returns how many frames a second the doodle tries to draw. See
+    /// `current_fps` for how many it really draws.
+    #[inline]
+    pub fn frame_rate(&self) -> u32 {
+        self.frame_rate
+    }
+    /// Stops drawing frames until `loop_` or `redraw` is called, like
+    /// Processing's `noLoop`. Events still run their callbacks, but the
+    /// doodle sleeps between them instead of drawing the same picture
+    /// over and over.
+    ///
+    /// The update callback only runs when a frame is drawn, and
+    /// `delta_seconds` counts the time since the last frame that was
+    /// drawn, however long ago that was.
+    ///
+    /// # Example
+    /// ```
+    /// # use doodle::*;
+    /// #[derive(Default)]
+    /// struct State {
+    ///     dots: Vec<(i32, i32)>,
+    /// }
+    /// // Only draw again when something changes.
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .setup(Box::new(|_, r| r.no_loop()))
+    ///     .on_mouse_down(Box::new(|state, r, _, x, y| {
+    ///         state.dots.push((x, y));
+    ///         r.redraw();
+    ///     }))
+    ///     .draw(Box::new(|state, r| {
+    ///         r.clear();
+    ///         for &(x, y) in &state.dots {
+    ///             r.fill_circle(x as f32, y as f32, 5.0);
+    ///         }
+    ///     }));
+    /// ```
+    #[inline]
+    pub fn no_loop(&mut self) {
+        self.looping = false;
+    }
+    /// Starts drawing frames again after `no_loop`, like Processing's
+    /// `loop`.
+    #[inline]
+    pub fn loop_(&mut self) {
+        self.looping = true;
+    }
+    /// Returns false if `no_loop` has stopped frames from being drawn.
+    #[inline]
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+    /// Draws one more frame after `no_loop`, once the events that are
+    /// waiting have been handled. Calling it more than once before the
+    /// frame is drawn still only draws one.
+    #[inline]
+    pub fn redraw(&mut self) {
+        self.redraw_requested = true;
+    }
+    /// Sets how fast scaled time passes compared to real time: 0.5 is slow
+    /// motion, 2 is fast forward and 0 freezes it. Frames are still drawn
+    /// and input still works while time is frozen.
+    ///
+    /// Scaled time is what `scaled_elapsed_seconds` and `delta_seconds`
+    /// report. `elapsed_seconds` and `now_millis` always report real time,
+    /// so frame timings measured with them aren't affected. Negative,
+    /// infinite and NaN scales are rejected.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) -> Result<()> {
+    /// if r.action_down("slow_motion") {
+    ///     r.set_time_scale(0.25)?;
+    /// } else {
+    ///     r.set_time_scale(1.0)?;
+    /// }
+    /// let angle = r.scaled_elapsed_seconds() * 3.0;
+    /// # let _ = angle;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[inline]
+    pub fn set_time_scale(&mut self, scale: f32) -> Result<()> {
+        self.clock.set_scale(scale)
+    }
+    /// Returns how fast scaled time passes compared to real time.
+    #[inline]
+    pub fn time_scale(&self) -> f32 {
+        self.clock.scale
+    }
+    /// Returns the number of scaled seconds between the doodle starting to
+    /// run and the start of the current frame. Like `elapsed_seconds` this
+    /// only changes between frames.
+    #[inline]
+    pub fn scaled_elapsed_seconds(&self) -> f64 {
+        self.clock.scaled_elapsed
+    }
+    /// Returns the number of scaled seconds between the start of the
+    /// previous frame and the start of the current one. Moving things by
+    /// their speed times this keeps them moving at the same speed whatever
+    /// the frame rate, and slows them down with the time scale.
+    #[inline]
+    pub fn delta_seconds(&self) -> f64 {
+        self.clock.scaled_delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn elapsed_is_sampled_once_per_frame() {
+        let mut clock = Clock::new();
+        clock.start();
+        let elapsed = clock.next_elapsed(None);
+        clock.begin_frame(elapsed);
+        let first = clock.frame_elapsed;
+        thread::sleep(Duration::from_millis(5));
+        // Nothing moves on until the next frame starts.
+        assert_eq!(clock.frame_elapsed, first);
+        assert_eq!(clock.frames, 1);
+        let elapsed = clock.next_elapsed(None);
+        clock.begin_frame(elapsed);
+        assert!(clock.frame_elapsed > first);
+        assert_eq!(clock.frames, 2);
+    }
+
+    #[test]
+    fn time_scale_rejects_negative_and_nan() {
+        let mut clock = Clock::new();
+        assert!(clock.set_scale(-1.0).is_err());
+        assert!(clock.set_scale(f32::NAN).is_err());
+        assert!(clock.set_scale(f32::INFINITY).is_err());
+        assert_eq!(clock.scale, 1.0);
+        assert!(clock.set_scale(0.0).is_ok());
+        assert!(clock.set_scale(0.5).is_ok());
+        assert_eq!(clock.scale, 0.5);
+    }
+
+    #[test]
+    fn scaled_time_follows_the_scale() {
+        let mut clock = Clock::new();
+        clock.begin_frame(0.0);
+        clock.set_scale(0.5).unwrap();
+        clock.begin_frame(1.0);
+        assert_eq!(clock.scaled_delta, 0.5);
+        clock.set_scale(0.0).unwrap();
+        clock.begin_frame(2.0);
+        assert_eq!(clock.scaled_elapsed, 0.5);
+        assert_eq!(clock.frame_elapsed, 2.0);
+    }
+
+    #[test]
+    fn headless_frames_are_fixed_steps() {
+        assert_eq!(frame_step(false, 60), None);
+        assert_eq!(frame_step(true, 0), Some(1.0));
+        let step = frame_step(true, 50);
+        assert_eq!(step, Some(0.02));
+        let mut clock = Clock::new();
+        clock.start();
+        for frame in 0..10 {
+            let elapsed = clock.next_elapsed(step);
+            assert_eq!(elapsed, if frame == 0 { 0.0 } else { clock.frame_elapsed + 0.02 });
+            clock.begin_frame(elapsed);
+            // However long the frame really took.
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!((clock.frame_elapsed - 0.18).abs() < 1e-9);
+        assert_eq!(clock.frames, 10);
+    }
+
+    // While `no_loop` is on, or the window is hidden and nothing is drawn,
+    // the clock isn't told about frames, so the next one sees the whole
+    // pause at once.
+    #[test]
+    fn a_pause_counts_towards_the_next_frame() {
+        let mut clock = Clock::new();
+        clock.begin_frame(0.0);
+        clock.begin_frame(0.5);
+        clock.begin_frame(10.5);
+        assert_eq!(clock.frame_elapsed, 10.5);
+        assert_eq!(clock.scaled_delta, 10.0);
+        assert_eq!(clock.scaled_elapsed, 10.5);
+        // The frames that weren't drawn aren't counted.
+        assert_eq!(clock.frames, 3);
+    }
+
+    #[test]
+    fn a_pause_is_scaled_by_the_scale_when_drawing_resumes() {
+        let mut clock = Clock::new();
+        clock.begin_frame(0.0);
+        clock.set_scale(0.5).unwrap();
+        clock.begin_frame(1.0);
+        clock.set_scale(2.0).unwrap();
+        clock.begin_frame(5.0);
+        assert_eq!(clock.scaled_delta, 8.0);
+        assert_eq!(clock.scaled_elapsed, 8.5);
+        // Frozen time stays frozen however long the pause was.
+        clock.set_scale(0.0).unwrap();
+        clock.begin_frame(60.0);
+        assert_eq!(clock.scaled_delta, 0.0);
+        assert_eq!(clock.scaled_elapsed, 8.5);
+        assert_eq!(clock.frame_elapsed, 60.0);
+    }
+
+    #[test]
+    fn real_time_keeps_going_during_a_pause() {
+        let mut clock = Clock::new();
+        clock.start();
+        let elapsed = clock.next_elapsed(None);
+        clock.begin_frame(elapsed);
+        let before = clock.frame_elapsed;
+        thread::sleep(Duration::from_millis(20));
+        let elapsed = clock.next_elapsed(None);
+        clock.begin_frame(elapsed);
+        assert!(clock.frame_elapsed - before >= 0.02);
+        assert_eq!(clock.scaled_delta, clock.frame_elapsed - before);
+    }
+
+    #[test]
+    fn headless_time_skips_pauses() {
+        let step = frame_step(true, 10);
+        let mut clock = Clock::new();
+        clock.start();
+        clock.begin_frame(clock.next_elapsed(step));
+        thread::sleep(Duration::from_millis(20));
+        clock.begin_frame(clock.next_elapsed(step));
+        assert!((clock.frame_elapsed - 0.1).abs() < 1e-9);
+        assert!((clock.scaled_delta - 0.1).abs() < 1e-9);
+    }
+}
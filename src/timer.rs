@@ -0,0 +1,151 @@
+//! Callbacks that run after a delay or every so often.
+
+use Renderer;
+
+/// A callback scheduled with `Renderer::after` or `Renderer::every`.
+pub type TimerCallback = Box<dyn FnMut(&mut Renderer)>;
+
+/// A handle to a scheduled callback, used to cancel it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+struct Timer {
+    id: TimerId,
+    /// When the timer next fires, in scaled seconds.
+    due: f64,
+    /// How long there is between runs, for timers that repeat.
+    interval: Option<f64>,
+    /// The callback is taken out of the timer while it runs.
+    callback: Option<TimerCallback>,
+}
+
+/// The timers that haven't finished yet.
+#[derive(Default)]
+pub(crate) struct Timers {
+    timers: Vec<Timer>,
+    next_id: u64,
+}
+
+impl Timers {
+    fn add(&mut self, due: f64, interval: Option<f64>, callback: TimerCallback) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        self.timers.push(Timer {
+            id,
+            due,
+            interval,
+            callback: Some(callback),
+        });
+        id
+    }
+    /// Returns the timer that is due first by `now`, along with its
+    /// callback.
+    fn take_due(&mut self, now: f64, ran: &[TimerId]) -> Option<(TimerId, TimerCallback)> {
+        let timer = self
+            .timers
+            .iter_mut()
+            .filter(|t| t.due <= now && t.callback.is_some() && !ran.contains(&t.id))
+            .min_by(|a, b| a.due.total_cmp(&b.due).then(a.id.0.cmp(&b.id.0)))?;
+        Some((timer.id, timer.callback.take().expect("the timer was checked above")))
+    }
+    /// Puts a callback back after it has run, unless its timer was
+    /// cancelled or doesn't repeat.
+    fn finish(&mut self, id: TimerId, callback: TimerCallback, now: f64) {
+        let index = match self.timers.iter().position(|t| t.id == id) {
+            Some(index) => index,
+            None => return,
+        };
+        match self.timers[index].interval {
+            Some(interval) => {
+                let timer = &mut self.timers[index];
+                // Skip runs that were missed instead of running them all
+                // at once after a long frame.
+                timer.due += interval;
+                if timer.due <= now {
+                    timer.due = now + interval;
+                }
+                timer.callback = Some(callback);
+            }
+            None => {
+                self.timers.remove(index);
+            }
+        }
+    }
+}
+
+impl Renderer {
+    /// Runs a callback once, `seconds` seconds from now.
+    ///
+    /// Timers count scaled time, so they slow down and freeze with
+    /// `set_time_scale`, and they only run at the start of a frame, before
+    /// the update callback. A timer that is due runs once, however late
+    /// the frame is.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State { level: u32 }
+    /// let builder = DoodleBuilder::<State>::new().on_key_down(Box::new(|state, r, key| {
+    ///     if key == Keycode::Return {
+    ///         state.level += 1;
+    ///         r.set_title("Get ready!").unwrap();
+    ///         r.after(2.0, Box::new(|r| r.set_title("Go!").unwrap()));
+    ///     }
+    /// }));
+    /// ```
+    pub fn after(&mut self, seconds: f64, callback: TimerCallback) -> TimerId {
+        let due = self.scaled_elapsed_seconds() + seconds.max(0.0);
+        self.timers.add(due, None, callback)
+    }
+    /// Runs a callback every `seconds` seconds until it's cancelled, starting
+    /// `seconds` seconds from now. See `after` for when timers run.
+    ///
+    /// Runs that are missed because frames are slower than the timer are
+    /// skipped, so the callback runs at most once a frame. An interval of 0
+    /// runs it every frame.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn setup(r: &mut Renderer) {
+    /// // Blink the cursor twice a second.
+    /// r.every(0.5, Box::new(|r| {
+    ///     let visible = r.is_cursor_shown();
+    ///     if visible {
+    ///         r.hide_cursor();
+    ///     } else {
+    ///         r.show_cursor();
+    ///     }
+    /// }));
+    /// # }
+    /// ```
+    pub fn every(&mut self, seconds: f64, callback: TimerCallback) -> TimerId {
+        let interval = seconds.max(0.0);
+        let due = self.scaled_elapsed_seconds() + interval;
+        self.timers.add(due, Some(interval), callback)
+    }
+    /// Cancels a timer, so its callback doesn't run again. A timer can
+    /// cancel itself from its own callback. Returns false if the timer had
+    /// already finished or been cancelled.
+    pub fn cancel_timer(&mut self, timer: TimerId) -> bool {
+        let count = self.timers.timers.len();
+        self.timers.timers.retain(|t| t.id != timer);
+        self.timers.timers.len() < count
+    }
+    /// Returns true if a timer is still waiting to run.
+    pub fn is_timer_active(&self, timer: TimerId) -> bool {
+        self.timers.timers.iter().any(|t| t.id == timer)
+    }
+    /// Runs the callbacks of the timers that are due, in the order they
+    /// are due.
+    pub(crate) fn run_timers(&mut self) {
+        let now = self.scaled_elapsed_seconds();
+        // Each timer runs at most once a frame, even if it repeats faster.
+        let mut ran = Vec::new();
+        while let Some((id, mut callback)) = self.timers.take_due(now, &ran) {
+            callback(self);
+            self.timers.finish(id, callback, now);
+            ran.push(id);
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! The transformation matrix applied to everything that is drawn.
+
+use ludomath::consts::TRANSFORM_IDENTITY;
+use ludomath::vec2d::{self, MaybeInvertible, Transform, Transformation, Vector};
+use sdl2::rect::Rect;
+use sdl2::render::{Canvas, Texture};
+use sdl2::sys;
+use sdl2::video::Window;
+
+use log::Level;
+use shapes::pixel_rect;
+use Renderer;
+
+/// The current transformation and the ones saved by push_matrix.
+pub(crate) struct TransformState {
+    current: Transform,
+    stack: Vec<Transform>,
+}
+
+impl Default for TransformState {
+    fn default() -> Self {
+        TransformState {
+            current: TRANSFORM_IDENTITY,
+            stack: Vec::new(),
+        }
+    }
+}
+
+impl TransformState {
+    /// Returns where a point in drawing coordinates ends up on the canvas.
+    #[inline]
+    pub(crate) fn apply(&self, x: f32, y: f32) -> (f32, f32) {
+        let point = self.current.transform(vec2d::Point::new(x, y));
+        (point.x, point.y)
+    }
+    /// Returns where a point on the canvas is in drawing coordinates, or
+    /// None if the transformation squashes everything flat.
+    pub(crate) fn unapply(&self, x: f32, y: f32) -> Option<(f32, f32)> {
+        let inverse = self.current.invert_checked()?;
+        let point = inverse.transform(vec2d::Point::new(x, y));
+        Some((point.x, point.y))
+    }
+    /// Returns true if there is no rotation or shear, so rectangles stay
+    /// rectangles with the same sides.
+    #[inline]
+    pub(crate) fn axis_aligned(&self) -> bool {
+        self.current.mat[0][1] == 0.0 && self.current.mat[1][0] == 0.0
+    }
+    /// Returns how much lengths along the x and y axes are stretched.
+    pub(crate) fn axis_scale(&self) -> (f32, f32) {
+        let [x_axis, y_axis] = self.current.mat;
+        (
+            Vector::from_vec2(x_axis).magnitude(),
+            Vector::from_vec2(y_axis).magnitude(),
+        )
+    }
+    /// Forgets the transformation and everything saved by push_matrix.
+    pub(crate) fn reset(&mut self) {
+        self.current = TRANSFORM_IDENTITY;
+        self.stack.clear();
+    }
+}
+
+/// Copies the `src` part of a texture into `dst`, which is in drawing
+/// coordinates, on the canvas.
+///
+/// SDL can only draw textures rotated and flipped, so a shear in the
+/// transformation is left out, and the texture is drawn at the rotation and
+/// scale of the transformation's x axis. The texture is drawn with the
+/// canvas's blend mode, like shapes are.
+pub(crate) fn copy_texture(
+    canvas: &mut Canvas<Window>,
+    transform: &TransformState,
+    texture: &Texture,
+    src: Option<Rect>,
+    dst: Rect,
+) -> ::std::result::Result<(), String> {
+    // sdl2 only sets a texture's blend mode through a mutable reference,
+    // which the textures shared through the renderer can't give out.
+    let blend_mode = canvas.blend_mode() as i32 as sys::SDL_BlendMode;
+    if unsafe { sys::SDL_SetTextureBlendMode(texture.raw(), blend_mode) } != 0 {
+        return Err(::sdl2::get_error());
+    }
+    let [[xx, xy], [yx, yy]] = transform.current.mat;
+    if transform.axis_aligned() && xx > 0.0 && yy > 0.0 {
+        let (left, top) = transform.apply(dst.left() as f32, dst.top() as f32);
+        let (right, bottom) = transform.apply(dst.right() as f32, dst.bottom() as f32);
+        return match pixel_rect(left, top, right - left, bottom - top) {
+            Some(rect) => canvas.copy(texture, src, rect),
+            None => Ok(()),
+        };
+    }
+    let x_scale = xx.hypot(xy);
+    let determinant = xx * yy - xy * yx;
+    if x_scale == 0.0 || determinant == 0.0 {
+        // Everything is squashed flat, so there's nothing to see.
+        return Ok(());
+    }
+    // A negative y scale means the transformation mirrors what's drawn.
+    let y_scale = determinant / x_scale;
+    let angle = f64::from(xy.atan2(xx).to_degrees());
+    let (width, height) = (dst.width() as f32 * x_scale, dst.height() as f32 * y_scale.abs());
+    let center = dst.center();
+    let (x, y) = transform.apply(center.x() as f32, center.y() as f32);
+    let rect = Rect::new(
+        (x - width / 2.0).round() as i32,
+        (y - height / 2.0).round() as i32,
+        (width.round() as u32).max(1),
+        (height.round() as u32).max(1),
+    );
+    canvas.copy_ex(texture, src, rect, angle, None, false, y_scale < 0.0)
+}
+
+impl Renderer {
+    /// Moves everything drawn after this call by (x, y).
+    ///
+    /// Like all transformations, this happens in the coordinates set up by
+    /// the transformations before it. Transformations are reset after setup
+    /// and after every frame.
+    #[inline]
+    pub fn translate(&mut self, x: f32, y: f32) {
+        self.transform.current.translate_local_mut(Vector::new(x, y));
+    }
+    /// Rotates everything drawn after this call by `angle` radians around
+    /// the origin, clockwise on the screen.
+    #[inline]
+    pub fn rotate(&mut self, angle: f32) {
+        self.transform.current.rotate_rad_mut(angle);
+    }
+    /// Stretches everything drawn after this call by `x` horizontally and
+    /// `y` vertically, away from the origin. Negative amounts mirror it.
+    #[inline]
+    pub fn scale(&mut self, x: f32, y: f32) {
+        self.transform.current.scale_mut(Vector::new(x, y));
+    }
+    /// Saves the current transformation, so it can be brought back with
+    /// `pop_matrix` after drawing something that is transformed further.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer) {
+    /// // A sun with a planet going around it, and a moon around that.
+    /// let t = r.elapsed_seconds() as f32;
+    /// let (width, height) = r.size();
+    /// r.translate(width as f32 / 2.0, height as f32 / 2.0);
+    /// r.fill_circle(0.0, 0.0, 40.0);
+    /// r.push_matrix();
+    /// r.rotate(t);
+    /// r.translate(150.0, 0.0);
+    /// r.fill_circle(0.0, 0.0, 15.0);
+    /// r.rotate(t * 4.0);
+    /// r.translate(30.0, 0.0);
+    /// r.fill_circle(0.0, 0.0, 5.0);
+    /// r.pop_matrix();
+    /// // Back in the middle of the window.
+    /// r.fill_rect(-5.0, 60.0, 10.0, 10.0);
+    /// # }
+    /// ```
+    pub fn push_matrix(&mut self) {
+        let current = self.transform.current;
+        self.transform.stack.push(current);
+    }
+    /// Brings back the transformation saved by the last `push_matrix`.
+    ///
+    /// Popping more times than pushing logs a warning and resets the
+    /// transformation.
+    pub fn pop_matrix(&mut self) {
+        self.transform.current = match self.transform.stack.pop() {
+            Some(transform) => transform,
+            None => {
+                self.logger
+                    .log(Level::Warn, || "pop_matrix was called more times than push_matrix".to_owned());
+                TRANSFORM_IDENTITY
+            }
+        };
+    }
+    /// Resets the transformation, so coordinates are in pixels from the top
+    /// left corner of the drawing area again. What `push_matrix` saved is
+    /// kept.
+    #[inline]
+    pub fn reset_matrix(&mut self) {
+        self.transform.current = TRANSFORM_IDENTITY;
+    }
+}
@@ -0,0 +1,509 @@
+//! Easing functions and tweens, for animating values smoothly from one to
+//! another.
+//!
+//! An easing function shapes how a value moves: linear movement starts and
+//! stops abruptly, while easing in starts slowly and easing out stops
+//! slowly. A `Tween` moves a value between points over time with them, and
+//! is moved on each frame with `delta_seconds`.
+//!
+//! # Example
+//! ```no_run
+//! # use doodle::*;
+//! use doodle::tween::{Easing, Tween};
+//! # #[derive(Default)] struct State;
+//! // Slide a panel in, hold it, then bounce it back out.
+//! let mut panel = Tween::new(-200.0f32, 0.0, 0.4)
+//!     .ease(Easing::CubicOut)
+//!     .wait(2.0)
+//!     .then(-200.0, 0.6)
+//!     .ease(Easing::BounceOut);
+//! let builder = DoodleBuilder::<State>::new().draw(Box::new(move |_, r| {
+//!     panel.update(r.delta_seconds());
+//!     r.clear();
+//!     r.fill_rect(panel.value(), 100.0, 200.0, 300.0);
+//! }));
+//! ```
+
+use std::f32::consts::PI;
+
+use {lerp_color, Color, Vec2};
+
+/// Values that can be mixed, so they can be tweened.
+pub trait Lerp: Copy {
+    /// Returns the value `t` of the way from `self` to `other`. Easing
+    /// functions like `BackOut` and `ElasticOut` overshoot, so `t` can be a
+    /// little outside of 0 to 1.
+    fn lerp(self, other: Self, t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    #[inline]
+    fn lerp(self, other: f32, t: f32) -> f32 {
+        self + (other - self) * t
+    }
+}
+
+impl Lerp for f64 {
+    #[inline]
+    fn lerp(self, other: f64, t: f32) -> f64 {
+        self + (other - self) * f64::from(t)
+    }
+}
+
+impl Lerp for Vec2 {
+    #[inline]
+    fn lerp(self, other: Vec2, t: f32) -> Vec2 {
+        Vec2::lerp(self, other, t)
+    }
+}
+
+/// Colors are clamped, so they can't overshoot.
+impl Lerp for Color {
+    #[inline]
+    fn lerp(self, other: Color, t: f32) -> Color {
+        lerp_color(self, other, t)
+    }
+}
+
+/// The standard easing functions, like the ones on easings.net.
+///
+/// `In` eases at the start, `Out` at the end, and `InOut` at both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Easing {
+    /// Moves at the same speed the whole way.
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    QuartIn,
+    QuartOut,
+    QuartInOut,
+    QuintIn,
+    QuintOut,
+    QuintInOut,
+    SineIn,
+    SineOut,
+    SineInOut,
+    ExpoIn,
+    ExpoOut,
+    ExpoInOut,
+    CircIn,
+    CircOut,
+    CircInOut,
+    /// Backs up a little before setting off.
+    BackIn,
+    /// Overshoots a little before settling.
+    BackOut,
+    BackInOut,
+    /// Wobbles like a spring being pulled back before it lets go.
+    ElasticIn,
+    /// Wobbles like a spring around the end.
+    ElasticOut,
+    ElasticInOut,
+    /// Bounces off the start, like a ball dropped backwards in time.
+    BounceIn,
+    /// Bounces to a stop at the end, like a dropped ball.
+    BounceOut,
+    BounceInOut,
+}
+
+/// How far the `Back` easings overshoot.
+const BACK: f32 = 1.701_58;
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984_375
+    }
+}
+
+/// Makes an `InOut` easing out of an `In` one, running it forwards over the
+/// first half and backwards over the second.
+fn in_out(t: f32, ease_in: fn(f32) -> f32) -> f32 {
+    if t < 0.5 {
+        ease_in(t * 2.0) / 2.0
+    } else {
+        1.0 - ease_in((1.0 - t) * 2.0) / 2.0
+    }
+}
+
+/// Makes an `Out` easing out of an `In` one by running it backwards.
+fn out(t: f32, ease_in: fn(f32) -> f32) -> f32 {
+    1.0 - ease_in(1.0 - t)
+}
+
+fn sine_in(t: f32) -> f32 {
+    1.0 - (t * PI / 2.0).cos()
+}
+
+fn expo_in(t: f32) -> f32 {
+    if t <= 0.0 {
+        0.0
+    } else {
+        2f32.powf(10.0 * t - 10.0)
+    }
+}
+
+fn circ_in(t: f32) -> f32 {
+    1.0 - (1.0 - t * t).max(0.0).sqrt()
+}
+
+fn back_in(t: f32) -> f32 {
+    (BACK + 1.0) * t * t * t - BACK * t * t
+}
+
+fn elastic_in(t: f32) -> f32 {
+    if t <= 0.0 || t >= 1.0 {
+        return t.clamp(0.0, 1.0);
+    }
+    -(2f32.powf(10.0 * t - 10.0)) * ((t * 10.0 - 10.75) * (2.0 * PI / 3.0)).sin()
+}
+
+fn bounce_in(t: f32) -> f32 {
+    1.0 - bounce_out(1.0 - t)
+}
+
+impl Easing {
+    /// Eases `t`, which goes from 0 at the start to 1 at the end. `t` is
+    /// clamped to between 0 and 1, and the result is 0 at the start and 1
+    /// at the end, though some easings go past them in between.
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t.powi(2),
+            Easing::QuadOut => out(t, |t| t.powi(2)),
+            Easing::QuadInOut => in_out(t, |t| t.powi(2)),
+            Easing::CubicIn => t.powi(3),
+            Easing::CubicOut => out(t, |t| t.powi(3)),
+            Easing::CubicInOut => in_out(t, |t| t.powi(3)),
+            Easing::QuartIn => t.powi(4),
+            Easing::QuartOut => out(t, |t| t.powi(4)),
+            Easing::QuartInOut => in_out(t, |t| t.powi(4)),
+            Easing::QuintIn => t.powi(5),
+            Easing::QuintOut => out(t, |t| t.powi(5)),
+            Easing::QuintInOut => in_out(t, |t| t.powi(5)),
+            Easing::SineIn => sine_in(t),
+            Easing::SineOut => out(t, sine_in),
+            Easing::SineInOut => in_out(t, sine_in),
+            Easing::ExpoIn => expo_in(t),
+            Easing::ExpoOut => out(t, expo_in),
+            Easing::ExpoInOut => in_out(t, expo_in),
+            Easing::CircIn => circ_in(t),
+            Easing::CircOut => out(t, circ_in),
+            Easing::CircInOut => in_out(t, circ_in),
+            Easing::BackIn => back_in(t),
+            Easing::BackOut => out(t, back_in),
+            Easing::BackInOut => in_out(t, back_in),
+            Easing::ElasticIn => elastic_in(t),
+            Easing::ElasticOut => out(t, elastic_in),
+            Easing::ElasticInOut => in_out(t, elastic_in),
+            Easing::BounceIn => bounce_in(t),
+            Easing::BounceOut => bounce_out(t),
+            Easing::BounceInOut => in_out(t, bounce_in),
+        }
+    }
+}
+
+/// One step of a tween.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Segment<T> {
+    from: T,
+    to: T,
+    seconds: f64,
+    easing: Easing,
+}
+
+/// A value that moves through a sequence of steps over time, each with its
+/// own length and easing.
+///
+/// A tween is made with its first step, and `then` and `wait` add more.
+/// It does nothing until `update` moves it on, so the same tween can be
+/// driven by real time, scaled time or anything else.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tween<T: Lerp> {
+    segments: Vec<Segment<T>>,
+    elapsed: f64,
+    looping: bool,
+}
+
+impl<T: Lerp> Tween<T> {
+    /// Creates a tween that moves from `from` to `to` over `seconds`
+    /// seconds, linearly until `ease` is used.
+    pub fn new(from: T, to: T, seconds: f64) -> Tween<T> {
+        Tween {
+            segments: vec![Segment {
+                from,
+                to,
+                seconds: seconds.max(0.0),
+                easing: Easing::Linear,
+            }],
+            elapsed: 0.0,
+            looping: false,
+        }
+    }
+    /// Sets the easing of the latest step.
+    pub fn ease(mut self, easing: Easing) -> Self {
+        if let Some(segment) = self.segments.last_mut() {
+            segment.easing = easing;
+        }
+        self
+    }
+    /// Adds a step that moves on from where the tween got to, reaching
+    /// `to` after `seconds` more seconds.
+    pub fn then(mut self, to: T, seconds: f64) -> Self {
+        let from = self.end();
+        self.segments.push(Segment {
+            from,
+            to,
+            seconds: seconds.max(0.0),
+            easing: Easing::Linear,
+        });
+        self
+    }
+    /// Adds a step that stays where the tween got to for `seconds` seconds.
+    pub fn wait(self, seconds: f64) -> Self {
+        let end = self.end();
+        self.then(end, seconds)
+    }
+    /// Sets whether the tween starts over after its last step, or stops
+    /// at the end. Tweens don't loop unless this turns it on.
+    #[inline]
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+    /// Moves the tween on by `dt` seconds.
+    pub fn update(&mut self, dt: f64) {
+        self.elapsed += dt.max(0.0);
+        let length = self.duration();
+        if self.looping && length > 0.0 {
+            self.elapsed %= length;
+        }
+    }
+    /// Returns the value the tween has got to.
+    pub fn value(&self) -> T {
+        let mut start = 0.0;
+        for segment in &self.segments {
+            // Steps that take no time are jumped straight past, rather than
+            // dividing by their length.
+            if segment.seconds > 0.0 && self.elapsed < start + segment.seconds {
+                let t = ((self.elapsed - start) / segment.seconds) as f32;
+                return segment.from.lerp(segment.to, segment.easing.apply(t));
+            }
+            start += segment.seconds;
+        }
+        self.end()
+    }
+    /// Returns how long all of the steps take together, in seconds.
+    pub fn duration(&self) -> f64 {
+        self.segments.iter().map(|s| s.seconds).sum()
+    }
+    /// Returns how far through the tween is, from 0 at the start to 1 at
+    /// the end.
+    pub fn progress(&self) -> f64 {
+        match self.duration() {
+            length if length > 0.0 => (self.elapsed / length).min(1.0),
+            _ => 1.0,
+        }
+    }
+    /// Returns true if the tween doesn't loop and has reached the end of
+    /// its last step.
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.elapsed >= self.duration()
+    }
+    /// Goes back to the start.
+    #[inline]
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+    }
+    /// Returns the value the tween ends on.
+    fn end(&self) -> T {
+        self.segments.last().expect("tweens always have a step").to
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EASINGS: [Easing; 31] = [
+        Easing::Linear,
+        Easing::QuadIn,
+        Easing::QuadOut,
+        Easing::QuadInOut,
+        Easing::CubicIn,
+        Easing::CubicOut,
+        Easing::CubicInOut,
+        Easing::QuartIn,
+        Easing::QuartOut,
+        Easing::QuartInOut,
+        Easing::QuintIn,
+        Easing::QuintOut,
+        Easing::QuintInOut,
+        Easing::SineIn,
+        Easing::SineOut,
+        Easing::SineInOut,
+        Easing::ExpoIn,
+        Easing::ExpoOut,
+        Easing::ExpoInOut,
+        Easing::CircIn,
+        Easing::CircOut,
+        Easing::CircInOut,
+        Easing::BackIn,
+        Easing::BackOut,
+        Easing::BackInOut,
+        Easing::ElasticIn,
+        Easing::ElasticOut,
+        Easing::ElasticInOut,
+        Easing::BounceIn,
+        Easing::BounceOut,
+        Easing::BounceInOut,
+    ];
+
+    /// Stops this compiling when an easing is added, until it's added to
+    /// `EASINGS` too.
+    #[allow(dead_code)]
+    fn listed(easing: Easing) {
+        match easing {
+            Easing::Linear
+            | Easing::QuadIn
+            | Easing::QuadOut
+            | Easing::QuadInOut
+            | Easing::CubicIn
+            | Easing::CubicOut
+            | Easing::CubicInOut
+            | Easing::QuartIn
+            | Easing::QuartOut
+            | Easing::QuartInOut
+            | Easing::QuintIn
+            | Easing::QuintOut
+            | Easing::QuintInOut
+            | Easing::SineIn
+            | Easing::SineOut
+            | Easing::SineInOut
+            | Easing::ExpoIn
+            | Easing::ExpoOut
+            | Easing::ExpoInOut
+            | Easing::CircIn
+            | Easing::CircOut
+            | Easing::CircInOut
+            | Easing::BackIn
+            | Easing::BackOut
+            | Easing::BackInOut
+            | Easing::ElasticIn
+            | Easing::ElasticOut
+            | Easing::ElasticInOut
+            | Easing::BounceIn
+            | Easing::BounceOut
+            | Easing::BounceInOut => (),
+        }
+    }
+
+    #[test]
+    fn easings_start_at_0_and_end_at_1() {
+        for &easing in &EASINGS {
+            assert!(easing.apply(0.0).abs() < 1e-6, "{:?} starts at {}", easing, easing.apply(0.0));
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{:?} ends at {}", easing, easing.apply(1.0));
+            // Going past the ends is clamped.
+            assert_eq!(easing.apply(-1.0), easing.apply(0.0));
+            assert_eq!(easing.apply(2.0), easing.apply(1.0));
+        }
+    }
+
+    #[test]
+    fn in_out_easings_are_halfway_in_the_middle() {
+        for &easing in EASINGS.iter().filter(|e| format!("{:?}", e).ends_with("InOut")) {
+            assert!((easing.apply(0.5) - 0.5).abs() < 1e-6, "{:?} is at {}", easing, easing.apply(0.5));
+        }
+        assert_eq!(Easing::QuadOut.apply(0.5), 0.75);
+        assert_eq!(Easing::QuadIn.apply(0.5), 0.25);
+    }
+
+    #[test]
+    fn values_at_segment_boundaries() {
+        let mut tween = Tween::new(0.0f32, 10.0, 1.0).then(20.0, 2.0);
+        assert_eq!(tween.value(), 0.0);
+        tween.update(0.5);
+        assert_eq!(tween.value(), 5.0);
+        tween.update(0.5);
+        assert_eq!(tween.value(), 10.0);
+        tween.update(1.0);
+        assert_eq!(tween.value(), 15.0);
+        tween.update(1.0);
+        assert_eq!(tween.value(), 20.0);
+        assert!(tween.is_finished());
+        tween.update(5.0);
+        assert_eq!(tween.value(), 20.0);
+        assert_eq!(tween.progress(), 1.0);
+    }
+
+    #[test]
+    fn steps_chain_on_from_each_other() {
+        let mut tween = Tween::new(0.0f64, 10.0, 1.0)
+            .ease(Easing::QuadIn)
+            .wait(1.0)
+            .then(0.0, 1.0)
+            .ease(Easing::QuadOut);
+        assert_eq!(tween.duration(), 3.0);
+        tween.update(0.5);
+        assert_eq!(tween.value(), 2.5);
+        tween.update(1.0);
+        assert_eq!(tween.value(), 10.0);
+        tween.update(0.5);
+        assert_eq!(tween.value(), 10.0);
+        tween.update(0.5);
+        assert_eq!(tween.value(), 2.5);
+        assert!(!tween.is_finished());
+        tween.restart();
+        assert_eq!(tween.value(), 0.0);
+    }
+
+    #[test]
+    fn looping_wraps_around() {
+        let mut tween = Tween::new(0.0f32, 10.0, 1.0).then(0.0, 1.0);
+        tween.set_looping(true);
+        tween.update(2.5);
+        assert_eq!(tween.value(), 5.0);
+        assert_eq!(tween.progress(), 0.25);
+        tween.update(1.0);
+        assert_eq!(tween.value(), 5.0);
+        tween.update(0.5);
+        assert_eq!(tween.value(), 0.0);
+        assert!(!tween.is_finished());
+    }
+
+    #[test]
+    fn steps_that_take_no_time_are_skipped() {
+        let mut tween = Tween::new(0.0f32, 10.0, 0.0).then(20.0, 1.0);
+        assert_eq!(tween.value(), 10.0);
+        tween.update(0.5);
+        assert_eq!(tween.value(), 15.0);
+        let mut instant = Tween::new(0.0f32, 10.0, -1.0);
+        assert_eq!(instant.duration(), 0.0);
+        assert_eq!(instant.value(), 10.0);
+        assert_eq!(instant.progress(), 1.0);
+        assert!(instant.is_finished());
+        instant.set_looping(true);
+        instant.update(1.0);
+        assert_eq!(instant.value(), 10.0);
+        let mut jump = Tween::new(0.0f32, 10.0, 1.0).then(50.0, 0.0).then(60.0, 1.0);
+        jump.update(1.0);
+        assert_eq!(jump.value(), 50.0);
+        jump.update(f64::NAN);
+        assert_eq!(jump.value(), 50.0);
+    }
+}
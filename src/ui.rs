@@ -0,0 +1,290 @@
+//! Immediate-mode widgets, for tweaking a sketch's parameters while it runs.
+//!
+//! Widgets are checked and drawn by the same call, on every frame they
+//! should be shown, so they keep no copy of the values they edit: a slider
+//! changes the sketch's own number as it's dragged. They're laid out in a
+//! column from where `Renderer::ui` starts it, and are drawn on top of
+//! whatever has been drawn so far, ignoring the transformation.
+//!
+//! # Example
+//! ```no_run
+//! # use doodle::*;
+//! struct Orbit {
+//!     angle: f32,
+//!     speed: f32,
+//!     trails: bool,
+//! }
+//! let orbit = Orbit { angle: 0.0, speed: 2.0, trails: false };
+//! let builder = DoodleBuilder::with_state(orbit).draw(Box::new(|orbit, r| {
+//!     orbit.angle += orbit.speed * r.delta_seconds() as f32;
+//!     if !orbit.trails {
+//!         r.clear();
+//!     }
+//!     r.fill_circle(400.0 + 200.0 * orbit.angle.cos(), 300.0 + 200.0 * orbit.angle.sin(), 10.0);
+//!
+//!     let mut ui = r.ui(10.0, 10.0);
+//!     ui.label("Orbit");
+//!     ui.slider("speed", &mut orbit.speed, 0.0..10.0);
+//!     ui.checkbox("trails", &mut orbit.trails);
+//!     if ui.button("Reset") {
+//!         orbit.angle = 0.0;
+//!     }
+//! }));
+//! ```
+
+use std::ops::Range;
+
+use sdl2::keyboard::Keycode;
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
+use sdl2::render::BlendMode;
+
+use geometry::Rect;
+use text::Font;
+use {Renderer, Result, Vec2};
+
+/// The point size of the text on widgets.
+const FONT_SIZE: u16 = 14;
+/// The height of a widget, in pixels.
+const ROW_HEIGHT: f32 = 22.0;
+/// The space between widgets, in pixels.
+const SPACING: f32 = 4.0;
+/// The space between the edge of a widget and its text, in pixels.
+const PADDING: f32 = 6.0;
+/// How wide widgets are unless `Ui::width` changes it, in pixels.
+const DEFAULT_WIDTH: f32 = 200.0;
+
+const IDLE: Color = Color { r: 30, g: 30, b: 30, a: 200 };
+const HOVERED: Color = Color { r: 60, g: 60, b: 60, a: 220 };
+const HELD: Color = Color { r: 90, g: 90, b: 90, a: 230 };
+const ACCENT: Color = Color { r: 70, g: 130, b: 220, a: 255 };
+const TEXT: Color = Color { r: 255, g: 255, b: 255, a: 255 };
+
+/// What widgets need to remember between frames.
+#[derive(Default)]
+pub(crate) struct UiState {
+    /// The label of the widget that the mouse was pressed on, while the
+    /// button is held.
+    held: Option<String>,
+}
+
+/// How the mouse is treating a widget this frame.
+#[derive(Debug, Clone, Copy)]
+struct Interaction {
+    hovered: bool,
+    held: bool,
+    clicked: bool,
+}
+
+impl Interaction {
+    fn background(self) -> Color {
+        if self.held {
+            HELD
+        } else if self.hovered {
+            HOVERED
+        } else {
+            IDLE
+        }
+    }
+}
+
+/// A column of widgets, started with `Renderer::ui`.
+///
+/// Widgets are told apart by their labels, so every widget that is shown
+/// at the same time needs a different one.
+pub struct Ui<'a> {
+    renderer: &'a mut Renderer,
+    x: f32,
+    y: f32,
+    width: f32,
+}
+
+impl Renderer {
+    /// Starts a column of widgets with its top left corner at (x, y), in
+    /// pixels from the top left corner of the drawing area. See the `ui`
+    /// module for an example.
+    pub fn ui(&mut self, x: f32, y: f32) -> Ui<'_> {
+        // A widget that stops being shown while it's held can't let go of
+        // the mouse itself.
+        if !self.input.mouse_down(MouseButton::Left) && !self.input.mouse_released(MouseButton::Left) {
+            self.ui.held = None;
+        }
+        Ui {
+            renderer: self,
+            x,
+            y,
+            width: DEFAULT_WIDTH,
+        }
+    }
+}
+
+impl<'a> Ui<'a> {
+    /// Sets how wide the widgets after this are, in pixels.
+    #[inline]
+    pub fn width(mut self, width: f32) -> Self {
+        self.width = width.max(0.0);
+        self
+    }
+    /// Leaves an empty gap of `pixels` pixels before the next widget.
+    #[inline]
+    pub fn space(&mut self, pixels: f32) {
+        self.y += pixels;
+    }
+    /// Shows a line of text.
+    pub fn label(&mut self, text: &str) {
+        let rect = self.next_row();
+        self.draw("draw a label", |r, font| {
+            r.canvas.set_draw_color(IDLE);
+            r.fill_rect(rect.x, rect.y, rect.width, rect.height);
+            draw_label(r, font, text, rect.x + PADDING, rect)
+        });
+    }
+    /// Shows a button, returning true on the frame it's clicked.
+    ///
+    /// A click is the left mouse button being pressed and let go of on
+    /// the button, so pressing it and sliding off cancels the click.
+    pub fn button(&mut self, label: &str) -> bool {
+        let rect = self.next_row();
+        let interaction = self.interact(label, rect);
+        self.draw("draw a button", |r, font| {
+            r.canvas.set_draw_color(interaction.background());
+            r.fill_rect(rect.x, rect.y, rect.width, rect.height);
+            let (width, _) = r.measure_text(font, label)?;
+            let x = rect.x + ((rect.width - width as f32) / 2.0).max(PADDING);
+            draw_label(r, font, label, x, rect)
+        });
+        interaction.clicked
+    }
+    /// Shows a checkbox that turns `value` on and off when it's clicked,
+    /// returning true if it changed `value`.
+    pub fn checkbox(&mut self, label: &str, value: &mut bool) -> bool {
+        let rect = self.next_row();
+        let interaction = self.interact(label, rect);
+        if interaction.clicked {
+            *value = !*value;
+        }
+        let checked = *value;
+        self.draw("draw a checkbox", |r, font| {
+            r.canvas.set_draw_color(interaction.background());
+            r.fill_rect(rect.x, rect.y, rect.width, rect.height);
+            let size = rect.height - 2.0 * SPACING;
+            let (x, y) = (rect.x + SPACING, rect.y + SPACING);
+            r.canvas.set_draw_color(TEXT);
+            r.draw_rect(x, y, size, size);
+            if checked {
+                r.canvas.set_draw_color(ACCENT);
+                r.fill_rect(x + 3.0, y + 3.0, size - 6.0, size - 6.0);
+            }
+            draw_label(r, font, label, x + size + PADDING, rect)
+        });
+        interaction.clicked
+    }
+    /// Shows a slider that sets `value` to somewhere in `range` while it's
+    /// dragged, returning true if it changed `value`. The value and the
+    /// label are shown on the slider.
+    ///
+    /// While the mouse is over the slider, the left and right arrow keys
+    /// nudge the value by a hundredth of the range.
+    pub fn slider(&mut self, label: &str, value: &mut f32, range: Range<f32>) -> bool {
+        let step = (range.end - range.start) / 100.0;
+        self.slide(label, value, range, step, 2)
+    }
+    /// Shows a slider for a whole number, like `slider`. The arrow keys
+    /// nudge the value by at least 1.
+    pub fn slider_int(&mut self, label: &str, value: &mut i32, range: Range<i32>) -> bool {
+        let (start, end) = (range.start as f32, range.end as f32);
+        let step = (end - start) / 100.0;
+        let step = if step.abs() < 1.0 { step.signum() } else { step.round() };
+        let mut float = *value as f32;
+        self.slide(label, &mut float, start..end, step, 0);
+        let old = *value;
+        *value = float.round() as i32;
+        *value != old
+    }
+    /// Shows a slider, with its value rounded to `decimals` places.
+    fn slide(&mut self, label: &str, value: &mut f32, range: Range<f32>, step: f32, decimals: usize) -> bool {
+        let rect = self.next_row();
+        let interaction = self.interact(label, rect);
+        let old = *value;
+        let span = range.end - range.start;
+        if interaction.held && rect.width > 0.0 {
+            let (x, _) = self.renderer.mouse_position();
+            let t = ((x as f32 - rect.x) / rect.width).clamp(0.0, 1.0);
+            *value = range.start + span * t;
+        } else if interaction.hovered {
+            let input = &self.renderer.input;
+            if input.key_pressed(Keycode::Left) {
+                *value -= step;
+            }
+            if input.key_pressed(Keycode::Right) {
+                *value += step;
+            }
+        }
+        if *value != old {
+            let (low, high) = (range.start.min(range.end), range.start.max(range.end));
+            *value = value.clamp(low, high);
+        }
+        let fraction = if span != 0.0 { ((*value - range.start) / span).clamp(0.0, 1.0) } else { 0.0 };
+        let text = format!("{}: {:.*}", label, decimals, *value);
+        self.draw("draw a slider", |r, font| {
+            r.canvas.set_draw_color(interaction.background());
+            r.fill_rect(rect.x, rect.y, rect.width, rect.height);
+            r.canvas.set_draw_color(Color { a: 160, ..ACCENT });
+            r.fill_rect(rect.x, rect.y, rect.width * fraction, rect.height);
+            draw_label(r, font, &text, rect.x + PADDING, rect)
+        });
+        *value != old
+    }
+    /// Returns where the next widget goes, and moves down past it.
+    fn next_row(&mut self) -> Rect {
+        let rect = Rect::new(self.x, self.y, self.width, ROW_HEIGHT);
+        self.y += ROW_HEIGHT + SPACING;
+        rect
+    }
+    /// Works out what the mouse is doing to the widget with `label`.
+    fn interact(&mut self, label: &str, rect: Rect) -> Interaction {
+        let (x, y) = self.renderer.mouse_position();
+        let hovered = rect.contains_point(Vec2::new(x as f32, y as f32));
+        let (input, state) = (&self.renderer.input, &mut self.renderer.ui);
+        if hovered && state.held.is_none() && input.mouse_pressed(MouseButton::Left) {
+            state.held = Some(label.to_owned());
+        }
+        let held = state.held.as_ref().is_some_and(|held| held == label);
+        let released = held && input.mouse_released(MouseButton::Left);
+        if released {
+            state.held = None;
+        }
+        Interaction {
+            hovered,
+            held: held && !released,
+            clicked: released && hovered,
+        }
+    }
+    /// Draws a widget in pixels, putting the drawing settings back after.
+    fn draw<F>(&mut self, what: &str, draw: F)
+    where
+        F: FnOnce(&mut Renderer, Font) -> Result<()>,
+    {
+        let r = &mut *self.renderer;
+        let color = r.canvas.draw_color();
+        let blend_mode = r.canvas.blend_mode();
+        let effect = r.text_effect.take();
+        r.push_matrix();
+        r.reset_matrix();
+        r.canvas.set_blend_mode(BlendMode::Blend);
+        let result = r.default_font(FONT_SIZE).and_then(|font| draw(r, font));
+        r.pop_matrix();
+        r.text_effect = effect;
+        r.canvas.set_blend_mode(blend_mode);
+        r.canvas.set_draw_color(color);
+        r.log_failure(what, result);
+    }
+}
+
+/// Draws a widget's text in white, starting at `x` and centered on the
+/// widget's row.
+fn draw_label(r: &mut Renderer, font: Font, text: &str, x: f32, rect: Rect) -> Result<()> {
+    let (_, height) = r.measure_text(font, text)?;
+    r.canvas.set_draw_color(TEXT);
+    r.draw_text(font, text, x, rect.y + (rect.height - height as f32) / 2.0)
+}
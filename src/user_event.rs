@@ -0,0 +1,86 @@
+//! Events sent to a running doodle from other threads.
+
+use std::any::Any;
+use std::fmt;
+use std::sync::mpsc::Sender;
+
+use {Error, Result};
+
+/// A value sent to a doodle with a DoodleEventSender.
+pub struct UserEvent(Box<dyn Any + Send>);
+
+impl UserEvent {
+    /// Wraps any value that can be sent between threads.
+    #[inline]
+    pub fn new<P: Any + Send>(payload: P) -> Self {
+        UserEvent(Box::new(payload))
+    }
+    /// Returns true if the payload is a `P`.
+    #[inline]
+    pub fn is<P: Any>(&self) -> bool {
+        self.0.is::<P>()
+    }
+    /// Returns a reference to the payload if it is a `P`.
+    #[inline]
+    pub fn downcast_ref<P: Any>(&self) -> Option<&P> {
+        self.0.downcast_ref()
+    }
+    /// Takes the payload out of the event if it is a `P`,
+    /// or gives the event back if it isn't.
+    pub fn downcast<P: Any>(self) -> ::std::result::Result<P, UserEvent> {
+        self.0.downcast().map(|p| *p).map_err(UserEvent)
+    }
+}
+
+impl fmt::Debug for UserEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("UserEvent")
+    }
+}
+
+/// Sends UserEvents to a doodle, which passes them to its user event
+/// handler on the main thread in the order they were sent.
+///
+/// A DoodleEventSender can be cloned and moved to other threads.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// # #[derive(Default)] struct State { result: u64 }
+/// let mut doodle = DoodleBuilder::<State>::new()
+///     .user_event(Box::new(|s, _, event| {
+///         if let Ok(result) = event.downcast::<u64>() {
+///             s.result = result;
+///         }
+///     }))
+///     .build()
+///     .unwrap();
+/// let sender = doodle.event_sender();
+/// std::thread::spawn(move || {
+///     let result = (1..100_000u64).sum::<u64>();
+///     let _ = sender.send(UserEvent::new(result));
+/// });
+/// doodle.run().unwrap();
+/// ```
+#[derive(Clone)]
+pub struct DoodleEventSender {
+    sender: Sender<UserEvent>,
+}
+
+impl DoodleEventSender {
+    pub(crate) fn new(sender: Sender<UserEvent>) -> Self {
+        DoodleEventSender { sender }
+    }
+    /// Sends an event to the doodle.
+    ///
+    /// Returns `Error::Disconnected` if the doodle no longer exists.
+    pub fn send(&self, event: UserEvent) -> Result<()> {
+        self.sender.send(event).map_err(|_| Error::Disconnected)
+    }
+}
+
+impl fmt::Debug for DoodleEventSender {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("DoodleEventSender")
+    }
+}
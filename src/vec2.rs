@@ -0,0 +1,228 @@
+//! A 2D vector for positions, velocities and directions.
+
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use Renderer;
+
+/// A 2D vector, in drawing coordinates when it's a position.
+///
+/// Vectors can be added, subtracted, negated, and multiplied or divided by
+/// a number. Drawing calls that end in `_between` or `_at` take vectors
+/// instead of separate coordinates.
+///
+/// # Example
+/// ```no_run
+/// # use doodle::*;
+/// # struct Ball { position: Vec2, velocity: Vec2 }
+/// # fn draw(ball: &mut Ball, r: &mut Renderer) {
+/// let dt = r.delta_seconds() as f32;
+/// let gravity = Vec2::new(0.0, 500.0);
+/// ball.velocity += gravity * dt;
+/// ball.position += ball.velocity * dt;
+/// r.clear();
+/// r.fill_circle_at(ball.position, 10.0);
+/// // Show where it's heading.
+/// let heading = ball.position + ball.velocity.normalize() * 30.0;
+/// r.draw_line_between(ball.position, heading);
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec2 {
+    /// The horizontal part, growing to the right.
+    pub x: f32,
+    /// The vertical part, growing downwards.
+    pub y: f32,
+}
+
+impl Vec2 {
+    /// The vector with a length of 0.
+    pub const ZERO: Vec2 = Vec2 { x: 0.0, y: 0.0 };
+
+    /// Creates a vector.
+    #[inline]
+    pub fn new(x: f32, y: f32) -> Vec2 {
+        Vec2 { x, y }
+    }
+    /// Creates a vector with a length of 1 pointing `angle` radians
+    /// clockwise from the right, the same way `Renderer::rotate` turns.
+    #[inline]
+    pub fn from_angle(angle: f32) -> Vec2 {
+        Vec2::new(angle.cos(), angle.sin())
+    }
+    /// Returns how long the vector is.
+    #[inline]
+    pub fn length(self) -> f32 {
+        self.x.hypot(self.y)
+    }
+    /// Returns the square of the length, which is quicker to work out and
+    /// is enough for comparing lengths.
+    #[inline]
+    pub fn length_squared(self) -> f32 {
+        self.dot(self)
+    }
+    /// Returns the distance between two positions.
+    #[inline]
+    pub fn distance(self, other: Vec2) -> f32 {
+        (other - self).length()
+    }
+    /// Returns a vector pointing the same way with a length of 1, or the
+    /// zero vector if this one has no length.
+    #[inline]
+    pub fn normalize(self) -> Vec2 {
+        let length = self.length();
+        if length == 0.0 {
+            Vec2::ZERO
+        } else {
+            self / length
+        }
+    }
+    /// Returns the dot product, which is 0 for vectors at right angles and
+    /// the product of the lengths for vectors pointing the same way.
+    #[inline]
+    pub fn dot(self, other: Vec2) -> f32 {
+        self.x * other.x + self.y * other.y
+    }
+    /// Returns the angle the vector points at in radians, clockwise from
+    /// the right, like `from_angle` takes.
+    #[inline]
+    pub fn angle(self) -> f32 {
+        self.y.atan2(self.x)
+    }
+    /// Returns the vector turned `angle` radians clockwise on the screen.
+    #[inline]
+    pub fn rotate(self, angle: f32) -> Vec2 {
+        let (sin, cos) = angle.sin_cos();
+        Vec2::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+    /// Returns the point `t` of the way from this vector to `other`, so 0
+    /// gives this vector and 1 gives `other`. `t` isn't clamped.
+    #[inline]
+    pub fn lerp(self, other: Vec2, t: f32) -> Vec2 {
+        self + (other - self) * t
+    }
+}
+
+impl From<(f32, f32)> for Vec2 {
+    #[inline]
+    fn from((x, y): (f32, f32)) -> Vec2 {
+        Vec2::new(x, y)
+    }
+}
+
+impl From<Vec2> for (f32, f32) {
+    #[inline]
+    fn from(v: Vec2) -> (f32, f32) {
+        (v.x, v.y)
+    }
+}
+
+impl Add for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn add(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn sub(self, other: Vec2) -> Vec2 {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f32> for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn mul(self, scale: f32) -> Vec2 {
+        Vec2::new(self.x * scale, self.y * scale)
+    }
+}
+
+impl Mul<Vec2> for f32 {
+    type Output = Vec2;
+    #[inline]
+    fn mul(self, v: Vec2) -> Vec2 {
+        v * self
+    }
+}
+
+impl Div<f32> for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn div(self, scale: f32) -> Vec2 {
+        Vec2::new(self.x / scale, self.y / scale)
+    }
+}
+
+impl Neg for Vec2 {
+    type Output = Vec2;
+    #[inline]
+    fn neg(self) -> Vec2 {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Vec2 {
+    #[inline]
+    fn add_assign(&mut self, other: Vec2) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vec2 {
+    #[inline]
+    fn sub_assign(&mut self, other: Vec2) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f32> for Vec2 {
+    #[inline]
+    fn mul_assign(&mut self, scale: f32) {
+        *self = *self * scale;
+    }
+}
+
+impl DivAssign<f32> for Vec2 {
+    #[inline]
+    fn div_assign(&mut self, scale: f32) {
+        *self = *self / scale;
+    }
+}
+
+impl Renderer {
+    /// Draws a point at a position, like `draw_point`.
+    #[inline]
+    pub fn draw_point_at(&mut self, position: Vec2) {
+        self.draw_point(position.x, position.y);
+    }
+    /// Draws a line between two positions, like `draw_line`.
+    #[inline]
+    pub fn draw_line_between(&mut self, a: Vec2, b: Vec2) {
+        self.draw_line(a.x, a.y, b.x, b.y);
+    }
+    /// Draws the outline of a circle around a position, like `draw_circle`.
+    #[inline]
+    pub fn draw_circle_at(&mut self, center: Vec2, radius: f32) {
+        self.draw_circle(center.x, center.y, radius);
+    }
+    /// Draws a filled circle around a position, like `fill_circle`.
+    #[inline]
+    pub fn fill_circle_at(&mut self, center: Vec2, radius: f32) {
+        self.fill_circle(center.x, center.y, radius);
+    }
+    /// Draws a line between two positions with the stroke style, like
+    /// `line`.
+    #[inline]
+    pub fn line_between(&mut self, a: Vec2, b: Vec2) {
+        self.line(a.x, a.y, b.x, b.y);
+    }
+    /// Draws a circle around a position with the fill and stroke styles,
+    /// like `circle`.
+    #[inline]
+    pub fn circle_at(&mut self, center: Vec2, radius: f32) {
+        self.circle(center.x, center.y, radius);
+    }
+}
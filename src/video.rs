@@ -0,0 +1,155 @@
+//! Writing videos by piping frames into ffmpeg.
+
+use std::ffi::OsString;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::thread::{self, JoinHandle};
+
+use capture::Frame;
+use {Error, Result};
+
+/// How `Renderer::start_recording_video` runs ffmpeg.
+///
+/// By default ffmpeg picks the codec from the file's extension, like H.264
+/// for `.mp4` and VP9 for `.webm`, at its own quality settings.
+///
+/// # Example
+/// ```
+/// # use doodle::VideoSettings;
+/// let settings = VideoSettings::new().codec("libx264").bitrate("8M");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoSettings {
+    ffmpeg: OsString,
+    codec: Option<String>,
+    bitrate: Option<String>,
+    args: Vec<String>,
+}
+
+impl Default for VideoSettings {
+    fn default() -> Self {
+        VideoSettings {
+            ffmpeg: OsString::from("ffmpeg"),
+            codec: None,
+            bitrate: None,
+            args: Vec::new(),
+        }
+    }
+}
+
+impl VideoSettings {
+    /// Creates the default settings.
+    #[inline]
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Sets the ffmpeg program that is run, for when it isn't on the PATH.
+    pub fn ffmpeg<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.ffmpeg = path.as_ref().as_os_str().to_owned();
+        self
+    }
+    /// Sets the ffmpeg video codec, like `libx264`, `libx265` or
+    /// `libvpx-vp9`.
+    pub fn codec(mut self, codec: &str) -> Self {
+        self.codec = Some(codec.to_owned());
+        self
+    }
+    /// Sets the bitrate the video aims for, in ffmpeg's notation, like
+    /// `"8M"` for 8 megabits a second.
+    pub fn bitrate(mut self, bitrate: &str) -> Self {
+        self.bitrate = Some(bitrate.to_owned());
+        self
+    }
+    /// Adds an argument that is passed to ffmpeg just before the output
+    /// file, like `-crf` and `18` for a quality setting.
+    pub fn arg(mut self, arg: &str) -> Self {
+        self.args.push(arg.to_owned());
+        self
+    }
+}
+
+/// An ffmpeg process that turns raw RGB frames written to it into a video.
+pub(crate) struct FfmpegWriter {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stderr: Option<JoinHandle<String>>,
+}
+
+impl FfmpegWriter {
+    pub(crate) fn spawn(
+        settings: &VideoSettings,
+        path: &Path,
+        size: (u32, u32),
+        fps: u32,
+    ) -> Result<FfmpegWriter> {
+        let mut command = Command::new(&settings.ffmpeg);
+        command
+            .args(["-y", "-loglevel", "error", "-f", "rawvideo", "-pixel_format", "rgb24"])
+            .arg("-video_size")
+            .arg(format!("{}x{}", size.0, size.1))
+            .arg("-framerate")
+            .arg(fps.to_string())
+            .args(["-i", "-"]);
+        if let Some(ref codec) = settings.codec {
+            command.arg("-c:v").arg(codec);
+        }
+        if let Some(ref bitrate) = settings.bitrate {
+            command.arg("-b:v").arg(bitrate);
+        }
+        // Most players only play 4:2:0 video, which needs an even size.
+        command.args(["-pix_fmt", "yuv420p", "-vf", "pad=ceil(iw/2)*2:ceil(ih/2)*2"]);
+        command.args(&settings.args).arg(path);
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| {
+                Error::Error(format!("couldn't run {}: {}", Path::new(&settings.ffmpeg).display(), error))
+            })?;
+        // ffmpeg's messages are read as they come, so it never waits for
+        // room to write them while the doodle waits for it to read frames.
+        let stderr = child.stderr.take().map(|mut stderr| {
+            thread::spawn(move || {
+                let mut text = String::new();
+                let _ = stderr.read_to_string(&mut text);
+                text
+            })
+        });
+        Ok(FfmpegWriter {
+            stdin: child.stdin.take(),
+            child,
+            stderr,
+        })
+    }
+    pub(crate) fn write_frame(&mut self, frame: &Frame) -> Result<()> {
+        let result = match self.stdin {
+            Some(ref mut stdin) => stdin.write_all(&frame.pixels),
+            None => Ok(()),
+        };
+        // Writing fails when ffmpeg has given up, and what it said about
+        // why is more use than a broken pipe.
+        result.or_else(|error| self.wait().and(Err(error.into())))
+    }
+    /// Waits for ffmpeg to write the rest of the video and exit.
+    pub(crate) fn finish(mut self) -> Result<()> {
+        self.wait()
+    }
+    fn wait(&mut self) -> Result<()> {
+        self.stdin = None;
+        let status = self.child.wait()?;
+        let messages = self.stderr.take().and_then(|t| t.join().ok()).unwrap_or_default();
+        if status.success() {
+            Ok(())
+        } else {
+            let messages = messages.trim();
+            Err(Error::Error(format!(
+                "ffmpeg failed ({}){}{}",
+                status,
+                if messages.is_empty() { "" } else { ": " },
+                messages
+            )))
+        }
+    }
+}
@@ -0,0 +1,240 @@
+//! Changing the window's title, icon, position, fullscreen mode and
+//! logical size, and bringing it to the user's attention.
+
+use std::path::Path;
+
+use sdl2::image::LoadSurface;
+use sdl2::rect::Rect;
+use sdl2::surface::Surface;
+use sdl2::video::{FullscreenType, WindowPos};
+
+use {Error, Image, PixelBuffer, Renderer, Result};
+
+/// How a window asks for attention with `request_attention`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlashOperation {
+    /// Stops flashing the window.
+    Cancel,
+    /// Flashes the window for a short time.
+    Briefly,
+    /// Flashes the window until it gets focus.
+    UntilFocused,
+}
+
+impl Renderer {
+    /// Changes the text in the window's title bar.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn draw(r: &mut Renderer, score: u32) {
+    /// let title = format!("Snake - {} points - {:.0} fps", score, r.current_fps());
+    /// r.set_title(&title).unwrap();
+    /// # }
+    /// ```
+    pub fn set_title(&mut self, title: &str) -> Result<()> {
+        self.canvas
+            .window_mut()
+            .set_title(title)
+            .map_err(|_| Error::Error("window titles can't contain nul characters".to_owned()))
+    }
+    /// Returns the text in the window's title bar.
+    #[inline]
+    pub fn title(&self) -> &str {
+        self.canvas.window().title()
+    }
+    /// Sets the window's icon to an image, which is best square and small,
+    /// like 32 by 32 pixels. Transparent parts of it stay transparent.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # fn setup(r: &mut Renderer) -> Result<()> {
+    /// let icon = r.load_image("assets/icon.png")?;
+    /// r.set_icon(icon)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_icon(&mut self, icon: Image) -> Result<()> {
+        let pixels = self.image_pixels(icon)?;
+        self.set_icon_pixels(&pixels)
+    }
+    /// Sets the window's icon from an image file, without loading it as an
+    /// `Image` first.
+    pub fn set_icon_file<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+        let surface = Surface::from_file(path)?;
+        self.canvas.window_mut().set_icon(surface);
+        Ok(())
+    }
+    /// Sets the window's icon from a pixel buffer, so it can be drawn by
+    /// the doodle.
+    pub fn set_icon_pixels(&mut self, icon: &PixelBuffer) -> Result<()> {
+        let surface = icon.to_surface()?;
+        self.canvas.window_mut().set_icon(surface);
+        Ok(())
+    }
+    /// Moves the window so its top left corner is at (x, y) on the
+    /// desktop, not counting its title bar and borders.
+    #[inline]
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.canvas
+            .window_mut()
+            .set_position(WindowPos::Positioned(x), WindowPos::Positioned(y));
+    }
+    /// Moves the window to the middle of the screen it's on.
+    #[inline]
+    pub fn center_window(&mut self) {
+        self.canvas
+            .window_mut()
+            .set_position(WindowPos::Centered, WindowPos::Centered);
+    }
+    /// Returns where the top left corner of the window is on the desktop,
+    /// not counting its title bar and borders.
+    #[inline]
+    pub fn position(&self) -> (i32, i32) {
+        self.canvas.window().position()
+    }
+    /// Switches the window to fullscreen or back to windowed while the
+    /// doodle is running.
+    ///
+    /// `FullscreenType::Desktop` covers the screen without changing the
+    /// display's resolution, and `FullscreenType::True` switches the
+    /// display to the size of the window. Use `set_display_mode` to pick
+    /// the resolution instead.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State;
+    /// // F11 toggles fullscreen.
+    /// let builder = DoodleBuilder::<State>::new().on_key_down(Box::new(|_, r, key| {
+    ///     if key == Keycode::F11 {
+    ///         let mode = match r.fullscreen() {
+    ///             FullscreenType::Off => FullscreenType::Desktop,
+    ///             _ => FullscreenType::Off,
+    ///         };
+    ///         r.set_fullscreen(mode).unwrap();
+    ///     }
+    /// }));
+    /// ```
+    pub fn set_fullscreen(&mut self, mode: FullscreenType) -> Result<()> {
+        self.canvas.window_mut().set_fullscreen(mode).map_err(Error::Error)?;
+        self.dirty = true;
+        Ok(())
+    }
+    /// Returns whether the window is fullscreen, and how.
+    #[inline]
+    pub fn fullscreen(&self) -> FullscreenType {
+        self.canvas.window().fullscreen_state()
+    }
+    /// Changes the logical size the drawing is scaled from while the doodle
+    /// is running. See `DoodleBuilder::logical_size`.
+    pub fn set_logical_size(&mut self, width: u32, height: u32) -> Result<()> {
+        self.flush_batch();
+        self.canvas.set_logical_size(width, height)?;
+        self.logical_size_set = true;
+        self.dirty = true;
+        self.fit_integer_scale()
+    }
+    /// Goes back to drawing in the window's own units, without a logical
+    /// size.
+    pub fn clear_logical_size(&mut self) -> Result<()> {
+        self.flush_batch();
+        self.logical_size_set = false;
+        self.dirty = true;
+        if self.high_dpi {
+            return self.window_resized();
+        }
+        self.canvas.set_logical_size(0, 0)?;
+        self.canvas.set_scale(1.0, 1.0)?;
+        self.canvas.set_viewport(None);
+        Ok(())
+    }
+    /// Returns the size of the window in pixels. This is bigger than the
+    /// size of the window on high-DPI screens when the doodle was built
+    /// with `DoodleBuilder::high_dpi`.
+    pub fn drawable_size(&self) -> (u32, u32) {
+        self.canvas.window().drawable_size()
+    }
+    /// Returns how many pixels of the screen there are to each unit of
+    /// the window's size, like 2 on most Retina displays and 1 on other
+    /// screens. Images drawn at this many times their size fill the
+    /// screen's pixels one to one.
+    pub fn scale_factor(&self) -> f32 {
+        let window = self.canvas.window();
+        match (window.size().0, window.drawable_size().0) {
+            (0, _) | (_, 0) => 1.0,
+            (width, pixels) => pixels as f32 / width as f32,
+        }
+    }
+    /// Returns true if the doodle was built with
+    /// `DoodleBuilder::headless`, and is drawing without a window.
+    #[inline]
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+    /// Keeps drawing coordinates matching the window after it changes size.
+    ///
+    /// On high-DPI windows without a logical size, the window's size in
+    /// its own units becomes the logical size, so SDL scales drawing up to
+    /// the pixels of the screen.
+    pub(crate) fn window_resized(&mut self) -> Result<()> {
+        self.flush_batch();
+        if self.high_dpi && !self.logical_size_set {
+            let (width, height) = self.canvas.window().size();
+            self.canvas.set_logical_size(width, height)?;
+        }
+        self.fit_integer_scale()
+    }
+    /// Sets whether the logical size is only scaled up a whole number of
+    /// times. See `DoodleBuilder::integer_scale`.
+    pub fn set_integer_scale(&mut self, integer_scale: bool) -> Result<()> {
+        self.integer_scale = integer_scale;
+        match self.canvas.logical_size() {
+            (0, 0) => Ok(()),
+            // Setting the size again makes SDL work out its own scale.
+            (width, height) => self.set_logical_size(width, height),
+        }
+    }
+    /// Replaces the scale SDL picked for the logical size with the biggest
+    /// whole number scale that fits, if integer scaling is on.
+    ///
+    /// The version of SDL that doodle's sdl2 crate supports can't do this
+    /// itself, so it's done again every time the window changes size.
+    pub(crate) fn fit_integer_scale(&mut self) -> Result<()> {
+        let (width, height) = self.canvas.logical_size();
+        if !self.integer_scale || !self.logical_size_set || width == 0 || height == 0 {
+            return Ok(());
+        }
+        let (output_width, output_height) = self.canvas.output_size()?;
+        let scale = (output_width / width).min(output_height / height).max(1);
+        self.canvas.set_scale(scale as f32, scale as f32)?;
+        // The viewport is in logical pixels, so the bars are too.
+        let x = (output_width / scale).saturating_sub(width) / 2;
+        let y = (output_height / scale).saturating_sub(height) / 2;
+        self.canvas.set_viewport(Rect::new(x as i32, y as i32, width, height));
+        self.dirty = true;
+        Ok(())
+    }
+    /// Raises the window above other windows and tries to give it focus.
+    ///
+    /// Window managers can refuse to hand over focus. If the window does
+    /// get it, the focus event arrives like any other, so input that is
+    /// released or pressed in between is tracked properly.
+    #[inline]
+    pub fn raise_window(&mut self) {
+        self.canvas.window_mut().raise();
+    }
+    /// Flashes the window's taskbar entry or dock icon to ask the user to
+    /// look at it, without taking focus.
+    ///
+    /// Flashing needs SDL 2.0.16, which the version of the sdl2 crate
+    /// doodle uses doesn't support, so for now this always returns
+    /// `Error::Unsupported`.
+    pub fn request_attention(&mut self, operation: FlashOperation) -> Result<()> {
+        Err(Error::Unsupported(format!(
+            "{:?} window flashing isn't supported by this version of SDL",
+            operation
+        )))
+    }
+}
@@ -0,0 +1,251 @@
+//! Extra windows next to the main one, like a control panel beside the
+//! output of a projection.
+//!
+//! Each extra window has its own SDL renderer, and images and fonts belong
+//! to the main one, so extra windows are drawn with the whole `Renderer`
+//! on an offscreen image which is then copied to the window. That copy goes
+//! through the CPU, so extra windows are best kept fairly small.
+
+use std::mem;
+
+use sdl2::event::{Event, WindowEvent};
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator};
+use sdl2::video::{Window, WindowContext};
+
+use capture::{BYTES_PER_PIXEL, FORMAT};
+use graphics::Graphics;
+use image::into_static;
+use {Callback, DoodleBuilder, Error, Handler, Renderer, Result};
+
+/// An extra window that was asked for with `DoodleBuilder::extra_window`.
+pub(crate) struct WindowSpec<T> {
+    pub(crate) name: String,
+    pub(crate) size: Option<(u32, u32)>,
+    pub(crate) draw: Option<Callback<T>>,
+}
+
+/// An extra window and what it takes to copy frames to it.
+pub(crate) struct ExtraWindow {
+    name: String,
+    visible: bool,
+    // The texture must be declared before the canvas and texture creator
+    // it belongs to, so that it's destroyed first.
+    texture: Option<Texture<'static>>,
+    texture_creator: TextureCreator<WindowContext>,
+    canvas: Canvas<Window>,
+    /// What the window is drawn on before it's copied.
+    frame: Option<Graphics>,
+    pixels: Vec<u8>,
+}
+
+/// Returns the window a window or mouse event happened in.
+///
+/// Keyboard events aren't included because they go to the doodle whichever
+/// of its windows has focus, while mouse positions are only meaningful in
+/// the window they're in.
+pub(crate) fn pointer_window_id(event: &Event) -> Option<u32> {
+    match *event {
+        Event::Window { window_id, .. }
+        | Event::MouseMotion { window_id, .. }
+        | Event::MouseButtonDown { window_id, .. }
+        | Event::MouseButtonUp { window_id, .. }
+        | Event::MouseWheel { window_id, .. } => Some(window_id),
+        _ => None,
+    }
+}
+
+impl Renderer {
+    /// Opens an extra window, shown until it's closed.
+    pub(crate) fn open_window(&mut self, name: &str, width: u32, height: u32) -> Result<()> {
+        let video = self.canvas.window().subsystem().clone();
+        let mut window = video.window(name, width, height);
+        if self.headless {
+            window.hidden();
+        } else {
+            window.position_centered();
+        }
+        let mut canvas = window.build()?.into_canvas();
+        if self.headless {
+            canvas = canvas.software();
+        }
+        let canvas = canvas.build()?;
+        self.windows.push(ExtraWindow {
+            name: name.to_owned(),
+            visible: true,
+            texture: None,
+            texture_creator: canvas.texture_creator(),
+            canvas,
+            frame: None,
+            pixels: Vec::new(),
+        });
+        Ok(())
+    }
+    /// Returns the id of the main window, which SDL's window and input
+    /// events carry as their `window_id`.
+    #[inline]
+    pub fn main_window_id(&self) -> u32 {
+        self.canvas.window().id()
+    }
+    /// Returns the id of the extra window with the name it was added with,
+    /// or None if there isn't one.
+    pub fn window_id(&self, name: &str) -> Option<u32> {
+        self.windows.iter().find(|w| w.name == name).map(|w| w.canvas.window().id())
+    }
+    /// Returns the name of the extra window with an id, or None if it's
+    /// the main window or no window of the doodle's.
+    ///
+    /// Mouse and window events from extra windows only go to the callback
+    /// set with `on_event`, since their positions aren't in the main
+    /// window, so this is how to tell which window they came from.
+    pub fn window_name(&self, window_id: u32) -> Option<&str> {
+        self.windows
+            .iter()
+            .find(|w| w.canvas.window().id() == window_id)
+            .map(|w| &w.name[..])
+    }
+    /// Shows or hides an extra window. Closing an extra window hides it,
+    /// and hidden windows aren't drawn.
+    pub fn set_window_visible(&mut self, name: &str, visible: bool) -> Result<()> {
+        let window = self
+            .windows
+            .iter_mut()
+            .find(|w| w.name == name)
+            .ok_or_else(|| Error::Error(format!("there's no extra window named {:?}", name)))?;
+        window.visible = visible;
+        if visible {
+            window.canvas.window_mut().show();
+        } else {
+            window.canvas.window_mut().hide();
+        }
+        Ok(())
+    }
+    /// Returns true if the window with an id is one of the extra windows.
+    #[inline]
+    pub(crate) fn is_extra_window(&self, window_id: u32) -> bool {
+        self.window_name(window_id).is_some()
+    }
+    /// Returns true if the doodle has any extra windows.
+    #[inline]
+    pub(crate) fn has_extra_windows(&self) -> bool {
+        !self.windows.is_empty()
+    }
+    /// Handles a window event from an extra window.
+    pub(crate) fn extra_window_event(&mut self, event: &Event) {
+        if let Event::Window { window_id, win_event: WindowEvent::Close, .. } = *event {
+            let name = self.window_name(window_id).map(str::to_owned);
+            if let Some(name) = name {
+                let result = self.set_window_visible(&name, false);
+                self.log_failure("hide the window", result);
+            }
+        }
+    }
+    /// Draws the extra window at `index` with `draw`, which draws on the
+    /// whole window in its own pixels, and shows what it drew.
+    pub(crate) fn draw_window<F: FnOnce(&mut Renderer)>(&mut self, index: usize, draw: F) -> Result<()> {
+        if !self.windows[index].visible {
+            return Ok(());
+        }
+        let (width, height) = self.windows[index].canvas.window().size();
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+        let frame = match self.windows[index].frame {
+            Some(frame) if frame.size() == (width, height) => frame,
+            old => {
+                if let Some(old) = old {
+                    self.unload_image(old.image());
+                }
+                let frame = self.create_graphics(width, height)?;
+                self.windows[index].frame = Some(frame);
+                frame
+            }
+        };
+        let mut pixels = mem::take(&mut self.windows[index].pixels);
+        let mut read = Ok((width, height));
+        self.draw_to(frame, |r| {
+            draw(r);
+            read = r.read_pixels_into(Some(Rect::new(0, 0, width, height)), &mut pixels);
+        })?;
+        read?;
+        let window = &mut self.windows[index];
+        let reusable = match window.texture {
+            Some(ref texture) => {
+                let query = texture.query();
+                (query.width, query.height) == (width, height)
+            }
+            None => false,
+        };
+        if !reusable {
+            let mut texture = window.texture_creator.create_texture_streaming(FORMAT, width, height)?;
+            texture.set_blend_mode(BlendMode::None);
+            window.texture = Some(into_static(texture));
+        }
+        let texture = window.texture.as_mut().expect("the window's texture was just created");
+        let result = texture
+            .update(None, &pixels, width as usize * BYTES_PER_PIXEL)
+            .map_err(|e| Error::Error(e.to_string()));
+        window.pixels = pixels;
+        result?;
+        window.canvas.copy(texture, None, None)?;
+        window.canvas.present();
+        Ok(())
+    }
+}
+
+impl<'a, T> DoodleBuilder<'a, T> {
+    /// Adds an extra window with a name, which is also its title, and a
+    /// size. `draw_window` sets what it shows.
+    ///
+    /// Extra windows are drawn after the main window each frame, in the
+    /// order they were added, and setup only draws on the main window.
+    /// Their mouse events only go to the callback set with `on_event`, so
+    /// the mouse functions of the Renderer and its widgets follow the main
+    /// window alone.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use doodle::*;
+    /// # #[derive(Default)] struct State { size: f32 }
+    /// let builder = DoodleBuilder::<State>::new()
+    ///     .draw(Box::new(|state, r| {
+    ///         r.clear();
+    ///         r.fill_circle(400.0, 300.0, state.size);
+    ///     }))
+    ///     .extra_window("meter", 320, 40)
+    ///     .draw_window("meter", Box::new(|state, r| {
+    ///         r.clear();
+    ///         r.fill_rect(10.0, 10.0, state.size, 20.0);
+    ///     }))
+    ///     .on_event(Box::new(|state, r, event| {
+    ///         // Clicking on the meter sets the size.
+    ///         if let Event::MouseButtonDown { window_id, x, .. } = *event {
+    ///             if r.window_name(window_id) == Some("meter") {
+    ///                 state.size = (x as f32 - 10.0).max(0.0);
+    ///             }
+    ///         }
+    ///     }));
+    /// ```
+    pub fn extra_window(mut self, name: &str, width: u32, height: u32) -> Self {
+        self.window_spec(name).size = Some((width, height));
+        self
+    }
+    /// Sets the callback that draws each frame of the extra window with a
+    /// name. Drawing is in the window's own coordinates, and
+    /// `Renderer::size` is the window's size while it runs.
+    pub fn draw_window(mut self, name: &str, draw: Handler<T>) -> Self {
+        self.window_spec(name).draw = Some(Callback::Plain(draw));
+        self
+    }
+    fn window_spec(&mut self, name: &str) -> &mut WindowSpec<T> {
+        if let Some(index) = self.windows.iter().position(|w| w.name == name) {
+            return &mut self.windows[index];
+        }
+        self.windows.push(WindowSpec {
+            name: name.to_owned(),
+            size: None,
+            draw: None,
+        });
+        self.windows.last_mut().expect("the window was just added")
+    }
+}
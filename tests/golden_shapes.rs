@@ -0,0 +1,63 @@
+//! Golden image tests of the shape primitives, drawn by headless doodles.
+//!
+//! A failing test saves what was drawn next to its golden image, and
+//! running with `DOODLE_BLESS=1` saves the new drawings as the golden
+//! images instead.
+
+extern crate doodle;
+
+use doodle::testing::{assert_image_matches, render_frame, Tolerance};
+use doodle::{Color, DoodleBuilder, PixelBuffer, Renderer};
+
+/// Draws one frame of a 64 by 64 doodle on black and returns it.
+fn draw_frame<F: FnMut(&mut Renderer) + 'static>(mut draw: F) -> PixelBuffer {
+    let builder = DoodleBuilder::<()>::new()
+        .width(64)
+        .height(64)
+        .draw(Box::new(move |_, r| {
+            r.background(Color::RGB(0, 0, 0));
+            draw(r);
+        }));
+    render_frame(builder, 1).unwrap()
+}
+
+#[test]
+fn fill_rect_matches_golden() {
+    let frame = draw_frame(|r| {
+        r.set_draw_color(Color::RGB(255, 0, 0));
+        r.fill_rect(8.0, 8.0, 24.0, 16.0);
+        // Rectangles between pixels are snapped to the pixels by their edges.
+        r.set_draw_color(Color::RGB(0, 255, 0));
+        r.fill_rect(20.5, 30.25, 30.0, 20.0);
+    });
+    assert_image_matches(&frame, "tests/golden/rects.png", Tolerance::exact()).unwrap();
+}
+
+#[test]
+fn fill_circle_matches_golden() {
+    let frame = draw_frame(|r| {
+        r.set_draw_color(Color::RGB(255, 255, 255));
+        r.fill_circle(32.0, 32.0, 20.0);
+        // Mostly off the canvas.
+        r.set_draw_color(Color::RGB(0, 0, 255));
+        r.fill_circle(0.0, 0.0, 12.0);
+    });
+    assert_image_matches(&frame, "tests/golden/circles.png", Tolerance::exact()).unwrap();
+}
+
+#[test]
+fn smooth_polygon_matches_golden() {
+    let frame = draw_frame(|r| {
+        r.fill(Color::RGB(255, 255, 255));
+        r.no_stroke();
+        r.smooth();
+        r.begin_shape();
+        r.vertex(8.3, 54.7);
+        r.vertex(32.0, 6.2);
+        r.vertex(57.6, 50.1);
+        r.end_shape(true);
+    });
+    // Blending the see-through edge pixels rounds a little differently
+    // between SDL's renderers.
+    assert_image_matches(&frame, "tests/golden/triangle.png", Tolerance::new(2, 0.0)).unwrap();
+}